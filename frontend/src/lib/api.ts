@@ -20,6 +20,17 @@ export type DashboardAccountSummary = {
   unknown: number
 }
 
+export type DashboardBalancerCandidate = {
+  provider: string
+  client_id: string
+  requests: number
+  in_flight: number
+  ewma_latency_ms: number | null
+  weight: number
+  hedges: number
+  hedge_wins: number
+}
+
 export type DashboardOverview = {
   health: DashboardHealth
   cards: DashboardOverviewCard[]
@@ -27,6 +38,8 @@ export type DashboardOverview = {
   available_model_count: number
   provider_names: string[]
   routing_strategy: string
+  balancer: DashboardBalancerCandidate[]
+  hedge_win_rate: number | null
 }
 
 export type DashboardAuthRecord = {
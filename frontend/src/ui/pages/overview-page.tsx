@@ -15,6 +15,7 @@ export function OverviewPage() {
   const overview = useOverviewQuery();
   const accountSummaries = overview.data?.account_summaries;
   const providerNames = overview.data?.provider_names ?? [];
+  const balancer = overview.data?.balancer ?? [];
 
   const summaryRows = useMemo(
     () =>
@@ -223,6 +224,53 @@ export function OverviewPage() {
                         No providers configured yet.
                       </div>
                     )}
+
+                    <div className="dashboard-panel rounded-2xl p-5">
+                      <div>
+                        <h3 className="text-lg font-semibold">Balancer</h3>
+                        <p className="text-muted-foreground mt-1 text-sm">
+                          Live load per account under {overview.data.routing_strategy}.
+                        </p>
+                      </div>
+                      <div className="mt-4 space-y-2">
+                        {balancer.length > 0 ? (
+                          balancer.map((candidate) => (
+                            <div
+                              key={candidate.client_id}
+                              className="dashboard-panel flex items-center justify-between gap-3 rounded-2xl px-4 py-3 text-sm"
+                            >
+                              <div className="min-w-0">
+                                <p className="text-foreground truncate font-medium">
+                                  {candidate.client_id}
+                                </p>
+                                <p className="text-muted-foreground text-xs">
+                                  {candidate.provider} · weight {candidate.weight}
+                                </p>
+                              </div>
+                              <div className="text-muted-foreground text-right text-xs">
+                                <p>
+                                  {candidate.in_flight} in flight · {candidate.requests} total
+                                </p>
+                                <p>
+                                  {candidate.ewma_latency_ms === null
+                                    ? "no latency yet"
+                                    : `${Math.round(candidate.ewma_latency_ms)} ms to first token`}
+                                </p>
+                                {candidate.hedges > 0 ? (
+                                  <p>
+                                    {candidate.hedge_wins}/{candidate.hedges} hedges won
+                                  </p>
+                                ) : null}
+                              </div>
+                            </div>
+                          ))
+                        ) : (
+                          <div className="text-muted-foreground text-sm">
+                            No accounts in rotation.
+                          </div>
+                        )}
+                      </div>
+                    </div>
                   </section>
                 </div>
               </section>
@@ -11,6 +11,7 @@ use tracing::{debug, info, warn};
 
 use crate::auth::antigravity::*;
 use crate::auth::store::{AuthRecord, FileTokenStore};
+use crate::providers::http_client::{self, SendTraced};
 
 // ── Token types ──────────────────────────────────────────────────────────────
 
@@ -36,7 +37,7 @@ struct UserInfo {
 /// 3. Exchange code for tokens
 /// 4. Fetch email + project_id
 /// 5. Save to auth-dir
-pub async fn login(store: &FileTokenStore) -> anyhow::Result<()> {
+pub async fn login(store: &FileTokenStore, proxy_url: Option<&str>) -> anyhow::Result<()> {
     let redirect_uri = format!("http://localhost:{}/oauth-callback", CALLBACK_PORT);
     let state = uuid::Uuid::new_v4().to_string();
 
@@ -55,7 +56,7 @@ pub async fn login(store: &FileTokenStore) -> anyhow::Result<()> {
     let code = wait_for_callback(&state).await?;
     info!("received auth code, exchanging for tokens...");
 
-    let client = reqwest::Client::new();
+    let client = http_client::default_client("antigravity", proxy_url)?;
 
     // Exchange code for tokens
     let tokens = exchange_code(&client, &code, &redirect_uri).await?;
@@ -394,7 +395,11 @@ pub async fn refresh_access_token(
         ("client_secret", CLIENT_SECRET),
     ];
 
-    let resp = client.post(TOKEN_ENDPOINT).form(&params).send().await?;
+    let resp = client
+        .post(TOKEN_ENDPOINT)
+        .form(&params)
+        .send_traced()
+        .await?;
 
     let status = resp.status();
     if !status.is_success() {
@@ -10,6 +10,7 @@ use crate::auth::codex::{save_auth_bundle, CLIENT_ID};
 use crate::auth::codex_login::{exchange_code_for_tokens_with_redirect_url, PKCECodes};
 use crate::auth::store::FileTokenStore;
 use crate::error::{AppError, AppResult};
+use crate::providers::http_client;
 
 #[derive(Debug, Serialize)]
 struct DeviceUserCodeRequest {
@@ -383,8 +384,8 @@ pub const DEVICE_TOKEN_EXCHANGE_REDIRECT_URI: &str = "https://auth.openai.com/de
 pub const DEVICE_LOGIN_TIMEOUT: Duration = Duration::from_secs(15 * 60);
 pub const DEVICE_LOGIN_HTTP_TIMEOUT: Duration = Duration::from_secs(30);
 
-fn build_device_login_client() -> AppResult<Client> {
-    Client::builder()
+fn build_device_login_client(proxy_url: Option<&str>) -> AppResult<Client> {
+    http_client::builder("codex", proxy_url)?
         .timeout(DEVICE_LOGIN_HTTP_TIMEOUT)
         // .connect_timeout(Duration::from_secs(5))
         .build()
@@ -432,12 +433,15 @@ impl CodexDeviceEndpoints {
 }
 
 /// Codex device-login entrypoint.
-pub async fn device_login(store: &FileTokenStore) -> AppResult<DeviceLoginResult> {
+pub async fn device_login(
+    store: &FileTokenStore,
+    proxy_url: Option<&str>,
+) -> AppResult<DeviceLoginResult> {
     match std::env::var("RUSUH_CODEX_AUTH_BASE_URL") {
         Ok(base_url) => device_login_with_base_url(store, &base_url).await,
         Err(_) => {
             let endpoints = CodexDeviceEndpoints::production();
-            let client = build_device_login_client()?;
+            let client = build_device_login_client(proxy_url)?;
 
             device_login_with_endpoints(store, &client, &endpoints).await
         }
@@ -449,7 +453,7 @@ pub async fn device_login_with_base_url(
     base_url: &str,
 ) -> AppResult<DeviceLoginResult> {
     let endpoints = CodexDeviceEndpoints::from_auth_base_url(base_url)?;
-    let client = build_device_login_client()?;
+    let client = build_device_login_client(None)?;
 
     device_login_with_endpoints(store, &client, &endpoints).await
 }
@@ -519,7 +523,7 @@ mod tests {
 
     #[test]
     fn device_login_client_matches_kiro_usage_checker_timeout_pattern() {
-        let _client = build_device_login_client().expect("device login client should build");
+        let _client = build_device_login_client(None).expect("device login client should build");
         assert_eq!(DEVICE_LOGIN_HTTP_TIMEOUT, Duration::from_secs(30));
     }
 
@@ -20,6 +20,7 @@ use crate::auth::codex::{
 };
 use crate::auth::store::FileTokenStore;
 use crate::error::{AppError, AppResult};
+use crate::providers::http_client;
 
 /// OAuth authorization endpoint.
 pub const AUTH_URL: &str = "https://auth.openai.com/oauth/authorize";
@@ -510,7 +511,10 @@ pub(crate) async fn exchange_code_for_tokens_with_redirect_url(
 }
 
 /// Run interactive Codex OAuth login and persist resulting auth file.
-pub async fn login(store: &FileTokenStore) -> AppResult<std::path::PathBuf> {
+pub async fn login(
+    store: &FileTokenStore,
+    proxy_url: Option<&str>,
+) -> AppResult<std::path::PathBuf> {
     let state = uuid::Uuid::new_v4().to_string();
     let pkce = generate_pkce_codes();
 
@@ -533,7 +537,7 @@ pub async fn login(store: &FileTokenStore) -> AppResult<std::path::PathBuf> {
         .map_err(|error| AppError::Auth(format!("failed to read callback url: {error}")))?;
 
     let code = resolve_callback_code_from_url(&callback_url, &state)?;
-    let client = Client::new();
+    let client = http_client::default_client("codex", proxy_url)?;
     let bundle =
         exchange_code_for_tokens_with_redirect(&client, &code, REDIRECT_URI, &pkce).await?;
 
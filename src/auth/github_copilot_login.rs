@@ -11,6 +11,7 @@ use crate::auth::github_copilot::{
 };
 use crate::auth::store::FileTokenStore;
 use crate::error::{AppError, AppResult};
+use crate::providers::http_client;
 
 const DEFAULT_HTTP_TIMEOUT: Duration = Duration::from_secs(30);
 
@@ -71,15 +72,18 @@ impl GithubCopilotLoginEndpoints {
     }
 }
 
-pub async fn login(store: &FileTokenStore) -> AppResult<std::path::PathBuf> {
+pub async fn login(
+    store: &FileTokenStore,
+    proxy_url: Option<&str>,
+) -> AppResult<std::path::PathBuf> {
     let endpoints = GithubCopilotLoginEndpoints::production();
-    let client = build_http_client()?;
+    let client = build_http_client(proxy_url)?;
     login_with_endpoints(store, &client, &endpoints).await
 }
 
 pub async fn login_with_base_url(store: &FileTokenStore, base_url: &str) -> AppResult<std::path::PathBuf> {
     let endpoints = GithubCopilotLoginEndpoints::from_base_url(base_url)?;
-    let client = build_http_client()?;
+    let client = build_http_client(None)?;
     login_with_endpoints(store, &client, &endpoints).await
 }
 
@@ -276,8 +280,8 @@ async fn validate_copilot_entitlement(client: &Client, url: &str, access_token:
     Ok(())
 }
 
-fn build_http_client() -> AppResult<Client> {
-    Client::builder()
+fn build_http_client(proxy_url: Option<&str>) -> AppResult<Client> {
+    http_client::builder("github-copilot", proxy_url)?
         .timeout(DEFAULT_HTTP_TIMEOUT)
         .build()
         .map_err(|error| AppError::Auth(format!("failed to build github copilot login client: {error}")))
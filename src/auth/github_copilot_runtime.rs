@@ -5,6 +5,7 @@ use reqwest::Client;
 use serde::Deserialize;
 
 use crate::auth::github_copilot::COPILOT_API_TOKEN_URL;
+use crate::providers::http_client::SendTraced;
 
 const COPILOT_USER_AGENT: &str = "GitHubCopilotChat/0.26.7";
 const COPILOT_EDITOR_VERSION: &str = "vscode/1.99.0";
@@ -68,7 +69,7 @@ pub async fn exchange_github_token_for_copilot_token_with_url(
         .bearer_auth(github_oauth_token.trim())
         .header(reqwest::header::USER_AGENT, "rusuh")
         .header(reqwest::header::ACCEPT, "application/json")
-        .send()
+        .send_traced()
         .await
         .map_err(|error| AppError::Auth(format!("copilot token exchange request failed: {error}")))?;
 
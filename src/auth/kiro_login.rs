@@ -11,6 +11,7 @@ use crate::auth::kiro::{
     REFRESH_SKEW_SECS, SCOPES, SSO_OIDC_ENDPOINT,
 };
 use crate::error::{AppError, AppResult};
+use crate::providers::http_client::{self, SendTraced};
 use axum::{
     extract::Query,
     response::{Html, IntoResponse},
@@ -259,9 +260,13 @@ pub struct SocialAuthClient {
 impl SocialAuthClient {
     /// Create a new social auth client with default HTTP client.
     pub fn new() -> Self {
-        Self {
-            http_client: reqwest::Client::new(),
-        }
+        Self::with_client(reqwest::Client::new())
+    }
+
+    /// Create a social auth client that sends through `http_client`, e.g. one
+    /// built for the account's proxy.
+    pub fn with_client(http_client: reqwest::Client) -> Self {
+        Self { http_client }
     }
 
     /// Exchange authorization code for access token.
@@ -321,7 +326,7 @@ impl SocialAuthClient {
             .header("User-Agent", "KiroIDE/1.0.0")
             .header("Accept", "application/json, text/plain, */*")
             .json(&payload)
-            .send()
+            .send_traced()
             .await
             .map_err(|e| AppError::Auth(format!("refresh request failed: {}", e)))?;
 
@@ -536,8 +541,12 @@ mod tests {
 use crate::auth::store::FileTokenStore;
 
 /// CLI login for KIRO via social OAuth (Google or GitHub).
-pub async fn login(store: &FileTokenStore, provider: &str) -> AppResult<()> {
-    let client = SocialAuthClient::new();
+pub async fn login(
+    store: &FileTokenStore,
+    provider: &str,
+    proxy_url: Option<&str>,
+) -> AppResult<()> {
+    let client = SocialAuthClient::with_client(http_client::default_client("kiro", proxy_url)?);
     // Call the appropriate login method based on provider
     let token_data = match provider {
         "google" => client.login_with_google().await?,
@@ -623,9 +632,13 @@ pub struct SSOOIDCClient {
 impl SSOOIDCClient {
     /// Create a new SSO OIDC client with default HTTP client.
     pub fn new() -> Self {
-        Self {
-            http_client: reqwest::Client::new(),
-        }
+        Self::with_client(reqwest::Client::new())
+    }
+
+    /// Create an SSO OIDC client that sends through `http_client`, e.g. one built
+    /// for the account's proxy.
+    pub fn with_client(http_client: reqwest::Client) -> Self {
+        Self { http_client }
     }
 
     /// Get OIDC endpoint for the given region.
@@ -834,7 +847,7 @@ impl SSOOIDCClient {
             .header("Content-Type", "application/json")
             .header("x-amz-target", "com.amazonaws.sso.oauth.CreateToken")
             .json(&payload)
-            .send()
+            .send_traced()
             .await
             .map_err(|e| AppError::Auth(format!("refresh token request failed: {}", e)))?;
 
@@ -1179,8 +1192,12 @@ impl SSOOIDCClient {
 // ── CLI Login Functions ──────────────────────────────────────────────────────
 
 /// CLI login for KIRO via AWS SSO (Builder ID or Enterprise IDC).
-pub async fn login_sso(store: &FileTokenStore, start_url: &str) -> AppResult<()> {
-    let client = SSOOIDCClient::new();
+pub async fn login_sso(
+    store: &FileTokenStore,
+    start_url: &str,
+    proxy_url: Option<&str>,
+) -> AppResult<()> {
+    let client = SSOOIDCClient::with_client(http_client::default_client("kiro", proxy_url)?);
     // Determine if Builder ID or Enterprise IDC based on start_url
     let token_data = if start_url == BUILDER_ID_START_URL {
         client.login_with_builder_id().await?
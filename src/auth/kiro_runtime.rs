@@ -18,6 +18,8 @@ use tracing::{debug, info, warn};
 
 use super::manager::AccountManager;
 use super::store::AuthRecord;
+use crate::error::{AppError, AppResult};
+use crate::providers::http_client;
 
 // ── Cooldown ──────────────────────────────────────────────────────────────────
 
@@ -140,6 +142,17 @@ impl CooldownManager {
     pub fn purge_expired(&mut self, now: Instant) {
         self.entries.retain(|_, e| now < e.expires_at);
     }
+
+    /// Cooldowns still running at `now`, as `(auth_id, model_id, entry)`.
+    pub fn active_entries(
+        &self,
+        now: Instant,
+    ) -> impl Iterator<Item = (&str, &str, &CooldownEntry)> {
+        self.entries
+            .iter()
+            .filter(move |(_, e)| now < e.expires_at)
+            .map(|((auth_id, model_id), e)| (auth_id.as_str(), model_id.as_str(), e))
+    }
 }
 
 // ── Quota ─────────────────────────────────────────────────────────────────────
@@ -164,8 +177,11 @@ pub enum QuotaStatus {
         next_reset: Option<i64>,
         breakdown: Option<QuotaBreakdown>,
     },
-    /// Quota is exhausted — requests should not be sent.
-    Exhausted { detail: String },
+    /// Quota is exhausted — requests should not be sent until `next_reset` (Unix milliseconds) if known.
+    Exhausted {
+        detail: String,
+        next_reset: Option<i64>,
+    },
 }
 
 impl QuotaStatus {
@@ -185,8 +201,8 @@ impl QuotaStatus {
     /// Returns the next reset timestamp (Unix milliseconds) if known.
     pub fn next_reset(&self) -> Option<i64> {
         match self {
-            Self::Available { next_reset, .. } => *next_reset,
-            _ => None,
+            Self::Available { next_reset, .. } | Self::Exhausted { next_reset, .. } => *next_reset,
+            Self::Unknown => None,
         }
     }
 }
@@ -295,6 +311,27 @@ impl KiroRateLimiter {
         state.cooldown_end = Some(now + calculate_backoff(state.fail_count));
     }
 
+    /// Auths still backing off at `now`, as `(token_key, fail_count, cooldown_end)`.
+    pub fn active_backoffs(&self, now: Instant) -> impl Iterator<Item = (&str, usize, Instant)> {
+        self.states.iter().filter_map(move |(key, state)| {
+            state
+                .cooldown_end
+                .filter(|cooldown_end| now < *cooldown_end)
+                .map(|cooldown_end| (key.as_str(), state.fail_count, cooldown_end))
+        })
+    }
+
+    /// Reinstate a backoff recorded before a restart.
+    pub fn restore_backoff(&mut self, token_key: &str, fail_count: usize, cooldown_end: Instant) {
+        self.states.insert(
+            token_key.to_string(),
+            TokenState {
+                fail_count,
+                cooldown_end: Some(cooldown_end),
+            },
+        );
+    }
+
     /// Clears failure backoff after a successful request.
     pub fn mark_token_success(&mut self, token_key: &str) {
         if let Some(state) = self.states.get_mut(token_key) {
@@ -394,11 +431,17 @@ impl KiroUsageChecker {
     ///
     /// In production, the base URL should be derived from the profile ARN region.
     /// For testing, it can point to a local mock server.
-    pub fn new(base_url: &str) -> Result<Self, reqwest::Error> {
-        let client = reqwest::Client::builder()
+    pub fn new(base_url: &str) -> AppResult<Self> {
+        Self::new_with_proxy(base_url, None)
+    }
+
+    /// Create a usage checker whose requests go through `proxy_url`.
+    pub fn new_with_proxy(base_url: &str, proxy_url: Option<&str>) -> AppResult<Self> {
+        let client = http_client::builder("kiro", proxy_url)?
             .timeout(std::time::Duration::from_secs(30))
             // .connect_timeout(std::time::Duration::from_secs(5))
-            .build()?;
+            .build()
+            .map_err(|error| AppError::Config(format!("failed to build kiro client: {error}")))?;
 
         Ok(Self {
             base_url: base_url.trim_end_matches('/').to_string(),
@@ -456,7 +499,10 @@ impl KiroUsageChecker {
 
             // Parse suspension errors and return as Exhausted status
             if status == reqwest::StatusCode::FORBIDDEN && body.contains("TEMPORARILY_SUSPENDED") {
-                return Ok(QuotaStatus::Exhausted { detail: body });
+                return Ok(QuotaStatus::Exhausted {
+                    detail: body,
+                    next_reset: None,
+                });
             }
 
             return Err(format!("HTTP error {}: {}", status, body).into());
@@ -478,6 +524,7 @@ fn compute_quota_status(usage: &UsageQuotaResponse) -> QuotaStatus {
     if usage.usage_breakdown_list.is_empty() {
         return QuotaStatus::Exhausted {
             detail: "No usage breakdown available".to_string(),
+            next_reset: None,
         };
     }
 
@@ -516,6 +563,7 @@ fn compute_quota_status(usage: &UsageQuotaResponse) -> QuotaStatus {
     if total_remaining <= 0.0 {
         QuotaStatus::Exhausted {
             detail: "All quota exhausted".to_string(),
+            next_reset,
         }
     } else {
         QuotaStatus::Available {
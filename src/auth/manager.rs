@@ -9,6 +9,7 @@ use tracing::{info, warn};
 
 use crate::auth::store::{AuthRecord, AuthStatus, FileTokenStore};
 use crate::error::AppResult;
+use crate::providers::http_client;
 
 /// Manages loaded auth accounts, grouped by provider.
 pub struct AccountManager {
@@ -16,6 +17,8 @@ pub struct AccountManager {
     /// provider_key → list of auth records
     /// provider_key → list of auth records
     accounts: tokio::sync::RwLock<HashMap<String, Vec<AuthRecord>>>,
+    /// Global `proxy-url`, used for calls made while loading accounts
+    proxy_url: Option<String>,
 }
 
 impl AccountManager {
@@ -24,9 +27,16 @@ impl AccountManager {
         Self {
             store,
             accounts: tokio::sync::RwLock::new(HashMap::new()),
+            proxy_url: None,
         }
     }
 
+    /// Route calls made while loading accounts through the global `proxy-url`.
+    pub fn with_proxy_url(mut self, proxy_url: Option<String>) -> Self {
+        self.proxy_url = proxy_url;
+        self
+    }
+
     /// Create a manager with a default store pointing at `auth_dir`.
     pub fn with_dir(auth_dir: impl Into<PathBuf>) -> Self {
         let store = Arc::new(FileTokenStore::new(auth_dir));
@@ -40,12 +50,20 @@ impl AccountManager {
         let mut records = self.store.list().await?;
 
         // Auto-fetch project_id for antigravity accounts that don't have one
-        let client = reqwest::Client::new();
         for record in &mut records {
             if record.provider == "antigravity"
                 && record.project_id().is_none()
                 && record.access_token().is_some()
             {
+                let proxy_url =
+                    http_client::effective_proxy_url(record.proxy_url(), self.proxy_url.as_deref());
+                let client = match http_client::default_client("antigravity", proxy_url) {
+                    Ok(client) => client,
+                    Err(e) => {
+                        warn!("could not auto-fetch project_id for {}: {e}", record.id);
+                        continue;
+                    }
+                };
                 let token = record.access_token().unwrap_or_default().to_string();
                 match crate::auth::antigravity_login::fetch_project_id(&client, &token).await {
                     Ok(pid) => {
@@ -142,6 +142,17 @@ impl AuthRecord {
         None
     }
 
+    /// Per-account upstream proxy override from metadata (`proxy_url`).
+    pub fn proxy_url(&self) -> Option<&str> {
+        if let Some(Value::String(s)) = self.metadata.get("proxy_url") {
+            let trimmed = s.trim();
+            if !trimmed.is_empty() {
+                return Some(trimmed);
+            }
+        }
+        None
+    }
+
     /// Derive effective status from the `disabled` flag and `status` field.
     /// If `disabled` is true, overrides to `Disabled` regardless of `status`.
     pub fn effective_status(&self) -> AuthStatus {
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 
 /// Top-level server configuration (mirrors config.example.yaml)
@@ -14,7 +15,10 @@ pub struct Config {
     pub auth_dir: String,
     /// API keys for incoming request authentication
     #[serde(rename = "api-keys")]
-    pub api_keys: Vec<String>,
+    pub api_keys: Vec<ApiKeyEntry>,
+    /// Rate limits applied to every client key that does not set its own
+    #[serde(rename = "key-rate-limit")]
+    pub key_rate_limit: KeyRateLimit,
     /// Enable debug logging
     pub debug: bool,
     /// Number of request retries
@@ -22,6 +26,19 @@ pub struct Config {
     pub request_retry: u32,
     /// Routing strategy
     pub routing: RoutingConfig,
+    /// Per-account circuit breaker for repeated upstream failures
+    #[serde(rename = "circuit-breaker")]
+    pub circuit_breaker: CircuitBreakerConfig,
+    /// Keep cooldowns, quota marks and sticky sessions in auth-dir across restarts
+    #[serde(rename = "persist-state")]
+    pub persist_state: PersistStateConfig,
+    /// Record token usage of every request in auth-dir
+    #[serde(rename = "usage-ledger")]
+    pub usage_ledger: UsageLedgerConfig,
+    /// OpenTelemetry span export
+    pub tracing: TracingConfig,
+    /// Prometheus `/metrics` endpoint
+    pub metrics: MetricsConfig,
     /// Proxy URL (socks5/http/https)
     #[serde(rename = "proxy-url")]
     pub proxy_url: Option<String>,
@@ -42,6 +59,9 @@ pub struct Config {
     pub openai_compat: Vec<OpenAICompatProvider>,
     #[serde(rename = "oauth-model-alias", default = "default_oauth_model_alias")]
     pub oauth_model_alias: HashMap<String, Vec<ModelAlias>>,
+    /// Public model names and the provider/model pairs that serve them
+    #[serde(rename = "model-routes", default = "default_model_routes")]
+    pub model_routes: Vec<ModelRoute>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -52,6 +72,78 @@ pub struct TlsConfig {
     pub key: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PersistStateConfig {
+    pub enable: bool,
+    /// Seconds between snapshots; the state is also written at shutdown
+    #[serde(rename = "interval-seconds")]
+    pub interval_seconds: u64,
+}
+
+impl Default for PersistStateConfig {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            interval_seconds: 60,
+        }
+    }
+}
+
+impl PersistStateConfig {
+    /// Snapshot period, never shorter than a second.
+    pub fn interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.interval_seconds.max(1))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UsageLedgerConfig {
+    /// Append records to a file in auth-dir; when off, usage is kept for this run only
+    pub enable: bool,
+}
+
+impl Default for UsageLedgerConfig {
+    fn default() -> Self {
+        Self { enable: true }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TracingConfig {
+    /// Export spans over OTLP/HTTP
+    pub enable: bool,
+    /// Collector traces endpoint
+    #[serde(rename = "otlp-endpoint")]
+    pub otlp_endpoint: String,
+    /// `service.name` resource attribute
+    #[serde(rename = "service-name")]
+    pub service_name: String,
+    /// Send `traceparent` / `tracestate` on upstream calls
+    #[serde(rename = "propagate-upstream")]
+    pub propagate_upstream: bool,
+}
+
+impl Default for TracingConfig {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            otlp_endpoint: "http://localhost:4318/v1/traces".to_string(),
+            service_name: "rusuh".to_string(),
+            propagate_upstream: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct MetricsConfig {
+    /// Serve `/metrics` to anyone; otherwise it needs the management key
+    pub public: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(default)]
 pub struct ManagementConfig {
@@ -64,8 +156,255 @@ pub struct ManagementConfig {
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(default)]
 pub struct RoutingConfig {
-    /// "round-robin" (default) or "fill-first"
+    /// "round-robin" (default), "fill-first", "least-busy", "ewma-latency" or "weighted"
     pub strategy: String,
+    /// Account weights for the "weighted" strategy, keyed by auth id or provider type;
+    /// unlisted accounts weigh 1
+    pub weights: HashMap<String, u32>,
+    /// Keep a conversation on the account that served it last
+    #[serde(rename = "session-affinity")]
+    pub session_affinity: SessionAffinityConfig,
+}
+
+impl RoutingConfig {
+    /// Weight for an account: its auth id wins over its provider type.
+    pub fn weight_for(&self, client_id: &str, provider_type: &str) -> u32 {
+        let lookup = |key: &str| {
+            self.weights
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(key))
+                .map(|(_, weight)| *weight)
+        };
+        lookup(client_id)
+            .or_else(|| lookup(provider_type))
+            .unwrap_or(1)
+    }
+}
+
+/// Where a request's sticky-session key comes from.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum SessionSource {
+    /// The header named by `header`
+    Header,
+    /// Anthropic `metadata.user_id`
+    MetadataUserId,
+    /// A hash of the conversation's leading messages
+    MessageHash,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionAffinityConfig {
+    /// Sources tried in order; the first one present keys the session. Empty disables it
+    pub sources: Vec<SessionSource>,
+    /// Header read by the `header` source
+    pub header: String,
+    /// Leading messages hashed by the `message-hash` source
+    #[serde(rename = "hash-messages")]
+    pub hash_messages: usize,
+}
+
+impl Default for SessionAffinityConfig {
+    fn default() -> Self {
+        Self {
+            sources: vec![SessionSource::Header, SessionSource::MetadataUserId],
+            header: "x-session-id".to_string(),
+            hash_messages: 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive transient failures that open an account's circuit; 0 disables it
+    #[serde(rename = "failure-threshold")]
+    pub failure_threshold: u32,
+    /// Seconds an open circuit waits before letting a probe request through
+    #[serde(rename = "open-seconds")]
+    pub open_seconds: u64,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            open_seconds: 30,
+        }
+    }
+}
+
+/// A client API key. A plain string in `api-keys` is an unrestricted key; the
+/// mapping form adds a name and a scope.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(from = "RawApiKey", into = "RawApiKey")]
+pub struct ApiKeyEntry {
+    pub key: String,
+    /// Label shown in logs and the management API
+    pub name: Option<String>,
+    pub scope: ApiKeyScope,
+}
+
+/// What a client key may reach. Empty lists and unset limits allow everything.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApiKeyScope {
+    /// Public model names the key may request
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub models: Vec<String>,
+    /// Provider types the key's requests may be served by
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub providers: Vec<String>,
+    /// Accounts the key may pin with `selected_auth_id` or be served by
+    #[serde(rename = "allowed-auth-ids", skip_serializing_if = "Vec::is_empty")]
+    pub allowed_auth_ids: Vec<String>,
+    /// The key is rejected from this moment on
+    #[serde(rename = "expires-at", skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Requests allowed per UTC day
+    #[serde(rename = "requests-per-day", skip_serializing_if = "Option::is_none")]
+    pub requests_per_day: Option<u64>,
+    /// Prompt plus completion tokens allowed per UTC day
+    #[serde(rename = "tokens-per-day", skip_serializing_if = "Option::is_none")]
+    pub tokens_per_day: Option<u64>,
+    /// Per-minute limits; unset ones come from the top-level `key-rate-limit`
+    #[serde(
+        rename = "rate-limit",
+        skip_serializing_if = "KeyRateLimit::is_unlimited"
+    )]
+    pub rate_limit: KeyRateLimit,
+}
+
+/// Short-term limits on one client key. Unset limits do not apply.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeyRateLimit {
+    #[serde(
+        rename = "requests-per-minute",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub requests_per_minute: Option<u32>,
+    /// Prompt plus completion tokens per minute
+    #[serde(rename = "tokens-per-minute", skip_serializing_if = "Option::is_none")]
+    pub tokens_per_minute: Option<u64>,
+    /// Streaming responses open at the same time
+    #[serde(rename = "concurrent-streams", skip_serializing_if = "Option::is_none")]
+    pub concurrent_streams: Option<u32>,
+}
+
+impl KeyRateLimit {
+    pub fn is_unlimited(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// These limits, with unset ones taken from `defaults`.
+    pub fn or(self, defaults: KeyRateLimit) -> Self {
+        Self {
+            requests_per_minute: self.requests_per_minute.or(defaults.requests_per_minute),
+            tokens_per_minute: self.tokens_per_minute.or(defaults.tokens_per_minute),
+            concurrent_streams: self.concurrent_streams.or(defaults.concurrent_streams),
+        }
+    }
+}
+
+impl ApiKeyEntry {
+    /// Label for logs: the name, or a prefix of the key.
+    pub fn label(&self) -> String {
+        match &self.name {
+            Some(name) => name.clone(),
+            None => format!("{}…", self.key.chars().take(8).collect::<String>()),
+        }
+    }
+
+    /// Stable id for usage records: a hash prefix of the key. Unlike the label it
+    /// tells apart unnamed keys that share a prefix, without exposing the key.
+    pub fn id(&self) -> String {
+        Sha256::digest(self.key.as_bytes())[..8]
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect()
+    }
+
+    pub fn is_expired(&self, now: chrono::DateTime<chrono::Utc>) -> bool {
+        self.scope
+            .expires_at
+            .is_some_and(|expires_at| now >= expires_at)
+    }
+
+    pub fn allows_model(&self, model: &str) -> bool {
+        allowed(&self.scope.models, model)
+    }
+
+    pub fn allows_provider(&self, provider_type: &str) -> bool {
+        allowed(&self.scope.providers, provider_type)
+    }
+
+    pub fn allows_auth_id(&self, client_id: &str) -> bool {
+        allowed(&self.scope.allowed_auth_ids, client_id)
+    }
+}
+
+fn allowed(list: &[String], value: &str) -> bool {
+    list.is_empty() || list.iter().any(|item| item.eq_ignore_ascii_case(value))
+}
+
+impl From<String> for ApiKeyEntry {
+    fn from(key: String) -> Self {
+        Self {
+            key,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<&str> for ApiKeyEntry {
+    fn from(key: &str) -> Self {
+        key.to_string().into()
+    }
+}
+
+impl PartialEq<&str> for ApiKeyEntry {
+    fn eq(&self, other: &&str) -> bool {
+        self.key == *other
+    }
+}
+
+/// Wire form of [`ApiKeyEntry`]: unscoped keys stay plain strings.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum RawApiKey {
+    Plain(String),
+    Scoped {
+        key: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+        #[serde(flatten)]
+        scope: ApiKeyScope,
+    },
+}
+
+impl From<RawApiKey> for ApiKeyEntry {
+    fn from(raw: RawApiKey) -> Self {
+        match raw {
+            RawApiKey::Plain(key) => key.into(),
+            RawApiKey::Scoped { key, name, scope } => Self { key, name, scope },
+        }
+    }
+}
+
+impl From<ApiKeyEntry> for RawApiKey {
+    fn from(entry: ApiKeyEntry) -> Self {
+        if entry.name.is_none() && entry.scope == ApiKeyScope::default() {
+            RawApiKey::Plain(entry.key)
+        } else {
+            RawApiKey::Scoped {
+                key: entry.key,
+                name: entry.name,
+                scope: entry.scope,
+            }
+        }
+    }
 }
 
 /// A provider API key entry with optional prefix, base URL, model aliases.
@@ -122,6 +461,180 @@ pub struct ModelAlias {
     pub fork: bool,
 }
 
+/// A public model name routed to an ordered list of provider/model targets.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(default)]
+pub struct ModelRoute {
+    pub name: String,
+    /// Routable, but left out of the public `/v1/models` catalog
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub hidden: bool,
+    /// Tried in order until one succeeds
+    pub targets: Vec<ModelRouteTarget>,
+    /// Launch a second account in parallel when the first has not answered
+    /// within this many milliseconds
+    #[serde(rename = "hedge-after-ms", skip_serializing_if = "Option::is_none")]
+    pub hedge_after_ms: Option<u64>,
+    /// Other public models to serve the request, in order, when every target of
+    /// this one is exhausted or unavailable
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fallback: Vec<String>,
+}
+
+impl ModelRoute {
+    /// Delay before a hedged second call; `None` when hedging is off.
+    pub fn hedge_after(&self) -> Option<std::time::Duration> {
+        self.hedge_after_ms
+            .filter(|&ms| ms > 0)
+            .map(std::time::Duration::from_millis)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ModelRouteTarget {
+    /// Provider type, e.g. "kiro" or "github-copilot"
+    pub provider: String,
+    /// Model id as that provider registers it
+    pub model: String,
+    /// Relative share of traffic; 0 takes the target out of rotation
+    #[serde(default = "default_route_weight")]
+    pub weight: u32,
+}
+
+impl ModelRouteTarget {
+    pub fn new(provider: &str, model: &str) -> Self {
+        Self {
+            provider: provider.to_string(),
+            model: model.to_string(),
+            weight: default_route_weight(),
+        }
+    }
+}
+
+fn default_route_weight() -> u32 {
+    1
+}
+
+fn default_model_routes() -> Vec<ModelRoute> {
+    let route = |name: &str, targets: &[(&str, &str)]| ModelRoute {
+        name: name.to_string(),
+        hidden: false,
+        targets: targets
+            .iter()
+            .map(|(provider, model)| ModelRouteTarget::new(provider, model))
+            .collect(),
+        ..Default::default()
+    };
+
+    vec![
+        route(
+            "claude-opus-4-6",
+            &[
+                ("github-copilot", "claude-opus-4.6"),
+                ("claude-api", "claude-opus-4-6"),
+            ],
+        ),
+        route(
+            "claude-opus-4-5",
+            &[
+                ("github-copilot", "claude-opus-4.5"),
+                ("claude-api", "claude-opus-4-5-20251101"),
+            ],
+        ),
+        route(
+            "claude-sonnet-4-6",
+            &[
+                ("zed", "claude-sonnet-4-6"),
+                ("github-copilot", "claude-sonnet-4.6"),
+                ("claude-api", "claude-sonnet-4-6"),
+            ],
+        ),
+        route(
+            "claude-sonnet-4-5",
+            &[
+                ("kiro", "kiro-claude-sonnet-4-5-agentic"),
+                ("kiro", "kiro-claude-sonnet-4-5"),
+                ("zed", "claude-sonnet-4-5"),
+                ("github-copilot", "claude-sonnet-4.5"),
+                ("claude-api", "claude-sonnet-4-5-20250929"),
+            ],
+        ),
+        route(
+            "claude-haiku-4-5",
+            &[
+                ("kiro", "kiro-claude-haiku-4-5-agentic"),
+                ("kiro", "kiro-claude-haiku-4-5"),
+                ("github-copilot", "claude-haiku-4.5"),
+                ("claude-api", "claude-haiku-4-5-20251001"),
+            ],
+        ),
+        route(
+            "gpt-5.4",
+            &[
+                ("codex", "gpt-5.4"),
+                ("github-copilot", "gpt-5.4"),
+                ("codex-api", "gpt-5.4"),
+            ],
+        ),
+        route(
+            "gpt-5.3-codex",
+            &[
+                ("codex", "gpt-5.3-codex"),
+                ("github-copilot", "gpt-5.3-codex"),
+                ("codex-api", "gpt-5.3-codex"),
+            ],
+        ),
+        ModelRoute {
+            hidden: true,
+            ..route(
+                "claude-sonnet-4-5-thinking",
+                &[("kiro", "kiro-claude-sonnet-4-5-agentic")],
+            )
+        },
+    ]
+}
+
+/// Reject route tables the router cannot use: blank or duplicate names, routes
+/// without targets, targets missing a provider or model, and fallbacks that name
+/// the route itself or no route at all.
+pub fn validate_model_routes(routes: &[ModelRoute]) -> anyhow::Result<()> {
+    let mut seen = std::collections::HashSet::new();
+    for route in routes {
+        let name = route.name.trim();
+        if name.is_empty() {
+            anyhow::bail!("model-routes entry has an empty name");
+        }
+        if !seen.insert(name.to_ascii_lowercase()) {
+            anyhow::bail!("model-routes has a duplicate entry for '{name}'");
+        }
+        if route.targets.is_empty() {
+            anyhow::bail!("model-routes entry '{name}' has no targets");
+        }
+        for target in &route.targets {
+            if target.provider.trim().is_empty() || target.model.trim().is_empty() {
+                anyhow::bail!("model-routes entry '{name}' has a target without provider or model");
+            }
+        }
+    }
+    for route in routes {
+        for fallback in &route.fallback {
+            if fallback.eq_ignore_ascii_case(&route.name) {
+                anyhow::bail!("model-routes entry '{}' falls back to itself", route.name);
+            }
+            if !routes
+                .iter()
+                .any(|other| other.name.eq_ignore_ascii_case(fallback))
+            {
+                anyhow::bail!(
+                    "model-routes entry '{}' falls back to unknown model '{fallback}'",
+                    route.name
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
 fn default_oauth_model_alias() -> HashMap<String, Vec<ModelAlias>> {
     HashMap::from([(
         "github-copilot".to_string(),
@@ -162,9 +675,15 @@ impl Default for Config {
             port: 0,
             auth_dir: String::new(),
             api_keys: Vec::new(),
+            key_rate_limit: KeyRateLimit::default(),
             debug: false,
             request_retry: 0,
             routing: RoutingConfig::default(),
+            circuit_breaker: CircuitBreakerConfig::default(),
+            persist_state: PersistStateConfig::default(),
+            usage_ledger: UsageLedgerConfig::default(),
+            tracing: TracingConfig::default(),
+            metrics: MetricsConfig::default(),
             proxy_url: None,
             tls: TlsConfig::default(),
             remote_management: ManagementConfig::default(),
@@ -173,6 +692,7 @@ impl Default for Config {
             claude_api_keys: Vec::new(),
             openai_compat: Vec::new(),
             oauth_model_alias: default_oauth_model_alias(),
+            model_routes: default_model_routes(),
         }
     }
 }
@@ -184,6 +704,18 @@ impl Config {
         Ok(cfg)
     }
 
+    /// The public route for `model`, if one is configured.
+    pub fn model_route(&self, model: &str) -> Option<&ModelRoute> {
+        self.model_routes
+            .iter()
+            .find(|route| route.name.eq_ignore_ascii_case(model))
+    }
+
+    /// The configured entry for client key `key`.
+    pub fn api_key(&self, key: &str) -> Option<&ApiKeyEntry> {
+        self.api_keys.iter().find(|entry| entry.key == key)
+    }
+
     pub fn load_optional(path: &str) -> anyhow::Result<Option<Self>> {
         match std::fs::read_to_string(path) {
             Ok(content) => {
@@ -7,6 +7,7 @@ use serde_json::{json, Value};
 
 use crate::auth::store::{AuthRecord, AuthStatus};
 use crate::config::{OpenAICompatProvider, ProviderKeyEntry};
+use crate::proxy::balancer::CandidateStats;
 use crate::proxy::ProxyState;
 
 #[derive(Debug, Clone, Serialize)]
@@ -34,6 +35,14 @@ pub struct DashboardAccountSummary {
     pub unknown: usize,
 }
 
+#[derive(Debug, Clone, Serialize)]
+pub struct DashboardBalancerCandidate {
+    pub provider: String,
+    pub client_id: String,
+    #[serde(flatten)]
+    pub stats: CandidateStats,
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct DashboardOverview {
     pub health: DashboardHealth,
@@ -42,6 +51,9 @@ pub struct DashboardOverview {
     pub available_model_count: usize,
     pub provider_names: Vec<String>,
     pub routing_strategy: String,
+    pub balancer: Vec<DashboardBalancerCandidate>,
+    /// Share of hedged calls that answered first; `None` before any hedge
+    pub hedge_win_rate: Option<f64>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -169,15 +181,35 @@ async fn build_overview(state: Arc<ProxyState>) -> DashboardOverview {
         .get_available_models("openai")
         .await
         .len();
-    let provider_names = state
-        .current_runtime_snapshot()
-        .await
+    let runtime_snapshot = state.current_runtime_snapshot().await;
+    let provider_names = runtime_snapshot
         .providers()
         .iter()
         .map(|provider| provider.name().to_string())
         .collect::<Vec<_>>();
-    let routing_strategy = cfg.routing.strategy.clone();
+    let balancer = runtime_snapshot
+        .providers()
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, provider)| {
+            Some(DashboardBalancerCandidate {
+                provider: provider.name().to_string(),
+                client_id: provider.client_id().to_string(),
+                stats: runtime_snapshot.balancer().stats(idx)?,
+            })
+        })
+        .collect::<Vec<_>>();
+    let routing_strategy = runtime_snapshot.balancer().strategy().as_str().to_string();
     let api_key_count = cfg.api_keys.len();
+    let hedges: u64 = balancer
+        .iter()
+        .map(|candidate| candidate.stats.hedges)
+        .sum();
+    let hedge_wins: u64 = balancer
+        .iter()
+        .map(|candidate| candidate.stats.hedge_wins)
+        .sum();
+    let hedge_win_rate = (hedges > 0).then(|| hedge_wins as f64 / hedges as f64);
 
     let cards = vec![
         DashboardOverviewCard {
@@ -204,6 +236,12 @@ async fn build_overview(state: Arc<ProxyState>) -> DashboardOverview {
                 available_model_count
             ),
         },
+        DashboardOverviewCard {
+            label: "Hedge wins".to_string(),
+            value: hedge_win_rate
+                .map_or_else(|| "n/a".to_string(), |rate| format!("{:.0}%", rate * 100.0)),
+            hint: format!("{hedge_wins} of {hedges} hedged call(s) answered first"),
+        },
     ];
 
     DashboardOverview {
@@ -216,6 +254,8 @@ async fn build_overview(state: Arc<ProxyState>) -> DashboardOverview {
         available_model_count,
         provider_names,
         routing_strategy,
+        balancer,
+        hedge_win_rate,
     }
 }
 
@@ -237,7 +277,7 @@ async fn build_accounts_payload(state: Arc<ProxyState>) -> DashboardAccountsPayl
 
 async fn build_api_keys_payload(state: Arc<ProxyState>) -> DashboardApiKeysPayload {
     let cfg = state.config.read().await;
-    let items = cfg.api_keys.clone();
+    let items: Vec<String> = cfg.api_keys.iter().map(|entry| entry.key.clone()).collect();
     let generated_only = items.iter().all(|key| key.starts_with("rsk-"));
     let total = items.len();
 
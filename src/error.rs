@@ -4,12 +4,14 @@ use axum::{
     Json,
 };
 use serde_json::json;
+use std::fmt;
+use std::time::Duration;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
 pub enum AppError {
     #[error("upstream error: {0}")]
-    Upstream(String),
+    Upstream(UpstreamError),
 
     #[error("authentication error: {0}")]
     Auth(String),
@@ -23,9 +25,19 @@ pub enum AppError {
     #[error("bad request: {0}")]
     BadRequest(String),
 
+    #[error("forbidden: {0}")]
+    Forbidden(String),
+
     #[error("quota exceeded for provider: {0}")]
     QuotaExceeded(String),
 
+    /// Rate limited, with the time until the limit lifts when known.
+    #[error("rate limited: {message}")]
+    RateLimited {
+        message: String,
+        retry_after: Option<Duration>,
+    },
+
     #[error("no available accounts for provider: {0}")]
     NoAccounts(String),
 
@@ -40,71 +52,287 @@ pub enum AppError {
     Internal(#[from] anyhow::Error),
 }
 
-impl IntoResponse for AppError {
-    fn into_response(self) -> Response {
-        let (status, message) = match &self {
-            AppError::Auth(_) => (StatusCode::UNAUTHORIZED, self.to_string()),
-            AppError::NotFound(_) => (StatusCode::NOT_FOUND, self.to_string()),
-            AppError::BadRequest(_) => (StatusCode::BAD_REQUEST, self.to_string()),
-            AppError::QuotaExceeded(_) | AppError::NoAccounts(_) => {
-                (StatusCode::TOO_MANY_REQUESTS, self.to_string())
-            }
-            _ => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
+/// How routing should treat a failed upstream call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpstreamErrorKind {
+    /// Credentials rejected or the account may not use the model (401, 403).
+    Auth,
+    /// Rate limit or exhausted quota (429).
+    RateLimited,
+    /// The upstream failed on a valid request (5xx).
+    Server,
+    /// No complete reply: connect failure, timeout or a broken read.
+    Transport,
+    /// The upstream rejected the request itself (other 4xx).
+    InvalidRequest,
+    /// Malformed reply or anything else a retry will not fix.
+    Other,
+}
+
+impl UpstreamErrorKind {
+    /// Kind implied by a non-success HTTP status.
+    pub fn from_status(status: StatusCode) -> Self {
+        match status.as_u16() {
+            401 | 403 => Self::Auth,
+            429 => Self::RateLimited,
+            408 => Self::Transport,
+            500..=599 => Self::Server,
+            400..=499 => Self::InvalidRequest,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// A failure reported by, or while talking to, an upstream provider.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpstreamError {
+    pub kind: UpstreamErrorKind,
+    /// Status of the upstream reply, when there was one
+    pub status: Option<StatusCode>,
+    /// Provider that produced the error
+    pub provider: Option<String>,
+    /// When the upstream said its rate limit lifts
+    pub retry_after: Option<Duration>,
+    pub message: String,
+}
+
+impl UpstreamError {
+    pub fn new(kind: UpstreamErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            status: None,
+            provider: None,
+            retry_after: None,
+            message: message.into(),
+        }
+    }
+
+    /// A non-success reply; the kind follows from `status`.
+    pub fn from_status(status: StatusCode, message: impl Into<String>) -> Self {
+        Self {
+            status: Some(status),
+            ..Self::new(UpstreamErrorKind::from_status(status), message)
+        }
+    }
+
+    /// A request that failed to send or whose reply could not be read.
+    pub fn from_reqwest(context: impl fmt::Display, error: &reqwest::Error) -> Self {
+        let kind = if error.is_builder() || error.is_decode() {
+            UpstreamErrorKind::Other
+        } else {
+            UpstreamErrorKind::Transport
         };
+        Self {
+            status: error.status(),
+            ..Self::new(kind, format!("{context}: {error}"))
+        }
+    }
+
+    /// A connection, timeout or read failure described only by a message.
+    pub fn transport(message: impl Into<String>) -> Self {
+        Self::new(UpstreamErrorKind::Transport, message)
+    }
+
+    /// A reply that could not be understood, or another non-retryable failure.
+    pub fn other(message: impl Into<String>) -> Self {
+        Self::new(UpstreamErrorKind::Other, message)
+    }
+
+    pub fn with_provider(mut self, provider: impl Into<String>) -> Self {
+        self.provider = Some(provider.into());
+        self
+    }
+
+    pub fn with_retry_after(mut self, retry_after: Option<Duration>) -> Self {
+        self.retry_after = retry_after;
+        self
+    }
+}
+
+impl fmt::Display for UpstreamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl From<UpstreamError> for AppError {
+    fn from(error: UpstreamError) -> Self {
+        AppError::Upstream(error)
+    }
+}
+
+/// Client API family whose error envelope a response should use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiFamily {
+    /// `{"error":{"message","type","code"}}`
+    OpenAi,
+    /// `{"type":"error","error":{"type","message"}}`
+    Anthropic,
+    /// `{"error":{"code","message","status"}}`
+    Gemini,
+}
 
-        let body = Json(json!({
-            "error": {
-                "message": message,
-                "type": "api_error",
-                "code": status.as_u16(),
+impl ApiFamily {
+    fn error_body(self, status: StatusCode, message: String) -> serde_json::Value {
+        match self {
+            ApiFamily::OpenAi => {
+                let error_type = match status.as_u16() {
+                    401 => "authentication_error",
+                    403 => "permission_error",
+                    429 => "rate_limit_error",
+                    400..=499 => "invalid_request_error",
+                    _ => "api_error",
+                };
+                json!({
+                    "error": {
+                        "message": message,
+                        "type": error_type,
+                        "code": status.as_u16(),
+                    }
+                })
+            }
+            ApiFamily::Anthropic => {
+                let error_type = match status.as_u16() {
+                    401 => "authentication_error",
+                    403 => "permission_error",
+                    404 => "not_found_error",
+                    413 => "request_too_large",
+                    429 => "rate_limit_error",
+                    400..=499 => "invalid_request_error",
+                    503 | 529 => "overloaded_error",
+                    _ => "api_error",
+                };
+                json!({
+                    "type": "error",
+                    "error": {
+                        "type": error_type,
+                        "message": message,
+                    }
+                })
             }
-        }));
+            ApiFamily::Gemini => {
+                let error_status = match status.as_u16() {
+                    401 => "UNAUTHENTICATED",
+                    403 => "PERMISSION_DENIED",
+                    404 => "NOT_FOUND",
+                    429 => "RESOURCE_EXHAUSTED",
+                    400..=499 => "INVALID_ARGUMENT",
+                    503 => "UNAVAILABLE",
+                    504 => "DEADLINE_EXCEEDED",
+                    _ => "INTERNAL",
+                };
+                json!({
+                    "error": {
+                        "code": status.as_u16(),
+                        "message": message,
+                        "status": error_status,
+                    }
+                })
+            }
+        }
+    }
+}
 
-        (status, body).into_response()
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        self.into_response_for(ApiFamily::OpenAi)
     }
 }
 
 impl AppError {
-    /// Whether this error is transient (5xx, timeout, connection) and worth retrying
-    /// on the *same* provider.
-    pub fn is_transient(&self) -> bool {
+    /// HTTP status returned to the client for this error.
+    ///
+    /// Requests the upstream rejected keep their 4xx status; upstream faults
+    /// become 502, or 503 when the upstream said it is unavailable.
+    pub fn status(&self) -> StatusCode {
         match self {
-            AppError::Upstream(msg) => {
-                let m = msg.to_lowercase();
-                // 5xx status codes
-                m.contains("500") || m.contains("502") || m.contains("503")
-                    || m.contains("504")
-                    // Connection / timeout errors
-                    || m.contains("timeout") || m.contains("timed out")
-                    || m.contains("connection reset") || m.contains("connection refused")
-                    || m.contains("broken pipe") || m.contains("stream read")
+            AppError::Auth(_) => StatusCode::UNAUTHORIZED,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            AppError::Forbidden(_) => StatusCode::FORBIDDEN,
+            AppError::QuotaExceeded(_) | AppError::RateLimited { .. } | AppError::NoAccounts(_) => {
+                StatusCode::TOO_MANY_REQUESTS
             }
-            _ => false,
+            AppError::Upstream(error) => match error.kind {
+                UpstreamErrorKind::RateLimited => StatusCode::TOO_MANY_REQUESTS,
+                UpstreamErrorKind::InvalidRequest => error
+                    .status
+                    .filter(StatusCode::is_client_error)
+                    .unwrap_or(StatusCode::BAD_REQUEST),
+                UpstreamErrorKind::Server
+                    if error.status == Some(StatusCode::SERVICE_UNAVAILABLE) =>
+                {
+                    StatusCode::SERVICE_UNAVAILABLE
+                }
+                _ => StatusCode::BAD_GATEWAY,
+            },
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// Render this error in the envelope `family` clients expect.
+    pub fn into_response_for(self, family: ApiFamily) -> Response {
+        let status = self.status();
+        let body = Json(family.error_body(status, self.to_string()));
+
+        let mut response = (status, body).into_response();
+        if let Some(retry_after) = self.retry_after() {
+            response.headers_mut().insert(
+                axum::http::header::RETRY_AFTER,
+                retry_after.as_secs().max(1).into(),
+            );
         }
+        response
     }
 
-    /// Whether this error is account-specific (401, 429) and should skip to next account.
+    /// The upstream failure behind this error, if it came from a provider call.
+    pub fn upstream(&self) -> Option<&UpstreamError> {
+        match self {
+            AppError::Upstream(error) => Some(error),
+            _ => None,
+        }
+    }
+
+    /// Kind of the upstream failure behind this error.
+    pub fn upstream_kind(&self) -> Option<UpstreamErrorKind> {
+        self.upstream().map(|error| error.kind)
+    }
+
+    /// Whether this error is transient (5xx, timeout, connection) and worth retrying
+    /// on the *same* provider.
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self.upstream_kind(),
+            Some(UpstreamErrorKind::Server | UpstreamErrorKind::Transport)
+        )
+    }
+
+    /// Whether this error is account-specific (401, 403, 429) and should skip to next account.
     pub fn is_account_error(&self) -> bool {
         match self {
             AppError::Auth(_) | AppError::QuotaExceeded(_) => true,
-            AppError::Upstream(msg) => {
-                let m = msg.to_lowercase();
-                m.contains("401")
-                    || m.contains("403")
-                    || m.contains("429")
-                    || m.contains("unauthorized")
-                    || m.contains("rate limit")
-                    || m.contains("quota")
-                    || m.contains("no_access_on_free_plan")
-            }
-            _ => false,
+            _ => matches!(
+                self.upstream_kind(),
+                Some(UpstreamErrorKind::Auth | UpstreamErrorKind::RateLimited)
+            ),
         }
     }
 
     /// Whether this error indicates quota exhaustion or provider unavailability,
     /// which should trigger cross-provider fallback.
     pub fn is_quota_or_unavailable(&self) -> bool {
-        matches!(self, AppError::QuotaExceeded(_) | AppError::NoAccounts(_))
+        match self {
+            AppError::QuotaExceeded(_) | AppError::NoAccounts(_) => true,
+            _ => self.upstream_kind() == Some(UpstreamErrorKind::RateLimited),
+        }
+    }
+
+    /// When the limit behind this error lifts, if known.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            AppError::RateLimited { retry_after, .. } => *retry_after,
+            _ => self.upstream().and_then(|error| error.retry_after),
+        }
     }
 }
 
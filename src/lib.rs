@@ -2,8 +2,11 @@ pub mod auth;
 pub mod config;
 pub mod dashboard_api;
 pub mod error;
+pub mod metrics;
 pub mod middleware;
 pub mod models;
 pub mod providers;
 pub mod proxy;
 pub mod router;
+pub mod telemetry;
+pub mod tls;
@@ -1,4 +1,4 @@
-use rusuh::{auth, config, middleware, providers, proxy, router};
+use rusuh::{auth, config, middleware, providers, proxy, router, telemetry, tls};
 use std::path::PathBuf;
 use std::sync::Arc;
 
@@ -6,6 +6,8 @@ use auth::cli::{Cli, Commands};
 use auth::manager::AccountManager;
 use clap::Parser;
 use tracing::{info, warn};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
 use tracing_subscriber::EnvFilter;
 
 fn load_config_or_default(path: &str) -> anyhow::Result<config::Config> {
@@ -17,14 +19,6 @@ fn load_config_or_default(path: &str) -> anyhow::Result<config::Config> {
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    // Init tracing
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| EnvFilter::new("rusuh=info,tower_http=debug")),
-        )
-        .init();
-
     // Load .env if present
     let _ = dotenvy::dotenv();
 
@@ -33,6 +27,32 @@ async fn main() -> anyhow::Result<()> {
     // Load config (optional — server works with defaults)
     let cfg = load_config_or_default(&cli.config)?;
 
+    // Init tracing, exporting spans when configured
+    let (otel_layer, tracer_provider) = if cfg.tracing.enable {
+        let (layer, provider) = telemetry::otlp_layer(&cfg.tracing)?;
+        (Some(layer), Some(provider))
+    } else {
+        (None, None)
+    };
+    tracing_subscriber::registry()
+        .with(
+            EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| EnvFilter::new("rusuh=info,tower_http=debug")),
+        )
+        .with(tracing_subscriber::fmt::layer())
+        .with(otel_layer)
+        .init();
+
+    let result = run(cli, cfg).await;
+    if let Some(provider) = tracer_provider {
+        if let Err(e) = provider.shutdown() {
+            eprintln!("failed to flush spans: {e}");
+        }
+    }
+    result
+}
+
+async fn run(cli: Cli, cfg: config::Config) -> anyhow::Result<()> {
     match cli.command.unwrap_or(Commands::Serve) {
         Commands::Serve => serve(cfg).await?,
         Commands::Login => {
@@ -41,7 +61,7 @@ async fn main() -> anyhow::Result<()> {
         Commands::AntigravityLogin => {
             let auth_dir = resolve_auth_dir(&cfg);
             let store = auth::store::FileTokenStore::new(&auth_dir);
-            auth::antigravity_login::login(&store).await?
+            auth::antigravity_login::login(&store, cfg.proxy_url.as_deref()).await?
         }
         Commands::KiroLogin {
             provider,
@@ -51,10 +71,12 @@ async fn main() -> anyhow::Result<()> {
             let store = auth::store::FileTokenStore::new(&auth_dir);
 
             match provider.as_str() {
-                "google" | "github" => auth::kiro_login::login(&store, &provider).await?,
+                "google" | "github" => {
+                    auth::kiro_login::login(&store, &provider, cfg.proxy_url.as_deref()).await?
+                }
                 "sso" => {
                     if let Some(url) = start_url {
-                        auth::kiro_login::login_sso(&store, &url).await?
+                        auth::kiro_login::login_sso(&store, &url, cfg.proxy_url.as_deref()).await?
                     } else {
                         eprintln!("Error: --start-url is required for SSO login");
                         std::process::exit(1);
@@ -72,7 +94,7 @@ async fn main() -> anyhow::Result<()> {
         Commands::CodexLogin => {
             let auth_dir = resolve_auth_dir(&cfg);
             let store = auth::store::FileTokenStore::new(&auth_dir);
-            let saved = auth::codex_login::login(&store).await?;
+            let saved = auth::codex_login::login(&store, cfg.proxy_url.as_deref()).await?;
             println!("\n✓ Codex credentials saved to: {}", saved.display());
         }
         Commands::CodexDeviceLogin => {
@@ -81,8 +103,11 @@ async fn main() -> anyhow::Result<()> {
         Commands::GithubCopilotLogin => {
             let auth_dir = resolve_auth_dir(&cfg);
             let store = auth::store::FileTokenStore::new(&auth_dir);
-            let saved = auth::github_copilot_login::login(&store).await?;
-            println!("\n✓ GitHub Copilot credentials saved to: {}", saved.display());
+            let saved = auth::github_copilot_login::login(&store, cfg.proxy_url.as_deref()).await?;
+            println!(
+                "\n✓ GitHub Copilot credentials saved to: {}",
+                saved.display()
+            );
         }
         Commands::ClaudeLogin => {
             println!("Claude Code login not yet implemented (milestone 2)");
@@ -115,7 +140,7 @@ async fn run_codex_device_login(cfg: &config::Config) -> anyhow::Result<()> {
 
     let auth_dir = resolve_auth_dir(cfg);
     let store = auth::store::FileTokenStore::new(&auth_dir);
-    let login = auth::codex_device::device_login(&store).await?;
+    let login = auth::codex_device::device_login(&store, cfg.proxy_url.as_deref()).await?;
     println!(
         "\n✓ Codex device credentials saved to: {}",
         login.saved_path.display()
@@ -150,8 +175,8 @@ fn ensure_api_keys(cfg: &mut config::Config) {
     let real_keys: Vec<_> = cfg
         .api_keys
         .iter()
-        .filter(|k| {
-            let k = k.trim();
+        .filter(|entry| {
+            let k = entry.key.trim();
             !k.is_empty() && !k.starts_with("your-api-key") && k != "changeme"
         })
         .cloned()
@@ -173,18 +198,23 @@ fn ensure_api_keys(cfg: &mut config::Config) {
     println!("  ║  Add to config.yaml under `api-keys:` to persist.           ║");
     println!("  ╚══════════════════════════════════════════════════════════════╝");
     println!();
-    cfg.api_keys = vec![key];
+    cfg.api_keys = vec![key.into()];
 }
 
 async fn serve(mut cfg: config::Config) -> anyhow::Result<()> {
+    config::validate_model_routes(&cfg.model_routes)?;
     let addr = cfg.listen_addr();
+    let tls_config = cfg.tls.clone();
+    let persist_state = cfg.persist_state.clone();
+    let usage_ledger = cfg.usage_ledger.clone();
     let auth_dir = resolve_auth_dir(&cfg);
     // Auto-generate API key if none configured
     ensure_api_keys(&mut cfg);
     info!("auth directory: {}", auth_dir.display());
 
     // Load accounts from auth-dir
-    let account_mgr = Arc::new(AccountManager::with_dir(&auth_dir));
+    let account_mgr =
+        Arc::new(AccountManager::with_dir(&auth_dir).with_proxy_url(cfg.proxy_url.clone()));
     if let Err(e) = account_mgr.reload().await {
         tracing::warn!("failed to load accounts: {e}");
     }
@@ -192,8 +222,10 @@ async fn serve(mut cfg: config::Config) -> anyhow::Result<()> {
     // Build model registry and Kiro runtime before providers so providers can share them.
     let model_registry = Arc::new(providers::model_registry::ModelRegistry::new());
     let mut kiro_runtime = proxy::KiroRuntimeState::default();
-    match auth::kiro_runtime::KiroUsageChecker::new("https://codewhisperer.us-east-1.amazonaws.com")
-    {
+    match auth::kiro_runtime::KiroUsageChecker::new_with_proxy(
+        "https://codewhisperer.us-east-1.amazonaws.com",
+        cfg.proxy_url.as_deref(),
+    ) {
         Ok(checker) => {
             kiro_runtime.quota_checker = Arc::new(checker);
         }
@@ -204,6 +236,19 @@ async fn serve(mut cfg: config::Config) -> anyhow::Result<()> {
 
     let mut state = proxy::ProxyState::new(cfg, account_mgr, model_registry, 0);
     state.kiro_runtime = kiro_runtime;
+    if usage_ledger.enable {
+        let path = auth_dir.join(proxy::usage_ledger::USAGE_FILE_NAME);
+        match proxy::usage_ledger::UsageLedger::open(&path).await {
+            Ok(ledger) => state.usage_ledger = ledger,
+            Err(e) => warn!("usage ledger disabled: {e}"),
+        }
+        // Keep daily key budgets across restarts.
+        state.key_budgets.seed_from_ledger(
+            &state.config.read().await.api_keys,
+            &state.usage_ledger,
+            chrono::Utc::now(),
+        );
+    }
     let state = Arc::new(state);
 
     match state.rebuild_runtime_snapshot().await {
@@ -223,22 +268,102 @@ async fn serve(mut cfg: config::Config) -> anyhow::Result<()> {
         }
     }
 
-    info!("Rusuh starting on http://{}", addr);
+    let routes = state.config.read().await.model_routes.clone();
+    for target in state.model_registry.unknown_route_targets(&routes).await {
+        warn!("model-routes target {target} is not served by any registered client");
+    }
+
+    // Restore cooldowns and sticky sessions now that the accounts they refer to exist.
+    let state_path = persist_state
+        .enable
+        .then(|| auth_dir.join(proxy::state_file::STATE_FILE_NAME));
+    if let Some(path) = &state_path {
+        match proxy::state_file::load(path).await {
+            Ok(Some(saved)) => {
+                state.restore_persisted_state(saved).await;
+                info!("restored routing state from {}", path.display());
+            }
+            Ok(None) => {}
+            Err(e) => warn!("ignoring saved routing state: {e}"),
+        }
+        proxy::state_file::spawn_writer(state.clone(), path.clone(), persist_state.interval());
+    }
+
+    let scheme = if tls_config.enable { "https" } else { "http" };
+    info!("Rusuh starting on {}://{}", scheme, addr);
 
     let app = router::build_router(state.clone())
         .layer(axum::middleware::from_fn_with_state(
-            state,
+            state.clone(),
             middleware::auth::api_key_auth,
         ))
-        .layer(tower_http::trace::TraceLayer::new_for_http());
+        .layer(
+            tower_http::trace::TraceLayer::new_for_http()
+                .make_span_with(telemetry::request_span::<axum::body::Body>),
+        );
+
+    let server = async {
+        if tls_config.enable {
+            let rustls_config = tls::load(&tls_config).await?;
+            tls::spawn_reload_watcher(rustls_config.clone(), tls_config, tls::RELOAD_POLL_INTERVAL);
+
+            let socket_addr = tokio::net::lookup_host(&addr)
+                .await?
+                .next()
+                .ok_or_else(|| anyhow::anyhow!("could not resolve listen address {addr}"))?;
+            info!("Listening on {} (tls)", socket_addr);
+            axum_server::bind_rustls(socket_addr, rustls_config)
+                .serve(app.into_make_service())
+                .await?;
+        } else {
+            let listener = tokio::net::TcpListener::bind(&addr).await?;
+            info!("Listening on {}", addr);
+            axum::serve(listener, app).await?;
+        }
+        anyhow::Ok(())
+    };
+
+    tokio::select! {
+        result = server => result?,
+        () = shutdown_signal() => info!("shutting down"),
+    }
 
-    let listener = tokio::net::TcpListener::bind(&addr).await?;
-    info!("Listening on {}", addr);
-    axum::serve(listener, app).await?;
+    state.usage_ledger.close().await;
+
+    if let Some(path) = &state_path {
+        if let Err(e) = proxy::state_file::save(&state, path).await {
+            warn!("failed to save routing state: {e}");
+        }
+    }
 
     Ok(())
 }
 
+/// Resolves on Ctrl-C or, on Unix, SIGTERM.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if tokio::signal::ctrl_c().await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(_) => std::future::pending::<()>().await,
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        () = ctrl_c => {}
+        () = terminate => {}
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::{load_config_or_default, run_codex_device_login_with_base_url, serve};
@@ -388,7 +513,7 @@ mod tests {
             auth_dir: temp.path().to_string_lossy().to_string(),
             host: "127.0.0.1".to_string(),
             port: 0,
-            api_keys: vec!["rsk-test".to_string()],
+            api_keys: vec!["rsk-test".into()],
             ..Default::default()
         };
 
@@ -0,0 +1,321 @@
+//! Prometheus metrics served on `/metrics`.
+//!
+//! Request, retry, latency and token metrics are updated as requests finish.
+//! Balancer counters, cooldowns and quota marks live with their owners and are
+//! copied into gauges on each scrape.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use once_cell::sync::Lazy;
+use prometheus::{
+    Encoder, GaugeVec, HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Opts, Registry,
+    TextEncoder,
+};
+
+use crate::proxy::usage::TokenUsage;
+use crate::proxy::ProxyState;
+
+/// Token refreshes that failed, by provider type. Process-wide because providers
+/// refresh their own tokens without a handle on the proxy state.
+static OAUTH_REFRESH_FAILURES: Lazy<IntCounterVec> = Lazy::new(|| {
+    IntCounterVec::new(
+        Opts::new(
+            "rusuh_oauth_refresh_failures_total",
+            "OAuth token refreshes that failed",
+        ),
+        &["provider"],
+    )
+    .expect("valid metric")
+});
+
+/// Count a failed token refresh for `provider`.
+pub fn oauth_refresh_failed(provider: &str) {
+    OAUTH_REFRESH_FAILURES.with_label_values(&[provider]).inc();
+}
+
+const LATENCY_BUCKETS: &[f64] = &[0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0];
+const STREAM_BUCKETS: &[f64] = &[0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0, 600.0];
+
+pub struct Metrics {
+    registry: Registry,
+    requests: IntCounterVec,
+    request_duration: HistogramVec,
+    retries: IntCounterVec,
+    failovers: IntCounterVec,
+    time_to_first_token: HistogramVec,
+    stream_duration: HistogramVec,
+    tokens: IntCounterVec,
+    balancer_requests: IntGaugeVec,
+    balancer_in_flight: IntGaugeVec,
+    balancer_latency: GaugeVec,
+    balancer_hedges: IntGaugeVec,
+    balancer_hedge_wins: IntGaugeVec,
+    cooldowns: GaugeVec,
+    quota_exceeded: GaugeVec,
+    /// Keeps concurrent scrapes from interleaving their gauge updates
+    scrape: Mutex<()>,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let registry = Registry::new();
+        let counter = |name: &str, help: &str, labels: &[&str]| {
+            let metric = IntCounterVec::new(Opts::new(name, help), labels).expect("valid metric");
+            registry
+                .register(Box::new(metric.clone()))
+                .expect("unique metric");
+            metric
+        };
+        let histogram = |name: &str, help: &str, labels: &[&str], buckets: &[f64]| {
+            let opts = HistogramOpts::new(name, help).buckets(buckets.to_vec());
+            let metric = HistogramVec::new(opts, labels).expect("valid metric");
+            registry
+                .register(Box::new(metric.clone()))
+                .expect("unique metric");
+            metric
+        };
+        let int_gauge = |name: &str, help: &str, labels: &[&str]| {
+            let metric = IntGaugeVec::new(Opts::new(name, help), labels).expect("valid metric");
+            registry
+                .register(Box::new(metric.clone()))
+                .expect("unique metric");
+            metric
+        };
+        let gauge = |name: &str, help: &str, labels: &[&str]| {
+            let metric = GaugeVec::new(Opts::new(name, help), labels).expect("valid metric");
+            registry
+                .register(Box::new(metric.clone()))
+                .expect("unique metric");
+            metric
+        };
+        let candidate = &["provider", "client_id"];
+        let account_model = &["client_id", "model"];
+
+        let metrics = Self {
+            requests: counter(
+                "rusuh_requests_total",
+                "Chat requests by route, served model, provider and status",
+                &["route", "model", "provider", "status"],
+            ),
+            request_duration: histogram(
+                "rusuh_request_duration_seconds",
+                "Time from arrival until the reply or stream was complete",
+                &["route"],
+                LATENCY_BUCKETS,
+            ),
+            retries: counter(
+                "rusuh_upstream_retries_total",
+                "Repeated calls to the same account after a transient error",
+                &["provider", "model"],
+            ),
+            failovers: counter(
+                "rusuh_failovers_total",
+                "Calls moved on to another account after one failed",
+                &["model"],
+            ),
+            time_to_first_token: histogram(
+                "rusuh_time_to_first_token_seconds",
+                "Time from arrival until a stream's first content",
+                &["provider", "model"],
+                LATENCY_BUCKETS,
+            ),
+            stream_duration: histogram(
+                "rusuh_stream_duration_seconds",
+                "Time from arrival until a stream ended",
+                &["provider", "model"],
+                STREAM_BUCKETS,
+            ),
+            tokens: counter(
+                "rusuh_tokens_total",
+                "Tokens reported by upstream replies, by kind (prompt, completion, cached)",
+                &["provider", "model", "kind"],
+            ),
+            balancer_requests: int_gauge(
+                "rusuh_balancer_requests",
+                "Requests the balancer started on each candidate since the accounts were loaded",
+                candidate,
+            ),
+            balancer_in_flight: int_gauge(
+                "rusuh_balancer_in_flight",
+                "Requests currently in flight on each candidate",
+                candidate,
+            ),
+            balancer_latency: gauge(
+                "rusuh_balancer_latency_ewma_seconds",
+                "Moving average time to first token of each candidate",
+                candidate,
+            ),
+            balancer_hedges: int_gauge(
+                "rusuh_balancer_hedges",
+                "Hedged calls launched on each candidate",
+                candidate,
+            ),
+            balancer_hedge_wins: int_gauge(
+                "rusuh_balancer_hedge_wins",
+                "Hedged calls on each candidate that answered first",
+                candidate,
+            ),
+            cooldowns: gauge(
+                "rusuh_cooldown_remaining_seconds",
+                "Seconds until an account may serve a model again after a cooldown",
+                account_model,
+            ),
+            quota_exceeded: gauge(
+                "rusuh_quota_exceeded_remaining_seconds",
+                "Seconds until an account's exhausted quota for a model resets",
+                account_model,
+            ),
+            registry: registry.clone(),
+            scrape: Mutex::new(()),
+        };
+        registry
+            .register(Box::new(OAUTH_REFRESH_FAILURES.clone()))
+            .expect("unique metric");
+        metrics
+    }
+
+    /// Count a finished chat request. `provider` is empty when no account answered.
+    pub fn record_request(
+        &self,
+        route: &str,
+        model: &str,
+        provider: &str,
+        status: u16,
+        elapsed: Duration,
+    ) {
+        self.requests
+            .with_label_values(&[route, model, provider, &status.to_string()])
+            .inc();
+        self.request_duration
+            .with_label_values(&[route])
+            .observe(elapsed.as_secs_f64());
+    }
+
+    pub fn record_retry(&self, provider: &str, model: &str) {
+        self.retries.with_label_values(&[provider, model]).inc();
+    }
+
+    pub fn record_failover(&self, model: &str) {
+        self.failovers.with_label_values(&[model]).inc();
+    }
+
+    pub fn record_first_token(&self, provider: &str, model: &str, elapsed: Duration) {
+        self.time_to_first_token
+            .with_label_values(&[provider, model])
+            .observe(elapsed.as_secs_f64());
+    }
+
+    pub fn record_stream(&self, provider: &str, model: &str, elapsed: Duration) {
+        self.stream_duration
+            .with_label_values(&[provider, model])
+            .observe(elapsed.as_secs_f64());
+    }
+
+    pub fn record_tokens(&self, provider: &str, model: &str, usage: TokenUsage) {
+        for (kind, count) in [
+            ("prompt", usage.prompt),
+            ("completion", usage.completion),
+            ("cached", usage.cached),
+        ] {
+            self.tokens
+                .with_label_values(&[provider, model, kind])
+                .inc_by(count);
+        }
+    }
+
+    /// Refresh the scrape-time gauges from `state` and encode every metric in the
+    /// Prometheus text format.
+    pub async fn render(&self, state: &ProxyState) -> String {
+        let runtime_snapshot = state.current_runtime_snapshot().await;
+        let candidates: Vec<_> = runtime_snapshot
+            .providers()
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, provider)| {
+                let stats = runtime_snapshot.balancer().stats(idx)?;
+                Some((
+                    provider.provider_type().to_string(),
+                    provider.client_id().to_string(),
+                    stats,
+                ))
+            })
+            .collect();
+        let now = Instant::now();
+        let cooldowns: Vec<_> = state
+            .kiro_runtime
+            .cooldown
+            .read()
+            .await
+            .active_entries(now)
+            .map(|(auth_id, model_id, entry)| {
+                (
+                    auth_id.to_string(),
+                    model_id.to_string(),
+                    entry.expires_at.saturating_duration_since(now),
+                )
+            })
+            .collect();
+        let quotas = state.model_registry.active_quota_marks().await;
+
+        let _scrape = self.scrape.lock().unwrap();
+        for gauge in [
+            &self.balancer_requests,
+            &self.balancer_in_flight,
+            &self.balancer_hedges,
+            &self.balancer_hedge_wins,
+        ] {
+            gauge.reset();
+        }
+        for gauge in [
+            &self.balancer_latency,
+            &self.cooldowns,
+            &self.quota_exceeded,
+        ] {
+            gauge.reset();
+        }
+        for (provider, client_id, stats) in &candidates {
+            let labels = [provider.as_str(), client_id.as_str()];
+            let as_gauge = |count: u64| i64::try_from(count).unwrap_or(i64::MAX);
+            self.balancer_requests
+                .with_label_values(&labels)
+                .set(as_gauge(stats.requests));
+            self.balancer_in_flight
+                .with_label_values(&labels)
+                .set(as_gauge(stats.in_flight));
+            self.balancer_hedges
+                .with_label_values(&labels)
+                .set(as_gauge(stats.hedges));
+            self.balancer_hedge_wins
+                .with_label_values(&labels)
+                .set(as_gauge(stats.hedge_wins));
+            if let Some(latency_ms) = stats.ewma_latency_ms {
+                self.balancer_latency
+                    .with_label_values(&labels)
+                    .set(latency_ms / 1000.0);
+            }
+        }
+        for (client_id, model, remaining) in &cooldowns {
+            self.cooldowns
+                .with_label_values(&[client_id, model])
+                .set(remaining.as_secs_f64());
+        }
+        for mark in &quotas {
+            self.quota_exceeded
+                .with_label_values(&[&mark.client_id, &mark.model_id])
+                .set(mark.until.saturating_duration_since(now).as_secs_f64());
+        }
+
+        let mut buffer = Vec::new();
+        if let Err(e) = TextEncoder::new().encode(&self.registry.gather(), &mut buffer) {
+            tracing::warn!("failed to encode metrics: {e}");
+        }
+        String::from_utf8(buffer).unwrap_or_default()
+    }
+}
@@ -15,19 +15,23 @@ use crate::proxy::ProxyState;
 
 /// Axum middleware that validates API key from Bearer token or x-api-key header.
 ///
+/// An accepted key's [`crate::config::ApiKeyEntry`] is added to the request extensions so
+/// handlers can enforce its scope. Expired keys are rejected here.
+///
 /// Skips auth when:
 /// - `api-keys` config is empty (auth disabled)
-/// - Path is `/health`
+/// - Path is `/health`, or `/metrics` (gated by the management key instead)
 /// - Path starts with `/v0/management/`
 pub async fn api_key_auth(
     state: axum::extract::State<Arc<ProxyState>>,
-    req: Request,
+    mut req: Request,
     next: Next,
 ) -> Response {
     let path = req.uri().path();
 
-    // Skip auth for health, management, dashboard read API, and OAuth callbacks
+    // Skip auth for health, metrics, management, dashboard read API, and OAuth callbacks
     if path == "/health"
+        || path == "/metrics"
         || path.starts_with("/v0/management")
         || path.starts_with("/dashboard")
         || path.ends_with("/callback")
@@ -36,24 +40,31 @@ pub async fn api_key_auth(
     }
 
     // If no API keys configured, auth is disabled
-    let api_keys = {
+    let entry = {
         let config = state.config.read().await;
-        config.api_keys.clone()
+        if config.api_keys.is_empty() {
+            None
+        } else {
+            // Extract key from Authorization: Bearer <key> or x-api-key header
+            match extract_api_key(&req).and_then(|key| config.api_key(&key).cloned()) {
+                Some(entry) => Some(entry),
+                None => return unauthorized_response(INVALID_KEY_MESSAGE, "invalid_api_key"),
+            }
+        }
     };
 
-    if api_keys.is_empty() {
-        return next.run(req).await;
-    }
-
-    // Extract key from Authorization: Bearer <key> or x-api-key header
-    let provided_key = extract_api_key(&req);
-
-    match provided_key {
-        Some(key) if api_keys.iter().any(|k| k == &key) => next.run(req).await,
-        _ => unauthorized_response(),
+    if let Some(entry) = entry {
+        if entry.is_expired(chrono::Utc::now()) {
+            return unauthorized_response("API key expired.", "expired_api_key");
+        }
+        req.extensions_mut().insert(entry);
     }
+    next.run(req).await
 }
 
+const INVALID_KEY_MESSAGE: &str =
+    "Invalid API key. Provide a valid key via Authorization: Bearer <key> or x-api-key header.";
+
 /// Extract API key from request headers.
 /// Checks `Authorization: Bearer <key>` first, then `x-api-key`.
 fn extract_api_key(req: &Request) -> Option<String> {
@@ -91,13 +102,13 @@ fn extract_api_key(req: &Request) -> Option<String> {
 }
 
 /// Return 401 Unauthorized in OpenAI-compatible error format.
-fn unauthorized_response() -> Response {
+fn unauthorized_response(message: &str, code: &str) -> Response {
     let body = Json(json!({
         "error": {
-            "message": "Invalid API key. Provide a valid key via Authorization: Bearer <key> or x-api-key header.",
+            "message": message,
             "type": "invalid_request_error",
             "param": null,
-            "code": "invalid_api_key"
+            "code": code
         }
     }));
 
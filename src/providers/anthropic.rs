@@ -0,0 +1,543 @@
+//! OpenAI chat completion ↔ Anthropic Messages translation shared by every provider
+//! that talks to an Anthropic-shaped upstream (the `claude-api-key` provider and Zed's
+//! anthropic models).
+
+use std::collections::HashMap;
+
+use serde_json::{json, Value};
+
+use crate::models::{
+    ChatCompletionRequest, ChatCompletionResponse, ChatMessage, Choice, MessageContent, Usage,
+};
+use crate::providers::api_key;
+use crate::providers::thinking;
+
+/// `max_tokens` sent when the client sets none, and the reply headroom kept on top
+/// of a thinking budget.
+pub const DEFAULT_MAX_TOKENS: u32 = 8192;
+
+/// Translate an OpenAI chat completion request into an Anthropic Messages body.
+pub fn chat_request_to_anthropic(
+    request: &ChatCompletionRequest,
+    model: &str,
+    stream: bool,
+) -> Value {
+    let mut system = Vec::new();
+    let mut messages: Vec<Value> = Vec::new();
+
+    for message in &request.messages {
+        match message.role.as_str() {
+            "system" | "developer" => {
+                let text = api_key::content_text(&message.content);
+                if !text.is_empty() {
+                    system.push(text);
+                }
+            }
+            "tool" => {
+                let block = json!({
+                    "type": "tool_result",
+                    "tool_use_id": message.tool_call_id.clone().unwrap_or_default(),
+                    "content": api_key::content_text(&message.content),
+                });
+                push_blocks(&mut messages, "user", vec![block]);
+            }
+            role => {
+                let role = if role == "assistant" {
+                    "assistant"
+                } else {
+                    "user"
+                };
+                let mut blocks = content_blocks(&message.content);
+                for call in message.tool_calls.iter().flatten() {
+                    let function = call.get("function");
+                    let input = match function.and_then(|function| function.get("arguments")) {
+                        Some(Value::String(raw)) if !raw.trim().is_empty() => {
+                            serde_json::from_str(raw).unwrap_or_else(|_| json!({}))
+                        }
+                        Some(value @ Value::Object(_)) => value.clone(),
+                        _ => json!({}),
+                    };
+                    blocks.push(json!({
+                        "type": "tool_use",
+                        "id": call.get("id").and_then(Value::as_str).unwrap_or_default(),
+                        "name": function
+                            .and_then(|function| function.get("name"))
+                            .and_then(Value::as_str)
+                            .unwrap_or_default(),
+                        "input": input,
+                    }));
+                }
+                if !blocks.is_empty() {
+                    push_blocks(&mut messages, role, blocks);
+                }
+            }
+        }
+    }
+
+    let mut body = json!({
+        "model": model,
+        "messages": messages,
+        "max_tokens": request.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS),
+        "stream": stream,
+    });
+
+    let thinking = thinking::requested_reasoning(request).map(|reasoning| {
+        thinking::anthropic_thinking(&reasoning, thinking::static_support(model).as_ref())
+    });
+    if let Some(budget) = thinking
+        .as_ref()
+        .and_then(|thinking| thinking["budget_tokens"].as_u64())
+    {
+        // max_tokens covers thinking plus reply, and extended thinking fixes temperature at 1.
+        if body["max_tokens"].as_u64().unwrap_or(0) <= budget {
+            body["max_tokens"] = json!(budget + u64::from(DEFAULT_MAX_TOKENS));
+        }
+    }
+
+    if !system.is_empty() {
+        body["system"] = json!(system.join("\n\n"));
+    }
+    if let Some(temperature) = request.temperature.filter(|_| {
+        thinking
+            .as_ref()
+            .is_none_or(|thinking| thinking["type"] == "disabled")
+    }) {
+        body["temperature"] = sampling_value(temperature);
+    }
+    if let Some(top_p) = request.top_p {
+        body["top_p"] = sampling_value(top_p);
+    }
+    if let Some(stop) = &request.stop {
+        let stops = stop_sequences(stop);
+        if !stops.is_empty() {
+            body["stop_sequences"] = Value::Array(stops);
+        }
+    }
+
+    if let Some(tools) = &request.tools {
+        let tools = tools
+            .iter()
+            .filter_map(|tool| {
+                let function = tool.get("function")?;
+                Some(json!({
+                    "name": function.get("name")?,
+                    "description": function.get("description").cloned().unwrap_or(json!("")),
+                    "input_schema": function
+                        .get("parameters")
+                        .cloned()
+                        .unwrap_or_else(|| json!({"type": "object", "properties": {}})),
+                }))
+            })
+            .collect::<Vec<_>>();
+        if !tools.is_empty() {
+            body["tools"] = json!(tools);
+        }
+    }
+
+    if let Some(choice) = request
+        .tool_choice
+        .as_ref()
+        .and_then(openai_tool_choice_to_anthropic)
+    {
+        body["tool_choice"] = choice;
+    }
+    if let Some(thinking) = thinking {
+        body["thinking"] = thinking;
+    }
+
+    body
+}
+
+/// Sampling parameters go out in their shortest decimal form, since widening the
+/// `f32` directly would send `0.7` as `0.699999988079071`.
+fn sampling_value(value: f32) -> Value {
+    value
+        .to_string()
+        .parse::<f64>()
+        .map_or_else(|_| json!(value), Value::from)
+}
+
+/// Normalize an OpenAI `stop` value (a string or an array) into Anthropic
+/// `stop_sequences`, stringifying scalars and dropping nested values.
+pub fn stop_sequences(stop: &Value) -> Vec<Value> {
+    match stop {
+        Value::String(text) => vec![Value::String(text.clone())],
+        Value::Array(items) => items
+            .iter()
+            .filter_map(|item| match item {
+                Value::String(text) => Some(Value::String(text.clone())),
+                Value::Number(_) | Value::Bool(_) | Value::Null => {
+                    Some(Value::String(item.to_string()))
+                }
+                _ => None,
+            })
+            .collect(),
+        Value::Number(_) | Value::Bool(_) | Value::Null => {
+            vec![Value::String(stop.to_string())]
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Append blocks to the conversation, merging consecutive turns of the same role
+/// since Anthropic requires strict user/assistant alternation.
+fn push_blocks(messages: &mut Vec<Value>, role: &str, blocks: Vec<Value>) {
+    if let Some(last) = messages.last_mut() {
+        if last["role"].as_str() == Some(role) {
+            if let Some(content) = last["content"].as_array_mut() {
+                content.extend(blocks);
+                return;
+            }
+        }
+    }
+    messages.push(json!({"role": role, "content": blocks}));
+}
+
+fn content_blocks(content: &MessageContent) -> Vec<Value> {
+    match content {
+        MessageContent::Text(text) if text.is_empty() => Vec::new(),
+        MessageContent::Text(text) => vec![json!({"type": "text", "text": text})],
+        MessageContent::Parts(parts) => parts
+            .iter()
+            .filter_map(|part| match part.part_type.as_str() {
+                "text" => part
+                    .text
+                    .as_ref()
+                    .map(|text| json!({"type": "text", "text": text})),
+                "image_url" => part.image_url.as_ref().map(|image| {
+                    match image
+                        .url
+                        .strip_prefix("data:")
+                        .and_then(|rest| rest.split_once(";base64,"))
+                    {
+                        Some((media_type, data)) => json!({
+                            "type": "image",
+                            "source": {"type": "base64", "media_type": media_type, "data": data},
+                        }),
+                        None => json!({
+                            "type": "image",
+                            "source": {"type": "url", "url": image.url},
+                        }),
+                    }
+                }),
+                _ => None,
+            })
+            .collect(),
+    }
+}
+
+fn openai_tool_choice_to_anthropic(choice: &Value) -> Option<Value> {
+    match choice {
+        Value::String(choice) => match choice.as_str() {
+            "auto" => Some(json!({"type": "auto"})),
+            "required" => Some(json!({"type": "any"})),
+            "none" => Some(json!({"type": "none"})),
+            _ => None,
+        },
+        Value::Object(_) => {
+            let name = choice.get("function")?.get("name")?.as_str()?;
+            Some(json!({"type": "tool", "name": name}))
+        }
+        _ => None,
+    }
+}
+
+fn finish_reason(stop_reason: Option<&str>) -> &'static str {
+    match stop_reason {
+        Some("max_tokens") => "length",
+        Some("tool_use") => "tool_calls",
+        _ => "stop",
+    }
+}
+
+/// Translate an Anthropic Messages response into an OpenAI chat completion.
+pub fn anthropic_to_chat_response(body: &Value, model: &str) -> ChatCompletionResponse {
+    let mut text = String::new();
+    let mut reasoning = String::new();
+    let mut tool_calls = Vec::new();
+
+    for block in body["content"].as_array().into_iter().flatten() {
+        match block["type"].as_str() {
+            Some("text") => text.push_str(block["text"].as_str().unwrap_or_default()),
+            Some("thinking") => reasoning.push_str(block["thinking"].as_str().unwrap_or_default()),
+            Some("tool_use") => tool_calls.push(json!({
+                "id": block["id"],
+                "type": "function",
+                "function": {
+                    "name": block["name"],
+                    "arguments": block.get("input").cloned().unwrap_or_else(|| json!({})).to_string(),
+                }
+            })),
+            _ => {}
+        }
+    }
+
+    let prompt_tokens = body["usage"]["input_tokens"].as_u64().unwrap_or(0) as u32;
+    let completion_tokens = body["usage"]["output_tokens"].as_u64().unwrap_or(0) as u32;
+
+    ChatCompletionResponse {
+        id: body["id"]
+            .as_str()
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("chatcmpl-{}", uuid::Uuid::new_v4())),
+        object: "chat.completion".to_string(),
+        created: chrono::Utc::now().timestamp(),
+        model: model.to_string(),
+        choices: vec![Choice {
+            index: 0,
+            message: Some(ChatMessage {
+                role: "assistant".to_string(),
+                content: MessageContent::Text(text),
+                name: None,
+                tool_calls: (!tool_calls.is_empty()).then_some(tool_calls),
+                tool_call_id: None,
+                reasoning_content: (!reasoning.is_empty()).then_some(reasoning),
+            }),
+            delta: None,
+            finish_reason: Some(finish_reason(body["stop_reason"].as_str()).to_string()),
+        }],
+        usage: Some(Usage {
+            prompt_tokens,
+            completion_tokens,
+            total_tokens: prompt_tokens + completion_tokens,
+        }),
+    }
+}
+
+/// Incremental translator from Anthropic stream events to OpenAI chunk payloads.
+pub struct AnthropicStreamTranslator {
+    id: String,
+    model: String,
+    created: i64,
+    /// Anthropic content-block index → OpenAI tool_call index.
+    tool_indices: HashMap<u64, u64>,
+    input_tokens: u64,
+    output_tokens: u64,
+    finish_reason: Option<&'static str>,
+}
+
+impl AnthropicStreamTranslator {
+    pub fn new(model: impl Into<String>) -> Self {
+        Self {
+            id: format!("chatcmpl-{}", uuid::Uuid::new_v4()),
+            model: model.into(),
+            created: chrono::Utc::now().timestamp(),
+            tool_indices: HashMap::new(),
+            input_tokens: 0,
+            output_tokens: 0,
+            finish_reason: None,
+        }
+    }
+
+    fn chunk(&self, delta: Value, finish_reason: Option<&str>) -> Value {
+        json!({
+            "id": self.id,
+            "object": "chat.completion.chunk",
+            "created": self.created,
+            "model": self.model,
+            "choices": [{"index": 0, "delta": delta, "finish_reason": finish_reason}],
+        })
+    }
+
+    /// Translate one Anthropic `data:` payload into zero or more OpenAI chunk payloads.
+    pub fn push_data(&mut self, data: &str) -> Vec<String> {
+        let Ok(event) = serde_json::from_str::<Value>(data) else {
+            return Vec::new();
+        };
+
+        match event["type"].as_str() {
+            Some("message_start") => {
+                self.input_tokens = event["message"]["usage"]["input_tokens"]
+                    .as_u64()
+                    .unwrap_or(0);
+                vec![self
+                    .chunk(json!({"role": "assistant", "content": ""}), None)
+                    .to_string()]
+            }
+            Some("content_block_start") if event["content_block"]["type"] == "tool_use" => {
+                let block_index = event["index"].as_u64().unwrap_or(0);
+                let tool_index = self.tool_indices.len() as u64;
+                self.tool_indices.insert(block_index, tool_index);
+                let block = &event["content_block"];
+                vec![self
+                    .chunk(
+                        json!({"tool_calls": [{
+                            "index": tool_index,
+                            "id": block["id"],
+                            "type": "function",
+                            "function": {"name": block["name"], "arguments": ""},
+                        }]}),
+                        None,
+                    )
+                    .to_string()]
+            }
+            Some("content_block_delta") => {
+                let delta = &event["delta"];
+                match delta["type"].as_str() {
+                    Some("text_delta") => {
+                        vec![self
+                            .chunk(json!({"content": delta["text"]}), None)
+                            .to_string()]
+                    }
+                    Some("thinking_delta") => vec![self
+                        .chunk(json!({"reasoning_content": delta["thinking"]}), None)
+                        .to_string()],
+                    Some("input_json_delta") => {
+                        let block_index = event["index"].as_u64().unwrap_or(0);
+                        let Some(&tool_index) = self.tool_indices.get(&block_index) else {
+                            return Vec::new();
+                        };
+                        vec![self
+                            .chunk(
+                                json!({"tool_calls": [{
+                                    "index": tool_index,
+                                    "function": {"arguments": delta["partial_json"]},
+                                }]}),
+                                None,
+                            )
+                            .to_string()]
+                    }
+                    _ => Vec::new(),
+                }
+            }
+            Some("message_delta") => {
+                if let Some(output) = event["usage"]["output_tokens"].as_u64() {
+                    self.output_tokens = output;
+                }
+                self.finish_reason = Some(finish_reason(event["delta"]["stop_reason"].as_str()));
+                Vec::new()
+            }
+            Some("message_stop") => {
+                let mut chunk = self.chunk(json!({}), Some(self.finish_reason.unwrap_or("stop")));
+                chunk["usage"] = json!({
+                    "prompt_tokens": self.input_tokens,
+                    "completion_tokens": self.output_tokens,
+                    "total_tokens": self.input_tokens + self.output_tokens,
+                });
+                vec![chunk.to_string(), "[DONE]".to_string()]
+            }
+            Some("error") => {
+                let message = event["error"]["message"]
+                    .as_str()
+                    .unwrap_or("anthropic stream error");
+                vec![json!({"error": {"message": message, "type": "upstream_error"}}).to_string()]
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(messages: Value) -> ChatCompletionRequest {
+        serde_json::from_value(json!({
+            "model": "claude-sonnet-4-6",
+            "messages": messages,
+            "tools": [{"type": "function", "function": {
+                "name": "lookup",
+                "parameters": {"type": "object", "properties": {"q": {"type": "string"}}}
+            }}],
+            "tool_choice": "required",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn chat_request_maps_system_tools_and_tool_results() {
+        let body = chat_request_to_anthropic(
+            &request(json!([
+                {"role": "system", "content": "be brief"},
+                {"role": "user", "content": "weather?"},
+                {"role": "assistant", "content": "", "tool_calls": [{
+                    "id": "toolu_1", "type": "function",
+                    "function": {"name": "lookup", "arguments": "{\"q\":\"rain\"}"}
+                }]},
+                {"role": "tool", "tool_call_id": "toolu_1", "content": "sunny"},
+                {"role": "user", "content": "thanks"}
+            ])),
+            "claude-sonnet-4-6",
+            false,
+        );
+
+        assert_eq!(body["system"], "be brief");
+        assert_eq!(body["max_tokens"], DEFAULT_MAX_TOKENS);
+        assert_eq!(body["tool_choice"]["type"], "any");
+        assert_eq!(
+            body["tools"][0]["input_schema"]["properties"]["q"]["type"],
+            "string"
+        );
+
+        let messages = body["messages"].as_array().unwrap();
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[1]["content"][0]["type"], "tool_use");
+        assert_eq!(messages[1]["content"][0]["input"]["q"], "rain");
+        assert_eq!(messages[2]["role"], "user");
+        assert_eq!(messages[2]["content"][0]["type"], "tool_result");
+        assert_eq!(messages[2]["content"][1]["text"], "thanks");
+    }
+
+    #[test]
+    fn anthropic_response_maps_tool_use_and_usage() {
+        let response = anthropic_to_chat_response(
+            &json!({
+                "id": "msg_1",
+                "content": [
+                    {"type": "text", "text": "checking"},
+                    {"type": "tool_use", "id": "toolu_1", "name": "lookup", "input": {"q": "rain"}}
+                ],
+                "stop_reason": "tool_use",
+                "usage": {"input_tokens": 10, "output_tokens": 4}
+            }),
+            "claude-sonnet-4-6",
+        );
+
+        let choice = &response.choices[0];
+        assert_eq!(choice.finish_reason.as_deref(), Some("tool_calls"));
+        let message = choice.message.as_ref().unwrap();
+        assert_eq!(api_key::content_text(&message.content), "checking");
+        assert_eq!(
+            message.tool_calls.as_ref().unwrap()[0]["function"]["arguments"],
+            "{\"q\":\"rain\"}"
+        );
+        assert_eq!(response.usage.unwrap().total_tokens, 14);
+    }
+
+    #[test]
+    fn stream_translator_maps_events_to_openai_chunks() {
+        let mut translator = AnthropicStreamTranslator::new("claude-sonnet-4-6");
+        let events = [
+            json!({"type": "message_start", "message": {"usage": {"input_tokens": 5}}}),
+            json!({"type": "content_block_start", "index": 0, "content_block": {"type": "text", "text": ""}}),
+            json!({"type": "content_block_delta", "index": 0, "delta": {"type": "text_delta", "text": "Hi"}}),
+            json!({"type": "content_block_start", "index": 1, "content_block": {"type": "tool_use", "id": "toolu_1", "name": "lookup"}}),
+            json!({"type": "content_block_delta", "index": 1, "delta": {"type": "input_json_delta", "partial_json": "{}"}}),
+            json!({"type": "message_delta", "delta": {"stop_reason": "tool_use"}, "usage": {"output_tokens": 3}}),
+            json!({"type": "message_stop"}),
+        ];
+
+        let chunks = events
+            .iter()
+            .flat_map(|event| translator.push_data(&event.to_string()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(chunks.last().map(String::as_str), Some("[DONE]"));
+        let parsed = chunks[..chunks.len() - 1]
+            .iter()
+            .map(|chunk| serde_json::from_str::<Value>(chunk).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(parsed[1]["choices"][0]["delta"]["content"], "Hi");
+        assert_eq!(
+            parsed[2]["choices"][0]["delta"]["tool_calls"][0]["index"],
+            0
+        );
+        assert_eq!(
+            parsed[2]["choices"][0]["delta"]["tool_calls"][0]["id"],
+            "toolu_1"
+        );
+        let last = parsed.last().unwrap();
+        assert_eq!(last["choices"][0]["finish_reason"], "tool_calls");
+        assert_eq!(last["usage"]["total_tokens"], 8);
+    }
+}
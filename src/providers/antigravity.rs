@@ -6,12 +6,15 @@ use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use serde_json::{json, Value};
 use tokio::sync::RwLock;
-use tracing::{debug, info, warn};
+use tracing::{debug, info, warn, Instrument};
 
 use crate::auth::antigravity_login;
 use crate::auth::store::AuthRecord;
-use crate::error::{AppError, AppResult};
+use crate::error::{AppError, AppResult, UpstreamError};
 use crate::models::{ChatCompletionRequest, ChatCompletionResponse, ModelInfo};
+use crate::providers::http_client::{self, SendTraced};
+use crate::providers::retry_after;
+use crate::providers::thinking;
 use crate::providers::{BoxStream, Provider};
 
 // ── Constants ────────────────────────────────────────────────────────────────
@@ -81,12 +84,23 @@ pub struct AntigravityProvider {
 }
 
 impl AntigravityProvider {
-    pub fn new(record: AuthRecord) -> Self {
+    pub fn new(record: AuthRecord) -> AppResult<Self> {
+        Self::new_with_proxy(record, None)
+    }
+
+    /// Build with the global `proxy-url` as fallback for the account's own `proxy_url`.
+    pub fn new_with_proxy(record: AuthRecord, default_proxy_url: Option<&str>) -> AppResult<Self> {
+        let proxy_url = http_client::effective_proxy_url(record.proxy_url(), default_proxy_url);
+        let client = http_client::default_client("antigravity", proxy_url)?;
+        Ok(Self::with_client(record, client))
+    }
+
+    fn with_client(record: AuthRecord, client: reqwest::Client) -> Self {
         let auth_file_path = record.path.clone();
         let token = TokenState::from_record(&record);
         Self {
             record,
-            client: reqwest::Client::new(),
+            client,
             token: RwLock::new(token),
             auth_file_path,
         }
@@ -121,9 +135,18 @@ impl AntigravityProvider {
             REFRESH_SKEW_SECS
         );
 
+        let refresh_span = tracing::info_span!(
+            "token_refresh",
+            provider = "antigravity",
+            client_id = %self.record.id,
+        );
         let resp = antigravity_login::refresh_access_token(&self.client, &state.refresh_token)
+            .instrument(refresh_span)
             .await
-            .map_err(|e| AppError::Auth(format!("token refresh failed: {e}")))?;
+            .map_err(|e| {
+                crate::metrics::oauth_refresh_failed("antigravity");
+                AppError::Auth(format!("token refresh failed: {e}"))
+            })?;
 
         let now = Utc::now();
         let expires_in = resp.expires_in.unwrap_or(3599);
@@ -282,6 +305,12 @@ impl AntigravityProvider {
         if let Some(max) = req.max_tokens {
             gen_config["maxOutputTokens"] = json!(max);
         }
+        if let Some(reasoning) = thinking::requested_reasoning(req) {
+            gen_config["thinkingConfig"] = thinking::gemini_thinking_config(
+                &reasoning,
+                thinking::static_support(model).as_ref(),
+            );
+        }
         if gen_config.as_object().is_some_and(|o| !o.is_empty()) {
             request_body["generationConfig"] = gen_config;
         }
@@ -318,11 +347,16 @@ impl AntigravityProvider {
             .cloned()
             .unwrap_or_default();
 
-        // Concatenate text parts
+        // Concatenate text parts; `thought` parts are the model's reasoning
         let mut text = String::new();
+        let mut reasoning = String::new();
         for part in &parts {
             if let Some(t) = part["text"].as_str() {
-                text.push_str(t);
+                if part["thought"].as_bool().unwrap_or(false) {
+                    reasoning.push_str(t);
+                } else {
+                    text.push_str(t);
+                }
             }
         }
 
@@ -361,6 +395,7 @@ impl AntigravityProvider {
                     name: None,
                     tool_calls: None,
                     tool_call_id: None,
+                    reasoning_content: (!reasoning.is_empty()).then_some(reasoning),
                 }),
                 delta: None,
                 finish_reason: Some(finish_reason),
@@ -394,7 +429,7 @@ impl Provider for AntigravityProvider {
                 .header("Content-Type", "application/json")
                 .header("User-Agent", USER_AGENT)
                 .json(&json!({}))
-                .send()
+                .send_traced()
                 .await;
 
             let resp = match resp {
@@ -417,7 +452,7 @@ impl Provider for AntigravityProvider {
             let data: Value = resp
                 .json()
                 .await
-                .map_err(|e| AppError::Upstream(format!("parse models: {e}")))?;
+                .map_err(|e| UpstreamError::from_reqwest("parse models", &e))?;
 
             let models_obj = match data.get("models").and_then(|m| m.as_object()) {
                 Some(m) => m,
@@ -473,7 +508,7 @@ impl Provider for AntigravityProvider {
                 .header("User-Agent", USER_AGENT)
                 .header("Accept", "application/json")
                 .json(&payload)
-                .send()
+                .send_traced()
                 .await;
 
             let resp = match resp {
@@ -485,24 +520,27 @@ impl Provider for AntigravityProvider {
             };
 
             let status = resp.status();
+            let headers = resp.headers().clone();
             let body: Value = resp
                 .json()
                 .await
-                .map_err(|e| AppError::Upstream(format!("parse response: {e}")))?;
+                .map_err(|e| UpstreamError::from_reqwest("parse response", &e))?;
 
             if !status.is_success() {
-                return Err(AppError::Upstream(format!(
-                    "antigravity error ({}): {}",
-                    status, body
-                )));
+                return Err(upstream_error(
+                    "antigravity error",
+                    status,
+                    &headers,
+                    &body.to_string(),
+                ));
             }
 
             return Ok(self.translate_response(&body, &req.model));
         }
 
-        Err(AppError::Upstream(
-            "all antigravity base URLs failed".into(),
-        ))
+        Err(AppError::Upstream(UpstreamError::transport(
+            "all antigravity base URLs failed",
+        )))
     }
 
     async fn chat_completion_stream(&self, req: &ChatCompletionRequest) -> AppResult<BoxStream> {
@@ -519,7 +557,7 @@ impl Provider for AntigravityProvider {
                 .header("User-Agent", USER_AGENT)
                 .header("Accept", "text/event-stream")
                 .json(&payload)
-                .send()
+                .send_traced()
                 .await;
             let resp = match resp {
                 Ok(r) => r,
@@ -530,11 +568,14 @@ impl Provider for AntigravityProvider {
             };
             if !resp.status().is_success() {
                 let status = resp.status();
+                let headers = resp.headers().clone();
                 let body = resp.text().await.unwrap_or_default();
-                return Err(AppError::Upstream(format!(
-                    "antigravity stream error ({}): {}",
-                    status, body
-                )));
+                return Err(upstream_error(
+                    "antigravity stream error",
+                    status,
+                    &headers,
+                    &body,
+                ));
             }
             let id = format!("chatcmpl-{}", uuid::Uuid::new_v4());
             let created = chrono::Utc::now().timestamp();
@@ -545,14 +586,27 @@ impl Provider for AntigravityProvider {
                 transform,
             ));
         }
-        Err(AppError::Upstream(
-            "all antigravity stream base URLs failed".into(),
-        ))
+        Err(AppError::Upstream(UpstreamError::transport(
+            "all antigravity stream base URLs failed",
+        )))
     }
 }
 
 // ── Helpers ──────────────────────────────────────────────────────────────────
 
+/// Non-success upstream reply; 429s keep the Google `RetryInfo` delay for the registry.
+fn upstream_error(
+    context: &str,
+    status: reqwest::StatusCode,
+    headers: &reqwest::header::HeaderMap,
+    body: &str,
+) -> AppError {
+    UpstreamError::from_status(status, format!("{context} ({status}): {body}"))
+        .with_provider("antigravity")
+        .with_retry_after(retry_after::for_status(status, headers, body, Utc::now()))
+        .into()
+}
+
 /// Parse token expiry from auth record metadata.
 ///
 /// Checks (in order, matching Go CLIProxy `tokenExpiry()`):
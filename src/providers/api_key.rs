@@ -0,0 +1,297 @@
+//! Shared plumbing for providers backed by static API keys from config
+//! (`gemini-api-key`, `codex-api-key`, `claude-api-key`, `openai-compatibility`).
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use reqwest::{Client, RequestBuilder};
+
+use crate::config::{ModelEntry, ProviderKeyEntry};
+use crate::error::{AppError, AppResult, UpstreamError};
+use crate::models::{MessageContent, ModelInfo};
+use crate::providers::http_client;
+use crate::providers::model_info::ExtModelInfo;
+use crate::providers::retry_after;
+
+/// Model catalog for one API-key entry.
+///
+/// Exposed ids are the configured alias (or upstream name) namespaced by the entry
+/// prefix; `upstream_model` maps a routed id back to the name the upstream expects.
+#[derive(Debug, Clone, Default)]
+pub struct KeyModelCatalog {
+    prefix: Option<String>,
+    /// (exposed id without prefix, upstream model name)
+    models: Vec<(String, String)>,
+}
+
+impl KeyModelCatalog {
+    /// Build the catalog from the entry's `models`, falling back to `defaults` when the
+    /// entry lists none, and dropping anything matched by `excluded-models`.
+    pub fn from_entry(entry: &ProviderKeyEntry, defaults: Vec<ExtModelInfo>) -> Self {
+        Self::new(
+            entry.prefix.as_deref(),
+            &entry.models,
+            &entry.excluded_models,
+            defaults,
+        )
+    }
+
+    pub fn new(
+        prefix: Option<&str>,
+        configured: &[ModelEntry],
+        excluded: &[String],
+        defaults: Vec<ExtModelInfo>,
+    ) -> Self {
+        let models: Vec<(String, String)> = if configured.is_empty() {
+            defaults
+                .into_iter()
+                .map(|model| (model.id.clone(), model.id))
+                .collect()
+        } else {
+            configured
+                .iter()
+                .filter(|model| !model.name.trim().is_empty())
+                .map(|model| {
+                    let exposed = model
+                        .alias
+                        .as_deref()
+                        .map(str::trim)
+                        .filter(|alias| !alias.is_empty())
+                        .unwrap_or(model.name.trim());
+                    (exposed.to_string(), model.name.trim().to_string())
+                })
+                .collect()
+        };
+
+        let models = models
+            .into_iter()
+            .filter(|(exposed, upstream)| {
+                !excluded.iter().any(|pattern| {
+                    matches_model_pattern(pattern, exposed)
+                        || matches_model_pattern(pattern, upstream)
+                })
+            })
+            .collect();
+
+        Self {
+            prefix: prefix
+                .map(|prefix| prefix.trim().trim_matches('/'))
+                .filter(|prefix| !prefix.is_empty())
+                .map(str::to_string),
+            models,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.models.is_empty()
+    }
+
+    /// Namespace an upstream-reported model id under the entry prefix.
+    pub fn exposed_model_info(&self, upstream_id: &str, owned_by: &str) -> ModelInfo {
+        ModelInfo {
+            id: self.exposed_id(upstream_id),
+            object: "model".to_string(),
+            created: chrono::Utc::now().timestamp(),
+            owned_by: owned_by.to_string(),
+        }
+    }
+
+    fn exposed_id(&self, model: &str) -> String {
+        match &self.prefix {
+            Some(prefix) => format!("{prefix}/{model}"),
+            None => model.to_string(),
+        }
+    }
+
+    /// Models as listed to the registry and `/v1/models`.
+    pub fn model_infos(&self, owned_by: &str) -> Vec<ModelInfo> {
+        let now = chrono::Utc::now().timestamp();
+        self.models
+            .iter()
+            .map(|(exposed, _)| ModelInfo {
+                id: self.exposed_id(exposed),
+                object: "model".to_string(),
+                created: now,
+                owned_by: owned_by.to_string(),
+            })
+            .collect()
+    }
+
+    /// Resolve a routed model id (possibly prefixed / aliased) to the upstream model name.
+    pub fn upstream_model(&self, requested: &str) -> String {
+        let unprefixed = self
+            .prefix
+            .as_deref()
+            .and_then(|prefix| requested.strip_prefix(prefix))
+            .and_then(|rest| rest.strip_prefix('/'))
+            .unwrap_or(requested);
+
+        self.models
+            .iter()
+            .find(|(exposed, _)| exposed.eq_ignore_ascii_case(unprefixed))
+            .map(|(_, upstream)| upstream.clone())
+            .unwrap_or_else(|| unprefixed.to_string())
+    }
+}
+
+/// Match an `excluded-models` entry, which may use a leading and/or trailing `*`.
+fn matches_model_pattern(pattern: &str, model: &str) -> bool {
+    let pattern = pattern.trim().to_ascii_lowercase();
+    let model = model.to_ascii_lowercase();
+    match (pattern.strip_prefix('*'), pattern.strip_suffix('*')) {
+        _ if pattern == "*" => true,
+        (Some(rest), _) if rest.ends_with('*') => model.contains(rest.trim_end_matches('*')),
+        (Some(suffix), _) => model.ends_with(suffix),
+        (None, Some(prefix)) => model.starts_with(prefix),
+        (None, None) => model == pattern,
+    }
+}
+
+/// Attach the entry's extra `headers` to an outgoing request.
+pub fn apply_headers(
+    mut builder: RequestBuilder,
+    headers: &HashMap<String, String>,
+) -> RequestBuilder {
+    for (name, value) in headers {
+        builder = builder.header(name.as_str(), value.as_str());
+    }
+    builder
+}
+
+/// Trimmed `base-url` override for an entry, or `default` when unset.
+pub fn base_url(entry: &ProviderKeyEntry, default: &str) -> String {
+    entry
+        .base_url
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .unwrap_or(default)
+        .trim_end_matches('/')
+        .to_string()
+}
+
+pub fn build_client(
+    provider: &str,
+    timeout: Duration,
+    proxy_url: Option<&str>,
+) -> AppResult<Client> {
+    http_client::builder(provider, proxy_url)?
+        .connect_timeout(Duration::from_secs(10))
+        .timeout(timeout)
+        .build()
+        .map_err(|error| AppError::Config(format!("failed to build {provider} client: {error}")))
+}
+
+pub fn build_stream_client(
+    provider: &str,
+    timeout: Duration,
+    proxy_url: Option<&str>,
+) -> AppResult<Client> {
+    http_client::builder(provider, proxy_url)?
+        .connect_timeout(Duration::from_secs(10))
+        .read_timeout(timeout)
+        .build()
+        .map_err(|error| {
+            AppError::Config(format!("failed to build {provider} stream client: {error}"))
+        })
+}
+
+/// Map a non-success upstream reply into the structured error routing decides on.
+///
+/// 429s carry the reset hint from `headers` or `body` so the account is held back
+/// until the limit actually lifts.
+pub fn map_upstream_error(
+    provider: &str,
+    status: reqwest::StatusCode,
+    headers: &reqwest::header::HeaderMap,
+    body: String,
+) -> AppError {
+    let retry_after = retry_after::for_status(status, headers, &body, chrono::Utc::now());
+    UpstreamError::from_status(
+        status,
+        format!("{provider} upstream error ({}): {}", status, body.trim()),
+    )
+    .with_provider(provider)
+    .with_retry_after(retry_after)
+    .into()
+}
+
+/// Flatten message content into plain text, dropping non-text parts.
+pub fn content_text(content: &MessageContent) -> String {
+    match content {
+        MessageContent::Text(text) => text.clone(),
+        MessageContent::Parts(parts) => parts
+            .iter()
+            .filter_map(|part| part.text.as_deref())
+            .collect::<Vec<_>>()
+            .join(""),
+    }
+}
+
+/// Internal routing hints that must never be forwarded upstream.
+pub const INTERNAL_EXTRA_KEYS: &[&str] = &["selected_auth_id", "execution_session_id"];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(
+        prefix: Option<&str>,
+        models: &[(&str, Option<&str>)],
+        excluded: &[&str],
+    ) -> ProviderKeyEntry {
+        ProviderKeyEntry {
+            api_key: "key".to_string(),
+            prefix: prefix.map(str::to_string),
+            models: models
+                .iter()
+                .map(|(name, alias)| ModelEntry {
+                    name: name.to_string(),
+                    alias: alias.map(str::to_string),
+                })
+                .collect(),
+            excluded_models: excluded.iter().map(|value| value.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn catalog_namespaces_aliases_and_resolves_upstream_names() {
+        let catalog = KeyModelCatalog::from_entry(
+            &entry(
+                Some("team"),
+                &[("gemini-2.5-pro", Some("pro")), ("gemini-2.5-flash", None)],
+                &[],
+            ),
+            vec![],
+        );
+
+        let ids = catalog
+            .model_infos("google")
+            .into_iter()
+            .map(|model| model.id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, ["team/pro", "team/gemini-2.5-flash"]);
+        assert_eq!(catalog.upstream_model("team/pro"), "gemini-2.5-pro");
+        assert_eq!(
+            catalog.upstream_model("team/gemini-2.5-flash"),
+            "gemini-2.5-flash"
+        );
+    }
+
+    #[test]
+    fn catalog_falls_back_to_defaults_and_applies_exclusions() {
+        let defaults = crate::providers::static_models::claude_models();
+        let catalog =
+            KeyModelCatalog::from_entry(&entry(None, &[], &["claude-3*", "*-20250514"]), defaults);
+
+        let ids = catalog
+            .model_infos("anthropic")
+            .into_iter()
+            .map(|model| model.id)
+            .collect::<Vec<_>>();
+        assert!(ids.contains(&"claude-sonnet-4-6".to_string()));
+        assert!(!ids.iter().any(|id| id.starts_with("claude-3")));
+        assert!(!ids.iter().any(|id| id.ends_with("-20250514")));
+    }
+}
@@ -0,0 +1,190 @@
+//! Anthropic API provider backed by `claude-api-key` config entries.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::StreamExt;
+use reqwest::Client;
+use serde_json::{json, Value};
+
+use crate::config::ProviderKeyEntry;
+use crate::error::{AppError, AppResult, UpstreamError};
+use crate::models::{ChatCompletionRequest, ChatCompletionResponse, ModelInfo};
+use crate::providers::anthropic::{
+    anthropic_to_chat_response, chat_request_to_anthropic, AnthropicStreamTranslator,
+};
+use crate::providers::api_key::{self, KeyModelCatalog};
+use crate::providers::http_client::SendTraced;
+use crate::providers::static_models;
+use crate::providers::thinking;
+use crate::providers::{AnthropicResponse, BoxStream, Provider};
+
+pub const PROVIDER_TYPE: &str = "claude-api";
+const DEFAULT_BASE_URL: &str = "https://api.anthropic.com";
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+pub struct ClaudeApiKeyProvider {
+    client_id: String,
+    entry: ProviderKeyEntry,
+    catalog: KeyModelCatalog,
+    client: Client,
+    stream_client: Client,
+}
+
+impl ClaudeApiKeyProvider {
+    pub fn new(entry: ProviderKeyEntry, client_id: String) -> AppResult<Self> {
+        if entry.api_key.trim().is_empty() {
+            return Err(AppError::Config(
+                "claude-api-key entry missing api-key".into(),
+            ));
+        }
+
+        Ok(Self {
+            client_id,
+            catalog: KeyModelCatalog::from_entry(&entry, static_models::claude_models()),
+            client: api_key::build_client(
+                PROVIDER_TYPE,
+                Duration::from_secs(300),
+                entry.proxy_url.as_deref(),
+            )?,
+            stream_client: api_key::build_stream_client(
+                PROVIDER_TYPE,
+                Duration::from_secs(300),
+                entry.proxy_url.as_deref(),
+            )?,
+            entry,
+        })
+    }
+
+    async fn send(&self, client: &Client, body: &Value) -> AppResult<reqwest::Response> {
+        let endpoint = format!(
+            "{}/v1/messages",
+            api_key::base_url(&self.entry, DEFAULT_BASE_URL)
+        );
+        let request = client
+            .post(endpoint)
+            .header("x-api-key", self.entry.api_key.trim())
+            .header("anthropic-version", ANTHROPIC_VERSION)
+            .header("content-type", "application/json")
+            .json(body);
+
+        let response = api_key::apply_headers(request, &self.entry.headers)
+            .send_traced()
+            .await
+            .map_err(|e| {
+                UpstreamError::from_reqwest(format!("{PROVIDER_TYPE} request failed"), &e)
+            })?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let headers = response.headers().clone();
+            let body = response.text().await.unwrap_or_default();
+            return Err(api_key::map_upstream_error(
+                PROVIDER_TYPE,
+                status,
+                &headers,
+                body,
+            ));
+        }
+
+        Ok(response)
+    }
+}
+
+#[async_trait]
+impl Provider for ClaudeApiKeyProvider {
+    fn name(&self) -> &str {
+        PROVIDER_TYPE
+    }
+
+    fn client_id(&self) -> &str {
+        &self.client_id
+    }
+
+    async fn list_models(&self) -> AppResult<Vec<ModelInfo>> {
+        Ok(self.catalog.model_infos("anthropic"))
+    }
+
+    async fn chat_completion(
+        &self,
+        request: &ChatCompletionRequest,
+    ) -> AppResult<ChatCompletionResponse> {
+        let upstream_model = self
+            .catalog
+            .upstream_model(thinking::strip_thinking_suffix(&request.model).0);
+        let body = chat_request_to_anthropic(request, &upstream_model, false);
+        let response = self.send(&self.client, &body).await?;
+
+        let body: Value = response.json().await.map_err(|e| {
+            UpstreamError::from_reqwest(
+                format!("failed to parse {PROVIDER_TYPE} response JSON"),
+                &e,
+            )
+        })?;
+
+        Ok(anthropic_to_chat_response(&body, &request.model))
+    }
+
+    async fn chat_completion_stream(
+        &self,
+        request: &ChatCompletionRequest,
+    ) -> AppResult<BoxStream> {
+        let upstream_model = self
+            .catalog
+            .upstream_model(thinking::strip_thinking_suffix(&request.model).0);
+        let body = chat_request_to_anthropic(request, &upstream_model, true);
+        let response = self.send(&self.stream_client, &body).await?;
+
+        let upstream = crate::proxy::stream::passthrough_sse_stream(response.bytes_stream());
+        let model = request.model.clone();
+        let stream = async_stream::stream! {
+            let mut translator = AnthropicStreamTranslator::new(model);
+            let payloads = crate::proxy::stream::sse_data_payloads(upstream);
+            futures::pin_mut!(payloads);
+
+            while let Some(payload) = payloads.next().await {
+                match payload {
+                    Ok(data) => {
+                        for chunk in translator.push_data(&data) {
+                            yield Ok(Bytes::from(format!("data: {chunk}\n\n")));
+                        }
+                    }
+                    Err(error) => {
+                        yield Err(error);
+                        break;
+                    }
+                }
+            }
+        };
+
+        Ok(Box::pin(stream))
+    }
+
+    fn supports_anthropic_messages(&self, _model: &str) -> bool {
+        true
+    }
+
+    async fn anthropic_messages(&self, body: &Value) -> AppResult<AnthropicResponse> {
+        let model = body["model"].as_str().unwrap_or_default().to_string();
+        let mut upstream_body = body.clone();
+        upstream_body["model"] = json!(self.catalog.upstream_model(&model));
+
+        if body["stream"].as_bool().unwrap_or(false) {
+            let response = self.send(&self.stream_client, &upstream_body).await?;
+            return Ok(AnthropicResponse::Stream(
+                crate::proxy::stream::passthrough_sse_stream(response.bytes_stream()),
+            ));
+        }
+
+        let response = self.send(&self.client, &upstream_body).await?;
+        let mut message: Value = response.json().await.map_err(|e| {
+            UpstreamError::from_reqwest(
+                format!("failed to parse {PROVIDER_TYPE} response JSON"),
+                &e,
+            )
+        })?;
+        message["model"] = json!(model);
+        Ok(AnthropicResponse::Message(message))
+    }
+}
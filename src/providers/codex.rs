@@ -3,15 +3,18 @@
 use std::time::Duration;
 
 use async_trait::async_trait;
-use futures::StreamExt;
 use reqwest::Client;
 use serde_json::{json, Value};
 
 use crate::auth::store::AuthRecord;
-use crate::error::{AppError, AppResult};
+use crate::error::{AppError, AppResult, UpstreamError};
 use crate::models::{ChatCompletionRequest, ChatCompletionResponse, ModelInfo, Usage};
+use crate::providers::http_client::{self, SendTraced};
+use crate::providers::retry_after;
 use crate::providers::static_models;
+use crate::providers::thinking;
 use crate::providers::{BoxStream, Provider};
+use crate::proxy::stream::{buffered_sse_stream, normalize_reasoning_chunk};
 
 pub struct CodexProvider {
     record: AuthRecord,
@@ -21,14 +24,20 @@ pub struct CodexProvider {
 
 impl CodexProvider {
     pub fn new(record: AuthRecord) -> AppResult<Self> {
+        Self::new_with_proxy(record, None)
+    }
+
+    /// Build with the global `proxy-url` as fallback for the account's own `proxy_url`.
+    pub fn new_with_proxy(record: AuthRecord, default_proxy_url: Option<&str>) -> AppResult<Self> {
         if record.access_token().is_none() {
             return Err(AppError::Auth("codex account missing access_token".into()));
         }
 
+        let proxy_url = http_client::effective_proxy_url(record.proxy_url(), default_proxy_url);
         Ok(Self {
+            codex_client: build_codex_client(Duration::from_secs(15), proxy_url)?,
+            codex_stream_client: build_codex_stream_client(Duration::from_secs(120), proxy_url)?,
             record,
-            codex_client: build_codex_client(Duration::from_secs(15))?,
-            codex_stream_client: build_codex_stream_client(Duration::from_secs(120))?,
         })
     }
 
@@ -50,29 +59,24 @@ impl CodexProvider {
             .to_string()
     }
 
-    fn map_upstream_error(status: reqwest::StatusCode, body: String) -> AppError {
-        match status {
-            reqwest::StatusCode::UNAUTHORIZED => AppError::Auth(format!(
-                "codex upstream unauthorized ({}): {}",
-                status,
-                body.trim()
-            )),
-            reqwest::StatusCode::TOO_MANY_REQUESTS => AppError::QuotaExceeded(format!(
-                "codex upstream rate limited ({}): {}",
-                status,
-                body.trim()
-            )),
-            _ => AppError::Upstream(format!(
-                "codex upstream error ({}): {}",
-                status,
-                body.trim()
-            )),
-        }
+    fn map_upstream_error(
+        status: reqwest::StatusCode,
+        headers: &reqwest::header::HeaderMap,
+        body: String,
+    ) -> AppError {
+        let retry_after = retry_after::for_status(status, headers, &body, chrono::Utc::now());
+        UpstreamError::from_status(
+            status,
+            format!("codex upstream error ({}): {}", status, body.trim()),
+        )
+        .with_provider("codex")
+        .with_retry_after(retry_after)
+        .into()
     }
 
     fn decode_chat_completion_response(body: Value) -> AppResult<ChatCompletionResponse> {
         let mut response: ChatCompletionResponse = serde_json::from_value(body.clone())
-            .map_err(|e| AppError::Upstream(format!("failed to decode codex response: {e}")))?;
+            .map_err(|e| UpstreamError::other(format!("failed to decode codex response: {e}")))?;
 
         if response.usage.is_none() {
             response.usage = body.get("usage").cloned().and_then(parse_usage);
@@ -83,14 +87,11 @@ impl CodexProvider {
 }
 
 pub fn normalize_codex_model(model: &str) -> String {
-    model
-        .trim()
-        .strip_suffix("-thinking")
-        .unwrap_or_else(|| model.trim())
-        .to_string()
+    thinking::strip_thinking_suffix(model).0.to_string()
 }
 
 pub fn prepare_codex_request(mut request: ChatCompletionRequest) -> ChatCompletionRequest {
+    thinking::apply_openai_effort(&mut request);
     request.model = normalize_codex_model(&request.model);
 
     request.extra.remove("previous_response_id");
@@ -134,16 +135,16 @@ pub fn parse_usage(usage: serde_json::Value) -> Option<Usage> {
     })
 }
 
-fn build_codex_client(timeout: Duration) -> AppResult<Client> {
-    Client::builder()
+fn build_codex_client(timeout: Duration, proxy_url: Option<&str>) -> AppResult<Client> {
+    http_client::builder("codex", proxy_url)?
         .connect_timeout(Duration::from_secs(5))
         .timeout(timeout)
         .build()
         .map_err(|error| AppError::Config(format!("failed to build codex client: {error}")))
 }
 
-fn build_codex_stream_client(timeout: Duration) -> AppResult<Client> {
-    Client::builder()
+fn build_codex_stream_client(timeout: Duration, proxy_url: Option<&str>) -> AppResult<Client> {
+    http_client::builder("codex", proxy_url)?
         .connect_timeout(Duration::from_secs(5))
         .timeout(timeout)
         .read_timeout(timeout)
@@ -194,22 +195,24 @@ impl Provider for CodexProvider {
             .header("content-type", "application/json")
             .header("accept", "application/json")
             .json(&prepared_request)
-            .send()
+            .send_traced()
             .await
-            .map_err(|e| AppError::Upstream(format!("codex request failed: {e}")))?;
+            .map_err(|e| UpstreamError::from_reqwest("codex request failed", &e))?;
 
         let status = response.status();
+        let headers = response.headers().clone();
         let body_text = response
             .text()
             .await
-            .map_err(|e| AppError::Upstream(format!("failed reading codex response body: {e}")))?;
+            .map_err(|e| UpstreamError::from_reqwest("failed reading codex response body", &e))?;
 
         if !status.is_success() {
-            return Err(Self::map_upstream_error(status, body_text));
+            return Err(Self::map_upstream_error(status, &headers, body_text));
         }
 
-        let body: Value = serde_json::from_str(&body_text)
-            .map_err(|e| AppError::Upstream(format!("failed to parse codex response JSON: {e}")))?;
+        let body: Value = serde_json::from_str(&body_text).map_err(|e| {
+            UpstreamError::other(format!("failed to parse codex response JSON: {e}"))
+        })?;
 
         Self::decode_chat_completion_response(body)
     }
@@ -229,22 +232,23 @@ impl Provider for CodexProvider {
             .header("content-type", "application/json")
             .header("accept", "text/event-stream")
             .json(&prepared_request)
-            .send()
+            .send_traced()
             .await
-            .map_err(|e| AppError::Upstream(format!("codex stream request failed: {e}")))?;
+            .map_err(|e| UpstreamError::from_reqwest("codex stream request failed", &e))?;
 
         let status = response.status();
         if !status.is_success() {
-            let body = response.text().await.map_err(|e| {
-                AppError::Upstream(format!("failed reading codex stream body: {e}"))
-            })?;
-            return Err(Self::map_upstream_error(status, body));
+            let headers = response.headers().clone();
+            let body = response
+                .text()
+                .await
+                .map_err(|e| UpstreamError::from_reqwest("failed reading codex stream body", &e))?;
+            return Err(Self::map_upstream_error(status, &headers, body));
         }
 
-        let stream = response.bytes_stream().map(|item| {
-            item.map_err(|e| AppError::Upstream(format!("codex stream read error: {e}")))
-        });
-
-        Ok(Box::pin(stream))
+        Ok(buffered_sse_stream(
+            response.bytes_stream(),
+            normalize_reasoning_chunk,
+        ))
     }
 }
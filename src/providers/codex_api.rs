@@ -0,0 +1,166 @@
+//! OpenAI API provider backed by `codex-api-key` config entries.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use reqwest::Client;
+use serde_json::{json, Value};
+
+use crate::config::ProviderKeyEntry;
+use crate::error::{AppError, AppResult, UpstreamError};
+use crate::models::{ChatCompletionRequest, ChatCompletionResponse, ModelInfo};
+use crate::providers::api_key::{self, KeyModelCatalog};
+use crate::providers::codex::parse_usage;
+use crate::providers::http_client::SendTraced;
+use crate::providers::static_models;
+use crate::providers::thinking;
+use crate::providers::{BoxStream, Provider};
+
+pub const PROVIDER_TYPE: &str = "codex-api";
+const DEFAULT_BASE_URL: &str = "https://api.openai.com/v1";
+
+pub struct CodexApiKeyProvider {
+    client_id: String,
+    entry: ProviderKeyEntry,
+    catalog: KeyModelCatalog,
+    client: Client,
+    stream_client: Client,
+}
+
+impl CodexApiKeyProvider {
+    pub fn new(entry: ProviderKeyEntry, client_id: String) -> AppResult<Self> {
+        if entry.api_key.trim().is_empty() {
+            return Err(AppError::Config(
+                "codex-api-key entry missing api-key".into(),
+            ));
+        }
+
+        Ok(Self {
+            client_id,
+            catalog: KeyModelCatalog::from_entry(&entry, static_models::openai_models()),
+            client: api_key::build_client(
+                PROVIDER_TYPE,
+                Duration::from_secs(120),
+                entry.proxy_url.as_deref(),
+            )?,
+            stream_client: api_key::build_stream_client(
+                PROVIDER_TYPE,
+                Duration::from_secs(120),
+                entry.proxy_url.as_deref(),
+            )?,
+            entry,
+        })
+    }
+
+    fn prepare_request(&self, request: &ChatCompletionRequest, stream: bool) -> Value {
+        let mut request = request.clone();
+        thinking::apply_openai_effort(&mut request);
+        request.model = self.catalog.upstream_model(&request.model);
+        request.stream = Some(stream);
+        for key in api_key::INTERNAL_EXTRA_KEYS {
+            request.extra.remove(*key);
+        }
+        if stream {
+            request
+                .extra
+                .entry("stream_options".to_string())
+                .or_insert_with(|| json!({"include_usage": true}));
+        }
+
+        serde_json::to_value(request).unwrap_or_default()
+    }
+
+    async fn send(
+        &self,
+        client: &Client,
+        body: &Value,
+        accept: &str,
+    ) -> AppResult<reqwest::Response> {
+        let endpoint = format!(
+            "{}/chat/completions",
+            api_key::base_url(&self.entry, DEFAULT_BASE_URL)
+        );
+        let request = client
+            .post(endpoint)
+            .bearer_auth(self.entry.api_key.trim())
+            .header("content-type", "application/json")
+            .header("accept", accept)
+            .json(body);
+
+        let response = api_key::apply_headers(request, &self.entry.headers)
+            .send_traced()
+            .await
+            .map_err(|e| {
+                UpstreamError::from_reqwest(format!("{PROVIDER_TYPE} request failed"), &e)
+            })?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let headers = response.headers().clone();
+            let body = response.text().await.unwrap_or_default();
+            return Err(api_key::map_upstream_error(
+                PROVIDER_TYPE,
+                status,
+                &headers,
+                body,
+            ));
+        }
+
+        Ok(response)
+    }
+}
+
+#[async_trait]
+impl Provider for CodexApiKeyProvider {
+    fn name(&self) -> &str {
+        PROVIDER_TYPE
+    }
+
+    fn client_id(&self) -> &str {
+        &self.client_id
+    }
+
+    async fn list_models(&self) -> AppResult<Vec<ModelInfo>> {
+        Ok(self.catalog.model_infos("openai"))
+    }
+
+    async fn chat_completion(
+        &self,
+        request: &ChatCompletionRequest,
+    ) -> AppResult<ChatCompletionResponse> {
+        let body = self.prepare_request(request, false);
+        let response = self.send(&self.client, &body, "application/json").await?;
+
+        let body: Value = response.json().await.map_err(|e| {
+            UpstreamError::from_reqwest(
+                format!("failed to parse {PROVIDER_TYPE} response JSON"),
+                &e,
+            )
+        })?;
+        let mut decoded: ChatCompletionResponse =
+            serde_json::from_value(body.clone()).map_err(|e| {
+                UpstreamError::other(format!("failed to decode {PROVIDER_TYPE} response: {e}"))
+            })?;
+        if decoded.usage.is_none() {
+            decoded.usage = body.get("usage").cloned().and_then(parse_usage);
+        }
+        decoded.model = request.model.clone();
+
+        Ok(decoded)
+    }
+
+    async fn chat_completion_stream(
+        &self,
+        request: &ChatCompletionRequest,
+    ) -> AppResult<BoxStream> {
+        let body = self.prepare_request(request, true);
+        let response = self
+            .send(&self.stream_client, &body, "text/event-stream")
+            .await?;
+
+        Ok(crate::proxy::stream::buffered_sse_stream(
+            response.bytes_stream(),
+            crate::proxy::stream::normalize_reasoning_chunk,
+        ))
+    }
+}
@@ -0,0 +1,451 @@
+//! Gemini API provider backed by `gemini-api-key` config entries.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use reqwest::Client;
+use serde_json::{json, Value};
+
+use crate::config::ProviderKeyEntry;
+use crate::error::{AppError, AppResult, UpstreamError};
+use crate::models::{
+    ChatCompletionRequest, ChatCompletionResponse, ChatMessage, Choice, MessageContent, ModelInfo,
+    Usage,
+};
+use crate::providers::api_key::{self, KeyModelCatalog};
+use crate::providers::http_client::SendTraced;
+use crate::providers::static_models;
+use crate::providers::thinking;
+use crate::providers::{BoxStream, Provider};
+
+pub const PROVIDER_TYPE: &str = "gemini-api";
+const DEFAULT_BASE_URL: &str = "https://generativelanguage.googleapis.com";
+
+pub struct GeminiApiKeyProvider {
+    client_id: String,
+    entry: ProviderKeyEntry,
+    catalog: KeyModelCatalog,
+    client: Client,
+    stream_client: Client,
+}
+
+impl GeminiApiKeyProvider {
+    pub fn new(entry: ProviderKeyEntry, client_id: String) -> AppResult<Self> {
+        if entry.api_key.trim().is_empty() {
+            return Err(AppError::Config(
+                "gemini-api-key entry missing api-key".into(),
+            ));
+        }
+
+        Ok(Self {
+            client_id,
+            catalog: KeyModelCatalog::from_entry(&entry, static_models::gemini_models()),
+            client: api_key::build_client(
+                PROVIDER_TYPE,
+                Duration::from_secs(300),
+                entry.proxy_url.as_deref(),
+            )?,
+            stream_client: api_key::build_stream_client(
+                PROVIDER_TYPE,
+                Duration::from_secs(300),
+                entry.proxy_url.as_deref(),
+            )?,
+            entry,
+        })
+    }
+
+    async fn send(
+        &self,
+        client: &Client,
+        url: String,
+        body: &Value,
+    ) -> AppResult<reqwest::Response> {
+        let request = client
+            .post(url)
+            .header("x-goog-api-key", self.entry.api_key.trim())
+            .header("content-type", "application/json")
+            .json(body);
+
+        let response = api_key::apply_headers(request, &self.entry.headers)
+            .send_traced()
+            .await
+            .map_err(|e| {
+                UpstreamError::from_reqwest(format!("{PROVIDER_TYPE} request failed"), &e)
+            })?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let headers = response.headers().clone();
+            let body = response.text().await.unwrap_or_default();
+            return Err(api_key::map_upstream_error(
+                PROVIDER_TYPE,
+                status,
+                &headers,
+                body,
+            ));
+        }
+
+        Ok(response)
+    }
+
+    fn endpoint(&self, model: &str, action: &str) -> String {
+        format!(
+            "{}/v1beta/models/{}:{}",
+            api_key::base_url(&self.entry, DEFAULT_BASE_URL),
+            model,
+            action
+        )
+    }
+}
+
+/// Translate an OpenAI chat completion request into a Gemini generateContent body.
+pub fn chat_request_to_gemini(request: &ChatCompletionRequest) -> Value {
+    let mut system_parts = Vec::new();
+    let mut contents = Vec::new();
+    // functionResponse parts are matched by name, which OpenAI tool messages only
+    // carry indirectly through the originating tool_call id.
+    let mut call_names: HashMap<String, String> = HashMap::new();
+
+    for message in &request.messages {
+        match message.role.as_str() {
+            "system" | "developer" => {
+                let text = api_key::content_text(&message.content);
+                if !text.is_empty() {
+                    system_parts.push(json!({"text": text}));
+                }
+            }
+            "tool" => {
+                let id = message.tool_call_id.clone().unwrap_or_default();
+                let name = call_names.get(&id).cloned().unwrap_or_else(|| id.clone());
+                let output = api_key::content_text(&message.content);
+                let response = serde_json::from_str::<Value>(&output)
+                    .ok()
+                    .filter(Value::is_object)
+                    .unwrap_or_else(|| json!({"result": output}));
+                contents.push(json!({
+                    "role": "user",
+                    "parts": [{"functionResponse": {"name": name, "response": response}}],
+                }));
+            }
+            role => {
+                let role = if role == "assistant" { "model" } else { "user" };
+                let mut parts = content_parts(&message.content);
+                for call in message.tool_calls.iter().flatten() {
+                    let function = call.get("function");
+                    let name = function
+                        .and_then(|function| function.get("name"))
+                        .and_then(Value::as_str)
+                        .unwrap_or_default();
+                    if let Some(id) = call.get("id").and_then(Value::as_str) {
+                        call_names.insert(id.to_string(), name.to_string());
+                    }
+                    let args = match function.and_then(|function| function.get("arguments")) {
+                        Some(Value::String(raw)) if !raw.trim().is_empty() => {
+                            serde_json::from_str(raw).unwrap_or_else(|_| json!({}))
+                        }
+                        Some(value @ Value::Object(_)) => value.clone(),
+                        _ => json!({}),
+                    };
+                    parts.push(json!({"functionCall": {"name": name, "args": args}}));
+                }
+                if !parts.is_empty() {
+                    contents.push(json!({"role": role, "parts": parts}));
+                }
+            }
+        }
+    }
+
+    let mut body = json!({ "contents": contents });
+
+    if !system_parts.is_empty() {
+        body["systemInstruction"] = json!({"parts": system_parts});
+    }
+
+    let mut generation_config = json!({});
+    if let Some(temperature) = request.temperature {
+        generation_config["temperature"] = json!(temperature);
+    }
+    if let Some(top_p) = request.top_p {
+        generation_config["topP"] = json!(top_p);
+    }
+    if let Some(max_tokens) = request.max_tokens {
+        generation_config["maxOutputTokens"] = json!(max_tokens);
+    }
+    if let Some(reasoning) = thinking::requested_reasoning(request) {
+        generation_config["thinkingConfig"] = thinking::gemini_thinking_config(
+            &reasoning,
+            thinking::static_support(&request.model).as_ref(),
+        );
+    }
+    match &request.stop {
+        Some(Value::String(stop)) => generation_config["stopSequences"] = json!([stop]),
+        Some(Value::Array(stops)) => generation_config["stopSequences"] = json!(stops),
+        _ => {}
+    }
+    if generation_config
+        .as_object()
+        .is_some_and(|config| !config.is_empty())
+    {
+        body["generationConfig"] = generation_config;
+    }
+
+    if let Some(tools) = &request.tools {
+        let declarations = tools
+            .iter()
+            .filter_map(|tool| tool.get("function").cloned())
+            .collect::<Vec<_>>();
+        if !declarations.is_empty() {
+            body["tools"] = json!([{ "functionDeclarations": declarations }]);
+        }
+    }
+
+    let mode = match &request.tool_choice {
+        Some(Value::String(choice)) if choice == "required" => Some(json!({"mode": "ANY"})),
+        Some(Value::String(choice)) if choice == "none" => Some(json!({"mode": "NONE"})),
+        Some(Value::String(choice)) if choice == "auto" => Some(json!({"mode": "AUTO"})),
+        Some(choice @ Value::Object(_)) => choice
+            .get("function")
+            .and_then(|function| function.get("name"))
+            .map(|name| json!({"mode": "ANY", "allowedFunctionNames": [name]})),
+        _ => None,
+    };
+    if let Some(mode) = mode {
+        body["toolConfig"] = json!({ "functionCallingConfig": mode });
+    }
+
+    body
+}
+
+fn content_parts(content: &MessageContent) -> Vec<Value> {
+    match content {
+        MessageContent::Text(text) if text.is_empty() => Vec::new(),
+        MessageContent::Text(text) => vec![json!({"text": text})],
+        MessageContent::Parts(parts) => parts
+            .iter()
+            .filter_map(|part| match part.part_type.as_str() {
+                "text" => part.text.as_ref().map(|text| json!({"text": text})),
+                "image_url" => part.image_url.as_ref().and_then(|image| {
+                    let (mime_type, data) = image
+                        .url
+                        .strip_prefix("data:")
+                        .and_then(|rest| rest.split_once(";base64,"))?;
+                    Some(json!({"inlineData": {"mimeType": mime_type, "data": data}}))
+                }),
+                _ => None,
+            })
+            .collect(),
+    }
+}
+
+/// Translate a Gemini generateContent response into an OpenAI chat completion.
+pub fn gemini_to_chat_response(body: &Value, model: &str) -> ChatCompletionResponse {
+    let candidate = &body["candidates"][0];
+    let mut text = String::new();
+    let mut reasoning = String::new();
+    let mut tool_calls = Vec::new();
+
+    for part in candidate["content"]["parts"]
+        .as_array()
+        .into_iter()
+        .flatten()
+    {
+        if part["thought"].as_bool() == Some(true) {
+            reasoning.push_str(part["text"].as_str().unwrap_or_default());
+            continue;
+        }
+        if let Some(part_text) = part["text"].as_str() {
+            text.push_str(part_text);
+        }
+        if let Some(call) = part.get("functionCall") {
+            tool_calls.push(json!({
+                "id": format!("call_{}", uuid::Uuid::new_v4().simple()),
+                "type": "function",
+                "function": {
+                    "name": call["name"],
+                    "arguments": call.get("args").cloned().unwrap_or_else(|| json!({})).to_string(),
+                }
+            }));
+        }
+    }
+
+    let finish_reason = if !tool_calls.is_empty() {
+        "tool_calls"
+    } else {
+        match candidate["finishReason"].as_str() {
+            Some("MAX_TOKENS") => "length",
+            Some("SAFETY") | Some("RECITATION") | Some("PROHIBITED_CONTENT") => "content_filter",
+            _ => "stop",
+        }
+    };
+
+    let usage = &body["usageMetadata"];
+    let prompt_tokens = usage["promptTokenCount"].as_u64().unwrap_or(0) as u32;
+    let completion_tokens = usage["candidatesTokenCount"].as_u64().unwrap_or(0) as u32;
+
+    ChatCompletionResponse {
+        id: format!("chatcmpl-{}", uuid::Uuid::new_v4()),
+        object: "chat.completion".to_string(),
+        created: chrono::Utc::now().timestamp(),
+        model: model.to_string(),
+        choices: vec![Choice {
+            index: 0,
+            message: Some(ChatMessage {
+                role: "assistant".to_string(),
+                content: MessageContent::Text(text),
+                name: None,
+                tool_calls: (!tool_calls.is_empty()).then_some(tool_calls),
+                tool_call_id: None,
+                reasoning_content: (!reasoning.is_empty()).then_some(reasoning),
+            }),
+            delta: None,
+            finish_reason: Some(finish_reason.to_string()),
+        }],
+        usage: Some(Usage {
+            prompt_tokens,
+            completion_tokens,
+            total_tokens: usage["totalTokenCount"]
+                .as_u64()
+                .map(|total| total as u32)
+                .unwrap_or(prompt_tokens + completion_tokens),
+        }),
+    }
+}
+
+#[async_trait]
+impl Provider for GeminiApiKeyProvider {
+    fn name(&self) -> &str {
+        PROVIDER_TYPE
+    }
+
+    fn client_id(&self) -> &str {
+        &self.client_id
+    }
+
+    async fn list_models(&self) -> AppResult<Vec<ModelInfo>> {
+        Ok(self.catalog.model_infos("google"))
+    }
+
+    async fn chat_completion(
+        &self,
+        request: &ChatCompletionRequest,
+    ) -> AppResult<ChatCompletionResponse> {
+        let upstream_model = self
+            .catalog
+            .upstream_model(thinking::strip_thinking_suffix(&request.model).0);
+        let body = chat_request_to_gemini(request);
+        let response = self
+            .send(
+                &self.client,
+                self.endpoint(&upstream_model, "generateContent"),
+                &body,
+            )
+            .await?;
+
+        let body: Value = response.json().await.map_err(|e| {
+            UpstreamError::from_reqwest(
+                format!("failed to parse {PROVIDER_TYPE} response JSON"),
+                &e,
+            )
+        })?;
+
+        Ok(gemini_to_chat_response(&body, &request.model))
+    }
+
+    async fn chat_completion_stream(
+        &self,
+        request: &ChatCompletionRequest,
+    ) -> AppResult<BoxStream> {
+        let upstream_model = self
+            .catalog
+            .upstream_model(thinking::strip_thinking_suffix(&request.model).0);
+        let body = chat_request_to_gemini(request);
+        let url = format!(
+            "{}?alt=sse",
+            self.endpoint(&upstream_model, "streamGenerateContent")
+        );
+        let response = self.send(&self.stream_client, url, &body).await?;
+
+        let transform = crate::proxy::stream::antigravity_to_openai_transform(
+            format!("chatcmpl-{}", uuid::Uuid::new_v4()),
+            request.model.clone(),
+            chrono::Utc::now().timestamp(),
+        );
+        Ok(crate::proxy::stream::buffered_sse_stream(
+            response.bytes_stream(),
+            transform,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chat_request_maps_tool_round_trip_to_function_parts() {
+        let request: ChatCompletionRequest = serde_json::from_value(json!({
+            "model": "gemini-2.5-pro",
+            "max_tokens": 128,
+            "messages": [
+                {"role": "system", "content": "be brief"},
+                {"role": "user", "content": "weather?"},
+                {"role": "assistant", "content": "", "tool_calls": [{
+                    "id": "call_1", "type": "function",
+                    "function": {"name": "lookup", "arguments": "{\"q\":\"rain\"}"}
+                }]},
+                {"role": "tool", "tool_call_id": "call_1", "content": "sunny"}
+            ],
+            "tools": [{"type": "function", "function": {"name": "lookup", "parameters": {"type": "object"}}}],
+            "tool_choice": "required"
+        }))
+        .unwrap();
+
+        let body = chat_request_to_gemini(&request);
+
+        assert_eq!(body["systemInstruction"]["parts"][0]["text"], "be brief");
+        assert_eq!(body["generationConfig"]["maxOutputTokens"], 128);
+        assert_eq!(
+            body["tools"][0]["functionDeclarations"][0]["name"],
+            "lookup"
+        );
+        assert_eq!(body["toolConfig"]["functionCallingConfig"]["mode"], "ANY");
+        assert_eq!(body["contents"][1]["role"], "model");
+        assert_eq!(
+            body["contents"][1]["parts"][0]["functionCall"]["args"]["q"],
+            "rain"
+        );
+        let response = &body["contents"][2]["parts"][0]["functionResponse"];
+        assert_eq!(response["name"], "lookup");
+        assert_eq!(response["response"]["result"], "sunny");
+    }
+
+    #[test]
+    fn gemini_response_maps_function_calls_and_usage() {
+        let response = gemini_to_chat_response(
+            &json!({
+                "candidates": [{
+                    "content": {"role": "model", "parts": [
+                        {"text": "thinking...", "thought": true},
+                        {"text": "checking"},
+                        {"functionCall": {"name": "lookup", "args": {"q": "rain"}}}
+                    ]},
+                    "finishReason": "STOP"
+                }],
+                "usageMetadata": {"promptTokenCount": 9, "candidatesTokenCount": 3, "totalTokenCount": 12}
+            }),
+            "gemini-2.5-pro",
+        );
+
+        let choice = &response.choices[0];
+        assert_eq!(choice.finish_reason.as_deref(), Some("tool_calls"));
+        let message = choice.message.as_ref().unwrap();
+        assert_eq!(api_key::content_text(&message.content), "checking");
+        assert_eq!(message.reasoning_content.as_deref(), Some("thinking..."));
+        assert_eq!(
+            message.tool_calls.as_ref().unwrap()[0]["function"]["arguments"],
+            "{\"q\":\"rain\"}"
+        );
+        assert_eq!(response.usage.unwrap().total_tokens, 12);
+    }
+}
@@ -6,7 +6,7 @@ use futures::StreamExt;
 use reqwest::Client;
 use serde_json::{json, Value};
 use tokio::sync::{Mutex, RwLock};
-use tracing::{debug, error};
+use tracing::{debug, error, Instrument};
 use url::Url;
 
 use crate::auth::github_copilot_runtime::{
@@ -14,12 +14,15 @@ use crate::auth::github_copilot_runtime::{
     token_is_still_valid_until,
 };
 use crate::auth::store::AuthRecord;
-use crate::error::{AppError, AppResult};
+use crate::error::{AppError, AppResult, UpstreamError};
 use crate::models::{
     ChatCompletionRequest, ChatCompletionResponse, ChatMessage, Choice, MessageContent,
     ModelInfo, Usage,
 };
+use crate::providers::http_client::{self, SendTraced};
+use crate::providers::retry_after;
 use crate::providers::static_models;
+use crate::providers::thinking;
 use crate::providers::{BoxStream, Provider};
 
 const DEFAULT_API_BASE_URL: &str = "https://api.githubcopilot.com";
@@ -47,16 +50,22 @@ pub struct GithubCopilotProvider {
 
 impl GithubCopilotProvider {
     pub fn new(record: AuthRecord) -> AppResult<Self> {
+        Self::new_with_proxy(record, None)
+    }
+
+    /// Build with the global `proxy-url` as fallback for the account's own `proxy_url`.
+    pub fn new_with_proxy(record: AuthRecord, default_proxy_url: Option<&str>) -> AppResult<Self> {
         if record.access_token().is_none() {
             return Err(AppError::Auth(
                 "github copilot account missing github oauth token".into(),
             ));
         }
 
+        let proxy_url = http_client::effective_proxy_url(record.proxy_url(), default_proxy_url);
         Ok(Self {
+            client: build_client(Duration::from_secs(30), proxy_url)?,
+            stream_client: build_stream_client(Duration::from_secs(120), proxy_url)?,
             record,
-            client: build_client(Duration::from_secs(30))?,
-            stream_client: build_stream_client(Duration::from_secs(120))?,
             cached_token: RwLock::new(None),
             refresh_mutex: Mutex::new(()),
         })
@@ -143,7 +152,13 @@ impl GithubCopilotProvider {
             self.github_oauth_token()?,
             &self.token_url(),
         )
-        .await?;
+        .instrument(tracing::info_span!(
+            "token_refresh",
+            provider = "github-copilot",
+            client_id = %self.record.id,
+        ))
+        .await
+        .inspect_err(|_| crate::metrics::oauth_refresh_failed("github-copilot"))?;
 
         let expires_at = Utc
             .timestamp_opt(exchanged.expires_at, 0)
@@ -195,12 +210,8 @@ impl GithubCopilotProvider {
     }
 
     fn normalize_model(model: &str) -> String {
-        let trimmed = model.trim();
-
-        // Strip -thinking suffix first
-        let without_thinking = trimmed
-            .strip_suffix("-thinking")
-            .unwrap_or(trimmed);
+        // Strip -thinking suffix first; the reasoning request travels as reasoning_effort
+        let (without_thinking, _) = thinking::strip_thinking_suffix(model);
 
         // Apply Copilot-specific aliases (dotted to hyphenated)
         // This matches the alias table used in static_models
@@ -359,6 +370,13 @@ impl GithubCopilotProvider {
                 } else if let Some(stop) = request.extra.get("stop") {
                     obj.insert("stop".to_string(), stop.clone());
                 }
+
+                if let Some(effort) = request.extra.get("reasoning_effort") {
+                    obj.insert(
+                        "reasoning".to_string(),
+                        json!({"effort": effort, "summary": "auto"}),
+                    );
+                }
             }
 
             return Ok(body);
@@ -424,11 +442,41 @@ impl GithubCopilotProvider {
             } else if let Some(stop) = request.extra.get("stop") {
                 obj.insert("stop".to_string(), stop.clone());
             }
+
+            if let Some(effort) = request.extra.get("reasoning_effort") {
+                obj.insert(
+                    "reasoning".to_string(),
+                    json!({"effort": effort, "summary": "auto"}),
+                );
+            }
         }
 
         Ok(body)
     }
 
+    /// Endpoint and body of a streamed request.
+    fn stream_request(&self, request: &ChatCompletionRequest) -> AppResult<(String, Value)> {
+        let mut request = request.clone();
+        thinking::apply_openai_effort(&mut request);
+        // Chat streams only end with a usage chunk when asked for one.
+        request
+            .extra
+            .entry("stream_options".to_string())
+            .or_insert_with(|| json!({"include_usage": true}));
+        let canonical_model = Self::normalize_model(&request.model);
+        if self.is_responses_request(&canonical_model, &request) {
+            Ok((
+                format!("{}/responses", self.api_base_url()),
+                self.convert_chat_to_responses_body(&request, &canonical_model)?,
+            ))
+        } else {
+            Ok((
+                format!("{}/chat/completions", self.api_base_url()),
+                self.normalize_chat_request(&request),
+            ))
+        }
+    }
+
     fn has_image_input(&self, request: &ChatCompletionRequest) -> bool {
         if let Some(input) = request.extra.get("input") {
             if input.to_string().contains("input_image") || input.to_string().contains("image_url") {
@@ -492,24 +540,23 @@ impl GithubCopilotProvider {
         Ok(headers)
     }
 
-    fn map_upstream_error(status: reqwest::StatusCode, body: String) -> AppError {
-        match status {
-            reqwest::StatusCode::UNAUTHORIZED => AppError::Auth(format!(
-                "github copilot upstream unauthorized ({}): {}",
-                status,
-                body.trim()
-            )),
-            reqwest::StatusCode::TOO_MANY_REQUESTS => AppError::QuotaExceeded(format!(
-                "github copilot upstream rate limited ({}): {}",
-                status,
-                body.trim()
-            )),
-            _ => AppError::Upstream(format!(
+    fn map_upstream_error(
+        status: reqwest::StatusCode,
+        headers: &reqwest::header::HeaderMap,
+        body: String,
+    ) -> AppError {
+        let retry_after = retry_after::for_status(status, headers, &body, chrono::Utc::now());
+        UpstreamError::from_status(
+            status,
+            format!(
                 "github copilot upstream error ({}): {}",
                 status,
                 body.trim()
-            )),
-        }
+            ),
+        )
+        .with_provider("github-copilot")
+        .with_retry_after(retry_after)
+        .into()
     }
 
     fn parse_chat_response(body: Value) -> AppResult<ChatCompletionResponse> {
@@ -531,7 +578,9 @@ impl GithubCopilotProvider {
         let choices = body
             .get("choices")
             .and_then(Value::as_array)
-            .ok_or_else(|| AppError::Upstream("failed to decode copilot chat response: missing choices".into()))?
+            .ok_or_else(|| {
+                UpstreamError::other("failed to decode copilot chat response: missing choices")
+            })?
             .iter()
             .enumerate()
             .map(|(idx, choice)| {
@@ -557,6 +606,7 @@ impl GithubCopilotProvider {
                         .get("tool_call_id")
                         .and_then(Value::as_str)
                         .map(str::to_string),
+                    reasoning_content: thinking::message_reasoning(&message),
                 });
 
                 let delta = choice.get("delta").cloned().map(|delta| ChatMessage {
@@ -575,6 +625,7 @@ impl GithubCopilotProvider {
                         .get("tool_call_id")
                         .and_then(Value::as_str)
                         .map(str::to_string),
+                    reasoning_content: thinking::message_reasoning(&delta),
                 });
 
                 Choice {
@@ -619,38 +670,61 @@ impl GithubCopilotProvider {
         // Validate and extract output array
         let output_array = body
             .get("output")
-            .ok_or_else(|| AppError::Upstream("responses response missing 'output' field".to_string()))?
+            .ok_or_else(|| UpstreamError::other("responses response missing 'output' field"))?
             .as_array()
-            .ok_or_else(|| AppError::Upstream("responses 'output' field is not an array".to_string()))?;
+            .ok_or_else(|| UpstreamError::other("responses 'output' field is not an array"))?;
 
         if output_array.is_empty() {
-            return Err(AppError::Upstream("responses 'output' array is empty".to_string()));
+            return Err(AppError::Upstream(UpstreamError::other(
+                "responses 'output' array is empty",
+            )));
         }
 
         // Collect all text fragments from all outputs
         let mut text_fragments = Vec::new();
+        let mut reasoning_fragments = Vec::new();
 
         for (output_idx, output_item) in output_array.iter().enumerate() {
+            if output_item.get("type").and_then(Value::as_str) == Some("reasoning") {
+                reasoning_fragments.extend(
+                    output_item
+                        .get("summary")
+                        .and_then(Value::as_array)
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|part| part.get("text").and_then(Value::as_str)),
+                );
+                continue;
+            }
+
             let content_array = output_item
                 .get("content")
-                .ok_or_else(|| AppError::Upstream(format!("output[{}] missing 'content' field", output_idx)))?
+                .ok_or_else(|| {
+                    UpstreamError::other(format!("output[{}] missing 'content' field", output_idx))
+                })?
                 .as_array()
-                .ok_or_else(|| AppError::Upstream(format!("output[{}] 'content' field is not an array", output_idx)))?;
+                .ok_or_else(|| {
+                    UpstreamError::other(format!(
+                        "output[{}] 'content' field is not an array",
+                        output_idx
+                    ))
+                })?;
 
             for (content_idx, content_item) in content_array.iter().enumerate() {
                 if let Some(text_value) = content_item.get("text") {
-                    let text_str = text_value
-                        .as_str()
-                        .ok_or_else(|| AppError::Upstream(format!(
+                    let text_str = text_value.as_str().ok_or_else(|| {
+                        UpstreamError::other(format!(
                             "output[{}].content[{}].text is not a string",
                             output_idx, content_idx
-                        )))?;
+                        ))
+                    })?;
                     text_fragments.push(text_str);
                 }
             }
         }
 
         let text = text_fragments.join("");
+        let reasoning = reasoning_fragments.join("");
 
         let usage = body.get("usage").map(|usage| Usage {
             prompt_tokens: usage
@@ -688,6 +762,7 @@ impl GithubCopilotProvider {
                     name: None,
                     tool_calls: None,
                     tool_call_id: None,
+                    reasoning_content: (!reasoning.is_empty()).then_some(reasoning),
                 }),
                 delta: None,
                 finish_reason: Some("stop".to_string()),
@@ -697,16 +772,16 @@ impl GithubCopilotProvider {
     }
 }
 
-fn build_client(timeout: Duration) -> AppResult<Client> {
-    Client::builder()
+fn build_client(timeout: Duration, proxy_url: Option<&str>) -> AppResult<Client> {
+    http_client::builder("github copilot", proxy_url)?
         .connect_timeout(Duration::from_secs(5))
         .timeout(timeout)
         .build()
         .map_err(|error| AppError::Config(format!("failed to build github copilot client: {error}")))
 }
 
-fn build_stream_client(timeout: Duration) -> AppResult<Client> {
-    Client::builder()
+fn build_stream_client(timeout: Duration, proxy_url: Option<&str>) -> AppResult<Client> {
+    http_client::builder("github copilot", proxy_url)?
         .connect_timeout(Duration::from_secs(5))
         .timeout(timeout)
         .read_timeout(timeout)
@@ -742,6 +817,9 @@ impl Provider for GithubCopilotProvider {
         request: &ChatCompletionRequest,
     ) -> AppResult<ChatCompletionResponse> {
         let token = self.copilot_api_token().await?;
+        let mut request = request.clone();
+        thinking::apply_openai_effort(&mut request);
+        let request = &request;
         let canonical_model = Self::normalize_model(&request.model);
         let use_responses = self.is_responses_request(&canonical_model, request);
         let include_vision = self.has_image_input(request);
@@ -761,22 +839,27 @@ impl Provider for GithubCopilotProvider {
             .post(endpoint)
             .headers(self.request_headers(&token, include_vision)?)
             .json(&body)
-            .send()
+            .send_traced()
             .await
-            .map_err(|error| AppError::Upstream(format!("github copilot request failed: {error}")))?;
+            .map_err(|error| {
+                UpstreamError::from_reqwest("github copilot request failed", &error)
+            })?;
 
         let status = response.status();
-        let body_text = response
-            .text()
-            .await
-            .map_err(|error| AppError::Upstream(format!("failed reading github copilot response body: {error}")))?;
+        let headers = response.headers().clone();
+        let body_text = response.text().await.map_err(|error| {
+            UpstreamError::from_reqwest("failed reading github copilot response body", &error)
+        })?;
 
         if !status.is_success() {
-            return Err(Self::map_upstream_error(status, body_text));
+            return Err(Self::map_upstream_error(status, &headers, body_text));
         }
 
-        let body: Value = serde_json::from_str(&body_text)
-            .map_err(|error| AppError::Upstream(format!("failed to parse github copilot response JSON: {error}")))?;
+        let body: Value = serde_json::from_str(&body_text).map_err(|error| {
+            UpstreamError::other(format!(
+                "failed to parse github copilot response JSON: {error}"
+            ))
+        })?;
 
         if use_responses {
             Self::parse_responses_response(body)
@@ -790,40 +873,93 @@ impl Provider for GithubCopilotProvider {
         request: &ChatCompletionRequest,
     ) -> AppResult<BoxStream> {
         let token = self.copilot_api_token().await?;
-        let canonical_model = Self::normalize_model(&request.model);
-        let use_responses = self.is_responses_request(&canonical_model, request);
-        let endpoint = if use_responses {
-            format!("{}/responses", self.api_base_url())
-        } else {
-            format!("{}/chat/completions", self.api_base_url())
-        };
-        let body = if use_responses {
-            self.convert_chat_to_responses_body(request, &canonical_model)?
-        } else {
-            self.normalize_chat_request(request)
-        };
+        let (endpoint, body) = self.stream_request(request)?;
 
         let response = self
             .stream_client
             .post(endpoint)
             .headers(self.request_headers(&token, self.has_image_input(request))?)
             .json(&body)
-            .send()
+            .send_traced()
             .await
-            .map_err(|error| AppError::Upstream(format!("github copilot stream request failed: {error}")))?;
+            .map_err(|error| {
+                UpstreamError::from_reqwest("github copilot stream request failed", &error)
+            })?;
 
         let status = response.status();
         if !status.is_success() {
+            let headers = response.headers().clone();
             let body = response.text().await.map_err(|error| {
-                AppError::Upstream(format!("failed reading github copilot stream body: {error}"))
+                UpstreamError::from_reqwest("failed reading github copilot stream body", &error)
             })?;
-            return Err(Self::map_upstream_error(status, body));
+            return Err(Self::map_upstream_error(status, &headers, body));
         }
 
         let stream = response.bytes_stream().map(|item| {
-            item.map_err(|error| AppError::Upstream(format!("github copilot stream read error: {error}")))
+            item.map_err(|error| {
+                AppError::Upstream(UpstreamError::from_reqwest(
+                    "github copilot stream read error",
+                    &error,
+                ))
+            })
         });
 
         Ok(Box::pin(stream))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::store::AuthStatus;
+
+    fn provider() -> GithubCopilotProvider {
+        let metadata = serde_json::from_value(json!({
+            "type": "github-copilot",
+            "provider_key": "github-copilot",
+            "access_token": "gho_test_token"
+        }))
+        .unwrap();
+        GithubCopilotProvider::new(AuthRecord {
+            id: "github-copilot-test.json".into(),
+            provider: "github-copilot".into(),
+            provider_key: "github-copilot".into(),
+            label: "copilot test".into(),
+            disabled: false,
+            status: AuthStatus::Active,
+            status_message: None,
+            last_refreshed_at: None,
+            path: std::env::temp_dir().join("github-copilot-test.json"),
+            metadata,
+            updated_at: Utc::now(),
+        })
+        .unwrap()
+    }
+
+    fn stream_request(model: &str) -> ChatCompletionRequest {
+        serde_json::from_value(json!({
+            "model": model,
+            "messages": [{"role": "user", "content": "hi"}],
+            "stream": true
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn chat_streams_ask_for_a_usage_chunk() {
+        let (endpoint, body) = provider()
+            .stream_request(&stream_request("gpt-4.1"))
+            .unwrap();
+        assert!(endpoint.ends_with("/chat/completions"));
+        assert_eq!(body["stream_options"]["include_usage"], true);
+    }
+
+    #[test]
+    fn responses_streams_carry_no_stream_options() {
+        let (endpoint, body) = provider()
+            .stream_request(&stream_request("gpt-5.3-codex"))
+            .unwrap();
+        assert!(endpoint.ends_with("/responses"));
+        assert!(body.get("stream_options").is_none());
+    }
+}
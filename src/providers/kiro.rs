@@ -12,19 +12,21 @@ use chrono::{DateTime, Utc};
 use reqwest::StatusCode;
 use sha2::{Digest, Sha256};
 use tokio::sync::RwLock;
-use tracing::debug;
+use tracing::{debug, Instrument};
 
 use crate::auth::kiro::{KiroTokenData, BUILDER_ID_START_URL, REFRESH_SKEW_SECS};
 use crate::auth::kiro_login::{SSOOIDCClient, SocialAuthClient};
 use crate::auth::kiro_runtime::{QuotaStatus, UsageCheckRequest};
 use crate::auth::store::AuthRecord;
-use crate::error::{AppError, AppResult};
+use crate::error::{AppError, AppResult, UpstreamError, UpstreamErrorKind};
 use crate::models::{ChatCompletionRequest, ChatCompletionResponse, ModelInfo};
+use crate::providers::http_client::{self, SendTraced};
 use crate::providers::kiro_outcome::{
     classify_kiro_response, cooldown_for_outcome, cooldown_reason_for_outcome,
     registry_action_for_outcome, KiroRequestOutcome, RegistryAction,
 };
 use crate::providers::model_registry::ModelRegistry;
+use crate::providers::retry_after;
 use crate::providers::{BoxStream, Provider};
 use crate::proxy::KiroRuntimeState;
 
@@ -248,6 +250,20 @@ impl KiroProvider {
         model_registry: Arc<ModelRegistry>,
         kiro_runtime: KiroRuntimeState,
     ) -> AppResult<Self> {
+        Self::new_with_runtime_and_proxy(record, client_id, model_registry, kiro_runtime, None)
+    }
+
+    /// Like [`Self::new_with_runtime`], with the global `proxy-url` as fallback for the
+    /// account's own `proxy_url`.
+    pub fn new_with_runtime_and_proxy(
+        record: AuthRecord,
+        client_id: String,
+        model_registry: Arc<ModelRegistry>,
+        kiro_runtime: KiroRuntimeState,
+        default_proxy_url: Option<&str>,
+    ) -> AppResult<Self> {
+        let proxy_url = http_client::effective_proxy_url(record.proxy_url(), default_proxy_url);
+        let client = http_client::default_client("kiro", proxy_url)?;
         let auth_file_path = record.path.clone();
         let account_name = record.label.clone();
         let auth_key = record.id.clone();
@@ -263,7 +279,7 @@ impl KiroProvider {
             auth_key,
             token: RwLock::new(token),
             token_data,
-            client: reqwest::Client::new(),
+            client,
             auth_file_path,
             region,
             retry_config: RetryConfig::default(),
@@ -417,9 +433,10 @@ impl KiroProvider {
                     .await;
                 Ok(())
             }
-            QuotaStatus::Exhausted { detail } => {
-                self.model_registry
-                    .set_quota_exceeded(&self.registry_client_id, model_id)
+            QuotaStatus::Exhausted { detail, next_reset } => {
+                let reset_in =
+                    next_reset.and_then(|reset| retry_after::until_epoch_millis(reset, Utc::now()));
+                self.mark_quota_exceeded(model_id, reset_in, Instant::now())
                     .await;
                 Err(AppError::QuotaExceeded(detail))
             }
@@ -530,10 +547,16 @@ impl KiroProvider {
             REFRESH_SKEW_SECS
         );
 
+        let refresh_span = tracing::info_span!(
+            "token_refresh",
+            provider = "kiro",
+            client_id = %self.registry_client_id,
+        );
         let refresh_result = if state.auth_method == "social" {
-            SocialAuthClient::new()
+            SocialAuthClient::with_client(self.client.clone())
                 .refresh_social_token(&state.refresh_token)
-                .await?
+                .instrument(refresh_span)
+                .await
         } else {
             let client_id = state
                 .client_id
@@ -550,7 +573,7 @@ impl KiroProvider {
                 .as_deref()
                 .filter(|value| !value.is_empty())
                 .unwrap_or(BUILDER_ID_START_URL);
-            SSOOIDCClient::new()
+            SSOOIDCClient::with_client(self.client.clone())
                 .refresh_token_with_region(
                     client_id,
                     client_secret,
@@ -558,8 +581,11 @@ impl KiroProvider {
                     &state.region,
                     start_url,
                 )
-                .await?
+                .instrument(refresh_span)
+                .await
         };
+        let refresh_result =
+            refresh_result.inspect_err(|_| crate::metrics::oauth_refresh_failed("kiro"))?;
 
         self.apply_refreshed_token(&mut state, refresh_result).await
     }
@@ -591,18 +617,11 @@ impl KiroProvider {
         Ok(new_token)
     }
 
-    /// Check if an error is retryable
+    /// Check if an error is retryable: socket errors, connection errors, timeouts
     fn is_retryable_error(error: &AppError) -> bool {
-        match error {
-            AppError::Upstream(msg) => {
-                // Socket errors, connection errors, timeouts
-                msg.contains("connection")
-                    || msg.contains("timeout")
-                    || msg.contains("socket")
-                    || msg.contains("broken pipe")
-            }
-            _ => false,
-        }
+        error
+            .upstream()
+            .is_some_and(|error| error.kind == UpstreamErrorKind::Transport)
     }
 
     /// Check if HTTP status code is retryable
@@ -630,10 +649,30 @@ impl KiroProvider {
         Duration::from_millis(final_delay)
     }
 
+    /// Mark `model_id` quota exceeded until the upstream reset when known, else for the
+    /// registry's default window.
+    async fn mark_quota_exceeded(&self, model_id: &str, reset_in: Option<Duration>, now: Instant) {
+        match reset_in {
+            Some(delay) => {
+                self.model_registry
+                    .set_quota_exceeded_until(&self.registry_client_id, model_id, now + delay)
+                    .await
+            }
+            None => {
+                self.model_registry
+                    .set_quota_exceeded(&self.registry_client_id, model_id)
+                    .await
+            }
+        }
+    }
+
+    /// Update registry, rate limiter and cooldown after a request; `retry_after` is the
+    /// upstream's reset hint and replaces the default quota window and 429 backoff.
     async fn apply_request_outcome(
         &self,
         model_id: &str,
         outcome: &KiroRequestOutcome,
+        retry_after: Option<Duration>,
         now: Instant,
     ) {
         match registry_action_for_outcome(outcome) {
@@ -647,9 +686,7 @@ impl KiroProvider {
                     .await;
             }
             RegistryAction::MarkQuotaExceeded => {
-                self.model_registry
-                    .set_quota_exceeded(&self.registry_client_id, model_id)
-                    .await;
+                self.mark_quota_exceeded(model_id, retry_after, now).await;
             }
             RegistryAction::SuspendClient { reason } => {
                 self.model_registry
@@ -678,7 +715,7 @@ impl KiroProvider {
                 KiroRequestOutcome::Success => cooldown.clear_cooldown(&self.auth_key, model_id),
                 _ => {
                     if let (Some(duration), Some(reason)) = (
-                        cooldown_for_outcome(outcome),
+                        cooldown_for_outcome(outcome).map(|d| retry_after.unwrap_or(d)),
                         cooldown_reason_for_outcome(outcome),
                     ) {
                         cooldown.set_cooldown(&self.auth_key, model_id, duration, reason, now);
@@ -799,13 +836,14 @@ impl KiroProvider {
                 .header("Accept", "application/vnd.amazon.eventstream")
                 .json(&kiro_request)
                 .timeout(self.retry_config.stream_read_timeout)
-                .send()
+                .send_traced()
                 .await;
 
             let resp = match resp {
                 Ok(r) => r,
                 Err(e) => {
-                    let err = AppError::Upstream(format!("kiro request failed: {e}"));
+                    let err =
+                        AppError::Upstream(UpstreamError::from_reqwest("kiro request failed", &e));
                     if Self::is_retryable_error(&err) && attempt < self.retry_config.max_retries {
                         last_error = Some(err);
                         continue;
@@ -816,11 +854,20 @@ impl KiroProvider {
 
             let status = resp.status();
             if !status.is_success() {
+                let headers = resp.headers().clone();
                 let body = format_upstream_error_body(resp.text().await);
                 let outcome = classify_kiro_response(status.as_u16(), &body, attempt);
-                self.apply_request_outcome(&req.model, &outcome, Instant::now())
+                let hint = retry_after::for_status(status, &headers, &body, Utc::now());
+                self.apply_request_outcome(&req.model, &outcome, hint, Instant::now())
                     .await;
-                let err = AppError::Upstream(format!("kiro error ({}): {}", status, body));
+                let err = AppError::from(
+                    UpstreamError::from_status(
+                        status,
+                        format!("kiro error ({}): {}", status, body),
+                    )
+                    .with_provider("kiro")
+                    .with_retry_after(hint),
+                );
 
                 if Self::is_retryable_status(status) && attempt < self.retry_config.max_retries {
                     last_error = Some(err);
@@ -831,13 +878,13 @@ impl KiroProvider {
 
             // Success - buffer and parse event stream
             let outcome = KiroRequestOutcome::Success;
-            self.apply_request_outcome(&req.model, &outcome, Instant::now())
+            self.apply_request_outcome(&req.model, &outcome, None, Instant::now())
                 .await;
 
             let bytes = resp
                 .bytes()
                 .await
-                .map_err(|e| AppError::Upstream(format!("failed to read response body: {e}")))?;
+                .map_err(|e| UpstreamError::from_reqwest("failed to read response body", &e))?;
 
             // Parse AWS Event Stream
             use std::io::Cursor;
@@ -845,12 +892,13 @@ impl KiroProvider {
             let parser = EventStreamParser::new(cursor);
             let messages = parser
                 .parse_all()
-                .map_err(|e| AppError::Upstream(format!("failed to parse event stream: {e}")))?;
+                .map_err(|e| UpstreamError::other(format!("failed to parse event stream: {e}")))?;
 
             return Ok(messages);
         }
 
-        Err(last_error.unwrap_or_else(|| AppError::Upstream("all retries failed".into())))
+        Err(last_error
+            .unwrap_or_else(|| AppError::Upstream(UpstreamError::other("all retries failed"))))
     }
 }
 
@@ -982,6 +1030,7 @@ mod tests {
         ) -> crate::auth::kiro_runtime::QuotaStatus {
             crate::auth::kiro_runtime::QuotaStatus::Exhausted {
                 detail: "test exhausted".into(),
+                next_reset: None,
             }
         }
     }
@@ -1230,6 +1279,7 @@ mod tests {
                 name: None,
                 tool_calls: None,
                 tool_call_id: None,
+                reasoning_content: None,
             }],
             stream: Some(true),
             max_tokens: Some(16),
@@ -1521,7 +1571,10 @@ mod tests {
         };
         let _ = shutdown.send(());
 
-        assert!(matches!(err, AppError::Upstream(message) if message.contains("429")));
+        assert!(matches!(
+            err,
+            AppError::Upstream(error) if error.kind == UpstreamErrorKind::RateLimited
+        ));
         assert!(
             !registry
                 .client_is_effectively_available(client_id, model_id)
@@ -1606,7 +1659,10 @@ mod tests {
         };
         let _ = shutdown.send(());
 
-        assert!(matches!(err, AppError::Upstream(message) if message.contains("403")));
+        assert!(matches!(
+            err,
+            AppError::Upstream(error) if error.status == Some(StatusCode::FORBIDDEN)
+        ));
         assert!(
             !registry
                 .client_is_effectively_available(client_id, model_id)
@@ -1632,6 +1688,54 @@ mod tests {
             .is_token_available("kiro-test.json", Instant::now()));
     }
 
+    #[tokio::test]
+    async fn token_refresh_goes_through_the_account_proxy() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        // Record the request line the proxy sees, then refuse the tunnel.
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (seen_tx, seen_rx) = oneshot::channel();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0; 4096];
+            let read = socket.read(&mut buf).await.unwrap();
+            let head = String::from_utf8_lossy(&buf[..read]).to_string();
+            let _ = seen_tx.send(head.lines().next().unwrap_or_default().to_string());
+            let _ = socket
+                .write_all(b"HTTP/1.1 502 Bad Gateway\r\ncontent-length: 0\r\n\r\n")
+                .await;
+        });
+
+        let mut record = test_record();
+        record
+            .metadata
+            .insert("auth_method".into(), json!("social"));
+        record
+            .metadata
+            .insert("expires_at".into(), json!("2020-01-01T00:00:00Z"));
+        record
+            .metadata
+            .insert("proxy_url".into(), json!(format!("http://{addr}")));
+        let provider = KiroProvider::new_with_runtime(
+            record,
+            "kiro_0".to_string(),
+            Arc::new(ModelRegistry::new()),
+            runtime_with_checker(Arc::new(NoOpQuotaChecker)),
+        )
+        .unwrap();
+
+        assert!(provider.ensure_access_token().await.is_err());
+        let request_line = tokio::time::timeout(Duration::from_secs(5), seen_rx)
+            .await
+            .expect("refresh did not reach the proxy")
+            .unwrap();
+        assert!(
+            request_line.starts_with("CONNECT prod.us-east-1.auth.desktop.kiro.dev:443"),
+            "{request_line}"
+        );
+    }
+
     #[tokio::test]
     async fn pre_request_check_blocks_auth_in_cooldown() {
         let registry = Arc::new(ModelRegistry::new());
@@ -11,6 +11,7 @@ use uuid::Uuid;
 use crate::models::{
     ChatCompletionRequest, ChatCompletionResponse, ChatMessage, Choice, MessageContent, Usage,
 };
+use crate::providers::thinking;
 
 // ── Native Kiro Request Structures ──────────────────────────────────────────
 
@@ -138,7 +139,18 @@ pub fn build_native_kiro_request(
     let model_id = upstream_kiro_model_id(&req.model);
 
     // Separate current message from history
-    let (current_content, history) = extract_messages_and_history(&req.messages, &model_id);
+    let (mut current_content, history) = extract_messages_and_history(&req.messages, &model_id);
+
+    // Kiro has no structured thinking field; the budget travels as a content prefix
+    if let Some(reasoning) = thinking::requested_reasoning(req).filter(|r| !r.is_disabled()) {
+        let budget = match reasoning.budget(thinking::static_support(&req.model).as_ref()) {
+            budget if budget > 0 => budget,
+            _ => thinking::Reasoning::Effort("medium".to_string()).budget(None),
+        };
+        current_content = format!(
+            "<thinking_mode>enabled</thinking_mode><max_thinking_length>{budget}</max_thinking_length>\n{current_content}"
+        );
+    }
 
     // Build current message
     let user_input_message = UserInputMessage {
@@ -166,6 +178,7 @@ pub fn build_native_kiro_request(
 }
 
 fn upstream_kiro_model_id(model: &str) -> String {
+    let (model, _) = thinking::strip_thinking_suffix(model);
     match model {
         "amazonq-auto" | "kiro-auto" | "auto" => "auto".to_string(),
         "amazonq-claude-opus-4-6"
@@ -193,8 +206,7 @@ fn upstream_kiro_model_id(model: &str) -> String {
         | "claude-sonnet-4-5"
         | "claude-sonnet-4.5"
         | "claude-sonnet-4.5-agentic"
-        | "kiro-claude-sonnet-4-5-agentic"
-        | "claude-sonnet-4.5-thinking" => "claude-sonnet-4.5".to_string(),
+        | "kiro-claude-sonnet-4-5-agentic" => "claude-sonnet-4.5".to_string(),
         "amazonq-claude-sonnet-4"
         | "amazonq-claude-sonnet-4-20250514"
         | "kiro-claude-sonnet-4"
@@ -231,14 +243,12 @@ fn extract_messages_and_history(
         let content = extract_text_content(&msg.content);
 
         match msg.role.as_str() {
-            "system" => {
-                // System messages go into current content as prefix
-                if !content.is_empty() {
-                    if !current_content.is_empty() {
-                        current_content.push_str("\n\n");
-                    }
-                    current_content.push_str(&content);
+            // System messages go into current content as prefix
+            "system" if !content.is_empty() => {
+                if !current_content.is_empty() {
+                    current_content.push_str("\n\n");
                 }
+                current_content.push_str(&content);
             }
             "user" => {
                 if is_last {
@@ -302,6 +312,8 @@ pub enum KiroEventType {
     MessageStop,
     /// Assistant response event (KIRO-specific)
     AssistantResponseEvent,
+    /// Reasoning text streamed ahead of the reply
+    ReasoningContentEvent,
     /// Tool use event
     ToolUseEvent,
     /// Usage/metrics event
@@ -326,6 +338,7 @@ impl KiroEventType {
             "message_delta" | "messageDelta" => Self::MessageDelta,
             "message_stop" | "messageStop" => Self::MessageStop,
             "assistantResponseEvent" => Self::AssistantResponseEvent,
+            "reasoningContentEvent" => Self::ReasoningContentEvent,
             "toolUseEvent" => Self::ToolUseEvent,
             "usageEvent" | "messageMetadataEvent" => Self::UsageEvent,
             "meteringEvent" => Self::MeteringEvent,
@@ -389,9 +402,35 @@ pub fn translate_kiro_event_to_openai_sse(
         }
 
         KiroEventType::ContentBlockDelta | KiroEventType::AssistantResponseEvent => {
-            // Extract text content
-            let text = extract_delta_text(&payload_json);
-            if text.is_empty() {
+            // Extract text content; thinking deltas go out as reasoning_content
+            let reasoning = extract_reasoning_text(&payload_json);
+            let delta = if !reasoning.is_empty() {
+                json!({ "reasoning_content": reasoning })
+            } else {
+                let text = extract_delta_text(&payload_json);
+                if text.is_empty() {
+                    return None;
+                }
+                json!({ "content": text })
+            };
+
+            let chunk = json!({
+                "id": chat_id,
+                "object": "chat.completion.chunk",
+                "created": created,
+                "model": model,
+                "choices": [{
+                    "index": 0,
+                    "delta": delta,
+                    "finish_reason": null
+                }]
+            });
+            Some(format_sse_event("message", &chunk))
+        }
+
+        KiroEventType::ReasoningContentEvent => {
+            let reasoning = extract_reasoning_event_text(&payload_json);
+            if reasoning.is_empty() {
                 return None;
             }
 
@@ -403,7 +442,7 @@ pub fn translate_kiro_event_to_openai_sse(
                 "choices": [{
                     "index": 0,
                     "delta": {
-                        "content": text
+                        "reasoning_content": reasoning
                     },
                     "finish_reason": null
                 }]
@@ -475,6 +514,27 @@ fn extract_delta_text(payload: &Value) -> String {
     String::new()
 }
 
+/// Extract reasoning text from a thinking delta or reasoningContentEvent payload
+fn extract_reasoning_text(payload: &Value) -> String {
+    if let Some(text) = payload["delta"]["thinking"].as_str() {
+        return text.to_string();
+    }
+    if let Some(text) = payload["reasoningText"]["text"].as_str() {
+        return text.to_string();
+    }
+    String::new()
+}
+
+/// Extract reasoning text from a reasoningContentEvent, which may also carry it flat
+fn extract_reasoning_event_text(payload: &Value) -> String {
+    let text = extract_reasoning_text(payload);
+    if text.is_empty() {
+        extract_delta_text(payload)
+    } else {
+        text
+    }
+}
+
 /// Extract usage information from payload
 fn extract_usage(payload: &Value) -> Option<Value> {
     let input_tokens = payload["inputTokens"]
@@ -499,6 +559,8 @@ fn extract_usage(payload: &Value) -> Option<Value> {
 pub struct KiroAggregatedResponse {
     /// Accumulated text content
     pub content: String,
+    /// Accumulated reasoning text
+    pub reasoning_content: String,
     /// Tool calls in OpenAI format
     pub tool_calls: Vec<Value>,
     /// Usage information
@@ -511,6 +573,7 @@ pub struct KiroAggregatedResponse {
 ///
 /// Processes event stream messages and accumulates:
 /// - Text content from assistantResponseEvent and contentBlockDelta
+/// - Reasoning text from reasoningContentEvent and thinking deltas
 /// - Tool calls from toolUseEvent and toolUses arrays
 /// - Usage data from usageEvent
 /// - Stop reason from messageStop
@@ -547,12 +610,20 @@ pub fn aggregate_kiro_messages(
 
             KiroEventType::ContentBlockDelta => {
                 // Extract delta text
-                let text = extract_delta_text(&payload);
-                if !text.is_empty() {
-                    result.content.push_str(&text);
+                let reasoning = extract_reasoning_text(&payload);
+                if !reasoning.is_empty() {
+                    result.reasoning_content.push_str(&reasoning);
+                } else {
+                    result.content.push_str(&extract_delta_text(&payload));
                 }
             }
 
+            KiroEventType::ReasoningContentEvent => {
+                result
+                    .reasoning_content
+                    .push_str(&extract_reasoning_event_text(&payload));
+            }
+
             KiroEventType::ToolUseEvent => {
                 // Extract tool from payload - supports both nested and flat shapes
                 if let Some(tool) = payload["tool"].as_object() {
@@ -655,6 +726,8 @@ pub fn build_openai_chat_completion_response(
 
     // Build assistant message
     let content = MessageContent::Text(aggregate.content);
+    let reasoning_content =
+        (!aggregate.reasoning_content.is_empty()).then_some(aggregate.reasoning_content);
     let tool_calls = if aggregate.tool_calls.is_empty() {
         None
     } else {
@@ -667,6 +740,7 @@ pub fn build_openai_chat_completion_response(
         name: None,
         tool_calls,
         tool_call_id: None,
+        reasoning_content,
     };
 
     // Build choice
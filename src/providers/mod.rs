@@ -1,30 +1,48 @@
+pub mod anthropic;
 pub mod antigravity;
+pub mod api_key;
+pub mod claude_api;
 pub mod codex;
+pub mod codex_api;
+pub mod gemini_api;
 pub mod github_copilot;
+pub mod http_client;
 pub mod kiro;
 pub mod kiro_outcome;
 pub mod kiro_stream;
 pub mod kiro_translator;
 pub mod model_info;
 pub mod model_registry;
+pub mod openai_compat;
 pub mod registry;
+pub mod retry_after;
 pub mod static_models;
+pub mod thinking;
 pub mod zed;
 pub mod zed_anthropic;
 pub mod zed_request;
 pub mod zed_response;
 
 use crate::{
-    error::AppResult,
+    error::{AppError, AppResult},
     models::{ChatCompletionRequest, ChatCompletionResponse, ModelInfo},
 };
 use async_trait::async_trait;
 use bytes::Bytes;
 use futures::Stream;
+use serde_json::Value;
 use std::pin::Pin;
 
 pub type BoxStream = Pin<Box<dyn Stream<Item = AppResult<Bytes>> + Send>>;
 
+/// Result of a native Anthropic Messages call.
+pub enum AnthropicResponse {
+    /// Complete Anthropic `message` object.
+    Message(Value),
+    /// Anthropic Messages SSE stream (`event:` / `data:` framed).
+    Stream(BoxStream),
+}
+
 /// Implemented by every upstream provider (Gemini, Claude, Codex, Qwen, iFlow, Antigravity…)
 #[async_trait]
 pub trait Provider: Send + Sync {
@@ -50,4 +68,20 @@ pub trait Provider: Send + Sync {
 
     /// Streaming chat completion — returns SSE byte stream
     async fn chat_completion_stream(&self, req: &ChatCompletionRequest) -> AppResult<BoxStream>;
+
+    /// Whether `model` can be served by [`Provider::anthropic_messages`] without a round
+    /// trip through the OpenAI request types.
+    fn supports_anthropic_messages(&self, _model: &str) -> bool {
+        false
+    }
+
+    /// Native Anthropic Messages call. `body` is the caller's `/v1/messages` body with
+    /// `model` already resolved to this provider's model id; `stream` in the body picks
+    /// the response variant.
+    async fn anthropic_messages(&self, _body: &Value) -> AppResult<AnthropicResponse> {
+        Err(AppError::BadRequest(format!(
+            "{} does not accept Anthropic Messages requests",
+            self.name()
+        )))
+    }
 }
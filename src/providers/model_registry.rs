@@ -8,19 +8,45 @@ use serde_json::{json, Value};
 use tokio::sync::RwLock;
 use tracing::debug;
 
-use super::model_info::ExtModelInfo;
+use super::model_info::{ExtModelInfo, ThinkingSupport};
 use super::static_models;
+use crate::config::ModelRoute;
 
 const QUOTA_EXPIRED_DURATION: Duration = Duration::from_secs(300); // 5 minutes
 
 // ── Registration ─────────────────────────────────────────────────────────────
 
+/// Quota-exceeded mark for one client on one model.
+#[derive(Debug, Clone, Copy)]
+struct QuotaMark {
+    until: Instant,
+    /// The expiry came from the upstream (Retry-After, resets_at, a quota probe)
+    /// rather than the default window, so it survives client re-registration.
+    reset_known: bool,
+}
+
+impl QuotaMark {
+    fn is_active(&self, now: Instant) -> bool {
+        now < self.until
+    }
+}
+
+/// An active quota mark, as exported for persistence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuotaMarkEntry {
+    pub client_id: String,
+    pub model_id: String,
+    pub until: Instant,
+    /// The upstream said when the quota resets
+    pub reset_known: bool,
+}
+
 struct ModelRegistration {
     info: ExtModelInfo,
     info_by_provider: HashMap<String, ExtModelInfo>,
     count: i32,
     last_updated: Instant,
-    quota_exceeded_clients: HashMap<String, Instant>,
+    quota_exceeded_clients: HashMap<String, QuotaMark>,
     providers: HashMap<String, i32>,
     suspended_clients: HashMap<String, String>,
 }
@@ -175,7 +201,13 @@ impl ModelRegistry {
                         .insert(provider.clone(), new_models[id].clone());
                 }
                 registration.last_updated = now;
-                registration.quota_exceeded_clients.remove(client_id);
+                if registration
+                    .quota_exceeded_clients
+                    .get(client_id)
+                    .is_some_and(|mark| !mark.reset_known || !mark.is_active(now))
+                {
+                    registration.quota_exceeded_clients.remove(client_id);
+                }
                 registration.suspended_clients.remove(client_id);
             }
         }
@@ -230,15 +262,61 @@ impl ModelRegistry {
         debug!("unregistered client {}", client_id);
     }
 
-    /// Mark model as quota exceeded for client.
+    /// Mark model as quota exceeded for client for the default window.
+    ///
+    /// Prefer [`ModelRegistry::set_quota_exceeded_until`] when the upstream says
+    /// when the quota resets.
     pub async fn set_quota_exceeded(&self, client_id: &str, model_id: &str) {
+        let until = Instant::now() + QUOTA_EXPIRED_DURATION;
+        self.mark_quota(client_id, model_id, until, false).await;
+    }
+
+    /// Mark model as quota exceeded for client until the upstream's reset time.
+    pub async fn set_quota_exceeded_until(&self, client_id: &str, model_id: &str, until: Instant) {
+        self.mark_quota(client_id, model_id, until, true).await;
+    }
+
+    /// Mark every model of a client as quota exceeded until `until`, for
+    /// account-wide limits reported by quota probes.
+    pub async fn set_client_quota_exceeded_until(&self, client_id: &str, until: Instant) {
+        let models = self
+            .client_models
+            .read()
+            .await
+            .get(client_id)
+            .cloned()
+            .unwrap_or_default();
+        for model_id in models {
+            self.mark_quota(client_id, &model_id, until, true).await;
+        }
+    }
+
+    async fn mark_quota(&self, client_id: &str, model_id: &str, until: Instant, reset_known: bool) {
         if let Some(reg) = self.models.write().await.get_mut(model_id) {
             reg.quota_exceeded_clients
-                .insert(client_id.to_string(), Instant::now());
-            debug!("marked {} quota exceeded for {}", model_id, client_id);
+                .insert(client_id.to_string(), QuotaMark { until, reset_known });
+            debug!(
+                "marked {} quota exceeded for {} ({}s)",
+                model_id,
+                client_id,
+                until.saturating_duration_since(Instant::now()).as_secs()
+            );
         }
     }
 
+    /// Time until a client's quota for a model resets, if it is currently exceeded.
+    pub async fn quota_reset_in(&self, client_id: &str, model_id: &str) -> Option<Duration> {
+        let now = Instant::now();
+        self.models
+            .read()
+            .await
+            .get(model_id)?
+            .quota_exceeded_clients
+            .get(client_id)
+            .filter(|mark| mark.is_active(now))
+            .map(|mark| mark.until - now)
+    }
+
     /// Clear quota exceeded for client/model.
     pub async fn clear_quota_exceeded(&self, client_id: &str, model_id: &str) {
         if let Some(reg) = self.models.write().await.get_mut(model_id) {
@@ -321,6 +399,53 @@ impl ModelRegistry {
         static_models::lookup_static_model(model_id)
     }
 
+    /// Thinking capabilities for a model, falling back to the static catalog when the
+    /// registered entry carries none (runtime model lists rarely do).
+    pub async fn thinking_support(
+        &self,
+        model_id: &str,
+        provider: &str,
+    ) -> Option<ThinkingSupport> {
+        let (base_model, _) = super::thinking::strip_thinking_suffix(model_id);
+        for id in [model_id, base_model] {
+            if let Some(thinking) = self
+                .get_model_info(id, provider)
+                .await
+                .and_then(|info| info.thinking)
+            {
+                return Some(thinking);
+            }
+            if let Some(thinking) =
+                static_models::lookup_static_model(id).and_then(|info| info.thinking)
+            {
+                return Some(thinking);
+            }
+        }
+        None
+    }
+
+    /// Route targets whose model is neither registered by a client of the target's
+    /// provider nor known to the static catalog, as `route -> provider/model`.
+    pub async fn unknown_route_targets(&self, routes: &[ModelRoute]) -> Vec<String> {
+        let mut unknown = Vec::new();
+        for route in routes {
+            for target in &route.targets {
+                let registered = self
+                    .get_model_providers(&target.model)
+                    .await
+                    .iter()
+                    .any(|provider| provider.eq_ignore_ascii_case(&target.provider));
+                if !registered && static_models::lookup_static_model(&target.model).is_none() {
+                    unknown.push(format!(
+                        "{} -> {}/{}",
+                        route.name, target.provider, target.model
+                    ));
+                }
+            }
+        }
+        unknown
+    }
+
     /// Get providers that supply a model, ordered by count desc.
     pub async fn get_model_providers(&self, model_id: &str) -> Vec<String> {
         let reg = self.models.read().await;
@@ -337,13 +462,51 @@ impl ModelRegistry {
         providers.into_iter().map(|(k, _)| k).collect()
     }
 
+    /// Quota marks still active now.
+    pub async fn active_quota_marks(&self) -> Vec<QuotaMarkEntry> {
+        let now = Instant::now();
+        let reg = self.models.read().await;
+        reg.iter()
+            .flat_map(|(model_id, registration)| {
+                registration
+                    .quota_exceeded_clients
+                    .iter()
+                    .filter(|(_, mark)| mark.is_active(now))
+                    .map(|(client_id, mark)| QuotaMarkEntry {
+                        client_id: client_id.clone(),
+                        model_id: model_id.clone(),
+                        until: mark.until,
+                        reset_known: mark.reset_known,
+                    })
+            })
+            .collect()
+    }
+
+    /// Reinstate a quota mark recorded before a restart, if the client still
+    /// serves the model.
+    pub async fn restore_quota_mark(&self, entry: QuotaMarkEntry) {
+        if self
+            .client_supports_model(&entry.client_id, &entry.model_id)
+            .await
+        {
+            self.mark_quota(
+                &entry.client_id,
+                &entry.model_id,
+                entry.until,
+                entry.reset_known,
+            )
+            .await;
+        }
+    }
+
     /// Cleanup expired quota entries.
     pub async fn cleanup_expired_quotas(&self) {
+        let now = Instant::now();
         let mut reg = self.models.write().await;
         for registration in reg.values_mut() {
             registration
                 .quota_exceeded_clients
-                .retain(|_, t| t.elapsed() < QUOTA_EXPIRED_DURATION);
+                .retain(|_, mark| mark.is_active(now));
         }
     }
 
@@ -364,14 +527,14 @@ impl ModelRegistry {
 
     /// Mark model as quota exceeded for client at a specific instant (for testability).
     pub async fn set_quota_exceeded_at(&self, client_id: &str, model_id: &str, at: Instant) {
-        if let Some(reg) = self.models.write().await.get_mut(model_id) {
-            reg.quota_exceeded_clients.insert(client_id.to_string(), at);
-        }
+        self.mark_quota(client_id, model_id, at + QUOTA_EXPIRED_DURATION, false)
+            .await;
     }
 
     /// Get list of client IDs that support a model and are effectively available
     /// (not quota-exceeded, not suspended).
     pub async fn available_clients_for_model(&self, model_id: &str) -> Vec<String> {
+        let now = Instant::now();
         let client_models = self.client_models.read().await;
         let reg = self.models.read().await;
 
@@ -385,8 +548,8 @@ impl ModelRegistry {
                 continue;
             }
             // Check quota-exceeded
-            if let Some(t) = registration.quota_exceeded_clients.get(client_id) {
-                if t.elapsed() < QUOTA_EXPIRED_DURATION {
+            if let Some(mark) = registration.quota_exceeded_clients.get(client_id) {
+                if mark.is_active(now) {
                     continue;
                 }
             }
@@ -418,8 +581,8 @@ impl ModelRegistry {
         };
 
         // Check quota-exceeded
-        if let Some(t) = registration.quota_exceeded_clients.get(client_id) {
-            if t.elapsed() < QUOTA_EXPIRED_DURATION {
+        if let Some(mark) = registration.quota_exceeded_clients.get(client_id) {
+            if mark.is_active(Instant::now()) {
                 return false;
             }
         }
@@ -515,9 +678,10 @@ impl ModelRegistry {
     }
 
     fn expired_quota_count(r: &ModelRegistration) -> i32 {
+        let now = Instant::now();
         r.quota_exceeded_clients
             .values()
-            .filter(|t| t.elapsed() < QUOTA_EXPIRED_DURATION)
+            .filter(|mark| mark.is_active(now))
             .count() as i32
     }
 
@@ -0,0 +1,227 @@
+//! Generic OpenAI-compatible upstream (OpenRouter, vLLM, llama.cpp, …) built from
+//! `openai-compatibility` config entries — one provider per `api-key-entries` item.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use reqwest::Client;
+use serde_json::{json, Value};
+use tracing::warn;
+
+use crate::config::{OpenAICompatKeyEntry, OpenAICompatProvider};
+use crate::error::{AppError, AppResult, UpstreamError};
+use crate::models::{ChatCompletionRequest, ChatCompletionResponse, ModelInfo};
+use crate::providers::api_key::{self, KeyModelCatalog};
+use crate::providers::codex::parse_usage;
+use crate::providers::http_client::SendTraced;
+use crate::providers::{BoxStream, Provider};
+
+/// Provider type used when an `openai-compatibility` entry has no `name`.
+pub const DEFAULT_PROVIDER_TYPE: &str = "openai-compatibility";
+
+pub struct OpenAICompatibleProvider {
+    name: String,
+    client_id: String,
+    base_url: String,
+    api_key: String,
+    headers: HashMap<String, String>,
+    catalog: KeyModelCatalog,
+    client: Client,
+    stream_client: Client,
+}
+
+impl OpenAICompatibleProvider {
+    pub fn new(
+        config: &OpenAICompatProvider,
+        key: &OpenAICompatKeyEntry,
+        client_id: String,
+    ) -> AppResult<Self> {
+        let base_url = config.base_url.trim().trim_end_matches('/').to_string();
+        if base_url.is_empty() {
+            return Err(AppError::Config(format!(
+                "openai-compatibility entry {} missing base-url",
+                config.name
+            )));
+        }
+
+        let name = Self::provider_type_for(config);
+        let proxy_url = key.proxy_url.as_deref();
+
+        Ok(Self {
+            client_id,
+            base_url,
+            api_key: key.api_key.trim().to_string(),
+            headers: config.headers.clone(),
+            catalog: KeyModelCatalog::new(config.prefix.as_deref(), &config.models, &[], vec![]),
+            client: api_key::build_client(&name, Duration::from_secs(300), proxy_url)?,
+            stream_client: api_key::build_stream_client(
+                &name,
+                Duration::from_secs(300),
+                proxy_url,
+            )?,
+            name,
+        })
+    }
+
+    /// Routing provider type for an entry — its configured `name`, lowercased.
+    pub fn provider_type_for(config: &OpenAICompatProvider) -> String {
+        let name = config.name.trim();
+        if name.is_empty() {
+            DEFAULT_PROVIDER_TYPE.to_string()
+        } else {
+            name.to_ascii_lowercase()
+        }
+    }
+
+    fn prepare_request(&self, request: &ChatCompletionRequest, stream: bool) -> Value {
+        let mut request = request.clone();
+        request.model = self.catalog.upstream_model(&request.model);
+        request.stream = Some(stream);
+        for key in api_key::INTERNAL_EXTRA_KEYS {
+            request.extra.remove(*key);
+        }
+        if stream {
+            request
+                .extra
+                .entry("stream_options".to_string())
+                .or_insert_with(|| json!({"include_usage": true}));
+        }
+
+        serde_json::to_value(request).unwrap_or_default()
+    }
+
+    fn authorize(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        let request = if self.api_key.is_empty() {
+            request
+        } else {
+            request.bearer_auth(&self.api_key)
+        };
+        api_key::apply_headers(request, &self.headers)
+    }
+
+    async fn send(
+        &self,
+        client: &Client,
+        body: &Value,
+        accept: &str,
+    ) -> AppResult<reqwest::Response> {
+        let request = client
+            .post(format!("{}/chat/completions", self.base_url))
+            .header("content-type", "application/json")
+            .header("accept", accept)
+            .json(body);
+
+        let response = self.authorize(request).send_traced().await.map_err(|e| {
+            UpstreamError::from_reqwest(format!("{} request failed", self.name), &e)
+        })?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let headers = response.headers().clone();
+            let body = response.text().await.unwrap_or_default();
+            return Err(api_key::map_upstream_error(
+                &self.name, status, &headers, body,
+            ));
+        }
+
+        Ok(response)
+    }
+
+    /// Ask the upstream for its model list when none are configured.
+    async fn fetch_upstream_models(&self) -> AppResult<Vec<ModelInfo>> {
+        let response = self
+            .authorize(self.client.get(format!("{}/models", self.base_url)))
+            .send_traced()
+            .await
+            .map_err(|e| {
+                UpstreamError::from_reqwest(format!("{} models request failed", self.name), &e)
+            })?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let headers = response.headers().clone();
+            let body = response.text().await.unwrap_or_default();
+            return Err(api_key::map_upstream_error(
+                &self.name, status, &headers, body,
+            ));
+        }
+
+        let body: Value = response.json().await.map_err(|e| {
+            UpstreamError::from_reqwest(format!("{} models parse failed", self.name), &e)
+        })?;
+
+        Ok(body["data"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|model| model["id"].as_str())
+            .map(|id| self.catalog.exposed_model_info(id, &self.name))
+            .collect())
+    }
+}
+
+#[async_trait]
+impl Provider for OpenAICompatibleProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn client_id(&self) -> &str {
+        &self.client_id
+    }
+
+    async fn list_models(&self) -> AppResult<Vec<ModelInfo>> {
+        if !self.catalog.is_empty() {
+            return Ok(self.catalog.model_infos(&self.name));
+        }
+
+        // An unreachable local server shouldn't fail the whole runtime refresh.
+        match self.fetch_upstream_models().await {
+            Ok(models) => Ok(models),
+            Err(e) => {
+                warn!(
+                    "{} ({}) model discovery failed: {e}",
+                    self.name, self.client_id
+                );
+                Ok(Vec::new())
+            }
+        }
+    }
+
+    async fn chat_completion(
+        &self,
+        request: &ChatCompletionRequest,
+    ) -> AppResult<ChatCompletionResponse> {
+        let body = self.prepare_request(request, false);
+        let response = self.send(&self.client, &body, "application/json").await?;
+
+        let body: Value = response.json().await.map_err(|e| {
+            UpstreamError::from_reqwest(format!("failed to parse {} response JSON", self.name), &e)
+        })?;
+        let mut decoded: ChatCompletionResponse =
+            serde_json::from_value(body.clone()).map_err(|e| {
+                UpstreamError::other(format!("failed to decode {} response: {e}", self.name))
+            })?;
+        if decoded.usage.is_none() {
+            decoded.usage = body.get("usage").cloned().and_then(parse_usage);
+        }
+        decoded.model = request.model.clone();
+
+        Ok(decoded)
+    }
+
+    async fn chat_completion_stream(
+        &self,
+        request: &ChatCompletionRequest,
+    ) -> AppResult<BoxStream> {
+        let body = self.prepare_request(request, true);
+        let response = self
+            .send(&self.stream_client, &body, "text/event-stream")
+            .await?;
+
+        Ok(crate::proxy::stream::passthrough_sse_stream(
+            response.bytes_stream(),
+        ))
+    }
+}
@@ -6,42 +6,59 @@ use std::sync::Arc;
 use tracing::{info, warn};
 
 use crate::auth::manager::AccountManager;
-use crate::config::Config;
+use crate::config::{Config, ProviderKeyEntry};
 use crate::providers::antigravity::AntigravityProvider;
+use crate::providers::claude_api::{self, ClaudeApiKeyProvider};
 use crate::providers::codex::CodexProvider;
+use crate::providers::codex_api::{self, CodexApiKeyProvider};
+use crate::providers::gemini_api::{self, GeminiApiKeyProvider};
 use crate::providers::github_copilot::GithubCopilotProvider;
+use crate::providers::http_client;
 use crate::providers::kiro::KiroProvider;
 use crate::providers::model_registry::ModelRegistry;
+use crate::providers::openai_compat::OpenAICompatibleProvider;
 use crate::providers::zed::ZedProvider;
 use crate::providers::Provider;
 use crate::proxy::KiroRuntimeState;
 
 /// Build all providers from loaded accounts and config.
 pub async fn build_providers(
-    _config: &Config,
+    config: &Config,
     accounts: &AccountManager,
     model_registry: Arc<ModelRegistry>,
     kiro_runtime: KiroRuntimeState,
 ) -> Vec<Arc<dyn Provider>> {
     let mut providers: Vec<Arc<dyn Provider>> = Vec::new();
+    let proxy_url = config.proxy_url.as_deref();
 
     // ── Antigravity (OAuth) ──────────────────────────────────────────────
     for record in accounts.accounts_for("antigravity").await {
-        info!(
-            "registering antigravity provider: {} ({})",
-            record.label, record.id
-        );
-        providers.push(Arc::new(AntigravityProvider::new(record)));
+        match AntigravityProvider::new_with_proxy(record.clone(), proxy_url) {
+            Ok(provider) => {
+                info!(
+                    "registering antigravity provider: {} ({})",
+                    record.label, record.id
+                );
+                providers.push(Arc::new(provider));
+            }
+            Err(e) => {
+                warn!(
+                    "skipping antigravity account {} ({}): {e}",
+                    record.label, record.id
+                );
+            }
+        }
     }
 
     // ── Kiro (AWS CodeWhisperer) ───────────────────────────────────────────
     for record in accounts.accounts_for("kiro").await {
         let client_id = format!("kiro_{}", providers.len());
-        match KiroProvider::new_with_runtime(
+        match KiroProvider::new_with_runtime_and_proxy(
             record.clone(),
             client_id,
             model_registry.clone(),
             kiro_runtime.clone(),
+            proxy_url,
         ) {
             Ok(provider) => {
                 info!(
@@ -61,7 +78,7 @@ pub async fn build_providers(
 
     // ── Codex (OpenAI OAuth) ─────────────────────────────────────────────
     for record in accounts.accounts_for("codex").await {
-        match CodexProvider::new(record.clone()) {
+        match CodexProvider::new_with_proxy(record.clone(), proxy_url) {
             Ok(provider) => {
                 info!(
                     "registering codex provider: {} ({})",
@@ -80,7 +97,7 @@ pub async fn build_providers(
 
     // ── GitHub Copilot ────────────────────────────────────────────────────
     for record in accounts.accounts_for("github-copilot").await {
-        match GithubCopilotProvider::new(record.clone()) {
+        match GithubCopilotProvider::new_with_proxy(record.clone(), proxy_url) {
             Ok(provider) => {
                 info!(
                     "registering github-copilot provider: {} ({})",
@@ -99,7 +116,7 @@ pub async fn build_providers(
 
     // ── Zed Cloud ────────────────────────────────────────────────────────
     for record in accounts.accounts_for("zed").await {
-        match ZedProvider::new(record.clone()) {
+        match ZedProvider::new_with_proxy(record.clone(), proxy_url) {
             Ok(provider) => {
                 info!("registering zed provider: {} ({})", record.label, record.id);
                 providers.push(Arc::new(provider));
@@ -119,10 +136,70 @@ pub async fn build_providers(
         );
     }
 
-    // ── API-key providers from config — future ───────────────────────────
-    // gemini-api-key, codex-api-key, claude-api-key, openai-compatibility
-    // will be built here when those providers are implemented.
+    // ── API-key providers from config ────────────────────────────────────
+    for (idx, entry) in config.gemini_api_keys.iter().enumerate() {
+        let client_id = format!("{}_{idx}", gemini_api::PROVIDER_TYPE);
+        match GeminiApiKeyProvider::new(with_default_proxy(entry, proxy_url), client_id.clone()) {
+            Ok(provider) => {
+                info!("registering gemini-api provider: {client_id}");
+                providers.push(Arc::new(provider));
+            }
+            Err(e) => warn!("skipping gemini-api-key entry {idx}: {e}"),
+        }
+    }
+
+    for (idx, entry) in config.codex_api_keys.iter().enumerate() {
+        let client_id = format!("{}_{idx}", codex_api::PROVIDER_TYPE);
+        match CodexApiKeyProvider::new(with_default_proxy(entry, proxy_url), client_id.clone()) {
+            Ok(provider) => {
+                info!("registering codex-api provider: {client_id}");
+                providers.push(Arc::new(provider));
+            }
+            Err(e) => warn!("skipping codex-api-key entry {idx}: {e}"),
+        }
+    }
+
+    for (idx, entry) in config.claude_api_keys.iter().enumerate() {
+        let client_id = format!("{}_{idx}", claude_api::PROVIDER_TYPE);
+        match ClaudeApiKeyProvider::new(with_default_proxy(entry, proxy_url), client_id.clone()) {
+            Ok(provider) => {
+                info!("registering claude-api provider: {client_id}");
+                providers.push(Arc::new(provider));
+            }
+            Err(e) => warn!("skipping claude-api-key entry {idx}: {e}"),
+        }
+    }
+
+    // ── OpenAI-compatible providers from config ──────────────────────────
+    for (idx, compat) in config.openai_compat.iter().enumerate() {
+        let provider_type = OpenAICompatibleProvider::provider_type_for(compat);
+        for (key_idx, key) in compat.api_key_entries.iter().enumerate() {
+            let client_id = format!("{provider_type}_{idx}_{key_idx}");
+            let mut key = key.clone();
+            key.proxy_url = http_client::effective_proxy_url(key.proxy_url.as_deref(), proxy_url)
+                .map(str::to_string);
+            match OpenAICompatibleProvider::new(compat, &key, client_id.clone()) {
+                Ok(provider) => {
+                    info!("registering openai-compatibility provider: {client_id}");
+                    providers.push(Arc::new(provider));
+                }
+                Err(e) => warn!("skipping openai-compatibility entry {idx}/{key_idx}: {e}"),
+            }
+        }
+    }
 
     info!("registered {} total provider(s)", providers.len());
     providers
 }
+
+/// Clone an API-key entry, falling back to the global `proxy-url` when it has none.
+fn with_default_proxy(
+    entry: &ProviderKeyEntry,
+    default_proxy_url: Option<&str>,
+) -> ProviderKeyEntry {
+    let mut entry = entry.clone();
+    entry.proxy_url =
+        http_client::effective_proxy_url(entry.proxy_url.as_deref(), default_proxy_url)
+            .map(str::to_string);
+    entry
+}
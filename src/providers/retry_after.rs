@@ -0,0 +1,83 @@
+//! Reset hints on rate-limit and quota responses.
+//!
+//! Upstreams say when a limit lifts in different places: the standard
+//! `Retry-After` header (seconds or an HTTP date), OpenAI/Codex
+//! `error.resets_at` / `error.resets_in_seconds`, and Google `RetryInfo`
+//! details with a `retryDelay` such as `"37s"`. [`from_response`] reads all of
+//! them so the model registry can keep an account out until the real reset.
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::StatusCode;
+use serde_json::Value;
+
+/// Time until the limit resets, from headers first and then the error body.
+pub fn from_response(headers: &HeaderMap, body: &str, now: DateTime<Utc>) -> Option<Duration> {
+    from_headers(headers, now).or_else(|| {
+        serde_json::from_str::<Value>(body)
+            .ok()
+            .and_then(|body| from_body(&body, now))
+    })
+}
+
+/// Reset hint for a rate-limited (429) reply; other statuses carry none.
+pub fn for_status(
+    status: StatusCode,
+    headers: &HeaderMap,
+    body: &str,
+    now: DateTime<Utc>,
+) -> Option<Duration> {
+    if status != StatusCode::TOO_MANY_REQUESTS {
+        return None;
+    }
+    from_response(headers, body, now)
+}
+
+/// `Retry-After` as delta-seconds or an HTTP date.
+pub fn from_headers(headers: &HeaderMap, now: DateTime<Utc>) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = DateTime::parse_from_rfc2822(value).ok()?;
+    until(at.with_timezone(&Utc), now)
+}
+
+/// Reset fields inside a JSON error body.
+pub fn from_body(body: &Value, now: DateTime<Utc>) -> Option<Duration> {
+    let error = body.get("error").unwrap_or(body);
+
+    if let Some(resets_at) = error.get("resets_at").and_then(Value::as_i64) {
+        if let Some(at) = DateTime::from_timestamp(resets_at, 0) {
+            return until(at, now);
+        }
+    }
+    if let Some(secs) = error.get("resets_in_seconds").and_then(Value::as_u64) {
+        return Some(Duration::from_secs(secs));
+    }
+
+    error
+        .get("details")
+        .and_then(Value::as_array)?
+        .iter()
+        .filter_map(|detail| detail.get("retryDelay").and_then(Value::as_str))
+        .find_map(parse_seconds_suffix)
+}
+
+/// Time from `now` until the epoch-milliseconds timestamp `reset_ms`, as quota
+/// probes report it.
+pub fn until_epoch_millis(reset_ms: i64, now: DateTime<Utc>) -> Option<Duration> {
+    until(DateTime::from_timestamp_millis(reset_ms)?, now)
+}
+
+fn until(at: DateTime<Utc>, now: DateTime<Utc>) -> Option<Duration> {
+    (at - now).to_std().ok().filter(|delay| !delay.is_zero())
+}
+
+/// Google duration strings such as `"37s"` or `"0.5s"`.
+fn parse_seconds_suffix(value: &str) -> Option<Duration> {
+    let secs: f64 = value.trim().strip_suffix('s')?.parse().ok()?;
+    (secs.is_finite() && secs > 0.0).then(|| Duration::from_secs_f64(secs))
+}
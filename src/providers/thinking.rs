@@ -0,0 +1,327 @@
+//! Unified reasoning controls shared by every provider.
+//!
+//! Clients ask for reasoning with OpenAI `reasoning_effort`, Responses `reasoning.effort`,
+//! Anthropic `thinking.budget_tokens` or a `-thinking` model suffix. The inbound
+//! translators keep those fields on the chat request, [`requested_reasoning`] reads them
+//! into one [`Reasoning`] value, and each provider applies it in its own upstream shape.
+
+use serde_json::{json, Value};
+
+use crate::error::{AppError, AppResult};
+use crate::models::ChatCompletionRequest;
+use crate::providers::model_info::ThinkingSupport;
+use crate::providers::static_models;
+
+/// Model suffix that turns reasoning on without an explicit effort or budget.
+pub const THINKING_SUFFIX: &str = "-thinking";
+
+/// Effort levels accepted on input, lowest first.
+const EFFORT_LEVELS: &[&str] = &["none", "minimal", "low", "medium", "high", "xhigh"];
+
+/// Effort applied when only the `-thinking` suffix is present.
+const DEFAULT_EFFORT: &str = "medium";
+
+/// A client's reasoning request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reasoning {
+    /// A named level such as `low` or `high`.
+    Effort(String),
+    /// A token budget; `0` disables reasoning and `-1` lets the model decide.
+    Budget(i64),
+}
+
+impl Reasoning {
+    /// Whether the client explicitly asked for no reasoning.
+    pub fn is_disabled(&self) -> bool {
+        match self {
+            Reasoning::Effort(effort) => effort == "none",
+            Reasoning::Budget(budget) => *budget == 0,
+        }
+    }
+
+    /// The request as an effort level, for upstreams that only take levels.
+    pub fn effort(&self) -> &str {
+        match self {
+            Reasoning::Effort(effort) => effort,
+            Reasoning::Budget(0) => "none",
+            Reasoning::Budget(budget) if *budget < 0 => DEFAULT_EFFORT,
+            Reasoning::Budget(budget) if *budget <= 512 => "minimal",
+            Reasoning::Budget(budget) if *budget <= 1024 => "low",
+            Reasoning::Budget(budget) if *budget <= 8192 => "medium",
+            Reasoning::Budget(budget) if *budget <= 24576 => "high",
+            Reasoning::Budget(_) => "xhigh",
+        }
+    }
+
+    /// The request as a token budget, clamped to the model's range when it is known.
+    pub fn budget(&self, support: Option<&ThinkingSupport>) -> i64 {
+        let budget = match self {
+            Reasoning::Budget(budget) => *budget,
+            Reasoning::Effort(effort) => match effort.as_str() {
+                "none" => 0,
+                "minimal" => 512,
+                "low" => 1024,
+                "medium" => 8192,
+                "high" => 24576,
+                _ => 32768,
+            },
+        };
+
+        match support {
+            Some(support) if budget > 0 && support.max > 0 => {
+                budget.clamp(i64::from(support.min), i64::from(support.max))
+            }
+            _ => budget,
+        }
+    }
+}
+
+/// Split a trailing `-thinking` off `model`, reporting whether it was present.
+pub fn strip_thinking_suffix(model: &str) -> (&str, bool) {
+    let model = model.trim();
+    match model.strip_suffix(THINKING_SUFFIX) {
+        Some(base) => (base, true),
+        None => (model, false),
+    }
+}
+
+/// Read the reasoning request carried by `req`, if any.
+///
+/// Explicit fields win over the `-thinking` suffix, which only implies the default effort.
+pub fn requested_reasoning(req: &ChatCompletionRequest) -> Option<Reasoning> {
+    if let Some(effort) = req.extra.get("reasoning_effort").and_then(Value::as_str) {
+        return Some(Reasoning::Effort(effort.trim().to_ascii_lowercase()));
+    }
+
+    if let Some(effort) = req
+        .extra
+        .get("reasoning")
+        .and_then(|reasoning| reasoning.get("effort"))
+        .and_then(Value::as_str)
+    {
+        return Some(Reasoning::Effort(effort.trim().to_ascii_lowercase()));
+    }
+
+    if let Some(thinking) = req.extra.get("thinking") {
+        match thinking.get("type").and_then(Value::as_str) {
+            Some("disabled") => return Some(Reasoning::Budget(0)),
+            Some("enabled") | None => {
+                if let Some(budget) = thinking.get("budget_tokens").and_then(Value::as_i64) {
+                    return Some(Reasoning::Budget(budget));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    strip_thinking_suffix(&req.model)
+        .1
+        .then(|| Reasoning::Effort(DEFAULT_EFFORT.to_string()))
+}
+
+/// Thinking capabilities of `model` from the static catalog, ignoring a `-thinking` suffix.
+///
+/// Providers use this to clamp budgets when building their upstream bodies.
+pub fn static_support(model: &str) -> Option<ThinkingSupport> {
+    let (base, _) = strip_thinking_suffix(model);
+    static_models::lookup_static_model(model)
+        .and_then(|info| info.thinking)
+        .or_else(|| static_models::lookup_static_model(base).and_then(|info| info.thinking))
+}
+
+/// Check `reasoning` against the model's advertised thinking capabilities.
+///
+/// Models without thinking metadata are not rejected; the upstream has the final say.
+pub fn validate(
+    reasoning: &Reasoning,
+    support: Option<&ThinkingSupport>,
+    model: &str,
+) -> AppResult<()> {
+    if let Reasoning::Effort(effort) = reasoning {
+        if !EFFORT_LEVELS.contains(&effort.as_str()) {
+            return Err(AppError::BadRequest(format!(
+                "unknown reasoning effort '{effort}'; expected one of {}",
+                EFFORT_LEVELS.join(", ")
+            )));
+        }
+    }
+    if let Reasoning::Budget(budget) = reasoning {
+        if *budget < -1 {
+            return Err(AppError::BadRequest(format!(
+                "thinking budget {budget} is invalid; use -1, 0 or a positive token count"
+            )));
+        }
+    }
+
+    let Some(support) = support else {
+        return Ok(());
+    };
+
+    if !support.levels.is_empty() {
+        let effort = reasoning.effort();
+        if support.levels.iter().any(|level| level == effort) {
+            return Ok(());
+        }
+        return Err(AppError::BadRequest(format!(
+            "reasoning effort '{effort}' is not supported by model {model}; expected one of {}",
+            support.levels.join(", ")
+        )));
+    }
+
+    match reasoning {
+        Reasoning::Effort(effort) if effort == "none" && !support.zero_allowed => Err(
+            AppError::BadRequest(format!("model {model} does not allow disabling reasoning")),
+        ),
+        Reasoning::Budget(0) if !support.zero_allowed => Err(AppError::BadRequest(format!(
+            "model {model} does not allow disabling reasoning"
+        ))),
+        Reasoning::Budget(-1) if !support.dynamic_allowed => Err(AppError::BadRequest(format!(
+            "model {model} does not support a dynamic thinking budget"
+        ))),
+        Reasoning::Budget(budget)
+            if *budget > 0
+                && support.max > 0
+                && (*budget < i64::from(support.min) || *budget > i64::from(support.max)) =>
+        {
+            Err(AppError::BadRequest(format!(
+                "thinking budget {budget} is outside the {}..={} range of model {model}",
+                support.min, support.max
+            )))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Fold every reasoning input on `req` into the chat-completions `reasoning_effort`
+/// field and drop the `-thinking` suffix, for upstreams that speak the OpenAI schema.
+pub fn apply_openai_effort(req: &mut ChatCompletionRequest) {
+    let reasoning = requested_reasoning(req);
+    req.model = strip_thinking_suffix(&req.model).0.to_string();
+    req.extra.remove("thinking");
+    req.extra.remove("reasoning");
+    if let Some(reasoning) = reasoning {
+        req.extra
+            .insert("reasoning_effort".to_string(), json!(reasoning.effort()));
+    }
+}
+
+/// Reasoning text on an upstream OpenAI-style message or delta, under any of the
+/// field names providers use for it.
+pub fn message_reasoning(message: &Value) -> Option<String> {
+    [
+        "reasoning_content",
+        "reasoning_text",
+        "reasoning",
+        "thinking",
+    ]
+    .iter()
+    .find_map(|key| message.get(*key).and_then(Value::as_str))
+    .filter(|text| !text.is_empty())
+    .map(str::to_string)
+}
+
+/// Anthropic `thinking` block for `reasoning`.
+pub fn anthropic_thinking(reasoning: &Reasoning, support: Option<&ThinkingSupport>) -> Value {
+    if reasoning.is_disabled() {
+        return json!({"type": "disabled"});
+    }
+    let budget = match reasoning.budget(support) {
+        budget if budget > 0 => budget,
+        _ => Reasoning::Effort(DEFAULT_EFFORT.to_string()).budget(support),
+    };
+    json!({"type": "enabled", "budget_tokens": budget})
+}
+
+/// Gemini `generationConfig.thinkingConfig` for `reasoning`.
+pub fn gemini_thinking_config(reasoning: &Reasoning, support: Option<&ThinkingSupport>) -> Value {
+    let budget = reasoning.budget(support);
+    json!({"thinkingBudget": budget, "includeThoughts": budget != 0})
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(model: &str, extra: Value) -> ChatCompletionRequest {
+        let mut body = json!({"model": model, "messages": []});
+        body.as_object_mut()
+            .unwrap()
+            .extend(extra.as_object().cloned().unwrap_or_default());
+        serde_json::from_value(body).unwrap()
+    }
+
+    fn budget_support(min: i32, max: i32, zero: bool, dynamic: bool) -> ThinkingSupport {
+        ThinkingSupport {
+            min,
+            max,
+            zero_allowed: zero,
+            dynamic_allowed: dynamic,
+            levels: vec![],
+        }
+    }
+
+    #[test]
+    fn requested_reasoning_reads_every_input_shape() {
+        assert_eq!(
+            requested_reasoning(&request("gpt-5.4", json!({"reasoning_effort": "High"}))),
+            Some(Reasoning::Effort("high".into()))
+        );
+        assert_eq!(
+            requested_reasoning(&request("gpt-5.4", json!({"reasoning": {"effort": "low"}}))),
+            Some(Reasoning::Effort("low".into()))
+        );
+        assert_eq!(
+            requested_reasoning(&request(
+                "claude-sonnet-4-5",
+                json!({"thinking": {"type": "enabled", "budget_tokens": 4096}})
+            )),
+            Some(Reasoning::Budget(4096))
+        );
+        assert_eq!(
+            requested_reasoning(&request(
+                "claude-sonnet-4-5",
+                json!({"thinking": {"type": "disabled"}})
+            )),
+            Some(Reasoning::Budget(0))
+        );
+        assert_eq!(
+            requested_reasoning(&request("claude-sonnet-4-5-thinking", json!({}))),
+            Some(Reasoning::Effort("medium".into()))
+        );
+        assert_eq!(requested_reasoning(&request("gpt-5.4", json!({}))), None);
+    }
+
+    #[test]
+    fn validate_checks_levels_and_budget_ranges() {
+        let levels = ThinkingSupport {
+            levels: vec!["low".into(), "medium".into(), "high".into()],
+            ..budget_support(0, 0, false, false)
+        };
+        assert!(validate(&Reasoning::Effort("high".into()), Some(&levels), "gpt-5.4").is_ok());
+        assert!(validate(&Reasoning::Effort("xhigh".into()), Some(&levels), "gpt-5.4").is_err());
+        assert!(validate(&Reasoning::Effort("extreme".into()), None, "gpt-5.4").is_err());
+
+        let claude = budget_support(1024, 128000, false, false);
+        assert!(validate(&Reasoning::Budget(4096), Some(&claude), "claude").is_ok());
+        assert!(validate(&Reasoning::Budget(512), Some(&claude), "claude").is_err());
+        assert!(validate(&Reasoning::Budget(0), Some(&claude), "claude").is_err());
+        assert!(validate(&Reasoning::Budget(-1), Some(&claude), "claude").is_err());
+        assert!(validate(&Reasoning::Budget(-1), None, "unknown").is_ok());
+    }
+
+    #[test]
+    fn effort_and_budget_convert_within_model_range() {
+        let flash = budget_support(0, 24576, true, true);
+        assert_eq!(
+            Reasoning::Effort("xhigh".into()).budget(Some(&flash)),
+            24576
+        );
+        assert_eq!(Reasoning::Effort("low".into()).budget(None), 1024);
+        assert_eq!(Reasoning::Budget(4096).effort(), "medium");
+        assert_eq!(Reasoning::Budget(0).effort(), "none");
+        assert_eq!(
+            anthropic_thinking(&Reasoning::Budget(-1), None),
+            json!({"type": "enabled", "budget_tokens": 8192})
+        );
+    }
+}
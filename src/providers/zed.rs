@@ -8,16 +8,23 @@ use async_trait::async_trait;
 use bytes::Bytes;
 use futures::StreamExt;
 use serde::Deserialize;
+use serde_json::{json, Value};
 use tokio::sync::Mutex;
 use tracing::{debug, warn};
 
 use crate::auth::store::{AuthRecord, FileTokenStore};
 use crate::auth::zed::parse_zed_credential;
-use crate::error::{AppError, AppResult};
+use crate::error::{AppError, AppResult, UpstreamError};
 use crate::models::{ChatCompletionRequest, ChatCompletionResponse, ModelInfo};
-use crate::providers::zed_request::translate_to_zed_request;
-use crate::providers::zed_response::{format_sse_event, parse_zed_response_with_model};
-use crate::providers::{BoxStream, Provider};
+use crate::providers::http_client::{self, SendTraced};
+use crate::providers::retry_after;
+use crate::providers::thinking;
+use crate::providers::zed_anthropic::anthropic_events_to_message;
+use crate::providers::zed_request::{
+    is_anthropic_model, translate_anthropic_to_zed_request, translate_to_zed_request,
+};
+use crate::providers::zed_response::{parse_zed_response_with_model, ZedStreamTranslator};
+use crate::providers::{AnthropicResponse, BoxStream, Provider};
 
 /// Token refresh buffer in seconds (refresh when less than this remains)
 const TOKEN_REFRESH_BUFFER_SECS: u64 = 60;
@@ -163,6 +170,13 @@ pub struct ZedProvider {
 impl ZedProvider {
     /// Create a new Zed provider from an auth record.
     pub fn new(record: AuthRecord) -> AppResult<Self> {
+        Self::new_with_proxy(record, None)
+    }
+
+    /// Build with the global `proxy-url` as fallback for the account's own `proxy_url`.
+    pub fn new_with_proxy(record: AuthRecord, default_proxy_url: Option<&str>) -> AppResult<Self> {
+        let proxy_url = http_client::effective_proxy_url(record.proxy_url(), default_proxy_url);
+        let http_client = http_client::default_client("zed", proxy_url)?;
         let metadata_value = serde_json::to_value(&record.metadata)
             .map_err(|e| AppError::Internal(anyhow::anyhow!("serialize metadata: {e}")))?;
         let (user_id, credential_json) = parse_zed_credential(&metadata_value)
@@ -175,7 +189,7 @@ impl ZedProvider {
             token_cache: Arc::new(Mutex::new(None)),
             models_cache: Arc::new(Mutex::new(None)),
             client: ZedClient,
-            http_client: reqwest::Client::new(),
+            http_client,
         })
     }
 
@@ -221,6 +235,11 @@ impl ZedProvider {
     }
 
     /// Refresh the API token from Zed Cloud.
+    #[tracing::instrument(
+        name = "token_refresh",
+        skip_all,
+        fields(provider = "zed", client_id = %self.record_id)
+    )]
     pub async fn refresh_token(&self) -> AppResult<()> {
         let needs_refresh = {
             let cache = self.token_cache.lock().await;
@@ -262,17 +281,19 @@ impl ZedProvider {
             .post(self.client.token_endpoint())
             .headers(headers)
             .body("")
-            .send()
+            .send_traced()
             .await
-            .map_err(|e| AppError::Upstream(format!("token refresh request failed: {e}")))?;
+            .map_err(|e| UpstreamError::from_reqwest("token refresh request failed", &e))?;
 
         if !response.status().is_success() {
             let status = response.status();
             let body = format_non_success_response_body(response.text().await);
-            return Err(AppError::Upstream(format!(
-                "token refresh failed: {} - {}",
-                status, body
-            )));
+            return Err(UpstreamError::from_status(
+                status,
+                format!("token refresh failed: {} - {}", status, body),
+            )
+            .with_provider("zed")
+            .into());
         }
 
         #[derive(Deserialize)]
@@ -283,12 +304,12 @@ impl ZedProvider {
         let token_resp: TokenResponse = response
             .json()
             .await
-            .map_err(|e| AppError::Upstream(format!("parse token response: {e}")))?;
+            .map_err(|e| UpstreamError::from_reqwest("parse token response", &e))?;
 
         if token_resp.token.is_empty() {
-            return Err(AppError::Upstream(
-                "token refresh returned empty token".into(),
-            ));
+            return Err(AppError::Upstream(UpstreamError::other(
+                "token refresh returned empty token",
+            )));
         }
 
         let mut cache = self.token_cache.lock().await;
@@ -319,9 +340,9 @@ impl ZedProvider {
                 .http_client
                 .get(self.client.models_endpoint())
                 .headers(headers)
-                .send()
+                .send_traced()
                 .await
-                .map_err(|e| AppError::Upstream(format!("models fetch failed: {e}")))?;
+                .map_err(|e| UpstreamError::from_reqwest("models fetch failed", &e))?;
 
             if !response.status().is_success() {
                 let status = response.status();
@@ -339,16 +360,18 @@ impl ZedProvider {
                 }
 
                 let body = format_non_success_response_body(response.text().await);
-                return Err(AppError::Upstream(format!(
-                    "models fetch failed: {} - {}",
-                    status, body
-                )));
+                return Err(UpstreamError::from_status(
+                    status,
+                    format!("models fetch failed: {} - {}", status, body),
+                )
+                .with_provider("zed")
+                .into());
             }
 
             let models_resp: ModelsResponse = response
                 .json()
                 .await
-                .map_err(|e| AppError::Upstream(format!("parse models response: {e}")))?;
+                .map_err(|e| UpstreamError::from_reqwest("parse models response", &e))?;
 
             let model_ids: Vec<String> = models_resp.models.into_iter().map(|m| m.id).collect();
 
@@ -359,9 +382,9 @@ impl ZedProvider {
             return Ok(());
         }
 
-        Err(AppError::Upstream(
-            "models fetch failed after stale-token retry".into(),
-        ))
+        Err(AppError::Upstream(UpstreamError::other(
+            "models fetch failed after stale-token retry",
+        )))
     }
 
     async fn force_refresh_token(&self) -> AppResult<()> {
@@ -369,7 +392,9 @@ impl ZedProvider {
             let mut cache = self.token_cache.lock().await;
             *cache = None;
         }
-        self.refresh_token().await
+        self.refresh_token()
+            .await
+            .inspect_err(|_| crate::metrics::oauth_refresh_failed("zed"))
     }
 
     /// Ensure we have a valid token, refreshing if needed.
@@ -383,7 +408,9 @@ impl ZedProvider {
             return Ok(());
         }
 
-        self.refresh_token().await?;
+        self.refresh_token()
+            .await
+            .inspect_err(|_| crate::metrics::oauth_refresh_failed("zed"))?;
 
         Ok(())
     }
@@ -421,64 +448,23 @@ impl Provider for ZedProvider {
     ) -> AppResult<ChatCompletionResponse> {
         self.ensure_token().await?;
 
+        let mut req = req.clone();
+        apply_reasoning(&mut req);
+        let req = &req;
+
         let req_json = serde_json::to_value(req)
             .map_err(|e| AppError::Internal(anyhow::anyhow!("serialize request: {e}")))?;
         let zed_req = translate_to_zed_request(&req_json)
             .map_err(|e| AppError::BadRequest(format!("translate request: {e}")))?;
 
-        for attempt in 0..2 {
-            let headers = self.build_headers().await?;
-
-            let response = self
-                .http_client
-                .post(self.client.completions_endpoint())
-                .headers(headers)
-                .json(&zed_req)
-                .send()
-                .await
-                .map_err(|e| AppError::Upstream(format!("completions request failed: {e}")))?;
-
-            if !response.status().is_success() {
-                let status = response.status();
-                let is_stale = self
-                    .client
-                    .is_stale_token_response(status.as_u16(), response.headers());
-
-                if is_stale && attempt == 0 {
-                    debug!(
-                        "stale zed token detected for completion request user {}; refreshing and retrying",
-                        self.user_id
-                    );
-                    self.force_refresh_token().await?;
-                    continue;
-                }
+        let response = self.post_completions(&zed_req, "completions").await?;
+        let zed_resp = read_zed_response_body(response).await?;
 
-                let body = format_non_success_response_body(response.text().await);
-                return Err(AppError::Upstream(format!(
-                    "completions failed: {} - {}",
-                    status, body
-                )));
-            }
-
-            let zed_resp_text = response
-                .text()
-                .await
-                .map_err(|e| AppError::Upstream(format!("read completions response: {e}")))?;
-            let zed_resp: serde_json::Value = match serde_json::from_str(&zed_resp_text) {
-                Ok(value) => value,
-                Err(_) => serde_json::Value::String(zed_resp_text),
-            };
-
-            let validated = parse_zed_response_with_model(&zed_resp, Some(&req.model))
-                .map_err(|e| AppError::Upstream(format!("validate response: {e}")))?;
+        let validated = parse_zed_response_with_model(&zed_resp, Some(&req.model))
+            .map_err(|e| UpstreamError::other(format!("validate response: {e}")))?;
 
-            return serde_json::from_value(validated)
-                .map_err(|e| AppError::Internal(anyhow::anyhow!("deserialize response: {e}")));
-        }
-
-        Err(AppError::Upstream(
-            "completions failed after stale-token retry".into(),
-        ))
+        serde_json::from_value(validated)
+            .map_err(|e| AppError::Internal(anyhow::anyhow!("deserialize response: {e}")))
     }
 
     async fn chat_completion_stream(&self, req: &ChatCompletionRequest) -> AppResult<BoxStream> {
@@ -486,12 +472,62 @@ impl Provider for ZedProvider {
 
         let mut streaming_req = req.clone();
         streaming_req.stream = Some(true);
+        apply_reasoning(&mut streaming_req);
 
         let req_json = serde_json::to_value(&streaming_req)
             .map_err(|e| AppError::Internal(anyhow::anyhow!("serialize request: {e}")))?;
         let zed_req = translate_to_zed_request(&req_json)
             .map_err(|e| AppError::BadRequest(format!("translate request: {e}")))?;
 
+        let response = self.post_completions(&zed_req, "streaming").await?;
+        let mut translator = ZedStreamTranslator::new(&req.model);
+        Ok(jsonlines_sse_stream(response, move |line| {
+            translator.push_line(line)
+        }))
+    }
+
+    fn supports_anthropic_messages(&self, model: &str) -> bool {
+        is_anthropic_model(model)
+    }
+
+    async fn anthropic_messages(&self, body: &Value) -> AppResult<AnthropicResponse> {
+        self.ensure_token().await?;
+
+        let zed_req = translate_anthropic_to_zed_request(body)
+            .map_err(|e| AppError::BadRequest(format!("translate request: {e}")))?;
+
+        if body["stream"].as_bool().unwrap_or(false) {
+            let response = self.post_completions(&zed_req, "streaming").await?;
+            return Ok(AnthropicResponse::Stream(jsonlines_sse_stream(
+                response,
+                |line| Some(format_anthropic_sse_event(line)),
+            )));
+        }
+
+        let response = self.post_completions(&zed_req, "completions").await?;
+        let events = match read_zed_response_body(response).await? {
+            Value::String(raw) => raw
+                .lines()
+                .filter_map(|line| serde_json::from_str::<Value>(line.trim()).ok())
+                .collect(),
+            Value::Object(mut obj) => match obj.remove("events") {
+                Some(Value::Array(events)) => events,
+                // Already a complete Anthropic message.
+                _ => return Ok(AnthropicResponse::Message(Value::Object(obj))),
+            },
+            other => vec![other],
+        };
+
+        let model = body["model"].as_str().unwrap_or_default();
+        Ok(AnthropicResponse::Message(anthropic_events_to_message(
+            &events, model,
+        )))
+    }
+}
+
+impl ZedProvider {
+    /// POST a completions request, refreshing the token once on a stale-token response.
+    async fn post_completions(&self, zed_req: &Value, kind: &str) -> AppResult<reqwest::Response> {
         for attempt in 0..2 {
             let headers = self.build_headers().await?;
 
@@ -499,10 +535,10 @@ impl Provider for ZedProvider {
                 .http_client
                 .post(self.client.completions_endpoint())
                 .headers(headers)
-                .json(&zed_req)
-                .send()
+                .json(zed_req)
+                .send_traced()
                 .await
-                .map_err(|e| AppError::Upstream(format!("streaming request failed: {e}")))?;
+                .map_err(|e| UpstreamError::from_reqwest(format!("{kind} request failed"), &e))?;
 
             if !response.status().is_success() {
                 let status = response.status();
@@ -512,68 +548,138 @@ impl Provider for ZedProvider {
 
                 if is_stale && attempt == 0 {
                     debug!(
-                        "stale zed token detected for streaming request user {}; refreshing and retrying",
+                        "stale zed token detected for {kind} request user {}; refreshing and retrying",
                         self.user_id
                     );
                     self.force_refresh_token().await?;
                     continue;
                 }
 
+                let headers = response.headers().clone();
                 let body = format_non_success_response_body(response.text().await);
-                return Err(AppError::Upstream(format!(
-                    "streaming failed: {} - {}",
-                    status, body
-                )));
+                let retry_after =
+                    retry_after::for_status(status, &headers, &body, chrono::Utc::now());
+                return Err(UpstreamError::from_status(
+                    status,
+                    format!("{kind} failed: {} - {}", status, body),
+                )
+                .with_provider("zed")
+                .with_retry_after(retry_after)
+                .into());
             }
 
-            let upstream = response.bytes_stream();
-            let stream = async_stream::try_stream! {
-                let mut buffer = String::new();
-                futures::pin_mut!(upstream);
+            return Ok(response);
+        }
 
-                while let Some(chunk_result) = upstream.next().await {
-                    let chunk = chunk_result
-                        .map_err(|e| AppError::Upstream(format!("stream error: {e}")))?;
+        Err(AppError::Upstream(UpstreamError::other(format!(
+            "{kind} failed after stale-token retry"
+        ))))
+    }
+}
 
-                    buffer.push_str(&String::from_utf8_lossy(&chunk));
+/// Translate the unified reasoning request into the field Zed's upstream provider expects.
+fn apply_reasoning(req: &mut ChatCompletionRequest) {
+    let reasoning = thinking::requested_reasoning(req);
+    let support = thinking::static_support(&req.model);
+    req.model = thinking::strip_thinking_suffix(&req.model).0.to_string();
+    for key in ["reasoning_effort", "reasoning", "thinking"] {
+        req.extra.remove(key);
+    }
 
-                    while let Some(newline_pos) = buffer.find('\n') {
-                        let line = buffer[..newline_pos].trim_end_matches('\r').to_string();
-                        buffer = buffer[newline_pos + 1..].to_string();
+    let Some(reasoning) = reasoning else {
+        return;
+    };
+    if is_anthropic_model(&req.model) {
+        req.extra.insert(
+            "thinking".to_string(),
+            thinking::anthropic_thinking(&reasoning, support.as_ref()),
+        );
+    } else {
+        req.extra.insert(
+            "reasoning".to_string(),
+            json!({"effort": reasoning.effort(), "summary": "auto"}),
+        );
+    }
+}
 
-                        if line.trim().is_empty() {
-                            continue;
-                        }
+/// Read a completions body as JSON, falling back to the raw text (JSON Lines).
+async fn read_zed_response_body(response: reqwest::Response) -> AppResult<Value> {
+    let text = response
+        .text()
+        .await
+        .map_err(|e| UpstreamError::from_reqwest("read completions response", &e))?;
+    Ok(serde_json::from_str(&text).unwrap_or(Value::String(text)))
+}
+
+/// Formats one Anthropic stream event line as a named SSE event.
+fn format_anthropic_sse_event(json: &str) -> String {
+    let event_type = serde_json::from_str::<Value>(json)
+        .ok()
+        .and_then(|event| {
+            event
+                .get("type")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+        })
+        .unwrap_or_else(|| "message".to_string());
+    format!("event: {event_type}\ndata: {json}\n\n")
+}
 
-                        if serde_json::from_str::<serde_json::Value>(&line).is_err() {
-                            continue;
-                        }
+/// Re-frame Zed's JSON Lines completion stream as SSE, one event per JSON line.
+///
+/// `format` renders a line as SSE text, or skips it by returning `None`.
+fn jsonlines_sse_stream(
+    response: reqwest::Response,
+    mut format: impl FnMut(&str) -> Option<String> + Send + 'static,
+) -> BoxStream {
+    let upstream = response.bytes_stream();
+    let stream = async_stream::try_stream! {
+        let mut buffer = String::new();
+        futures::pin_mut!(upstream);
+
+        while let Some(chunk_result) = upstream.next().await {
+            let chunk = chunk_result
+                .map_err(|e| AppError::Upstream(UpstreamError::from_reqwest("stream error", &e)))?;
+
+            buffer.push_str(&String::from_utf8_lossy(&chunk));
+
+            while let Some(newline_pos) = buffer.find('\n') {
+                let line = buffer[..newline_pos].trim_end_matches('\r').to_string();
+                buffer = buffer[newline_pos + 1..].to_string();
+
+                if line.trim().is_empty() {
+                    continue;
+                }
 
-                        yield Bytes::from(format_sse_event(&line));
-                    }
+                if serde_json::from_str::<Value>(&line).is_err() {
+                    continue;
                 }
 
-                let trailing = buffer.trim();
-                if !trailing.is_empty() && serde_json::from_str::<serde_json::Value>(trailing).is_ok() {
-                    yield Bytes::from(format_sse_event(trailing));
+                if let Some(event) = format(&line) {
+                    yield Bytes::from(event);
                 }
-            };
+            }
+        }
 
-            return Ok(Box::pin(stream));
+        let trailing = buffer.trim();
+        if !trailing.is_empty() && serde_json::from_str::<Value>(trailing).is_ok() {
+            if let Some(event) = format(trailing) {
+                yield Bytes::from(event);
+            }
         }
+    };
 
-        Err(AppError::Upstream(
-            "streaming failed after stale-token retry".into(),
-        ))
-    }
+    Box::pin(stream)
 }
 
 /// Scan the auth store for Zed accounts and create provider instances.
 ///
 /// Returns a list of (filename, provider) tuples for each valid Zed auth file.
-/// Invalid auth files are logged and skipped.
+/// Invalid auth files are logged and skipped. `default_proxy_url` is the global
+/// `proxy-url`, used for accounts without their own.
 pub async fn scan_zed_providers(
     store: &FileTokenStore,
+    default_proxy_url: Option<&str>,
 ) -> AppResult<Vec<(String, Arc<ZedProvider>)>> {
     let records = store.list().await?;
     let mut providers = Vec::new();
@@ -583,7 +689,7 @@ pub async fn scan_zed_providers(
             continue;
         }
 
-        match ZedProvider::new(record.clone()) {
+        match ZedProvider::new_with_proxy(record.clone(), default_proxy_url) {
             Ok(provider) => {
                 let filename = record
                     .path
@@ -1,114 +1,31 @@
 use anyhow::{anyhow, Result};
 use serde_json::{json, Value};
 
+use crate::providers::anthropic;
+
 /// Converts Anthropic Messages API response to OpenAI format
-/// Preserves thinking blocks in metadata
+/// Thinking blocks are returned as the message's `reasoning_content`, tool_use
+/// blocks as `tool_calls` (see [`anthropic::anthropic_to_chat_response`])
 pub fn convert_anthropic_to_openai(anthropic_response: &Value) -> Result<Value> {
     let obj = anthropic_response
         .as_object()
         .ok_or_else(|| anyhow!("Response must be an object"))?;
 
-    let id = obj
-        .get("id")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| anyhow!("Missing id field"))?;
+    if !obj.get("id").is_some_and(Value::is_string) {
+        return Err(anyhow!("Missing id field"));
+    }
 
     let model = obj
         .get("model")
         .and_then(|v| v.as_str())
         .ok_or_else(|| anyhow!("Missing model field"))?;
 
-    let content = obj
-        .get("content")
-        .and_then(|v| v.as_array())
-        .ok_or_else(|| anyhow!("Missing or invalid content field"))?;
-
-    let stop_reason = obj
-        .get("stop_reason")
-        .and_then(|v| v.as_str())
-        .unwrap_or("stop");
-
-    // Extract text and thinking blocks
-    let mut text_parts = Vec::new();
-    let mut thinking_text = None;
-
-    for block in content {
-        if let Some(block_type) = block.get("type").and_then(|v| v.as_str()) {
-            match block_type {
-                "text" => {
-                    if let Some(text) = block.get("text").and_then(|v| v.as_str()) {
-                        text_parts.push(text);
-                    }
-                }
-                "thinking" => {
-                    if let Some(thinking) = block.get("thinking").and_then(|v| v.as_str()) {
-                        thinking_text = Some(thinking);
-                    }
-                }
-                _ => {}
-            }
-        }
-    }
-
-    let combined_text = text_parts.join("");
-
-    // Build message object
-    let mut message = json!({
-        "role": "assistant",
-        "content": combined_text
-    });
-
-    // Add thinking if present
-    if let Some(thinking) = thinking_text {
-        message["thinking"] = Value::String(thinking.to_string());
-    }
-
-    // Map stop_reason
-    let finish_reason = match stop_reason {
-        "end_turn" => "stop",
-        "max_tokens" => "length",
-        "stop_sequence" => "stop",
-        _ => "stop",
-    };
-
-    // Build usage
-    let mut usage = json!({
-        "prompt_tokens": 0,
-        "completion_tokens": 0,
-        "total_tokens": 0
-    });
-
-    if let Some(anthropic_usage) = obj.get("usage") {
-        let input_tokens = anthropic_usage
-            .get("input_tokens")
-            .and_then(|v| v.as_u64())
-            .unwrap_or(0);
-        let output_tokens = anthropic_usage
-            .get("output_tokens")
-            .and_then(|v| v.as_u64())
-            .unwrap_or(0);
-
-        usage = json!({
-            "prompt_tokens": input_tokens,
-            "completion_tokens": output_tokens,
-            "total_tokens": input_tokens + output_tokens
-        });
+    if !obj.get("content").is_some_and(Value::is_array) {
+        return Err(anyhow!("Missing or invalid content field"));
     }
 
-    Ok(json!({
-        "id": id,
-        "object": "chat.completion",
-        "created": chrono::Utc::now().timestamp(),
-        "model": model,
-        "choices": [
-            {
-                "index": 0,
-                "message": message,
-                "finish_reason": finish_reason
-            }
-        ],
-        "usage": usage
-    }))
+    let response = anthropic::anthropic_to_chat_response(anthropic_response, model);
+    serde_json::to_value(response).map_err(|e| anyhow!("Failed to serialize response: {e}"))
 }
 
 /// Converts OpenAI format request to Anthropic Messages API format
@@ -183,33 +100,146 @@ pub fn convert_openai_to_anthropic(openai_request: &Value) -> Result<Value> {
         anthropic_request["top_p"] = top_p.clone();
     }
     if let Some(stop) = obj.get("stop") {
-        let stop_sequences = match stop {
-            Value::String(text) => vec![Value::String(text.clone())],
-            Value::Array(items) => items
-                .iter()
-                .filter_map(|item| match item {
-                    Value::String(text) => Some(Value::String(text.clone())),
-                    Value::Number(_) | Value::Bool(_) | Value::Null => {
-                        Some(Value::String(item.to_string()))
+        anthropic_request["stop_sequences"] = Value::Array(anthropic::stop_sequences(stop));
+    }
+
+    Ok(anthropic_request)
+}
+
+/// Assembles an Anthropic Messages response from the stream events Zed returns
+/// for the anthropic provider, keeping thinking, tool_use and citation blocks.
+pub fn anthropic_events_to_message(events: &[Value], fallback_model: &str) -> Value {
+    let mut message = json!({
+        "id": format!("msg_{}", uuid::Uuid::new_v4().simple()),
+        "type": "message",
+        "role": "assistant",
+        "model": fallback_model,
+        "content": [],
+        "stop_reason": null,
+        "stop_sequence": null,
+        "usage": {"input_tokens": 0, "output_tokens": 0},
+    });
+    let mut blocks: Vec<Value> = Vec::new();
+    let mut partial_json: Vec<String> = Vec::new();
+
+    for event in events {
+        match event.get("type").and_then(Value::as_str) {
+            Some("message_start") => {
+                if let Some(start) = event.get("message").and_then(Value::as_object) {
+                    for (key, value) in start {
+                        if key != "content" {
+                            message[key] = value.clone();
+                        }
+                    }
+                }
+            }
+            Some("content_block_start") => {
+                let index = event["index"].as_u64().unwrap_or(blocks.len() as u64) as usize;
+                while blocks.len() <= index {
+                    blocks.push(Value::Null);
+                    partial_json.push(String::new());
+                }
+                blocks[index] = event["content_block"].clone();
+            }
+            Some("content_block_delta") => {
+                let index = event["index"].as_u64().unwrap_or(0) as usize;
+                let (Some(block), Some(json_buf)) =
+                    (blocks.get_mut(index), partial_json.get_mut(index))
+                else {
+                    continue;
+                };
+                let delta = &event["delta"];
+                match delta["type"].as_str() {
+                    Some("text_delta") => append_str(block, "text", &delta["text"]),
+                    Some("thinking_delta") => append_str(block, "thinking", &delta["thinking"]),
+                    Some("signature_delta") => append_str(block, "signature", &delta["signature"]),
+                    Some("input_json_delta") => {
+                        json_buf.push_str(delta["partial_json"].as_str().unwrap_or_default())
+                    }
+                    Some("citations_delta") => {
+                        if !block["citations"].is_array() {
+                            block["citations"] = json!([]);
+                        }
+                        if let Some(citations) = block["citations"].as_array_mut() {
+                            citations.push(delta["citation"].clone());
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Some("content_block_stop") => {
+                let index = event["index"].as_u64().unwrap_or(0) as usize;
+                if let (Some(block), Some(json_buf)) =
+                    (blocks.get_mut(index), partial_json.get(index))
+                {
+                    if !json_buf.is_empty() {
+                        block["input"] =
+                            serde_json::from_str(json_buf).unwrap_or_else(|_| json!({}));
+                    }
+                }
+            }
+            Some("message_delta") => {
+                for key in ["stop_reason", "stop_sequence"] {
+                    if let Some(value) = event["delta"].get(key) {
+                        message[key] = value.clone();
                     }
-                    _ => None,
-                })
-                .collect::<Vec<_>>(),
-            Value::Number(_) | Value::Bool(_) | Value::Null => {
-                vec![Value::String(stop.to_string())]
+                }
+                if let Some(usage) = event.get("usage").and_then(Value::as_object) {
+                    for (key, value) in usage {
+                        message["usage"][key] = value.clone();
+                    }
+                }
             }
-            _ => Vec::new(),
-        };
-        anthropic_request["stop_sequences"] = Value::Array(stop_sequences);
+            _ => {}
+        }
     }
 
-    Ok(anthropic_request)
+    message["content"] = Value::Array(blocks.into_iter().filter(|b| !b.is_null()).collect());
+    message
+}
+
+fn append_str(block: &mut Value, key: &str, delta: &Value) {
+    let mut text = block[key].as_str().unwrap_or_default().to_string();
+    text.push_str(delta.as_str().unwrap_or_default());
+    block[key] = Value::String(text);
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn anthropic_events_assemble_thinking_text_and_tool_use_blocks() {
+        let events = vec![
+            json!({"type": "message_start", "message": {"id": "msg_1", "model": "claude-sonnet-4-6", "usage": {"input_tokens": 12, "output_tokens": 0}}}),
+            json!({"type": "content_block_start", "index": 0, "content_block": {"type": "thinking", "thinking": ""}}),
+            json!({"type": "content_block_delta", "index": 0, "delta": {"type": "thinking_delta", "thinking": "hmm"}}),
+            json!({"type": "content_block_delta", "index": 0, "delta": {"type": "signature_delta", "signature": "sig"}}),
+            json!({"type": "content_block_stop", "index": 0}),
+            json!({"type": "content_block_start", "index": 1, "content_block": {"type": "text", "text": ""}}),
+            json!({"type": "content_block_delta", "index": 1, "delta": {"type": "text_delta", "text": "Hi"}}),
+            json!({"type": "content_block_stop", "index": 1}),
+            json!({"type": "content_block_start", "index": 2, "content_block": {"type": "tool_use", "id": "toolu_1", "name": "lookup", "input": {}}}),
+            json!({"type": "content_block_delta", "index": 2, "delta": {"type": "input_json_delta", "partial_json": "{\"q\":"}}),
+            json!({"type": "content_block_delta", "index": 2, "delta": {"type": "input_json_delta", "partial_json": "\"x\"}"}}),
+            json!({"type": "content_block_stop", "index": 2}),
+            json!({"type": "message_delta", "delta": {"stop_reason": "tool_use"}, "usage": {"output_tokens": 7}}),
+            json!({"type": "message_stop"}),
+        ];
+
+        let message = anthropic_events_to_message(&events, "fallback");
+
+        assert_eq!(message["id"], "msg_1");
+        assert_eq!(message["model"], "claude-sonnet-4-6");
+        assert_eq!(message["content"][0]["thinking"], "hmm");
+        assert_eq!(message["content"][0]["signature"], "sig");
+        assert_eq!(message["content"][1]["text"], "Hi");
+        assert_eq!(message["content"][2]["input"]["q"], "x");
+        assert_eq!(message["stop_reason"], "tool_use");
+        assert_eq!(message["usage"]["input_tokens"], 12);
+        assert_eq!(message["usage"]["output_tokens"], 7);
+    }
+
     #[test]
     fn test_convert_anthropic_to_openai() {
         let response = json!({
@@ -2,6 +2,9 @@ use anyhow::{anyhow, Result};
 use serde_json::{json, Map, Value};
 use uuid::Uuid;
 
+use crate::models::ChatCompletionRequest;
+use crate::providers::anthropic;
+
 /// Normalizes model names for Zed API
 /// Maps model prefixes to Zed's expected format:
 /// - claude-* -> anthropic/
@@ -55,6 +58,11 @@ fn provider_and_model_for_zed(model: &str) -> (String, String) {
     }
 }
 
+/// Whether Zed serves `model` through its Anthropic provider.
+pub fn is_anthropic_model(model: &str) -> bool {
+    provider_and_model_for_zed(model).0 == "anthropic"
+}
+
 fn normalize_text_parts(content: &Value, part_type: &str) -> Vec<Value> {
     match content {
         Value::String(text) => vec![json!({ "type": part_type, "text": text })],
@@ -71,17 +79,6 @@ fn normalize_text_parts(content: &Value, part_type: &str) -> Vec<Value> {
     }
 }
 
-fn build_anthropic_message(message: &Value) -> Option<Value> {
-    let obj = message.as_object()?;
-    let role = obj.get("role")?.as_str()?;
-    let content = obj.get("content").unwrap_or(&Value::Null);
-
-    Some(json!({
-        "role": role,
-        "content": normalize_text_parts(content, "text")
-    }))
-}
-
 fn build_openai_input_item(message: &Value) -> Option<Value> {
     let obj = message.as_object()?;
     let role = match obj.get("role")?.as_str()? {
@@ -112,33 +109,15 @@ fn copy_if_present(
     }
 }
 
-fn build_anthropic_provider_request(obj: &Map<String, Value>, model_id: &str) -> Value {
-    let messages = obj
-        .get("messages")
-        .and_then(|value| value.as_array())
-        .map(|messages| {
-            messages
-                .iter()
-                .filter_map(build_anthropic_message)
-                .collect::<Vec<_>>()
-        })
-        .unwrap_or_default();
-
-    let mut provider_request = Map::new();
-    provider_request.insert("model".to_string(), Value::String(model_id.to_string()));
-    provider_request.insert("messages".to_string(), Value::Array(messages));
-    provider_request.insert(
-        "max_tokens".to_string(),
-        obj.get("max_tokens")
-            .cloned()
-            .unwrap_or_else(|| json!(8192)),
-    );
-    copy_if_present(&mut provider_request, obj, "stream", "stream");
-    copy_if_present(&mut provider_request, obj, "temperature", "temperature");
-    copy_if_present(&mut provider_request, obj, "tools", "tools");
-    copy_if_present(&mut provider_request, obj, "tool_choice", "tool_choice");
-
-    Value::Object(provider_request)
+/// Anthropic models go through the same converter as the `claude-api-key` provider, so
+/// tools, images and the thinking `max_tokens` headroom match across both.
+fn build_anthropic_provider_request(obj: &Map<String, Value>, model_id: &str) -> Result<Value> {
+    let request: ChatCompletionRequest = serde_json::from_value(Value::Object(obj.clone()))
+        .map_err(|e| anyhow!("Invalid chat request: {e}"))?;
+    let stream = request.stream.unwrap_or(false);
+    Ok(anthropic::chat_request_to_anthropic(
+        &request, model_id, stream,
+    ))
 }
 
 fn build_openai_provider_request(obj: &Map<String, Value>, model_id: &str) -> Value {
@@ -159,6 +138,7 @@ fn build_openai_provider_request(obj: &Map<String, Value>, model_id: &str) -> Va
     copy_if_present(&mut provider_request, obj, "stream", "stream");
     copy_if_present(&mut provider_request, obj, "temperature", "temperature");
     copy_if_present(&mut provider_request, obj, "top_p", "top_p");
+    copy_if_present(&mut provider_request, obj, "reasoning", "reasoning");
 
     if let Some(value) = obj
         .get("max_output_tokens")
@@ -224,19 +204,61 @@ pub fn translate_to_zed_request(openai_request: &Value) -> Result<Value> {
 
     let (provider, model_id) = provider_and_model_for_zed(model);
     let provider_request = match provider.as_str() {
-        "anthropic" => build_anthropic_provider_request(obj, &model_id),
+        "anthropic" => build_anthropic_provider_request(obj, &model_id)?,
         "open_ai" => build_openai_provider_request(obj, &model_id),
         _ => build_generic_provider_request(obj, &provider, &model_id),
     };
 
-    Ok(json!({
+    Ok(zed_envelope(&provider, &model_id, provider_request))
+}
+
+/// Wraps a raw Anthropic Messages body in a Zed completions request.
+///
+/// Unlike [`translate_to_zed_request`], every Anthropic field (`system` blocks,
+/// `cache_control`, `thinking`, `tool_result` content arrays, …) is forwarded as-is.
+pub fn translate_anthropic_to_zed_request(anthropic_request: &Value) -> Result<Value> {
+    let obj = anthropic_request
+        .as_object()
+        .ok_or_else(|| anyhow!("Request must be an object"))?;
+
+    let model = obj
+        .get("model")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow!("Missing required field: model"))?;
+
+    if !obj.get("messages").is_some_and(Value::is_array) {
+        return Err(anyhow!("messages must be an array"));
+    }
+
+    let (provider, model_id) = provider_and_model_for_zed(model);
+    if provider != "anthropic" {
+        return Err(anyhow!(
+            "model {model} is not served by the anthropic provider"
+        ));
+    }
+
+    let mut provider_request = obj.clone();
+    provider_request.insert("model".to_string(), Value::String(model_id.clone()));
+    provider_request
+        .entry("max_tokens".to_string())
+        .or_insert_with(|| json!(anthropic::DEFAULT_MAX_TOKENS));
+
+    Ok(zed_envelope(
+        &provider,
+        &model_id,
+        Value::Object(provider_request),
+    ))
+}
+
+fn zed_envelope(provider: &str, model_id: &str, provider_request: Value) -> Value {
+    json!({
         "thread_id": Uuid::new_v4().to_string(),
         "prompt_id": Uuid::new_v4().to_string(),
         "intent": "user_prompt",
         "provider": provider,
         "model": model_id,
         "provider_request": provider_request,
-    }))
+    })
 }
 
 #[cfg(test)]
@@ -244,6 +266,70 @@ mod tests {
     use super::*;
     use serde_json::json;
 
+    #[test]
+    fn anthropic_passthrough_keeps_native_fields() {
+        let request = json!({
+            "model": "claude-sonnet-4-6",
+            "system": [{"type": "text", "text": "sys", "cache_control": {"type": "ephemeral"}}],
+            "thinking": {"type": "enabled", "budget_tokens": 2048},
+            "messages": [{
+                "role": "user",
+                "content": [{
+                    "type": "tool_result",
+                    "tool_use_id": "toolu_1",
+                    "content": [{"type": "text", "text": "42"}]
+                }]
+            }]
+        });
+
+        let zed = translate_anthropic_to_zed_request(&request).unwrap();
+
+        assert_eq!(zed["provider"], "anthropic");
+        let provider_request = &zed["provider_request"];
+        assert_eq!(provider_request["model"], "claude-sonnet-4-6");
+        assert_eq!(provider_request["max_tokens"], 8192);
+        assert_eq!(provider_request["thinking"]["budget_tokens"], 2048);
+        assert_eq!(
+            provider_request["system"][0]["cache_control"]["type"],
+            "ephemeral"
+        );
+        assert_eq!(
+            provider_request["messages"][0]["content"][0]["content"][0]["text"],
+            "42"
+        );
+
+        assert!(translate_anthropic_to_zed_request(&json!({
+            "model": "gpt-4o",
+            "messages": []
+        }))
+        .is_err());
+    }
+
+    #[test]
+    fn anthropic_models_use_the_shared_anthropic_converter() {
+        let request = json!({
+            "model": "claude-sonnet-4-6",
+            "messages": [{"role": "user", "content": "weather?"}],
+            "max_tokens": 1000,
+            "thinking": {"type": "enabled", "budget_tokens": 4096},
+            "tools": [{"type": "function", "function": {
+                "name": "lookup",
+                "parameters": {"type": "object", "properties": {}}
+            }}]
+        });
+
+        let zed = translate_to_zed_request(&request).unwrap();
+
+        let chat: ChatCompletionRequest = serde_json::from_value(request).unwrap();
+        let expected = anthropic::chat_request_to_anthropic(&chat, "claude-sonnet-4-6", false);
+        assert_eq!(zed["provider_request"], expected);
+        assert_eq!(
+            zed["provider_request"]["max_tokens"],
+            4096 + anthropic::DEFAULT_MAX_TOKENS
+        );
+        assert_eq!(zed["provider_request"]["tools"][0]["name"], "lookup");
+    }
+
     #[test]
     fn test_normalize_model() {
         assert_eq!(
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use anyhow::{anyhow, Result};
 use serde_json::{json, Value};
 
@@ -66,6 +68,7 @@ fn convert_events_to_openai(response: &Value, fallback_model: Option<&str>) -> R
 
     let mut id = String::from("chatcmpl-zed");
     let mut text = String::new();
+    let mut reasoning = String::new();
     let mut prompt_tokens = 0u64;
     let mut completion_tokens = 0u64;
 
@@ -116,7 +119,13 @@ fn convert_events_to_openai(response: &Value, fallback_model: Option<&str>) -> R
                                 text.push_str(chunk);
                             }
                         }
-                        Some("thinking_delta") => {}
+                        Some("thinking_delta") => {
+                            if let Some(chunk) =
+                                delta.get("thinking").and_then(|value| value.as_str())
+                            {
+                                reasoning.push_str(chunk);
+                            }
+                        }
                         _ => {}
                     }
                 }
@@ -126,6 +135,11 @@ fn convert_events_to_openai(response: &Value, fallback_model: Option<&str>) -> R
                     text.push_str(chunk);
                 }
             }
+            "response.reasoning_summary_text.delta" | "response.reasoning_text.delta" => {
+                if let Some(chunk) = event.get("delta").and_then(|value| value.as_str()) {
+                    reasoning.push_str(chunk);
+                }
+            }
             "response.completed" => {
                 if let Some(response_obj) = event.get("response") {
                     if let Some(event_model) =
@@ -149,6 +163,14 @@ fn convert_events_to_openai(response: &Value, fallback_model: Option<&str>) -> R
         }
     }
 
+    let mut message = json!({
+        "role": "assistant",
+        "content": text,
+    });
+    if !reasoning.is_empty() {
+        message["reasoning_content"] = json!(reasoning);
+    }
+
     Ok(json!({
         "id": id,
         "object": "chat.completion",
@@ -157,10 +179,7 @@ fn convert_events_to_openai(response: &Value, fallback_model: Option<&str>) -> R
         "choices": [
             {
                 "index": 0,
-                "message": {
-                    "role": "assistant",
-                    "content": text,
-                },
+                "message": message,
                 "finish_reason": "stop"
             }
         ],
@@ -220,6 +239,105 @@ pub fn parse_zed_response_with_model(
     parse_openai_like_response(response)
 }
 
+/// Converts Zed's streamed JSON Lines events into OpenAI `chat.completion.chunk` SSE.
+///
+/// Zed relays the upstream provider's own stream: Anthropic Messages events, OpenAI
+/// Responses events, or lines that are already chat chunks (passed through as-is).
+pub struct ZedStreamTranslator {
+    id: String,
+    model: String,
+    created: i64,
+    /// Anthropic content block index -> OpenAI tool call index.
+    tool_indexes: HashMap<u64, usize>,
+}
+
+impl ZedStreamTranslator {
+    pub fn new(model: &str) -> Self {
+        Self {
+            id: format!("chatcmpl-{}", uuid::Uuid::new_v4()),
+            model: model.to_string(),
+            created: chrono::Utc::now().timestamp(),
+            tool_indexes: HashMap::new(),
+        }
+    }
+
+    /// Translate one JSON line, returning the SSE frame to emit, if any.
+    pub fn push_line(&mut self, line: &str) -> Option<String> {
+        let event: Value = serde_json::from_str(line).ok()?;
+        if event.get("choices").is_some() {
+            return Some(format_sse_event(line));
+        }
+
+        let (delta, finish_reason) = match event["type"].as_str().unwrap_or_default() {
+            "message_start" => {
+                if let Some(id) = event["message"]["id"].as_str() {
+                    self.id = id.to_string();
+                }
+                (json!({"role": "assistant", "content": ""}), None)
+            }
+            "content_block_start" if event["content_block"]["type"] == "tool_use" => {
+                let block = event["index"].as_u64().unwrap_or_default();
+                let index = self.tool_indexes.len();
+                self.tool_indexes.insert(block, index);
+                let tool_call = json!({
+                    "index": index,
+                    "id": event["content_block"]["id"],
+                    "type": "function",
+                    "function": {"name": event["content_block"]["name"], "arguments": ""},
+                });
+                (json!({ "tool_calls": [tool_call] }), None)
+            }
+            "content_block_delta" => {
+                let delta = &event["delta"];
+                match delta["type"].as_str() {
+                    Some("text_delta") => (json!({ "content": delta["text"] }), None),
+                    Some("thinking_delta") => {
+                        (json!({ "reasoning_content": delta["thinking"] }), None)
+                    }
+                    Some("input_json_delta") => {
+                        let block = event["index"].as_u64().unwrap_or_default();
+                        let index = *self.tool_indexes.get(&block)?;
+                        let tool_call = json!({
+                            "index": index,
+                            "function": {"arguments": delta["partial_json"]},
+                        });
+                        (json!({ "tool_calls": [tool_call] }), None)
+                    }
+                    _ => return None,
+                }
+            }
+            "message_delta" => {
+                let finish_reason = match event["delta"]["stop_reason"].as_str()? {
+                    "max_tokens" => "length",
+                    "tool_use" => "tool_calls",
+                    _ => "stop",
+                };
+                (json!({}), Some(finish_reason))
+            }
+            "response.output_text.delta" => (json!({ "content": event["delta"] }), None),
+            "response.reasoning_summary_text.delta" | "response.reasoning_text.delta" => {
+                (json!({ "reasoning_content": event["delta"] }), None)
+            }
+            "response.completed" => (json!({}), Some("stop")),
+            "response.incomplete" => (json!({}), Some("length")),
+            _ => return None,
+        };
+
+        let chunk = json!({
+            "id": self.id,
+            "object": "chat.completion.chunk",
+            "created": self.created,
+            "model": self.model,
+            "choices": [{
+                "index": 0,
+                "delta": delta,
+                "finish_reason": finish_reason,
+            }],
+        });
+        Some(format_sse_event(&chunk.to_string()))
+    }
+}
+
 /// Extracts content from the first choice's message in a Zed API response
 pub fn extract_content(response: &serde_json::Value) -> anyhow::Result<String> {
     let choices = response
@@ -423,4 +541,53 @@ mod tests {
             .to_string()
             .contains("Response missing required field: model"));
     }
+
+    #[test]
+    fn test_events_response_collects_thinking_as_reasoning_content() {
+        let response = json!({
+            "model": "claude-sonnet-4-5",
+            "events": [
+                {"type": "content_block_delta", "index": 0, "delta": {"type": "thinking_delta", "thinking": "hmm"}},
+                {"type": "content_block_delta", "index": 1, "delta": {"type": "text_delta", "text": "Hi"}}
+            ]
+        });
+
+        let result = parse_zed_response(&response).unwrap();
+        assert_eq!(result["choices"][0]["message"]["content"], "Hi");
+        assert_eq!(result["choices"][0]["message"]["reasoning_content"], "hmm");
+    }
+
+    #[test]
+    fn test_stream_translator_emits_openai_chunks() {
+        let mut translator = ZedStreamTranslator::new("claude-sonnet-4-5");
+        let chunk = |frame: Option<String>| -> Value {
+            let frame = frame.expect("chunk");
+            serde_json::from_str(frame.trim().strip_prefix("data: ").unwrap()).unwrap()
+        };
+
+        let thinking = chunk(translator.push_line(
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"thinking_delta","thinking":"hmm"}}"#,
+        ));
+        assert_eq!(thinking["object"], "chat.completion.chunk");
+        assert_eq!(thinking["choices"][0]["delta"]["reasoning_content"], "hmm");
+
+        let tool = chunk(translator.push_line(
+            r#"{"type":"content_block_start","index":2,"content_block":{"type":"tool_use","id":"toolu_1","name":"lookup"}}"#,
+        ));
+        assert_eq!(tool["choices"][0]["delta"]["tool_calls"][0]["index"], 0);
+        let args = chunk(translator.push_line(
+            r#"{"type":"content_block_delta","index":2,"delta":{"type":"input_json_delta","partial_json":"{}"}}"#,
+        ));
+        assert_eq!(
+            args["choices"][0]["delta"]["tool_calls"][0]["function"]["arguments"],
+            "{}"
+        );
+
+        let done = chunk(
+            translator.push_line(r#"{"type":"message_delta","delta":{"stop_reason":"tool_use"}}"#),
+        );
+        assert_eq!(done["choices"][0]["finish_reason"], "tool_calls");
+
+        assert!(translator.push_line(r#"{"type":"ping"}"#).is_none());
+    }
 }
@@ -1,6 +1,14 @@
-//! Round-robin and fill-first account/provider load balancing.
+//! Account/provider load balancing: round-robin, fill-first, least-busy,
+//! EWMA latency and weighted selection.
 
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::Serialize;
+
+/// Weight of the newest sample in the latency moving average.
+const EWMA_ALPHA: f64 = 0.3;
 
 /// Load-balancing strategy.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -9,21 +17,109 @@ pub enum Strategy {
     RoundRobin,
     /// Exhaust first candidate before moving to next.
     FillFirst,
+    /// Pick the candidate with the fewest requests in flight.
+    LeastBusy,
+    /// Pick the candidate with the lowest recent time to first token.
+    EwmaLatency,
+    /// Distribute requests in proportion to configured weights.
+    Weighted,
 }
 
 impl Strategy {
     pub fn parse(s: &str) -> Self {
         match s.to_lowercase().as_str() {
             "fill-first" | "fill_first" | "fillfirst" => Self::FillFirst,
+            "least-busy" | "least_busy" | "leastbusy" => Self::LeastBusy,
+            "ewma-latency" | "ewma_latency" | "ewmalatency" | "latency" => Self::EwmaLatency,
+            "weighted" => Self::Weighted,
             _ => Self::RoundRobin,
         }
     }
+
+    /// Canonical config spelling.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::RoundRobin => "round-robin",
+            Self::FillFirst => "fill-first",
+            Self::LeastBusy => "least-busy",
+            Self::EwmaLatency => "ewma-latency",
+            Self::Weighted => "weighted",
+        }
+    }
 }
 
-/// Per-candidate usage counter.
+/// Live counters for one candidate.
 #[derive(Debug)]
 struct CandidateCounter {
     requests: AtomicU64,
+    in_flight: AtomicU64,
+    /// Moving average time to first token in microseconds; 0 until the first sample
+    ewma_latency_us: AtomicU64,
+    /// Hedged calls launched on this candidate, and how many answered first
+    hedges: AtomicU64,
+    hedge_wins: AtomicU64,
+    weight: u32,
+}
+
+impl CandidateCounter {
+    fn new(weight: u32) -> Self {
+        Self {
+            requests: AtomicU64::new(0),
+            in_flight: AtomicU64::new(0),
+            ewma_latency_us: AtomicU64::new(0),
+            hedges: AtomicU64::new(0),
+            hedge_wins: AtomicU64::new(0),
+            weight,
+        }
+    }
+
+    fn record_latency(&self, latency: Duration) {
+        let sample = latency.as_micros().clamp(1, u128::from(u64::MAX)) as u64;
+        let blend = |current: u64| {
+            if current == 0 {
+                return sample;
+            }
+            (EWMA_ALPHA * sample as f64 + (1.0 - EWMA_ALPHA) * current as f64).max(1.0) as u64
+        };
+        let counter = &self.ewma_latency_us;
+        let _ = counter.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |c| Some(blend(c)));
+    }
+}
+
+/// Point-in-time view of one candidate's counters.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CandidateStats {
+    pub requests: u64,
+    pub in_flight: u64,
+    /// Moving average time to first token; `None` until a request has completed
+    pub ewma_latency_ms: Option<f64>,
+    pub weight: u32,
+    /// Hedged calls launched on this candidate
+    pub hedges: u64,
+    /// Hedged calls that answered before the call they backed up
+    pub hedge_wins: u64,
+}
+
+/// Marks a request as in flight on one candidate until dropped.
+///
+/// Owns its counter, so it can travel with a response stream after the
+/// balancer that issued it has been replaced.
+#[derive(Debug)]
+pub struct InFlight {
+    counter: Arc<CandidateCounter>,
+}
+
+impl InFlight {
+    /// Feed the time to first token (or to the full response) into the latency average.
+    pub fn record_latency(&self, latency: Duration) {
+        self.counter.record_latency(latency);
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.counter.in_flight.fetch_sub(1, Ordering::Relaxed);
+    }
 }
 
 /// Thread-safe load balancer that picks the next candidate index.
@@ -33,25 +129,37 @@ pub struct Balancer {
     /// Global round-robin counter (wraps around)
     rr_counter: AtomicU64,
     /// Per-candidate request counters for tracking
-    counters: Vec<CandidateCounter>,
+    counters: Vec<Arc<CandidateCounter>>,
+    /// Smooth weighted round-robin state, one slot per candidate
+    current_weights: Mutex<Vec<i64>>,
 }
 
 impl Balancer {
-    /// Create a balancer for `n` candidates.
+    /// Create a balancer for `n` candidates of equal weight.
     pub fn new(strategy: Strategy, n: usize) -> Self {
-        let counters = (0..n)
-            .map(|_| CandidateCounter {
-                requests: AtomicU64::new(0),
-            })
+        Self::with_weights(strategy, vec![1; n])
+    }
+
+    /// Create a balancer with one weight per candidate, used by [`Strategy::Weighted`].
+    pub fn with_weights(strategy: Strategy, weights: Vec<u32>) -> Self {
+        let n = weights.len();
+        let counters = weights
+            .into_iter()
+            .map(|weight| Arc::new(CandidateCounter::new(weight)))
             .collect();
 
         Self {
             strategy,
             rr_counter: AtomicU64::new(0),
             counters,
+            current_weights: Mutex::new(vec![0; n]),
         }
     }
 
+    pub fn strategy(&self) -> Strategy {
+        self.strategy
+    }
+
     /// Pick the next candidate index from the given candidate list.
     ///
     /// `candidates` is a slice of indices into the provider list.
@@ -75,18 +183,102 @@ impl Balancer {
                 // Always pick the first candidate
                 candidates[0]
             }
+            Strategy::LeastBusy => self.pick_min(candidates, |counter| {
+                counter.in_flight.load(Ordering::Relaxed)
+            }),
+            // Candidates without a sample yet score 0, so each one gets tried.
+            Strategy::EwmaLatency => self.pick_min(candidates, |counter| {
+                counter.ewma_latency_us.load(Ordering::Relaxed)
+            }),
+            Strategy::Weighted => self.pick_weighted(candidates),
         };
 
         self.increment(chosen);
         chosen
     }
 
+    /// Choose `preferred` while it is still a candidate, otherwise pick by strategy.
+    pub fn pick_preferring(&self, candidates: &[usize], preferred: Option<usize>) -> usize {
+        match preferred.filter(|idx| candidates.contains(idx)) {
+            Some(idx) => {
+                self.increment(idx);
+                idx
+            }
+            None => self.pick(candidates),
+        }
+    }
+
+    /// Lowest-scoring candidate; ties rotate so equal candidates share load.
+    fn pick_min(&self, candidates: &[usize], score: impl Fn(&CandidateCounter) -> u64) -> usize {
+        let tick = self.rr_counter.fetch_add(1, Ordering::Relaxed) as usize;
+        (0..candidates.len())
+            .map(|offset| candidates[(tick + offset) % candidates.len()])
+            .min_by_key(|&idx| self.counters.get(idx).map_or(u64::MAX, |c| score(c)))
+            .unwrap_or(candidates[0])
+    }
+
+    /// Smooth weighted round-robin over the given subset of candidates.
+    fn pick_weighted(&self, candidates: &[usize]) -> usize {
+        let mut current = self
+            .current_weights
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut total = 0i64;
+        let mut best: Option<usize> = None;
+
+        for &idx in candidates {
+            let weight = i64::from(self.weight(idx));
+            if weight == 0 || idx >= current.len() {
+                continue;
+            }
+            current[idx] += weight;
+            total += weight;
+            if best.is_none_or(|best| current[idx] > current[best]) {
+                best = Some(idx);
+            }
+        }
+
+        match best {
+            Some(idx) => {
+                current[idx] -= total;
+                idx
+            }
+            None => candidates[0],
+        }
+    }
+
+    fn weight(&self, idx: usize) -> u32 {
+        self.counters.get(idx).map_or(1, |c| c.weight)
+    }
+
     fn increment(&self, idx: usize) {
         if idx < self.counters.len() {
             self.counters[idx].requests.fetch_add(1, Ordering::Relaxed);
         }
     }
 
+    /// Count a hedged call on `idx`; it adds to the request total like any other call.
+    pub fn record_hedge(&self, idx: usize) {
+        if let Some(counter) = self.counters.get(idx) {
+            counter.requests.fetch_add(1, Ordering::Relaxed);
+            counter.hedges.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Count a hedged call on `idx` that answered first.
+    pub fn record_hedge_win(&self, idx: usize) {
+        if let Some(counter) = self.counters.get(idx) {
+            counter.hedge_wins.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Mark a request as in flight on `idx` until the returned guard is dropped.
+    pub fn begin(&self, idx: usize) -> Option<InFlight> {
+        let counter = self.counters.get(idx)?.clone();
+        counter.in_flight.fetch_add(1, Ordering::Relaxed);
+        Some(InFlight { counter })
+    }
+
     /// Get the request count for a candidate.
     pub fn request_count(&self, idx: usize) -> u64 {
         self.counters
@@ -102,4 +294,37 @@ impl Balancer {
             .map(|c| c.requests.load(Ordering::Relaxed))
             .sum()
     }
+
+    /// Live counters for a candidate.
+    pub fn stats(&self, idx: usize) -> Option<CandidateStats> {
+        let counter = self.counters.get(idx)?;
+        let latency_us = counter.ewma_latency_us.load(Ordering::Relaxed);
+        Some(CandidateStats {
+            requests: counter.requests.load(Ordering::Relaxed),
+            in_flight: counter.in_flight.load(Ordering::Relaxed),
+            ewma_latency_ms: (latency_us > 0).then(|| latency_us as f64 / 1000.0),
+            weight: counter.weight,
+            hedges: counter.hedges.load(Ordering::Relaxed),
+            hedge_wins: counter.hedge_wins.load(Ordering::Relaxed),
+        })
+    }
+}
+
+/// Index drawn from `weights` in proportion to each entry, given a uniform `roll`.
+///
+/// Returns `None` when every weight is zero.
+pub fn weighted_index(weights: &[u32], roll: u64) -> Option<usize> {
+    let total: u64 = weights.iter().map(|&w| u64::from(w)).sum();
+    if total == 0 {
+        return None;
+    }
+    let mut point = roll % total;
+    for (idx, &weight) in weights.iter().enumerate() {
+        let weight = u64::from(weight);
+        if point < weight {
+            return Some(idx);
+        }
+        point -= weight;
+    }
+    None
 }
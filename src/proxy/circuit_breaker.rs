@@ -0,0 +1,222 @@
+//! Per-account circuit breaker shared by every provider.
+//!
+//! Each `(client_id, model)` pair starts closed. After `failure-threshold`
+//! consecutive transient failures (5xx, timeouts, dropped connections) it opens
+//! and is skipped for `open-seconds`. Then it goes half-open: one probe request is
+//! let through, and its outcome either closes the circuit or opens it again.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::config::CircuitBreakerConfig;
+
+type CircuitKey = (String, String);
+
+/// Circuit state for one account/model pair.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CircuitState {
+    /// Requests flow normally.
+    Closed,
+    /// Requests are refused until the open period ends.
+    Open,
+    /// A single probe request is allowed through.
+    HalfOpen,
+}
+
+#[derive(Debug)]
+struct CircuitEntry {
+    state: CircuitState,
+    consecutive_failures: u32,
+    /// Open: when the circuit may be probed. Half-open: when an unanswered probe lapses.
+    until: Option<Instant>,
+    last_error: Option<String>,
+}
+
+/// Point-in-time view of one circuit, as shown by the management API.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CircuitStatus {
+    pub client_id: String,
+    pub model: String,
+    pub state: CircuitState,
+    pub consecutive_failures: u32,
+    /// Seconds until an open circuit is probed again
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_in_secs: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+/// Thread-safe circuit breaker keyed by `(client_id, model)`.
+///
+/// Only pairs that have failed are tracked; a success forgets the pair.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    open_for: Duration,
+    entries: Mutex<HashMap<CircuitKey, CircuitEntry>>,
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self::new(&CircuitBreakerConfig::default())
+    }
+}
+
+impl CircuitBreaker {
+    pub fn new(config: &CircuitBreakerConfig) -> Self {
+        Self {
+            failure_threshold: config.failure_threshold,
+            open_for: Duration::from_secs(config.open_seconds.max(1)),
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn key(client_id: &str, model: &str) -> CircuitKey {
+        (client_id.to_ascii_lowercase(), model.to_ascii_lowercase())
+    }
+
+    fn entries(&self) -> std::sync::MutexGuard<'_, HashMap<CircuitKey, CircuitEntry>> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Whether `client_id` may serve `model` at `now`, without claiming a probe.
+    ///
+    /// Used to filter routing candidates; [`CircuitBreaker::allow`] makes the claim
+    /// right before the request is sent.
+    pub fn is_available(&self, client_id: &str, model: &str, now: Instant) -> bool {
+        if self.failure_threshold == 0 {
+            return true;
+        }
+        self.entries()
+            .get(&Self::key(client_id, model))
+            .is_none_or(|entry| {
+                entry.state == CircuitState::Closed || entry.until.is_some_and(|until| now >= until)
+            })
+    }
+
+    /// Whether a request may be sent to `client_id` for `model` at `now`.
+    ///
+    /// An open circuit whose period has ended turns half-open and admits this
+    /// caller as its probe; further callers are refused until the probe reports
+    /// back or lapses.
+    pub fn allow(&self, client_id: &str, model: &str, now: Instant) -> bool {
+        if self.failure_threshold == 0 {
+            return true;
+        }
+        let mut entries = self.entries();
+        let Some(entry) = entries.get_mut(&Self::key(client_id, model)) else {
+            return true;
+        };
+
+        match entry.state {
+            CircuitState::Closed => true,
+            CircuitState::Open | CircuitState::HalfOpen
+                if entry.until.is_some_and(|until| now >= until) =>
+            {
+                if entry.state == CircuitState::Open {
+                    tracing::info!("circuit for {client_id}/{model} is half-open; probing");
+                }
+                entry.state = CircuitState::HalfOpen;
+                entry.until = Some(now + self.open_for);
+                true
+            }
+            CircuitState::Open | CircuitState::HalfOpen => false,
+        }
+    }
+
+    /// Record a successful request, closing the circuit.
+    pub fn record_success(&self, client_id: &str, model: &str) {
+        if let Some(entry) = self.entries().remove(&Self::key(client_id, model)) {
+            if entry.state != CircuitState::Closed {
+                tracing::info!("circuit for {client_id}/{model} closed");
+            }
+        }
+    }
+
+    /// Record a transient failure; returns the resulting state.
+    pub fn record_failure(
+        &self,
+        client_id: &str,
+        model: &str,
+        error: &str,
+        now: Instant,
+    ) -> CircuitState {
+        if self.failure_threshold == 0 {
+            return CircuitState::Closed;
+        }
+        let mut entries = self.entries();
+        let entry = entries
+            .entry(Self::key(client_id, model))
+            .or_insert(CircuitEntry {
+                state: CircuitState::Closed,
+                consecutive_failures: 0,
+                until: None,
+                last_error: None,
+            });
+        entry.consecutive_failures = entry.consecutive_failures.saturating_add(1);
+        entry.last_error = Some(error.to_string());
+
+        let trips = match entry.state {
+            CircuitState::Closed => entry.consecutive_failures >= self.failure_threshold,
+            CircuitState::HalfOpen => true,
+            CircuitState::Open => false,
+        };
+        if trips {
+            tracing::warn!(
+                "circuit for {client_id}/{model} opened for {}s after {} consecutive failure(s): {error}",
+                self.open_for.as_secs(),
+                entry.consecutive_failures
+            );
+            entry.state = CircuitState::Open;
+            entry.until = Some(now + self.open_for);
+        }
+        entry.state
+    }
+
+    /// Current state of the circuit for `client_id` and `model`.
+    pub fn state(&self, client_id: &str, model: &str) -> CircuitState {
+        self.entries()
+            .get(&Self::key(client_id, model))
+            .map_or(CircuitState::Closed, |entry| entry.state)
+    }
+
+    /// Every tracked circuit, sorted by account then model.
+    pub fn snapshot(&self, now: Instant) -> Vec<CircuitStatus> {
+        let mut circuits: Vec<CircuitStatus> = self
+            .entries()
+            .iter()
+            .map(|((client_id, model), entry)| CircuitStatus {
+                client_id: client_id.clone(),
+                model: model.clone(),
+                state: entry.state,
+                consecutive_failures: entry.consecutive_failures,
+                retry_in_secs: entry
+                    .until
+                    .filter(|_| entry.state == CircuitState::Open)
+                    .map(|until| until.saturating_duration_since(now).as_secs()),
+                last_error: entry.last_error.clone(),
+            })
+            .collect();
+        circuits.sort_by(|a, b| (&a.client_id, &a.model).cmp(&(&b.client_id, &b.model)));
+        circuits
+    }
+
+    /// Forget circuits for `client_id` (all models when `model` is `None`),
+    /// or every circuit when `client_id` is `None`. Returns how many were cleared.
+    pub fn reset(&self, client_id: Option<&str>, model: Option<&str>) -> usize {
+        let mut entries = self.entries();
+        let before = entries.len();
+        entries.retain(|(entry_client, entry_model), _| {
+            let client_matches = client_id.is_none_or(|id| entry_client.eq_ignore_ascii_case(id));
+            let model_matches = model.is_none_or(|m| entry_model.eq_ignore_ascii_case(m));
+            !(client_matches && model_matches)
+        });
+        before - entries.len()
+    }
+}
@@ -0,0 +1,994 @@
+//! Claude Messages API translation — converts Anthropic `/v1/messages` requests into
+//! OpenAI chat completion requests and shapes routed responses back into Anthropic form.
+
+use std::collections::{BTreeMap, HashMap};
+
+use bytes::Bytes;
+use futures::StreamExt;
+use serde_json::{json, Map, Value};
+
+use crate::error::AppError;
+use crate::models::{
+    ChatCompletionRequest, ChatCompletionResponse, ChatMessage, ContentPart, ImageUrl,
+    MessageContent,
+};
+use crate::providers::BoxStream;
+use crate::proxy::translate::{self, message_thinking, text_message, tool_arguments_value};
+
+/// Translate an Anthropic Messages request body into an OpenAI chat completion request.
+pub fn claude_body_to_chat_request(body: Value) -> Result<ChatCompletionRequest, AppError> {
+    let Value::Object(mut body) = body else {
+        return Err(AppError::BadRequest(
+            "messages request body must be a JSON object".to_string(),
+        ));
+    };
+
+    let model = body
+        .get("model")
+        .and_then(Value::as_str)
+        .ok_or_else(|| AppError::BadRequest("messages request missing model".to_string()))?
+        .to_string();
+
+    let source_messages = match body.remove("messages") {
+        Some(Value::Array(messages)) => messages,
+        _ => {
+            return Err(AppError::BadRequest(
+                "messages request missing messages array".to_string(),
+            ))
+        }
+    };
+
+    let mut messages = Vec::with_capacity(source_messages.len() + 1);
+
+    if let Some(system) = body.get("system") {
+        let text = system_text(system)?;
+        if !text.is_empty() {
+            messages.push(text_message("system", text));
+        }
+    }
+
+    for (idx, message) in source_messages.into_iter().enumerate() {
+        append_claude_message(&mut messages, idx, message)?;
+    }
+
+    let tools = body
+        .get("tools")
+        .and_then(Value::as_array)
+        .map(|tools| {
+            tools
+                .iter()
+                .filter_map(claude_tool_to_openai)
+                .collect::<Vec<_>>()
+        })
+        .filter(|tools| !tools.is_empty());
+
+    let tool_choice = body
+        .get("tool_choice")
+        .and_then(claude_tool_choice_to_openai);
+
+    let stop = body
+        .get("stop_sequences")
+        .and_then(Value::as_array)
+        .filter(|sequences| !sequences.is_empty())
+        .map(|sequences| Value::Array(sequences.clone()));
+
+    Ok(ChatCompletionRequest {
+        model,
+        messages,
+        stream: body.get("stream").and_then(Value::as_bool),
+        max_tokens: body
+            .get("max_tokens")
+            .and_then(Value::as_u64)
+            .map(|value| value as u32),
+        temperature: body
+            .get("temperature")
+            .and_then(Value::as_f64)
+            .map(|value| value as f32),
+        top_p: body
+            .get("top_p")
+            .and_then(Value::as_f64)
+            .map(|value| value as f32),
+        tools,
+        tool_choice,
+        stop,
+        extra: body
+            .get("thinking")
+            .map(|thinking| HashMap::from([("thinking".to_string(), thinking.clone())]))
+            .unwrap_or_default(),
+    })
+}
+
+fn system_text(system: &Value) -> Result<String, AppError> {
+    match system {
+        Value::String(text) => Ok(text.clone()),
+        Value::Array(blocks) => Ok(blocks
+            .iter()
+            .filter_map(|block| block.get("text").and_then(Value::as_str))
+            .collect::<Vec<_>>()
+            .join("\n\n")),
+        Value::Null => Ok(String::new()),
+        _ => Err(AppError::BadRequest(
+            "messages request system must be a string or an array of text blocks".to_string(),
+        )),
+    }
+}
+
+fn append_claude_message(
+    messages: &mut Vec<ChatMessage>,
+    idx: usize,
+    message: Value,
+) -> Result<(), AppError> {
+    let role = match message.get("role").and_then(Value::as_str) {
+        Some(role @ ("user" | "assistant")) => role.to_string(),
+        _ => {
+            return Err(AppError::BadRequest(format!(
+                "messages item {idx} must have role \"user\" or \"assistant\""
+            )))
+        }
+    };
+
+    let blocks = match message.get("content") {
+        Some(Value::String(text)) => {
+            messages.push(text_message(&role, text.clone()));
+            return Ok(());
+        }
+        Some(Value::Array(blocks)) => blocks,
+        _ => {
+            return Err(AppError::BadRequest(format!(
+                "messages item {idx} content must be a string or an array of content blocks"
+            )))
+        }
+    };
+
+    let mut parts = Vec::new();
+    let mut tool_calls = Vec::new();
+    let mut tool_results = Vec::new();
+
+    for block in blocks {
+        match block.get("type").and_then(Value::as_str) {
+            Some("text") => {
+                if let Some(text) = block.get("text").and_then(Value::as_str) {
+                    parts.push(ContentPart {
+                        part_type: "text".to_string(),
+                        text: Some(text.to_string()),
+                        image_url: None,
+                    });
+                }
+            }
+            Some("image") => {
+                if let Some(url) = block.get("source").and_then(image_source_url) {
+                    parts.push(ContentPart {
+                        part_type: "image_url".to_string(),
+                        text: None,
+                        image_url: Some(ImageUrl { url, detail: None }),
+                    });
+                }
+            }
+            Some("tool_use") => {
+                let id = block.get("id").and_then(Value::as_str).unwrap_or_default();
+                let name = block
+                    .get("name")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default();
+                let input = block.get("input").cloned().unwrap_or_else(|| json!({}));
+                tool_calls.push(json!({
+                    "id": id,
+                    "type": "function",
+                    "function": {
+                        "name": name,
+                        "arguments": input.to_string(),
+                    }
+                }));
+            }
+            Some("tool_result") => {
+                let tool_use_id = block
+                    .get("tool_use_id")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string();
+                let content = block
+                    .get("content")
+                    .map(tool_result_text)
+                    .unwrap_or_default();
+                tool_results.push((tool_use_id, content));
+            }
+            // Thinking, redacted thinking and unknown blocks have no chat-completions equivalent.
+            _ => {}
+        }
+    }
+
+    for (tool_use_id, content) in tool_results {
+        messages.push(ChatMessage {
+            role: "tool".to_string(),
+            content: MessageContent::Text(content),
+            name: None,
+            tool_calls: None,
+            tool_call_id: Some(tool_use_id),
+            reasoning_content: None,
+        });
+    }
+
+    if parts.is_empty() && tool_calls.is_empty() {
+        return Ok(());
+    }
+
+    let content = if parts.iter().all(|part| part.part_type == "text") {
+        MessageContent::Text(
+            parts
+                .iter()
+                .filter_map(|part| part.text.as_deref())
+                .collect::<Vec<_>>()
+                .join(""),
+        )
+    } else {
+        MessageContent::Parts(parts)
+    };
+
+    messages.push(ChatMessage {
+        role,
+        content,
+        name: None,
+        tool_calls: (!tool_calls.is_empty()).then_some(tool_calls),
+        tool_call_id: None,
+        reasoning_content: None,
+    });
+
+    Ok(())
+}
+
+fn image_source_url(source: &Value) -> Option<String> {
+    match source.get("type").and_then(Value::as_str) {
+        Some("base64") => {
+            let media_type = source
+                .get("media_type")
+                .and_then(Value::as_str)
+                .unwrap_or("image/png");
+            let data = source.get("data").and_then(Value::as_str)?;
+            Some(format!("data:{media_type};base64,{data}"))
+        }
+        Some("url") => source
+            .get("url")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        _ => None,
+    }
+}
+
+fn tool_result_text(content: &Value) -> String {
+    match content {
+        Value::String(text) => text.clone(),
+        Value::Array(blocks) => blocks
+            .iter()
+            .filter_map(|block| block.get("text").and_then(Value::as_str))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+fn claude_tool_to_openai(tool: &Value) -> Option<Value> {
+    let name = tool.get("name").and_then(Value::as_str)?;
+    let mut function = Map::new();
+    function.insert("name".to_string(), json!(name));
+    if let Some(description) = tool.get("description") {
+        function.insert("description".to_string(), description.clone());
+    }
+    function.insert(
+        "parameters".to_string(),
+        tool.get("input_schema")
+            .cloned()
+            .unwrap_or_else(|| json!({"type": "object", "properties": {}})),
+    );
+
+    Some(json!({
+        "type": "function",
+        "function": Value::Object(function),
+    }))
+}
+
+fn claude_tool_choice_to_openai(choice: &Value) -> Option<Value> {
+    match choice.get("type").and_then(Value::as_str)? {
+        "auto" => Some(json!("auto")),
+        "any" => Some(json!("required")),
+        "none" => Some(json!("none")),
+        "tool" => {
+            let name = choice.get("name").and_then(Value::as_str)?;
+            Some(json!({"type": "function", "function": {"name": name}}))
+        }
+        _ => None,
+    }
+}
+
+/// Map an OpenAI `finish_reason` onto an Anthropic `stop_reason`.
+fn stop_reason(finish_reason: Option<&str>) -> &'static str {
+    match finish_reason {
+        Some("length") => "max_tokens",
+        Some("tool_calls") | Some("function_call") => "tool_use",
+        _ => "end_turn",
+    }
+}
+
+fn message_id(id: &str) -> String {
+    if id.starts_with("msg_") {
+        id.to_string()
+    } else {
+        format!("msg_{}", uuid::Uuid::new_v4().simple())
+    }
+}
+
+/// Shape a routed chat completion into an Anthropic Messages response.
+pub fn chat_response_to_claude(response: &ChatCompletionResponse, model: &str) -> Value {
+    let choice = response.choices.first();
+    let message = choice.and_then(|choice| choice.message.as_ref());
+
+    let mut content = Vec::new();
+    if let Some(message) = message {
+        if let Some(thinking) = message_thinking(message) {
+            content.push(json!({"type": "thinking", "thinking": thinking, "signature": ""}));
+        }
+
+        let text = match &message.content {
+            MessageContent::Text(text) => text.clone(),
+            MessageContent::Parts(parts) => parts
+                .iter()
+                .filter_map(|part| part.text.as_deref())
+                .collect::<Vec<_>>()
+                .join(""),
+        };
+        if !text.is_empty() {
+            content.push(json!({"type": "text", "text": text}));
+        }
+
+        for call in message.tool_calls.iter().flatten() {
+            let function = call.get("function");
+            content.push(json!({
+                "type": "tool_use",
+                "id": call.get("id").and_then(Value::as_str).unwrap_or_default(),
+                "name": function
+                    .and_then(|function| function.get("name"))
+                    .and_then(Value::as_str)
+                    .unwrap_or_default(),
+                "input": tool_arguments_value(function.and_then(|function| function.get("arguments"))),
+            }));
+        }
+    }
+
+    let has_tool_use = content
+        .iter()
+        .any(|block| block["type"].as_str() == Some("tool_use"));
+    let finish_reason = choice.and_then(|choice| choice.finish_reason.as_deref());
+    let stop_reason = if has_tool_use {
+        "tool_use"
+    } else {
+        stop_reason(finish_reason)
+    };
+
+    let usage = response.usage.clone().unwrap_or_default();
+
+    json!({
+        "id": message_id(&response.id),
+        "type": "message",
+        "role": "assistant",
+        "model": model,
+        "content": content,
+        "stop_reason": stop_reason,
+        "stop_sequence": null,
+        "usage": {
+            "input_tokens": usage.prompt_tokens,
+            "output_tokens": usage.completion_tokens,
+        }
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OpenBlock {
+    Thinking,
+    Text,
+    ToolUse(u64),
+}
+
+/// Incremental translator from OpenAI `chat.completion.chunk` payloads to Anthropic
+/// Messages stream events.
+pub struct ClaudeStreamTranslator {
+    id: String,
+    model: String,
+    started: bool,
+    finished: bool,
+    next_index: u64,
+    open_block: Option<(OpenBlock, u64)>,
+    tool_blocks: BTreeMap<u64, u64>,
+    stop_reason: Option<&'static str>,
+    /// The upstream sent a `finish_reason`
+    finish_seen: bool,
+    input_tokens: u64,
+    output_tokens: u64,
+}
+
+impl ClaudeStreamTranslator {
+    pub fn new(model: impl Into<String>) -> Self {
+        Self {
+            id: format!("msg_{}", uuid::Uuid::new_v4().simple()),
+            model: model.into(),
+            started: false,
+            finished: false,
+            next_index: 0,
+            open_block: None,
+            tool_blocks: BTreeMap::new(),
+            stop_reason: None,
+            finish_seen: false,
+            input_tokens: 0,
+            output_tokens: 0,
+        }
+    }
+
+    fn event(name: &str, data: Value) -> String {
+        format!("event: {name}\ndata: {data}\n\n")
+    }
+
+    fn ensure_started(&mut self, events: &mut Vec<String>) {
+        if self.started {
+            return;
+        }
+        self.started = true;
+        events.push(Self::event(
+            "message_start",
+            json!({
+                "type": "message_start",
+                "message": {
+                    "id": self.id,
+                    "type": "message",
+                    "role": "assistant",
+                    "model": self.model,
+                    "content": [],
+                    "stop_reason": null,
+                    "stop_sequence": null,
+                    "usage": {
+                        "input_tokens": self.input_tokens,
+                        "output_tokens": 0,
+                    }
+                }
+            }),
+        ));
+    }
+
+    fn close_open_block(&mut self, events: &mut Vec<String>) {
+        if let Some((_, index)) = self.open_block.take() {
+            events.push(Self::event(
+                "content_block_stop",
+                json!({"type": "content_block_stop", "index": index}),
+            ));
+        }
+    }
+
+    fn open_block(
+        &mut self,
+        block: OpenBlock,
+        content_block: Value,
+        events: &mut Vec<String>,
+    ) -> u64 {
+        if let Some((open, index)) = self.open_block {
+            if open == block {
+                return index;
+            }
+        }
+        self.close_open_block(events);
+        let index = self.next_index;
+        self.next_index += 1;
+        self.open_block = Some((block, index));
+        events.push(Self::event(
+            "content_block_start",
+            json!({
+                "type": "content_block_start",
+                "index": index,
+                "content_block": content_block,
+            }),
+        ));
+        index
+    }
+
+    fn delta(index: u64, delta: Value) -> String {
+        Self::event(
+            "content_block_delta",
+            json!({"type": "content_block_delta", "index": index, "delta": delta}),
+        )
+    }
+
+    fn record_usage(&mut self, usage: &Value) {
+        translate::record_usage(usage, &mut self.input_tokens, &mut self.output_tokens);
+    }
+
+    /// Translate one upstream `data:` payload into zero or more Anthropic SSE events.
+    pub fn push_data(&mut self, data: &str) -> Vec<String> {
+        let mut events = Vec::new();
+        if self.finished {
+            return events;
+        }
+
+        if data == "[DONE]" {
+            return self.finish();
+        }
+
+        let Ok(chunk) = serde_json::from_str::<Value>(data) else {
+            return events;
+        };
+
+        if let Some(error) = chunk.get("error") {
+            let message = error
+                .get("message")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .unwrap_or_else(|| error.to_string());
+            return self.fail(&message);
+        }
+
+        if let Some(usage) = chunk.get("usage").filter(|usage| usage.is_object()) {
+            self.record_usage(usage);
+        }
+
+        self.ensure_started(&mut events);
+
+        let Some(choice) = chunk
+            .get("choices")
+            .and_then(Value::as_array)
+            .and_then(|choices| choices.first())
+        else {
+            return events;
+        };
+        let delta = choice.get("delta").cloned().unwrap_or(Value::Null);
+
+        for key in ["reasoning_content", "thinking"] {
+            if let Some(thinking) = delta.get(key).and_then(Value::as_str) {
+                if !thinking.is_empty() {
+                    let index = self.open_block(
+                        OpenBlock::Thinking,
+                        json!({"type": "thinking", "thinking": ""}),
+                        &mut events,
+                    );
+                    events.push(Self::delta(
+                        index,
+                        json!({"type": "thinking_delta", "thinking": thinking}),
+                    ));
+                }
+            }
+        }
+
+        if let Some(text) = delta.get("content").and_then(Value::as_str) {
+            if !text.is_empty() {
+                let index = self.open_block(
+                    OpenBlock::Text,
+                    json!({"type": "text", "text": ""}),
+                    &mut events,
+                );
+                events.push(Self::delta(
+                    index,
+                    json!({"type": "text_delta", "text": text}),
+                ));
+            }
+        }
+
+        for call in delta
+            .get("tool_calls")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            let call_index = call.get("index").and_then(Value::as_u64).unwrap_or(0);
+            let function = call.get("function");
+            let index = match self.tool_blocks.get(&call_index) {
+                Some(&index)
+                    if self.open_block == Some((OpenBlock::ToolUse(call_index), index)) =>
+                {
+                    index
+                }
+                _ => {
+                    let id = call
+                        .get("id")
+                        .and_then(Value::as_str)
+                        .map(str::to_string)
+                        .unwrap_or_else(|| format!("toolu_{}", uuid::Uuid::new_v4().simple()));
+                    let name = function
+                        .and_then(|function| function.get("name"))
+                        .and_then(Value::as_str)
+                        .unwrap_or_default();
+                    let index = self.open_block(
+                        OpenBlock::ToolUse(call_index),
+                        json!({"type": "tool_use", "id": id, "name": name, "input": {}}),
+                        &mut events,
+                    );
+                    self.tool_blocks.insert(call_index, index);
+                    index
+                }
+            };
+
+            let arguments = match function.and_then(|function| function.get("arguments")) {
+                Some(Value::String(arguments)) => arguments.clone(),
+                Some(Value::Null) | None => String::new(),
+                Some(other) => other.to_string(),
+            };
+            if !arguments.is_empty() {
+                events.push(Self::delta(
+                    index,
+                    json!({"type": "input_json_delta", "partial_json": arguments}),
+                ));
+            }
+            self.stop_reason = Some("tool_use");
+        }
+
+        if let Some(finish_reason) = choice.get("finish_reason").and_then(Value::as_str) {
+            self.finish_seen = true;
+            if self.stop_reason != Some("tool_use") {
+                self.stop_reason = Some(stop_reason(Some(finish_reason)));
+            }
+        }
+
+        events
+    }
+
+    /// Close any open block and emit the terminal `message_delta` / `message_stop` pair.
+    pub fn finish(&mut self) -> Vec<String> {
+        let mut events = Vec::new();
+        if self.finished {
+            return events;
+        }
+        self.ensure_started(&mut events);
+        self.close_open_block(&mut events);
+        self.finished = true;
+
+        events.push(Self::event(
+            "message_delta",
+            json!({
+                "type": "message_delta",
+                "delta": {
+                    "stop_reason": self.stop_reason.unwrap_or("end_turn"),
+                    "stop_sequence": null,
+                },
+                "usage": {"output_tokens": self.output_tokens},
+            }),
+        ));
+        events.push(Self::event("message_stop", json!({"type": "message_stop"})));
+        events
+    }
+
+    /// Emit an Anthropic `error` event in place of the remaining reply.
+    fn fail(&mut self, message: &str) -> Vec<String> {
+        let mut events = Vec::new();
+        self.ensure_started(&mut events);
+        events.push(Self::event(
+            "error",
+            json!({"type": "error", "error": {"type": "api_error", "message": message}}),
+        ));
+        self.finished = true;
+        events
+    }
+
+    /// The upstream stream is over: stop cleanly if it sent `[DONE]` or a finish
+    /// reason, otherwise report the reply as cut off.
+    pub fn end_of_stream(&mut self) -> Vec<String> {
+        if self.finished {
+            Vec::new()
+        } else if self.finish_seen {
+            self.finish()
+        } else {
+            self.fail(crate::proxy::stream::STREAM_CUT_OFF)
+        }
+    }
+}
+
+/// Re-shape an OpenAI SSE stream into an Anthropic Messages SSE stream.
+pub fn claude_sse_stream(upstream: BoxStream, model: String) -> BoxStream {
+    let stream = async_stream::stream! {
+        let mut translator = ClaudeStreamTranslator::new(model);
+        let payloads = crate::proxy::stream::sse_data_payloads(upstream);
+        futures::pin_mut!(payloads);
+
+        while let Some(payload) = payloads.next().await {
+            let events = match payload {
+                Ok(data) => translator.push_data(&data),
+                Err(error) => translator.push_data(
+                    &json!({"error": {"message": error.to_string()}}).to_string(),
+                ),
+            };
+            for event in events {
+                yield Ok(Bytes::from(event));
+            }
+        }
+
+        for event in translator.end_of_stream() {
+            yield Ok(Bytes::from(event));
+        }
+    };
+
+    Box::pin(stream)
+}
+
+/// Name `model` in the `message_start` event of a native Anthropic stream, as the
+/// non-stream reply does. Lines are buffered only until that event is through; the
+/// rest of the stream passes on untouched.
+pub fn native_stream_with_model(upstream: BoxStream, model: String) -> BoxStream {
+    let stream = async_stream::stream! {
+        let mut upstream = upstream;
+        let mut pending: Vec<u8> = Vec::new();
+        let mut renamed = false;
+
+        while let Some(chunk) = upstream.next().await {
+            let chunk = match chunk {
+                Ok(chunk) => chunk,
+                Err(error) => {
+                    if !pending.is_empty() {
+                        yield Ok(Bytes::from(std::mem::take(&mut pending)));
+                    }
+                    yield Err(error);
+                    return;
+                }
+            };
+            if renamed {
+                yield Ok(chunk);
+                continue;
+            }
+
+            pending.extend_from_slice(&chunk);
+            let mut out = Vec::new();
+            while !renamed {
+                let Some(end) = pending.iter().position(|&byte| byte == b'\n') else {
+                    break;
+                };
+                let line: Vec<u8> = pending.drain(..=end).collect();
+                match message_start_with_model(&line, &model) {
+                    Some(line) => {
+                        out.extend_from_slice(line.as_bytes());
+                        renamed = true;
+                    }
+                    None => out.extend_from_slice(&line),
+                }
+            }
+            if renamed {
+                out.append(&mut pending);
+            }
+            if !out.is_empty() {
+                yield Ok(Bytes::from(out));
+            }
+        }
+
+        if !pending.is_empty() {
+            yield Ok(Bytes::from(pending));
+        }
+    };
+
+    Box::pin(stream)
+}
+
+/// `line` with `message.model` replaced, when it is the `data:` line of a
+/// `message_start` event.
+fn message_start_with_model(line: &[u8], model: &str) -> Option<String> {
+    let line = std::str::from_utf8(line).ok()?;
+    let data = line.trim_end().strip_prefix("data:")?.trim();
+    let mut event: Value = serde_json::from_str(data).ok()?;
+    if event["type"] != "message_start" {
+        return None;
+    }
+    event
+        .get_mut("message")?
+        .as_object_mut()?
+        .insert("model".to_string(), Value::String(model.to_string()));
+    let ending = if line.ends_with("\r\n") { "\r\n" } else { "\n" };
+    Some(format!("data: {event}{ending}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Choice, Usage};
+
+    #[test]
+    fn claude_body_maps_system_tools_and_tool_results() {
+        let request = claude_body_to_chat_request(json!({
+            "model": "claude-sonnet-4-5",
+            "max_tokens": 1024,
+            "system": [{"type": "text", "text": "be brief"}],
+            "stop_sequences": ["END"],
+            "tools": [{
+                "name": "lookup",
+                "description": "look things up",
+                "input_schema": {"type": "object", "properties": {"q": {"type": "string"}}}
+            }],
+            "tool_choice": {"type": "any"},
+            "messages": [
+                {"role": "user", "content": "weather?"},
+                {"role": "assistant", "content": [
+                    {"type": "text", "text": "checking"},
+                    {"type": "tool_use", "id": "toolu_1", "name": "lookup", "input": {"q": "rain"}}
+                ]},
+                {"role": "user", "content": [
+                    {"type": "tool_result", "tool_use_id": "toolu_1", "content": [{"type": "text", "text": "sunny"}]},
+                    {"type": "text", "text": "thanks"}
+                ]}
+            ]
+        }))
+        .expect("valid claude body should translate");
+
+        assert_eq!(request.model, "claude-sonnet-4-5");
+        assert_eq!(request.max_tokens, Some(1024));
+        assert_eq!(request.stop, Some(json!(["END"])));
+        assert_eq!(request.tool_choice, Some(json!("required")));
+        assert_eq!(
+            request.tools.as_ref().unwrap()[0]["function"]["parameters"]["properties"]["q"]["type"],
+            "string"
+        );
+
+        let roles = request
+            .messages
+            .iter()
+            .map(|message| message.role.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(roles, ["system", "user", "assistant", "tool", "user"]);
+
+        let assistant = &request.messages[2];
+        let call = &assistant.tool_calls.as_ref().unwrap()[0];
+        assert_eq!(call["id"], "toolu_1");
+        assert_eq!(call["function"]["arguments"], "{\"q\":\"rain\"}");
+
+        let tool = &request.messages[3];
+        assert_eq!(tool.tool_call_id.as_deref(), Some("toolu_1"));
+        match &tool.content {
+            MessageContent::Text(text) => assert_eq!(text, "sunny"),
+            other => panic!("expected text tool result, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn claude_body_rejects_missing_messages() {
+        let error = claude_body_to_chat_request(json!({"model": "claude-sonnet-4-5"}))
+            .expect_err("messages are required");
+
+        assert!(
+            matches!(error, AppError::BadRequest(message) if message.contains("messages array"))
+        );
+    }
+
+    #[test]
+    fn chat_response_maps_text_tool_use_and_usage() {
+        let response = ChatCompletionResponse {
+            id: "chatcmpl-1".to_string(),
+            object: "chat.completion".to_string(),
+            created: 0,
+            model: "kiro-claude-sonnet-4-5".to_string(),
+            choices: vec![Choice {
+                index: 0,
+                message: Some(ChatMessage {
+                    role: "assistant".to_string(),
+                    content: MessageContent::Text("let me check".to_string()),
+                    name: None,
+                    tool_calls: Some(vec![json!({
+                        "id": "call_1",
+                        "type": "function",
+                        "function": {"name": "lookup", "arguments": "{\"q\":\"rain\"}"}
+                    })]),
+                    tool_call_id: None,
+                    reasoning_content: None,
+                }),
+                delta: None,
+                finish_reason: Some("tool_calls".to_string()),
+            }],
+            usage: Some(Usage {
+                prompt_tokens: 12,
+                completion_tokens: 7,
+                total_tokens: 19,
+            }),
+        };
+
+        let claude = chat_response_to_claude(&response, "claude-sonnet-4-5");
+
+        assert_eq!(claude["type"], "message");
+        assert_eq!(claude["model"], "claude-sonnet-4-5");
+        assert!(claude["id"].as_str().unwrap().starts_with("msg_"));
+        assert_eq!(claude["content"][0]["text"], "let me check");
+        assert_eq!(claude["content"][1]["type"], "tool_use");
+        assert_eq!(claude["content"][1]["input"]["q"], "rain");
+        assert_eq!(claude["stop_reason"], "tool_use");
+        assert_eq!(claude["usage"]["input_tokens"], 12);
+        assert_eq!(claude["usage"]["output_tokens"], 7);
+    }
+
+    #[test]
+    fn stream_translator_emits_block_lifecycle() {
+        let mut translator = ClaudeStreamTranslator::new("claude-sonnet-4-5");
+        let mut events = Vec::new();
+        events.extend(translator.push_data(
+            &json!({"choices": [{"index": 0, "delta": {"role": "assistant", "content": "Hel"}}]})
+                .to_string(),
+        ));
+        events.extend(translator.push_data(
+            &json!({"choices": [{"index": 0, "delta": {"content": "lo"}}]}).to_string(),
+        ));
+        events.extend(translator.push_data(
+            &json!({"choices": [{"index": 0, "delta": {"tool_calls": [{
+                "index": 0, "id": "call_1", "function": {"name": "lookup", "arguments": "{\"q\":"}
+            }]}}]})
+            .to_string(),
+        ));
+        events.extend(
+            translator.push_data(
+                &json!({"choices": [{"index": 0, "delta": {"tool_calls": [{
+                "index": 0, "function": {"arguments": "\"rain\"}"}
+            }]}, "finish_reason": "tool_calls"}]})
+                .to_string(),
+            ),
+        );
+        events.extend(translator.push_data("[DONE]"));
+        events.extend(translator.finish());
+
+        let names = events
+            .iter()
+            .map(|event| event.lines().next().unwrap().trim_start_matches("event: "))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                "message_start",
+                "content_block_start",
+                "content_block_delta",
+                "content_block_delta",
+                "content_block_stop",
+                "content_block_start",
+                "content_block_delta",
+                "content_block_delta",
+                "content_block_stop",
+                "message_delta",
+                "message_stop",
+            ]
+        );
+        assert!(events[5].contains("\"tool_use\""));
+        assert!(events[9].contains("\"stop_reason\":\"tool_use\""));
+    }
+
+    #[test]
+    fn stream_translator_reports_a_cut_off_stream_as_an_error() {
+        let mut translator = ClaudeStreamTranslator::new("claude-sonnet-4-5");
+        translator.push_data(
+            &json!({"choices": [{"index": 0, "delta": {"content": "Hel"}}]}).to_string(),
+        );
+        let events = translator.end_of_stream();
+
+        assert_eq!(events.len(), 1);
+        assert!(events[0].starts_with("event: error"));
+        assert!(events[0].contains(crate::proxy::stream::STREAM_CUT_OFF));
+
+        let mut translator = ClaudeStreamTranslator::new("claude-sonnet-4-5");
+        translator.push_data(
+            &json!({"choices": [{"index": 0, "delta": {"content": "Hi"}, "finish_reason": "stop"}]})
+                .to_string(),
+        );
+        let events = translator.end_of_stream();
+        assert!(events.last().unwrap().starts_with("event: message_stop"));
+    }
+
+    #[tokio::test]
+    async fn native_stream_names_the_requested_model_in_message_start() {
+        let chunks = [
+            "event: message_start\ndata: {\"type\":\"message_start\",\"message\":{\"id\":\"msg_1\",",
+            "\"model\":\"claude-sonnet-4-6-20260101\"}}\n\nevent: ping\ndata: {\"type\":\"ping\"}\n\n",
+            "event: message_stop\ndata: {\"type\":\"message_stop\"}\n\n",
+        ];
+        let upstream: BoxStream = Box::pin(futures::stream::iter(
+            chunks.map(|chunk| Ok(Bytes::from(chunk))),
+        ));
+
+        let body = native_stream_with_model(upstream, "claude-sonnet-4.6".to_string())
+            .map(|chunk| String::from_utf8(chunk.unwrap().to_vec()).unwrap())
+            .collect::<String>()
+            .await;
+
+        let start = body
+            .lines()
+            .find_map(|line| line.strip_prefix("data: "))
+            .map(|data| serde_json::from_str::<Value>(data).unwrap())
+            .unwrap();
+        assert_eq!(start["message"]["model"], "claude-sonnet-4.6");
+        assert_eq!(start["message"]["id"], "msg_1");
+        assert!(body.starts_with("event: message_start\n"));
+        assert!(body.ends_with(
+            "event: ping\ndata: {\"type\":\"ping\"}\n\nevent: message_stop\ndata: {\"type\":\"message_stop\"}\n\n"
+        ));
+    }
+}
@@ -1,7 +1,13 @@
 use std::collections::HashSet;
 use std::time::Duration;
 
+use axum::http::HeaderMap;
 use moka::{policy::EvictionPolicy, sync::Cache};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::config::{SessionAffinityConfig, SessionSource};
+use crate::models::ChatMessage;
 
 const DEFAULT_EXECUTION_SESSION_MAX_CAPACITY: u64 = 10_000;
 const DEFAULT_EXECUTION_SESSION_TTL: Duration = Duration::from_secs(60 * 60);
@@ -60,6 +66,14 @@ impl ExecutionSessionStore {
         self.selected_auth_by_session.run_pending_tasks();
     }
 
+    /// Every live `(session_id, selected_auth_id)` pair.
+    pub fn entries(&self) -> Vec<(String, String)> {
+        self.selected_auth_by_session
+            .iter()
+            .map(|(session_id, auth_id)| (session_id.to_string(), auth_id))
+            .collect()
+    }
+
     pub async fn invalidate_selected_auth(&self, selected_auth_id: &str) {
         let mut stale_sessions = Vec::new();
 
@@ -101,11 +115,124 @@ impl ExecutionSessionStore {
     }
 }
 
+/// Session key for a request that did not send `execution_session_id`, taken from
+/// the first configured source present.
+///
+/// Keys are scoped to `model`, so a conversation that mixes models keeps one account
+/// per model.
+pub fn derive_session_key(
+    config: &SessionAffinityConfig,
+    headers: &HeaderMap,
+    model: &str,
+    messages: &[ChatMessage],
+    metadata: Option<&Value>,
+) -> Option<String> {
+    let non_empty = |value: &str| {
+        let value = value.trim();
+        (!value.is_empty()).then(|| value.to_string())
+    };
+    config.sources.iter().find_map(|source| {
+        let (label, value) = match source {
+            SessionSource::Header => (
+                "header",
+                headers
+                    .get(config.header.as_str())
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(non_empty)?,
+            ),
+            SessionSource::MetadataUserId => (
+                "user",
+                metadata
+                    .and_then(|metadata| metadata.get("user_id"))
+                    .and_then(Value::as_str)
+                    .and_then(non_empty)?,
+            ),
+            SessionSource::MessageHash => (
+                "messages",
+                hash_leading_messages(messages, config.hash_messages)?,
+            ),
+        };
+        Some(format!("{label}:{model}:{value}"))
+    })
+}
+
+fn hash_leading_messages(messages: &[ChatMessage], count: usize) -> Option<String> {
+    if count == 0 || messages.is_empty() {
+        return None;
+    }
+    let leading = serde_json::to_vec(&messages[..count.min(messages.len())]).ok()?;
+    let digest = Sha256::digest(&leading);
+    Some(digest[..16].iter().map(|b| format!("{b:02x}")).collect())
+}
+
 #[cfg(test)]
 mod tests {
     use std::time::Duration;
 
-    use super::ExecutionSessionStore;
+    use axum::http::HeaderMap;
+    use serde_json::json;
+
+    use super::{derive_session_key, ExecutionSessionStore};
+    use crate::config::{SessionAffinityConfig, SessionSource};
+    use crate::models::{ChatMessage, MessageContent};
+
+    fn message(role: &str, text: &str) -> ChatMessage {
+        ChatMessage {
+            role: role.to_string(),
+            content: MessageContent::Text(text.to_string()),
+            name: None,
+            tool_calls: None,
+            tool_call_id: None,
+            reasoning_content: None,
+        }
+    }
+
+    #[test]
+    fn session_key_comes_from_first_configured_source_present() {
+        let config = SessionAffinityConfig::default();
+        let mut headers = HeaderMap::new();
+        let metadata = json!({"user_id": "user-42"});
+
+        assert_eq!(
+            derive_session_key(&config, &headers, "team-gpt", &[], Some(&metadata)),
+            Some("user:team-gpt:user-42".to_string())
+        );
+        headers.insert("x-session-id", "conv-1".parse().unwrap());
+        assert_eq!(
+            derive_session_key(&config, &headers, "team-gpt", &[], Some(&metadata)),
+            Some("header:team-gpt:conv-1".to_string())
+        );
+        assert_eq!(
+            derive_session_key(&config, &HeaderMap::new(), "team-gpt", &[], None),
+            None
+        );
+    }
+
+    #[test]
+    fn message_hash_covers_only_leading_messages() {
+        let config = SessionAffinityConfig {
+            sources: vec![SessionSource::MessageHash],
+            hash_messages: 2,
+            ..Default::default()
+        };
+        let headers = HeaderMap::new();
+        let turn_one = [message("system", "be brief"), message("user", "hi")];
+        let turn_two = [
+            message("system", "be brief"),
+            message("user", "hi"),
+            message("assistant", "hello"),
+            message("user", "more"),
+        ];
+        let other = [message("system", "be brief"), message("user", "bye")];
+
+        let key = |messages: &[ChatMessage]| {
+            derive_session_key(&config, &headers, "team-gpt", messages, None)
+        };
+        assert!(key(&turn_one).is_some());
+        assert_eq!(key(&turn_one), key(&turn_two));
+        assert_ne!(key(&turn_one), key(&other));
+        assert_eq!(key(&[]), None);
+    }
 
     #[tokio::test]
     async fn execution_sessions_expire_after_ttl() {
@@ -0,0 +1,836 @@
+//! Gemini generateContent translation — converts `/v1beta/models/{model}:generateContent`
+//! bodies into OpenAI chat completion requests and shapes routed responses back into
+//! Gemini `candidates` / `usageMetadata` form.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+use bytes::Bytes;
+use futures::StreamExt;
+use serde_json::{json, Map, Value};
+
+use crate::error::AppError;
+use crate::models::{
+    ChatCompletionRequest, ChatCompletionResponse, ChatMessage, ContentPart, ImageUrl,
+    MessageContent,
+};
+use crate::providers::BoxStream;
+use crate::proxy::translate::{self, message_thinking, text_message, tool_arguments_value};
+
+/// Gemini model action parsed from the `{model}:{action}` path segment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GeminiAction {
+    GenerateContent,
+    StreamGenerateContent,
+}
+
+/// Split `gemini-2.5-pro:streamGenerateContent` into the model id and action.
+pub fn parse_model_action(model_action: &str) -> Result<(String, GeminiAction), AppError> {
+    let model_action = model_action.strip_prefix("models/").unwrap_or(model_action);
+    let (model, action) = model_action.rsplit_once(':').ok_or_else(|| {
+        AppError::BadRequest(format!(
+            "expected {{model}}:{{action}} path segment, got {model_action}"
+        ))
+    })?;
+    if model.is_empty() {
+        return Err(AppError::BadRequest(
+            "Gemini request path is missing a model".to_string(),
+        ));
+    }
+
+    let action = match action {
+        "generateContent" => GeminiAction::GenerateContent,
+        "streamGenerateContent" => GeminiAction::StreamGenerateContent,
+        other => {
+            return Err(AppError::BadRequest(format!(
+                "unsupported Gemini model action: {other}"
+            )))
+        }
+    };
+
+    Ok((model.to_string(), action))
+}
+
+/// Translate a Gemini generateContent body into an OpenAI chat completion request.
+pub fn gemini_body_to_chat_request(
+    model: &str,
+    body: Value,
+    stream: bool,
+) -> Result<ChatCompletionRequest, AppError> {
+    let Value::Object(body) = body else {
+        return Err(AppError::BadRequest(
+            "generateContent request body must be a JSON object".to_string(),
+        ));
+    };
+
+    let contents = match body.get("contents") {
+        Some(Value::Array(contents)) => contents,
+        _ => {
+            return Err(AppError::BadRequest(
+                "generateContent request missing contents array".to_string(),
+            ))
+        }
+    };
+
+    let mut messages = Vec::with_capacity(contents.len() + 1);
+
+    if let Some(system) = body
+        .get("systemInstruction")
+        .or_else(|| body.get("system_instruction"))
+    {
+        let text = parts_text(system.get("parts"));
+        if !text.is_empty() {
+            messages.push(text_message("system", text));
+        }
+    }
+
+    // Gemini pairs functionResponse with functionCall by name (or optional id), while
+    // OpenAI needs explicit tool_call ids, so remember the ids handed out per name.
+    let mut pending_calls: HashMap<String, VecDeque<String>> = HashMap::new();
+    let mut call_counter = 0usize;
+
+    for (idx, content) in contents.iter().enumerate() {
+        append_gemini_content(
+            &mut messages,
+            idx,
+            content,
+            &mut pending_calls,
+            &mut call_counter,
+        )?;
+    }
+
+    let tools = body
+        .get("tools")
+        .and_then(Value::as_array)
+        .map(|tools| {
+            tools
+                .iter()
+                .flat_map(|tool| {
+                    tool.get("functionDeclarations")
+                        .or_else(|| tool.get("function_declarations"))
+                        .and_then(Value::as_array)
+                        .into_iter()
+                        .flatten()
+                })
+                .filter_map(gemini_function_to_openai)
+                .collect::<Vec<_>>()
+        })
+        .filter(|tools| !tools.is_empty());
+
+    let tool_choice = body
+        .get("toolConfig")
+        .and_then(|config| config.get("functionCallingConfig"))
+        .and_then(gemini_tool_config_to_openai);
+
+    let generation_config = body.get("generationConfig").cloned().unwrap_or(Value::Null);
+
+    let stop = generation_config
+        .get("stopSequences")
+        .and_then(Value::as_array)
+        .filter(|sequences| !sequences.is_empty())
+        .map(|sequences| Value::Array(sequences.clone()));
+
+    let mut extra = HashMap::new();
+    if let Some(config) = generation_config.get("thinkingConfig") {
+        if let Some(level) = config.get("thinkingLevel").and_then(Value::as_str) {
+            extra.insert("reasoning_effort".to_string(), json!(level));
+        } else if let Some(budget) = config.get("thinkingBudget").and_then(Value::as_i64) {
+            let thinking = if budget == 0 {
+                json!({"type": "disabled"})
+            } else {
+                json!({"type": "enabled", "budget_tokens": budget})
+            };
+            extra.insert("thinking".to_string(), thinking);
+        }
+    }
+
+    Ok(ChatCompletionRequest {
+        model: model.to_string(),
+        messages,
+        stream: Some(stream),
+        max_tokens: generation_config
+            .get("maxOutputTokens")
+            .and_then(Value::as_u64)
+            .map(|value| value as u32),
+        temperature: generation_config
+            .get("temperature")
+            .and_then(Value::as_f64)
+            .map(|value| value as f32),
+        top_p: generation_config
+            .get("topP")
+            .and_then(Value::as_f64)
+            .map(|value| value as f32),
+        tools,
+        tool_choice,
+        stop,
+        extra,
+    })
+}
+
+fn parts_text(parts: Option<&Value>) -> String {
+    parts
+        .and_then(Value::as_array)
+        .map(|parts| {
+            parts
+                .iter()
+                .filter_map(|part| part.get("text").and_then(Value::as_str))
+                .collect::<Vec<_>>()
+                .join("\n\n")
+        })
+        .unwrap_or_default()
+}
+
+fn append_gemini_content(
+    messages: &mut Vec<ChatMessage>,
+    idx: usize,
+    content: &Value,
+    pending_calls: &mut HashMap<String, VecDeque<String>>,
+    call_counter: &mut usize,
+) -> Result<(), AppError> {
+    let role = match content.get("role").and_then(Value::as_str) {
+        Some("model") => "assistant",
+        Some("user") | Some("function") | Some("tool") | None => "user",
+        Some(other) => {
+            return Err(AppError::BadRequest(format!(
+                "contents item {idx} has unsupported role \"{other}\""
+            )))
+        }
+    };
+
+    let Some(parts) = content.get("parts").and_then(Value::as_array) else {
+        return Err(AppError::BadRequest(format!(
+            "contents item {idx} must have a parts array"
+        )));
+    };
+
+    let mut content_parts = Vec::new();
+    let mut tool_calls = Vec::new();
+    let mut tool_results = Vec::new();
+
+    for part in parts {
+        // Thought summaries are model-side only; don't replay them as visible text.
+        if part.get("thought").and_then(Value::as_bool) == Some(true) {
+            continue;
+        }
+
+        if let Some(text) = part.get("text").and_then(Value::as_str) {
+            content_parts.push(ContentPart {
+                part_type: "text".to_string(),
+                text: Some(text.to_string()),
+                image_url: None,
+            });
+        } else if let Some(inline) = part.get("inlineData").or_else(|| part.get("inline_data")) {
+            let mime_type = inline
+                .get("mimeType")
+                .or_else(|| inline.get("mime_type"))
+                .and_then(Value::as_str)
+                .unwrap_or("image/png");
+            if let Some(data) = inline.get("data").and_then(Value::as_str) {
+                content_parts.push(ContentPart {
+                    part_type: "image_url".to_string(),
+                    text: None,
+                    image_url: Some(ImageUrl {
+                        url: format!("data:{mime_type};base64,{data}"),
+                        detail: None,
+                    }),
+                });
+            }
+        } else if let Some(call) = part.get("functionCall") {
+            let name = call
+                .get("name")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string();
+            let id = call
+                .get("id")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .unwrap_or_else(|| {
+                    *call_counter += 1;
+                    format!("call_{}_{}", name, call_counter)
+                });
+            pending_calls
+                .entry(name.clone())
+                .or_default()
+                .push_back(id.clone());
+            let args = call.get("args").cloned().unwrap_or_else(|| json!({}));
+            tool_calls.push(json!({
+                "id": id,
+                "type": "function",
+                "function": {
+                    "name": name,
+                    "arguments": args.to_string(),
+                }
+            }));
+        } else if let Some(response) = part.get("functionResponse") {
+            let name = response
+                .get("name")
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            let id = response
+                .get("id")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .or_else(|| pending_calls.get_mut(name).and_then(VecDeque::pop_front))
+                .unwrap_or_else(|| format!("call_{name}"));
+            let output = match response.get("response") {
+                Some(Value::String(text)) => text.clone(),
+                Some(value) => value.to_string(),
+                None => String::new(),
+            };
+            tool_results.push((id, output));
+        }
+    }
+
+    for (tool_call_id, output) in tool_results {
+        messages.push(ChatMessage {
+            role: "tool".to_string(),
+            content: MessageContent::Text(output),
+            name: None,
+            tool_calls: None,
+            tool_call_id: Some(tool_call_id),
+            reasoning_content: None,
+        });
+    }
+
+    if content_parts.is_empty() && tool_calls.is_empty() {
+        return Ok(());
+    }
+
+    let content = if content_parts.iter().all(|part| part.part_type == "text") {
+        MessageContent::Text(
+            content_parts
+                .iter()
+                .filter_map(|part| part.text.as_deref())
+                .collect::<Vec<_>>()
+                .join(""),
+        )
+    } else {
+        MessageContent::Parts(content_parts)
+    };
+
+    messages.push(ChatMessage {
+        role: role.to_string(),
+        content,
+        name: None,
+        tool_calls: (!tool_calls.is_empty()).then_some(tool_calls),
+        tool_call_id: None,
+        reasoning_content: None,
+    });
+
+    Ok(())
+}
+
+fn gemini_function_to_openai(declaration: &Value) -> Option<Value> {
+    let name = declaration.get("name").and_then(Value::as_str)?;
+    let mut function = Map::new();
+    function.insert("name".to_string(), json!(name));
+    if let Some(description) = declaration.get("description") {
+        function.insert("description".to_string(), description.clone());
+    }
+    function.insert(
+        "parameters".to_string(),
+        declaration
+            .get("parameters")
+            .or_else(|| declaration.get("parametersJsonSchema"))
+            .cloned()
+            .unwrap_or_else(|| json!({"type": "object", "properties": {}})),
+    );
+
+    Some(json!({
+        "type": "function",
+        "function": Value::Object(function),
+    }))
+}
+
+fn gemini_tool_config_to_openai(config: &Value) -> Option<Value> {
+    match config.get("mode").and_then(Value::as_str)? {
+        "AUTO" => Some(json!("auto")),
+        "NONE" => Some(json!("none")),
+        "ANY" => {
+            let allowed = config
+                .get("allowedFunctionNames")
+                .and_then(Value::as_array)
+                .filter(|names| names.len() == 1)
+                .and_then(|names| names[0].as_str());
+            match allowed {
+                Some(name) => Some(json!({"type": "function", "function": {"name": name}})),
+                None => Some(json!("required")),
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Map an OpenAI `finish_reason` onto a Gemini `finishReason`.
+fn finish_reason(finish_reason: Option<&str>) -> &'static str {
+    match finish_reason {
+        Some("length") => "MAX_TOKENS",
+        Some("content_filter") => "SAFETY",
+        _ => "STOP",
+    }
+}
+
+fn usage_metadata(prompt_tokens: u64, completion_tokens: u64) -> Value {
+    json!({
+        "promptTokenCount": prompt_tokens,
+        "candidatesTokenCount": completion_tokens,
+        "totalTokenCount": prompt_tokens + completion_tokens,
+    })
+}
+
+/// Shape a routed chat completion into a Gemini generateContent response.
+pub fn chat_response_to_gemini(response: &ChatCompletionResponse, model: &str) -> Value {
+    let choice = response.choices.first();
+    let message = choice.and_then(|choice| choice.message.as_ref());
+
+    let mut parts = Vec::new();
+    if let Some(message) = message {
+        if let Some(thinking) = message_thinking(message) {
+            parts.push(json!({"text": thinking, "thought": true}));
+        }
+
+        let text = match &message.content {
+            MessageContent::Text(text) => text.clone(),
+            MessageContent::Parts(content_parts) => content_parts
+                .iter()
+                .filter_map(|part| part.text.as_deref())
+                .collect::<Vec<_>>()
+                .join(""),
+        };
+        if !text.is_empty() {
+            parts.push(json!({"text": text}));
+        }
+
+        for call in message.tool_calls.iter().flatten() {
+            let function = call.get("function");
+            parts.push(json!({
+                "functionCall": {
+                    "name": function
+                        .and_then(|function| function.get("name"))
+                        .and_then(Value::as_str)
+                        .unwrap_or_default(),
+                    "args": tool_arguments_value(function.and_then(|function| function.get("arguments"))),
+                }
+            }));
+        }
+    }
+
+    let usage = response.usage.clone().unwrap_or_default();
+
+    json!({
+        "candidates": [{
+            "content": {"role": "model", "parts": parts},
+            "finishReason": finish_reason(choice.and_then(|choice| choice.finish_reason.as_deref())),
+            "index": 0,
+        }],
+        "usageMetadata": usage_metadata(usage.prompt_tokens.into(), usage.completion_tokens.into()),
+        "modelVersion": model,
+        "responseId": response.id,
+    })
+}
+
+/// Incremental translator from OpenAI `chat.completion.chunk` payloads to Gemini
+/// streamGenerateContent response chunks.
+///
+/// Gemini streams function calls as whole parts, so tool-call argument deltas are
+/// buffered and flushed together with the terminal chunk.
+pub struct GeminiStreamTranslator {
+    model: String,
+    response_id: String,
+    finished: bool,
+    tool_calls: BTreeMap<u64, (String, String)>,
+    finish_reason: Option<&'static str>,
+    prompt_tokens: u64,
+    completion_tokens: u64,
+}
+
+impl GeminiStreamTranslator {
+    pub fn new(model: impl Into<String>) -> Self {
+        Self {
+            model: model.into(),
+            response_id: uuid::Uuid::new_v4().simple().to_string(),
+            finished: false,
+            tool_calls: BTreeMap::new(),
+            finish_reason: None,
+            prompt_tokens: 0,
+            completion_tokens: 0,
+        }
+    }
+
+    fn chunk(&self, parts: Vec<Value>, finish_reason: Option<&str>) -> Value {
+        let mut candidate = json!({
+            "content": {"role": "model", "parts": parts},
+            "index": 0,
+        });
+        if let Some(finish_reason) = finish_reason {
+            candidate["finishReason"] = json!(finish_reason);
+        }
+
+        let mut chunk = json!({
+            "candidates": [candidate],
+            "modelVersion": self.model,
+            "responseId": self.response_id,
+        });
+        if finish_reason.is_some() {
+            chunk["usageMetadata"] = usage_metadata(self.prompt_tokens, self.completion_tokens);
+        }
+        chunk
+    }
+
+    fn record_usage(&mut self, usage: &Value) {
+        translate::record_usage(usage, &mut self.prompt_tokens, &mut self.completion_tokens);
+    }
+
+    /// Translate one upstream `data:` payload into zero or more Gemini response chunks.
+    pub fn push_data(&mut self, data: &str) -> Vec<Value> {
+        let mut chunks = Vec::new();
+        if self.finished {
+            return chunks;
+        }
+
+        if data == "[DONE]" {
+            return self.finish();
+        }
+
+        let Ok(chunk) = serde_json::from_str::<Value>(data) else {
+            return chunks;
+        };
+
+        if let Some(error) = chunk.get("error") {
+            let message = error
+                .get("message")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .unwrap_or_else(|| error.to_string());
+            return self.fail(&message);
+        }
+
+        if let Some(usage) = chunk.get("usage").filter(|usage| usage.is_object()) {
+            self.record_usage(usage);
+        }
+
+        let Some(choice) = chunk
+            .get("choices")
+            .and_then(Value::as_array)
+            .and_then(|choices| choices.first())
+        else {
+            return chunks;
+        };
+        let delta = choice.get("delta").cloned().unwrap_or(Value::Null);
+
+        let mut parts = Vec::new();
+        for key in ["reasoning_content", "thinking"] {
+            if let Some(thinking) = delta.get(key).and_then(Value::as_str) {
+                if !thinking.is_empty() {
+                    parts.push(json!({"text": thinking, "thought": true}));
+                }
+            }
+        }
+        if let Some(text) = delta.get("content").and_then(Value::as_str) {
+            if !text.is_empty() {
+                parts.push(json!({"text": text}));
+            }
+        }
+        if !parts.is_empty() {
+            chunks.push(self.chunk(parts, None));
+        }
+
+        for call in delta
+            .get("tool_calls")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            let call_index = call.get("index").and_then(Value::as_u64).unwrap_or(0);
+            let function = call.get("function");
+            let entry = self.tool_calls.entry(call_index).or_default();
+            if let Some(name) = function
+                .and_then(|function| function.get("name"))
+                .and_then(Value::as_str)
+            {
+                entry.0.push_str(name);
+            }
+            match function.and_then(|function| function.get("arguments")) {
+                Some(Value::String(arguments)) => entry.1.push_str(arguments),
+                Some(Value::Null) | None => {}
+                Some(other) => entry.1.push_str(&other.to_string()),
+            }
+        }
+
+        if let Some(reason) = choice.get("finish_reason").and_then(Value::as_str) {
+            self.finish_reason = Some(finish_reason(Some(reason)));
+        }
+
+        chunks
+    }
+
+    /// Flush buffered function calls and emit the terminal chunk with `finishReason`.
+    pub fn finish(&mut self) -> Vec<Value> {
+        if self.finished {
+            return Vec::new();
+        }
+        self.finished = true;
+
+        let parts = std::mem::take(&mut self.tool_calls)
+            .into_values()
+            .map(|(name, arguments)| {
+                json!({
+                    "functionCall": {
+                        "name": name,
+                        "args": tool_arguments_value(Some(&Value::String(arguments))),
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        vec![self.chunk(parts, Some(self.finish_reason.unwrap_or("STOP")))]
+    }
+
+    /// Emit a Gemini error object in place of the remaining reply.
+    fn fail(&mut self, message: &str) -> Vec<Value> {
+        self.finished = true;
+        vec![json!({
+            "error": {"code": 500, "message": message, "status": "INTERNAL"}
+        })]
+    }
+
+    /// The upstream stream is over: finish cleanly if it sent `[DONE]` or a finish
+    /// reason, otherwise report the reply as cut off.
+    pub fn end_of_stream(&mut self) -> Vec<Value> {
+        if self.finished {
+            Vec::new()
+        } else if self.finish_reason.is_some() {
+            self.finish()
+        } else {
+            self.fail(crate::proxy::stream::STREAM_CUT_OFF)
+        }
+    }
+}
+
+/// Re-shape an OpenAI SSE stream into a Gemini streamGenerateContent body.
+///
+/// With `sse` set (the `?alt=sse` form) each chunk is its own `data:` event; otherwise
+/// the chunks are streamed as elements of a single JSON array, as the REST API does.
+pub fn gemini_stream(upstream: BoxStream, model: String, sse: bool) -> BoxStream {
+    let stream = async_stream::stream! {
+        let mut translator = GeminiStreamTranslator::new(model);
+        let payloads = crate::proxy::stream::sse_data_payloads(upstream);
+        futures::pin_mut!(payloads);
+        let mut emitted = 0usize;
+
+        if !sse {
+            yield Ok(Bytes::from_static(b"["));
+        }
+
+        loop {
+            let chunks = match payloads.next().await {
+                Some(Ok(data)) => translator.push_data(&data),
+                Some(Err(error)) => translator.push_data(
+                    &json!({"error": {"message": error.to_string()}}).to_string(),
+                ),
+                None => translator.end_of_stream(),
+            };
+            for chunk in chunks {
+                let frame = if sse {
+                    format!("data: {chunk}\r\n\r\n")
+                } else if emitted == 0 {
+                    chunk.to_string()
+                } else {
+                    format!(",\r\n{chunk}")
+                };
+                emitted += 1;
+                yield Ok(Bytes::from(frame));
+            }
+
+            if translator.finished {
+                break;
+            }
+        }
+
+        if !sse {
+            yield Ok(Bytes::from_static(b"]"));
+        }
+    };
+
+    Box::pin(stream)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Choice, Usage};
+
+    #[test]
+    fn parse_model_action_splits_model_and_action() {
+        assert_eq!(
+            parse_model_action("gemini-2.5-pro:streamGenerateContent").unwrap(),
+            (
+                "gemini-2.5-pro".to_string(),
+                GeminiAction::StreamGenerateContent
+            )
+        );
+        assert!(matches!(
+            parse_model_action("gemini-2.5-pro:embedContent"),
+            Err(AppError::BadRequest(_))
+        ));
+        assert!(matches!(
+            parse_model_action("gemini-2.5-pro"),
+            Err(AppError::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn gemini_body_maps_system_tools_and_function_responses() {
+        let request = gemini_body_to_chat_request(
+            "claude-sonnet-4-5",
+            json!({
+                "systemInstruction": {"parts": [{"text": "be brief"}]},
+                "generationConfig": {
+                    "maxOutputTokens": 256,
+                    "temperature": 0.2,
+                    "stopSequences": ["END"]
+                },
+                "tools": [{"functionDeclarations": [{
+                    "name": "lookup",
+                    "description": "look things up",
+                    "parameters": {"type": "object", "properties": {"q": {"type": "string"}}}
+                }]}],
+                "toolConfig": {"functionCallingConfig": {"mode": "ANY"}},
+                "contents": [
+                    {"role": "user", "parts": [{"text": "weather?"}]},
+                    {"role": "model", "parts": [
+                        {"text": "checking"},
+                        {"functionCall": {"name": "lookup", "args": {"q": "rain"}}}
+                    ]},
+                    {"role": "user", "parts": [
+                        {"functionResponse": {"name": "lookup", "response": {"result": "sunny"}}}
+                    ]}
+                ]
+            }),
+            false,
+        )
+        .expect("valid gemini body should translate");
+
+        assert_eq!(request.model, "claude-sonnet-4-5");
+        assert_eq!(request.stream, Some(false));
+        assert_eq!(request.max_tokens, Some(256));
+        assert_eq!(request.stop, Some(json!(["END"])));
+        assert_eq!(request.tool_choice, Some(json!("required")));
+        assert_eq!(
+            request.tools.as_ref().unwrap()[0]["function"]["name"],
+            "lookup"
+        );
+
+        let roles = request
+            .messages
+            .iter()
+            .map(|message| message.role.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(roles, ["system", "user", "assistant", "tool"]);
+
+        let call = &request.messages[2].tool_calls.as_ref().unwrap()[0];
+        assert_eq!(call["function"]["arguments"], "{\"q\":\"rain\"}");
+        assert_eq!(
+            request.messages[3].tool_call_id.as_deref(),
+            call["id"].as_str()
+        );
+    }
+
+    #[test]
+    fn chat_response_maps_candidates_and_usage_metadata() {
+        let response = ChatCompletionResponse {
+            id: "chatcmpl-1".to_string(),
+            object: "chat.completion".to_string(),
+            created: 0,
+            model: "kiro-claude-sonnet-4-5".to_string(),
+            choices: vec![Choice {
+                index: 0,
+                message: Some(ChatMessage {
+                    role: "assistant".to_string(),
+                    content: MessageContent::Text("let me check".to_string()),
+                    name: None,
+                    tool_calls: Some(vec![json!({
+                        "id": "call_1",
+                        "type": "function",
+                        "function": {"name": "lookup", "arguments": "{\"q\":\"rain\"}"}
+                    })]),
+                    tool_call_id: None,
+                    reasoning_content: None,
+                }),
+                delta: None,
+                finish_reason: Some("tool_calls".to_string()),
+            }],
+            usage: Some(Usage {
+                prompt_tokens: 12,
+                completion_tokens: 7,
+                total_tokens: 19,
+            }),
+        };
+
+        let gemini = chat_response_to_gemini(&response, "claude-sonnet-4-5");
+        let candidate = &gemini["candidates"][0];
+
+        assert_eq!(candidate["content"]["role"], "model");
+        assert_eq!(candidate["content"]["parts"][0]["text"], "let me check");
+        assert_eq!(
+            candidate["content"]["parts"][1]["functionCall"]["args"]["q"],
+            "rain"
+        );
+        assert_eq!(candidate["finishReason"], "STOP");
+        assert_eq!(gemini["usageMetadata"]["promptTokenCount"], 12);
+        assert_eq!(gemini["usageMetadata"]["totalTokenCount"], 19);
+    }
+
+    #[test]
+    fn stream_translator_buffers_function_calls_until_finish() {
+        let mut translator = GeminiStreamTranslator::new("claude-sonnet-4-5");
+        let mut chunks = Vec::new();
+        chunks.extend(translator.push_data(
+            &json!({"choices": [{"index": 0, "delta": {"content": "Hi"}}]}).to_string(),
+        ));
+        chunks.extend(translator.push_data(
+            &json!({"choices": [{"index": 0, "delta": {"tool_calls": [{
+                "index": 0, "id": "call_1", "function": {"name": "lookup", "arguments": "{\"q\":"}
+            }]}}]})
+            .to_string(),
+        ));
+        chunks.extend(
+            translator.push_data(
+                &json!({"choices": [{"index": 0, "delta": {"tool_calls": [{
+                "index": 0, "function": {"arguments": "\"rain\"}"}
+            }]}, "finish_reason": "tool_calls"}]})
+                .to_string(),
+            ),
+        );
+        chunks.extend(translator.push_data("[DONE]"));
+        chunks.extend(translator.finish());
+
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(
+            chunks[0]["candidates"][0]["content"]["parts"][0]["text"],
+            "Hi"
+        );
+        let last = &chunks[1]["candidates"][0];
+        assert_eq!(
+            last["content"]["parts"][0]["functionCall"]["args"]["q"],
+            "rain"
+        );
+        assert_eq!(last["finishReason"], "STOP");
+        assert!(chunks[1]["usageMetadata"].is_object());
+    }
+
+    #[test]
+    fn stream_translator_reports_a_cut_off_stream_as_an_error() {
+        let mut translator = GeminiStreamTranslator::new("claude-sonnet-4-5");
+        translator
+            .push_data(&json!({"choices": [{"index": 0, "delta": {"content": "Hi"}}]}).to_string());
+        let chunks = translator.end_of_stream();
+
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(
+            chunks[0]["error"]["message"],
+            crate::proxy::stream::STREAM_CUT_OFF
+        );
+        assert!(translator.end_of_stream().is_empty());
+    }
+}
@@ -1,30 +1,201 @@
 use axum::{
-    extract::{Path, State},
+    extract::{FromRequestParts, MatchedPath, Path, Query, State},
+    http::{header::CONTENT_TYPE, request::Parts, HeaderMap, HeaderValue},
     response::{IntoResponse, Response},
     Json,
 };
+use futures::StreamExt;
 use serde_json::{json, Value};
-use std::{collections::HashSet, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    future::Future,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use crate::{
-    error::AppError,
-    models::{ChatCompletionRequest, ChatMessage, MessageContent, ModelInfo, ModelsResponse},
-    providers::Provider,
-    proxy::ProxyState,
+    config::{ApiKeyEntry, KeyRateLimit, ModelRoute, ModelRouteTarget},
+    error::{ApiFamily, AppError, UpstreamError, UpstreamErrorKind},
+    models::{
+        ChatCompletionRequest, ChatCompletionResponse, ChatMessage, MessageContent, ModelInfo,
+        ModelsResponse,
+    },
+    providers::{AnthropicResponse, BoxStream, Provider},
+    proxy::{
+        balancer::{weighted_index, Balancer, InFlight, Strategy},
+        circuit_breaker::CircuitState,
+        execution_session::derive_session_key,
+        key_rate_limit::StreamPermit,
+        stream::hold_until_content,
+        usage::{observe_stream, ReplyEnd, TokenUsage},
+        usage_ledger::UsageRecord,
+        ProxyState,
+    },
 };
 
-const PUBLIC_MODEL_OPUS_46: &str = "claude-opus-4-6";
-const PUBLIC_MODEL_OPUS_45: &str = "claude-opus-4-5";
-const PUBLIC_MODEL_SONNET_46: &str = "claude-sonnet-4-6";
-const PUBLIC_MODEL_SONNET_45: &str = "claude-sonnet-4-5";
-const PUBLIC_MODEL_HAIKU_45: &str = "claude-haiku-4-5";
-const PUBLIC_MODEL_GPT_54: &str = "gpt-5.4";
-const PUBLIC_MODEL_GPT_53_CODEX: &str = "gpt-5.3-codex";
-
-#[derive(Clone, Copy)]
-struct RouteTarget {
-    provider: &'static str,
-    model: &'static str,
+/// Response header naming the public model that actually served the request.
+pub const SERVED_MODEL_HEADER: &str = "x-rusuh-served-model";
+
+/// Request header a client sets to `off` to refuse cross-model fallback.
+pub const FALLBACK_HEADER: &str = "x-rusuh-fallback";
+
+/// Whether the caller accepts being served by a fallback model.
+fn fallback_allowed(headers: &HeaderMap) -> bool {
+    let opt_out = headers
+        .get(FALLBACK_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.trim().to_ascii_lowercase());
+    !matches!(opt_out.as_deref(), Some("off" | "false" | "0" | "no"))
+}
+
+/// Routed chat result before it is shaped for the caller's API surface.
+pub(crate) enum RouteOutcome {
+    Completion(ChatCompletionResponse),
+    Stream(BoxStream),
+    /// Served natively from the caller's Anthropic Messages body.
+    Anthropic(AnthropicResponse),
+}
+
+impl IntoResponse for RouteOutcome {
+    fn into_response(self) -> Response {
+        match self {
+            RouteOutcome::Completion(response) => Json(response).into_response(),
+            RouteOutcome::Stream(stream)
+            | RouteOutcome::Anthropic(AnthropicResponse::Stream(stream)) => {
+                crate::proxy::stream::sse_response(stream)
+            }
+            RouteOutcome::Anthropic(AnthropicResponse::Message(message)) => {
+                Json(message).into_response()
+            }
+        }
+    }
+}
+
+impl RouteOutcome {
+    fn is_stream(&self) -> bool {
+        matches!(
+            self,
+            RouteOutcome::Stream(_) | RouteOutcome::Anthropic(AnthropicResponse::Stream(_))
+        )
+    }
+
+    /// Report latency to the balancer and keep `in_flight` alive while a stream is read.
+    ///
+    /// Streams report time to their first chunk; complete responses report total time.
+    fn track(self, in_flight: InFlight, started: Instant) -> Self {
+        fn track_stream(stream: BoxStream, in_flight: InFlight, started: Instant) -> BoxStream {
+            let mut first = true;
+            Box::pin(stream.inspect(move |_| {
+                if std::mem::take(&mut first) {
+                    in_flight.record_latency(started.elapsed());
+                }
+            }))
+        }
+
+        match self {
+            RouteOutcome::Stream(stream) => {
+                RouteOutcome::Stream(track_stream(stream, in_flight, started))
+            }
+            RouteOutcome::Anthropic(AnthropicResponse::Stream(stream)) => RouteOutcome::Anthropic(
+                AnthropicResponse::Stream(track_stream(stream, in_flight, started)),
+            ),
+            outcome => {
+                in_flight.record_latency(started.elapsed());
+                outcome
+            }
+        }
+    }
+
+    /// Hand the reply's token usage and how it ended to `report`: right away for
+    /// complete replies, once the stream is done for streams.
+    fn observe_usage(self, report: impl FnOnce(TokenUsage, ReplyEnd) + Send + 'static) -> Self {
+        match self {
+            RouteOutcome::Completion(response) => {
+                report(
+                    response.usage.as_ref().map(Into::into).unwrap_or_default(),
+                    ReplyEnd::Complete,
+                );
+                RouteOutcome::Completion(response)
+            }
+            RouteOutcome::Anthropic(AnthropicResponse::Message(message)) => {
+                report(
+                    TokenUsage::from_value(&message["usage"]).unwrap_or_default(),
+                    ReplyEnd::Complete,
+                );
+                RouteOutcome::Anthropic(AnthropicResponse::Message(message))
+            }
+            RouteOutcome::Stream(stream) => RouteOutcome::Stream(observe_stream(stream, report)),
+            RouteOutcome::Anthropic(AnthropicResponse::Stream(stream)) => {
+                RouteOutcome::Anthropic(AnthropicResponse::Stream(observe_stream(stream, report)))
+            }
+        }
+    }
+}
+
+/// A routed result together with the account that produced it.
+pub(crate) struct Served {
+    outcome: RouteOutcome,
+    client_id: String,
+    /// Provider type of the account
+    provider: String,
+}
+
+impl IntoResponse for Served {
+    fn into_response(self) -> Response {
+        self.outcome.into_response()
+    }
+}
+
+/// A served result together with the model that served it.
+pub(crate) struct Routed {
+    served: Served,
+    /// Differs from the requested model when a fallback chain took over.
+    served_model: String,
+}
+
+impl Routed {
+    fn observe_usage(self, report: impl FnOnce(TokenUsage, ReplyEnd) + Send + 'static) -> Self {
+        Self {
+            served: Served {
+                outcome: self.served.outcome.observe_usage(report),
+                ..self.served
+            },
+            served_model: self.served_model,
+        }
+    }
+
+    /// Shape the outcome with `respond` and tag the response with the served model.
+    fn respond(self, respond: impl FnOnce(RouteOutcome) -> Response) -> Response {
+        let mut response = respond(self.served.outcome);
+        if let Ok(value) = HeaderValue::from_str(&self.served_model) {
+            response.headers_mut().insert(SERVED_MODEL_HEADER, value);
+        }
+        response
+    }
+}
+
+/// Request headers plus the client key the auth middleware accepted, if auth is on.
+pub struct Client {
+    headers: HeaderMap,
+    key: Option<ApiKeyEntry>,
+    /// Route pattern the request matched, e.g. `/v1/chat/completions`
+    route: String,
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for Client {
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self {
+            headers: parts.headers.clone(),
+            key: parts.extensions.get::<ApiKeyEntry>().cloned(),
+            route: parts
+                .extensions
+                .get::<MatchedPath>()
+                .map(|path| path.as_str().to_string())
+                .unwrap_or_default(),
+        })
+    }
 }
 
 // ── Health ────────────────────────────────────────────────────────────────────
@@ -33,6 +204,12 @@ pub async fn health() -> Response {
     Json(json!({ "status": "ok", "service": "rusuh" })).into_response()
 }
 
+/// GET /metrics — Prometheus text exposition
+pub async fn metrics(State(state): State<Arc<ProxyState>>) -> Response {
+    let body = state.metrics.render(&state).await;
+    ([(CONTENT_TYPE, prometheus::TEXT_FORMAT)], body).into_response()
+}
+
 // ── OpenAI-compatible ─────────────────────────────────────────────────────────
 
 /// GET /v1/models
@@ -48,39 +225,68 @@ pub async fn list_models(State(state): State<Arc<ProxyState>>) -> Response {
 /// POST /v1/chat/completions
 pub async fn chat_completions(
     State(state): State<Arc<ProxyState>>,
+    client: Client,
     Json(req): Json<ChatCompletionRequest>,
 ) -> Result<Response, AppError> {
-    route_chat(state, req, None).await
+    Ok(route_chat(state, req, None, &client)
+        .await?
+        .respond(IntoResponse::into_response))
 }
 
 /// POST /v1/responses
 pub async fn responses(
     State(state): State<Arc<ProxyState>>,
+    client: Client,
     Json(body): Json<Value>,
 ) -> Result<Response, AppError> {
     let req = responses_body_to_chat_request(body)?;
-    route_chat(state, req, None).await
+    let requested_model = req.model.clone();
+
+    let routed = route_chat(state, req, None, &client).await?;
+    Ok(routed.respond(|outcome| match outcome {
+        RouteOutcome::Completion(response) => Json(
+            crate::proxy::responses::chat_response_to_responses(&response, &requested_model),
+        )
+        .into_response(),
+        RouteOutcome::Stream(stream) => crate::proxy::stream::sse_response(
+            crate::proxy::responses::responses_sse_stream(stream, requested_model),
+        ),
+        outcome @ RouteOutcome::Anthropic(_) => outcome.into_response(),
+    }))
 }
 
 /// POST /v1/responses/compact
+///
+/// Compaction always runs as a single non-streaming completion and replies with a
+/// `response.compaction` object carrying the replacement history.
 pub async fn responses_compact(
     State(state): State<Arc<ProxyState>>,
+    client: Client,
     Json(body): Json<Value>,
 ) -> Result<Response, AppError> {
-    // TODO: /v1/responses/compact currently matches /v1/responses intentionally
-    // until compact-specific response shaping is implemented in route_chat.
-    let req = responses_body_to_chat_request(body)?;
-    route_chat(state, req, None).await
+    let mut req = responses_body_to_chat_request(body)?;
+    req.stream = Some(false);
+    let requested_model = req.model.clone();
+
+    let routed = route_chat(state, req, None, &client).await?;
+    Ok(routed.respond(|outcome| match outcome {
+        RouteOutcome::Completion(response) => Json(
+            crate::proxy::responses::chat_response_to_compaction(&response, &requested_model),
+        )
+        .into_response(),
+        outcome => outcome.into_response(),
+    }))
 }
 
 /// POST /v1/messages  (Claude-compatible)
 pub async fn claude_messages(
-    State(_state): State<Arc<ProxyState>>,
-    Json(_body): Json<Value>,
-) -> Result<Response, AppError> {
-    Err(AppError::BadRequest(
-        "Claude messages endpoint not yet implemented".to_string(),
-    ))
+    State(state): State<Arc<ProxyState>>,
+    client: Client,
+    Json(body): Json<Value>,
+) -> Response {
+    route_claude(state, body, None, &client)
+        .await
+        .unwrap_or_else(|e| e.into_response_for(ApiFamily::Anthropic))
 }
 
 // ── Gemini-compatible ─────────────────────────────────────────────────────────
@@ -103,13 +309,50 @@ pub async fn list_models_gemini(State(state): State<Arc<ProxyState>>) -> Respons
 
 /// POST /v1beta/models/:model_action
 pub async fn gemini_generate(
-    State(_state): State<Arc<ProxyState>>,
-    Path(_model_action): Path<String>,
-    Json(_body): Json<Value>,
+    State(state): State<Arc<ProxyState>>,
+    Path(model_action): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
+    client: Client,
+    Json(body): Json<Value>,
+) -> Response {
+    route_gemini(state, &model_action, &params, &client, body)
+        .await
+        .unwrap_or_else(|e| e.into_response_for(ApiFamily::Gemini))
+}
+
+/// Route a Gemini `generateContent` body and shape the result back into Gemini form.
+async fn route_gemini(
+    state: Arc<ProxyState>,
+    model_action: &str,
+    params: &HashMap<String, String>,
+    client: &Client,
+    body: Value,
 ) -> Result<Response, AppError> {
-    Err(AppError::BadRequest(
-        "Gemini generate endpoint not yet implemented".to_string(),
-    ))
+    let (model, action) = crate::proxy::gemini::parse_model_action(model_action)?;
+    let is_stream = action == crate::proxy::gemini::GeminiAction::StreamGenerateContent;
+    let req = crate::proxy::gemini::gemini_body_to_chat_request(&model, body, is_stream)?;
+
+    let routed = route_chat(state, req, None, client).await?;
+    Ok(routed.respond(|outcome| match outcome {
+        RouteOutcome::Completion(response) => Json(crate::proxy::gemini::chat_response_to_gemini(
+            &response, &model,
+        ))
+        .into_response(),
+        RouteOutcome::Stream(stream) => {
+            let sse = params.get("alt").map(String::as_str) == Some("sse");
+            let stream = crate::proxy::gemini::gemini_stream(stream, model, sse);
+            if sse {
+                crate::proxy::stream::sse_response(stream)
+            } else {
+                (
+                    [(axum::http::header::CONTENT_TYPE, "application/json")],
+                    axum::body::Body::from_stream(stream),
+                )
+                    .into_response()
+            }
+        }
+        outcome @ RouteOutcome::Anthropic(_) => outcome.into_response(),
+    }))
 }
 
 // ── Amp provider aliases ──────────────────────────────────────────────────────
@@ -129,19 +372,23 @@ pub async fn amp_list_models(
 pub async fn amp_chat_completions(
     State(state): State<Arc<ProxyState>>,
     Path(provider): Path<String>,
+    client: Client,
     Json(req): Json<ChatCompletionRequest>,
 ) -> Result<Response, AppError> {
-    route_chat(state, req, Some(provider)).await
+    Ok(route_chat(state, req, Some(provider), &client)
+        .await?
+        .respond(IntoResponse::into_response))
 }
 
 pub async fn amp_claude_messages(
-    State(_state): State<Arc<ProxyState>>,
-    Path(_provider): Path<String>,
-    Json(_body): Json<Value>,
-) -> Result<Response, AppError> {
-    Err(AppError::BadRequest(
-        "Amp Claude messages endpoint not yet implemented".to_string(),
-    ))
+    State(state): State<Arc<ProxyState>>,
+    Path(provider): Path<String>,
+    client: Client,
+    Json(body): Json<Value>,
+) -> Response {
+    route_claude(state, body, Some(provider), &client)
+        .await
+        .unwrap_or_else(|e| e.into_response_for(ApiFamily::Anthropic))
 }
 
 // ── Internal helpers ──────────────────────────────────────────────────────────
@@ -188,33 +435,25 @@ async fn public_catalog_models(state: &ProxyState) -> Vec<ModelInfo> {
     let now = chrono::Utc::now().timestamp();
     let mut models = Vec::new();
     let runtime_snapshot = state.current_runtime_snapshot().await;
+    let routes = state.config.read().await.model_routes.clone();
 
-    for public_model in [
-        PUBLIC_MODEL_OPUS_46,
-        PUBLIC_MODEL_OPUS_45,
-        PUBLIC_MODEL_SONNET_46,
-        PUBLIC_MODEL_SONNET_45,
-        PUBLIC_MODEL_HAIKU_45,
-        PUBLIC_MODEL_GPT_54,
-        PUBLIC_MODEL_GPT_53_CODEX,
-    ] {
-        let targets = public_route_targets(public_model);
+    for route in routes.iter().filter(|route| !route.hidden) {
         let mut available = false;
-        for target in targets {
-            for client_id in runtime_snapshot.available_clients_for_model(target.model) {
+        for target in route.targets.iter().filter(|target| target.weight > 0) {
+            for client_id in runtime_snapshot.available_clients_for_model(&target.model) {
                 let Some(provider) = runtime_snapshot.providers().iter().find(|provider| {
                     provider.client_id().eq_ignore_ascii_case(client_id)
                         && provider
                             .provider_type()
-                            .eq_ignore_ascii_case(target.provider)
+                            .eq_ignore_ascii_case(&target.provider)
                 }) else {
                     continue;
                 };
 
-                if runtime_snapshot.client_supports_model(provider.client_id(), target.model)
+                if runtime_snapshot.client_supports_model(provider.client_id(), &target.model)
                     && state
                         .model_registry
-                        .client_is_effectively_available(provider.client_id(), target.model)
+                        .client_is_effectively_available(provider.client_id(), &target.model)
                         .await
                 {
                     available = true;
@@ -228,7 +467,7 @@ async fn public_catalog_models(state: &ProxyState) -> Vec<ModelInfo> {
 
         if available {
             models.push(ModelInfo {
-                id: public_model.to_string(),
+                id: route.name.clone(),
                 object: "model".to_string(),
                 created: now,
                 owned_by: "rusuh".to_string(),
@@ -239,98 +478,19 @@ async fn public_catalog_models(state: &ProxyState) -> Vec<ModelInfo> {
     models
 }
 
-fn public_route_targets(model: &str) -> &'static [RouteTarget] {
-    match model {
-        PUBLIC_MODEL_OPUS_46 => &[RouteTarget {
-            provider: "github-copilot",
-            model: "claude-opus-4.6",
-        }],
-        PUBLIC_MODEL_OPUS_45 => &[RouteTarget {
-            provider: "github-copilot",
-            model: "claude-opus-4.5",
-        }],
-        PUBLIC_MODEL_SONNET_46 => &[
-            RouteTarget {
-                provider: "zed",
-                model: "claude-sonnet-4-6",
-            },
-            RouteTarget {
-                provider: "github-copilot",
-                model: "claude-sonnet-4.6",
-            },
-        ],
-        PUBLIC_MODEL_SONNET_45 => &[
-            RouteTarget {
-                provider: "kiro",
-                model: "kiro-claude-sonnet-4-5-agentic",
-            },
-            RouteTarget {
-                provider: "kiro",
-                model: "kiro-claude-sonnet-4-5",
-            },
-            RouteTarget {
-                provider: "zed",
-                model: "claude-sonnet-4-5",
-            },
-            RouteTarget {
-                provider: "github-copilot",
-                model: "claude-sonnet-4.5",
-            },
-        ],
-        PUBLIC_MODEL_HAIKU_45 => &[
-            RouteTarget {
-                provider: "kiro",
-                model: "kiro-claude-haiku-4-5-agentic",
-            },
-            RouteTarget {
-                provider: "kiro",
-                model: "kiro-claude-haiku-4-5",
-            },
-            RouteTarget {
-                provider: "github-copilot",
-                model: "claude-haiku-4.5",
-            },
-        ],
-        PUBLIC_MODEL_GPT_54 => &[
-            RouteTarget {
-                provider: "codex",
-                model: "gpt-5.4",
-            },
-            RouteTarget {
-                provider: "github-copilot",
-                model: "gpt-5.4",
-            },
-        ],
-        PUBLIC_MODEL_GPT_53_CODEX => &[
-            RouteTarget {
-                provider: "codex",
-                model: "gpt-5.3-codex",
-            },
-            RouteTarget {
-                provider: "github-copilot",
-                model: "gpt-5.3-codex",
-            },
-        ],
-        _ => &[],
-    }
-}
-
-fn reserved_public_route_targets(model: &str) -> &'static [RouteTarget] {
-    match model {
-        "claude-sonnet-4-5-thinking" => &[RouteTarget {
-            provider: "kiro",
-            model: "kiro-claude-sonnet-4-5-agentic",
-        }],
-        _ => &[],
+/// Whether `model` is a `prefix/model` id exposed by an API-key entry with a `prefix`.
+///
+/// Prefixed ids are an explicit opt-in by the operator, so they are routable on the
+/// public endpoint the same way a provider-pinned route is.
+async fn is_namespaced_key_model(state: &ProxyState, model: &str) -> bool {
+    if !model.contains('/') {
+        return false;
     }
-}
-
-fn is_reserved_public_model(model: &str) -> bool {
-    !reserved_public_route_targets(model).is_empty()
-}
-
-fn is_public_model(model: &str) -> bool {
-    !public_route_targets(model).is_empty() || is_reserved_public_model(model)
+    !state
+        .current_runtime_snapshot()
+        .await
+        .available_clients_for_model(model)
+        .is_empty()
 }
 
 /// Resolve model name through configured OAuth aliases only.
@@ -401,6 +561,7 @@ fn responses_body_to_chat_request(body: Value) -> Result<ChatCompletionRequest,
             name: None,
             tool_calls: None,
             tool_call_id: None,
+            reasoning_content: None,
         }],
         Value::Null => vec![ChatMessage {
             role: "user".to_string(),
@@ -408,6 +569,7 @@ fn responses_body_to_chat_request(body: Value) -> Result<ChatCompletionRequest,
             name: None,
             tool_calls: None,
             tool_call_id: None,
+            reasoning_content: None,
         }],
         Value::Array(items) => {
             let mut messages = Vec::with_capacity(items.len());
@@ -419,6 +581,7 @@ fn responses_body_to_chat_request(body: Value) -> Result<ChatCompletionRequest,
                         name: None,
                         tool_calls: None,
                         tool_call_id: None,
+                        reasoning_content: None,
                     }),
                     Value::Object(map) => {
                         let text = map
@@ -458,6 +621,7 @@ fn responses_body_to_chat_request(body: Value) -> Result<ChatCompletionRequest,
                                 .get("tool_call_id")
                                 .and_then(Value::as_str)
                                 .map(str::to_string),
+                            reasoning_content: None,
                         });
                     }
                     _ => {
@@ -501,72 +665,512 @@ fn responses_body_to_chat_request(body: Value) -> Result<ChatCompletionRequest,
     })
 }
 
+/// Route an Anthropic Messages body and shape the result back into Claude form.
+///
+/// Providers that speak Anthropic natively receive the original body untouched;
+/// everyone else gets the OpenAI translation.
+async fn route_claude(
+    state: Arc<ProxyState>,
+    body: Value,
+    provider_hint: Option<String>,
+    client: &Client,
+) -> Result<Response, AppError> {
+    let anthropic_body = body.clone();
+    let req = crate::proxy::claude::claude_body_to_chat_request(body)?;
+    let requested_model = req.model.clone();
+
+    let routed = route_request(state, req, provider_hint, client, Some(&anthropic_body)).await?;
+    Ok(routed.respond(|outcome| match outcome {
+        RouteOutcome::Anthropic(AnthropicResponse::Message(mut message)) => {
+            message["model"] = Value::String(requested_model);
+            Json(message).into_response()
+        }
+        RouteOutcome::Anthropic(AnthropicResponse::Stream(stream)) => {
+            crate::proxy::stream::sse_response(crate::proxy::claude::native_stream_with_model(
+                stream,
+                requested_model,
+            ))
+        }
+        RouteOutcome::Completion(response) => Json(crate::proxy::claude::chat_response_to_claude(
+            &response,
+            &requested_model,
+        ))
+        .into_response(),
+        RouteOutcome::Stream(stream) => crate::proxy::stream::sse_response(
+            crate::proxy::claude::claude_sse_stream(stream, requested_model),
+        ),
+    }))
+}
+
 async fn route_chat(
     state: Arc<ProxyState>,
     req: ChatCompletionRequest,
     provider_hint: Option<String>,
-) -> Result<Response, AppError> {
-    let is_stream = req.stream.unwrap_or(false);
+    client: &Client,
+) -> Result<Routed, AppError> {
+    route_request(state, req, provider_hint, client, None).await
+}
+
+/// Route `req`; `anthropic_body` is the caller's original Messages body, handed to
+/// providers that accept it natively instead of the translated request.
+///
+/// Public models whose targets are all exhausted or unavailable hand over to their
+/// route's fallback chain unless the caller opted out in its headers.
+///
+/// A client key is checked against its scope, rate limits and daily budget before
+/// anything is routed; the tokens a reply used are charged once known.
+///
+/// Every request ends up in the usage ledger: failures right away, replies once
+/// their token usage is known.
+#[tracing::instrument(
+    name = "route",
+    skip_all,
+    fields(
+        model = %req.model,
+        served_model = tracing::field::Empty,
+        client_id = tracing::field::Empty,
+        otel.status_code = tracing::field::Empty,
+    )
+)]
+async fn route_request(
+    state: Arc<ProxyState>,
+    req: ChatCompletionRequest,
+    provider_hint: Option<String>,
+    client: &Client,
+    anthropic_body: Option<&Value>,
+) -> Result<Routed, AppError> {
+    let started = Instant::now();
+    let requested_model = req.model.clone();
 
     // Resolve aliases early so dotted models like "claude-sonnet-4.6" match public routes
     let mut req = req;
     let resolved_model = {
         let config = state.config.read().await;
-        resolve_oauth_model_alias(&config, &req.model)
+        let span = tracing::info_span!(
+            "resolve_alias",
+            model = %req.model,
+            resolved = tracing::field::Empty,
+        );
+        let resolved = span.in_scope(|| resolve_oauth_model_alias(&config, &req.model));
+        span.record("resolved", resolved.as_str());
+        resolved
     };
     req.model = resolved_model;
+    let session = request_session(&state, &req, &client.headers, anthropic_body).await;
+    let caller = Caller {
+        session,
+        key: client.key.as_ref(),
+    };
 
-    if provider_hint.is_none() {
-        let public_targets = public_route_targets(&req.model);
-        if !public_targets.is_empty() {
-            return try_route_with_targets(state, &req, public_targets, is_stream).await;
+    let key_label = caller.key.map(|key| key.label().to_string());
+    let key_id = caller.key.map(ApiKeyEntry::id);
+    let routed = match admit_key(&state, &caller, &requested_model, &req, &provider_hint).await {
+        Ok(charge) => dispatch(
+            state.clone(),
+            &req,
+            provider_hint,
+            client,
+            &caller,
+            anthropic_body,
+        )
+        .await
+        .map(|routed| (routed, charge)),
+        Err(e) => Err(e),
+    };
+    let (routed, charge) = match routed {
+        Ok(routed) => routed,
+        Err(e) => {
+            tracing::Span::current().record("otel.status_code", "error");
+            let status = e.status().as_u16();
+            let model = metric_model_label(&state, &req.model).await;
+            state
+                .metrics
+                .record_request(&client.route, model, "", status, started.elapsed());
+            state.usage_ledger.record(UsageRecord {
+                at: chrono::Utc::now(),
+                key: key_label,
+                key_id,
+                client_id: None,
+                provider: None,
+                model: req.model,
+                prompt_tokens: 0,
+                completion_tokens: 0,
+                cached_tokens: 0,
+                latency_ms: elapsed_ms(started),
+                status,
+            });
+            return Err(e);
         }
-        let reserved_targets = reserved_public_route_targets(&req.model);
-        if !reserved_targets.is_empty() {
-            return try_route_with_targets(state, &req, reserved_targets, is_stream).await;
+    };
+
+    let client_id = routed.served.client_id.clone();
+    let provider = routed.served.provider.clone();
+    let model = routed.served_model.clone();
+    let route = client.route.clone();
+    tracing::Span::current()
+        .record("served_model", model.as_str())
+        .record("client_id", client_id.as_str());
+    // Streams are held until their first content, so they arrive here at first token.
+    let is_stream = routed.served.outcome.is_stream();
+    if is_stream {
+        state
+            .metrics
+            .record_first_token(&provider, &model, started.elapsed());
+    }
+    Ok(routed.observe_usage(move |usage, end| {
+        if let Some(charge) = charge {
+            charge.settle(&state, usage.total());
         }
+        let elapsed = started.elapsed();
+        if is_stream {
+            state.metrics.record_stream(&provider, &model, elapsed);
+        }
+        let status = end.status();
+        state
+            .metrics
+            .record_request(&route, &model, &provider, status, elapsed);
+        state.metrics.record_tokens(&provider, &model, usage);
+        state.usage_ledger.record(UsageRecord {
+            at: chrono::Utc::now(),
+            key: key_label,
+            key_id,
+            client_id: Some(client_id),
+            provider: Some(provider),
+            model,
+            prompt_tokens: usage.prompt,
+            completion_tokens: usage.completion,
+            cached_tokens: usage.cached,
+            latency_ms: elapsed_ms(started),
+            status,
+        });
+    }))
+}
+
+/// Model label for a request that was not served: the model when it is a public
+/// route or served by some account, so made-up names cannot add metric series.
+async fn metric_model_label<'a>(state: &ProxyState, model: &'a str) -> &'a str {
+    let serving = state.model_registry.get_model_providers(model).await;
+    if serving.is_empty() && state.config.read().await.model_route(model).is_none() {
+        "unknown"
+    } else {
+        model
+    }
+}
+
+fn elapsed_ms(started: Instant) -> u64 {
+    u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX)
+}
+
+/// What an admitted client key still owes once its reply's token usage is known.
+struct KeyCharge {
+    key: String,
+    limits: KeyRateLimit,
+    /// Released when the charge is settled, i.e. when the stream ends
+    stream_permit: Option<StreamPermit>,
+}
+
+impl KeyCharge {
+    fn settle(self, state: &ProxyState, tokens: u64) {
+        drop(self.stream_permit);
+        state
+            .key_rate_limiter
+            .record_tokens(&self.key, self.limits, tokens, Instant::now());
+        state
+            .key_budgets
+            .record_tokens(&self.key, tokens, chrono::Utc::now());
+    }
+}
+
+/// Check the caller's key against its scope, rate limits and daily budget.
+async fn admit_key(
+    state: &ProxyState,
+    caller: &Caller<'_>,
+    requested_model: &str,
+    req: &ChatCompletionRequest,
+    provider_hint: &Option<String>,
+) -> Result<Option<KeyCharge>, AppError> {
+    let Some(key) = caller.key else {
+        return Ok(None);
+    };
+    check_key_scope(key, requested_model, req, provider_hint.as_deref())?;
+    let limits = key
+        .scope
+        .rate_limit
+        .or(state.config.read().await.key_rate_limit);
+    let is_stream = req.stream.unwrap_or(false);
+    // A request the daily budget refuses must not use up a per-minute slot. The
+    // budget can run out between the check and the count when requests race, so
+    // the slot is given back then; dropping the permit frees the stream slot.
+    let utc_now = chrono::Utc::now();
+    state.key_budgets.check(key, utc_now)?;
+    let now = Instant::now();
+    let stream_permit = state
+        .key_rate_limiter
+        .admit(&key.key, limits, is_stream, now)?;
+    if let Err(e) = state.key_budgets.admit(key, utc_now) {
+        state.key_rate_limiter.refund(&key.key, limits);
+        return Err(e);
+    }
+    Ok(Some(KeyCharge {
+        key: key.key.clone(),
+        limits,
+        stream_permit,
+    }))
+}
 
-        if !is_public_model(&req.model) {
+/// Route an admitted request to its public route or to the accounts serving the model.
+async fn dispatch(
+    state: Arc<ProxyState>,
+    req: &ChatCompletionRequest,
+    provider_hint: Option<String>,
+    client: &Client,
+    caller: &Caller<'_>,
+    anthropic_body: Option<&Value>,
+) -> Result<Routed, AppError> {
+    let is_stream = req.stream.unwrap_or(false);
+    let public_route = match provider_hint {
+        Some(_) => None,
+        None => state.config.read().await.model_route(&req.model).cloned(),
+    };
+    if let Some(route) = public_route {
+        route_with_fallback(
+            state,
+            req,
+            route,
+            fallback_allowed(&client.headers),
+            caller,
+            is_stream,
+            anthropic_body,
+        )
+        .await
+    } else {
+        if provider_hint.is_none() && !is_namespaced_key_model(&state, &req.model).await {
             return Err(AppError::BadRequest(format!(
                 "Model '{}' is not available on the public endpoint. Use one of the curated public models or a provider-pinned route.",
                 req.model
             )));
         }
+        let served = try_route_with_model(
+            state,
+            req,
+            &provider_hint,
+            caller,
+            is_stream,
+            anthropic_body,
+        )
+        .await?;
+        Ok(Routed {
+            served,
+            served_model: req.model.clone(),
+        })
     }
+}
 
-    try_route_with_model(state, &req, &provider_hint, is_stream).await
+/// Refuse a request that reaches outside its client key's scope.
+fn check_key_scope(
+    key: &ApiKeyEntry,
+    requested_model: &str,
+    req: &ChatCompletionRequest,
+    provider_hint: Option<&str>,
+) -> Result<(), AppError> {
+    if !key.allows_model(requested_model) && !key.allows_model(&req.model) {
+        return Err(AppError::Forbidden(format!(
+            "API key '{}' may not use model '{requested_model}'",
+            key.label()
+        )));
+    }
+    if let Some(provider) = provider_hint.filter(|provider| !key.allows_provider(provider)) {
+        return Err(AppError::Forbidden(format!(
+            "API key '{}' may not use provider '{provider}'",
+            key.label()
+        )));
+    }
+    if let Some(auth_id) = requested_auth_id(req).filter(|auth_id| !key.allows_auth_id(auth_id)) {
+        return Err(AppError::Forbidden(format!(
+            "API key '{}' may not select account '{auth_id}'",
+            key.label()
+        )));
+    }
+    Ok(())
 }
 
-/// Internal helper: attempt routing with a specific model name.
-async fn try_route_with_model(
-    state: Arc<ProxyState>,
+/// Who a request is routed for.
+#[derive(Default)]
+struct Caller<'a> {
+    session: Option<Session>,
+    /// Client key whose scope narrows the candidate accounts
+    key: Option<&'a ApiKeyEntry>,
+}
+
+impl Caller<'_> {
+    fn session_key(&self) -> Option<&str> {
+        self.session.as_ref().map(|session| session.key.as_str())
+    }
+
+    fn allows_model(&self, model: &str) -> bool {
+        self.key.is_none_or(|key| key.allows_model(model))
+    }
+
+    fn allows_provider(&self, provider_type: &str) -> bool {
+        self.key
+            .is_none_or(|key| key.allows_provider(provider_type))
+    }
+
+    fn allows_account(&self, provider: &dyn Provider) -> bool {
+        self.key.is_none_or(|key| {
+            key.allows_provider(provider.provider_type())
+                && key.allows_auth_id(provider.client_id())
+        })
+    }
+}
+
+/// Sticky-session key of a request.
+struct Session {
+    key: String,
+    /// Sent as `execution_session_id`, which pins the account outright; derived keys
+    /// only prefer it.
+    explicit: bool,
+}
+
+/// The caller's `execution_session_id`, or a key derived from the configured
+/// session-affinity sources.
+async fn request_session(
+    state: &ProxyState,
     req: &ChatCompletionRequest,
-    provider_hint: &Option<String>,
-    is_stream: bool,
-) -> Result<Response, AppError> {
-    let request_selected_auth_id = req
+    headers: &HeaderMap,
+    anthropic_body: Option<&Value>,
+) -> Option<Session> {
+    if let Some(key) = req
         .extra
-        .get("selected_auth_id")
+        .get("execution_session_id")
         .and_then(Value::as_str)
         .map(str::trim)
         .filter(|value| !value.is_empty())
-        .map(ToString::to_string);
+    {
+        return Some(Session {
+            key: key.to_string(),
+            explicit: true,
+        });
+    }
 
-    let execution_session_id = req
-        .extra
-        .get("execution_session_id")
+    let metadata = anthropic_body
+        .and_then(|body| body.get("metadata"))
+        .or_else(|| req.extra.get("metadata"));
+    let config = state.config.read().await;
+    derive_session_key(
+        &config.routing.session_affinity,
+        headers,
+        &req.model,
+        &req.messages,
+        metadata,
+    )
+    .map(|key| Session {
+        key,
+        explicit: false,
+    })
+}
+
+/// The account the request names with `selected_auth_id`.
+fn requested_auth_id(req: &ChatCompletionRequest) -> Option<&str> {
+    req.extra
+        .get("selected_auth_id")
         .and_then(Value::as_str)
         .map(str::trim)
         .filter(|value| !value.is_empty())
-        .map(ToString::to_string);
+}
 
-    let effective_selected_auth_id = if let Some(selected_auth_id) = request_selected_auth_id {
-        Some(selected_auth_id)
-    } else if let Some(session_id) = execution_session_id.as_ref() {
-        state.execution_sessions.get_selected_auth(session_id).await
+/// The account a session is pinned to, unless the request named one itself.
+///
+/// A pin to an account outside the caller's key scope is ignored.
+async fn selected_auth_id(
+    state: &ProxyState,
+    req: &ChatCompletionRequest,
+    caller: &Caller<'_>,
+) -> Option<String> {
+    match (requested_auth_id(req), &caller.session) {
+        (Some(selected_auth_id), _) => Some(selected_auth_id.to_string()),
+        (None, Some(session)) if session.explicit => state
+            .execution_sessions
+            .get_selected_auth(&session.key)
+            .await
+            .filter(|auth_id| caller.key.is_none_or(|key| key.allows_auth_id(auth_id))),
+        _ => None,
+    }
+}
+
+/// Serve `req` from `route`, then from each model in its fallback chain in turn
+/// while the previous one is exhausted or unavailable. Chains are not followed
+/// transitively: only the requested route's list is consulted.
+async fn route_with_fallback(
+    state: Arc<ProxyState>,
+    req: &ChatCompletionRequest,
+    route: ModelRoute,
+    allow_fallback: bool,
+    caller: &Caller<'_>,
+    is_stream: bool,
+    anthropic_body: Option<&Value>,
+) -> Result<Routed, AppError> {
+    let fallback = if allow_fallback {
+        route.fallback.clone()
     } else {
-        None
+        Vec::new()
     };
+    let mut fallback = fallback.into_iter();
+    let mut current = route;
+    loop {
+        let error = match try_route_with_targets(
+            state.clone(),
+            req,
+            &current.targets,
+            current.hedge_after(),
+            caller,
+            is_stream,
+            anthropic_body,
+        )
+        .await
+        {
+            Ok(served) => {
+                return Ok(Routed {
+                    served,
+                    served_model: current.name,
+                })
+            }
+            Err(e) if e.is_quota_or_unavailable() => e,
+            Err(e) => return Err(e),
+        };
+
+        let mut next = None;
+        for name in fallback.by_ref() {
+            if !caller.allows_model(&name) {
+                continue;
+            }
+            next = state.config.read().await.model_route(&name).cloned();
+            if next.is_some() {
+                break;
+            }
+        }
+        let Some(next) = next else {
+            return Err(error);
+        };
+        tracing::warn!(
+            "model {} unavailable ({error}), falling back to {}",
+            current.name,
+            next.name
+        );
+        current = next;
+    }
+}
+
+/// Internal helper: attempt routing with a specific model name.
+async fn try_route_with_model(
+    state: Arc<ProxyState>,
+    req: &ChatCompletionRequest,
+    provider_hint: &Option<String>,
+    caller: &Caller<'_>,
+    is_stream: bool,
+    anthropic_body: Option<&Value>,
+) -> Result<Served, AppError> {
+    let effective_selected_auth_id = selected_auth_id(&state, req, caller).await;
 
     let runtime_snapshot = state.current_runtime_snapshot().await;
     let candidates = resolve_candidates_for_model(
@@ -575,6 +1179,7 @@ async fn try_route_with_model(
         &req.model,
         provider_hint,
         effective_selected_auth_id.as_deref(),
+        caller,
     )
     .await;
     execute_candidates(
@@ -583,7 +1188,9 @@ async fn try_route_with_model(
         req,
         candidates,
         is_stream,
-        execution_session_id.as_deref(),
+        caller.session_key(),
+        anthropic_body,
+        None,
     )
     .await
 }
@@ -591,45 +1198,44 @@ async fn try_route_with_model(
 async fn try_route_with_targets(
     state: Arc<ProxyState>,
     req: &ChatCompletionRequest,
-    targets: &[RouteTarget],
+    targets: &[ModelRouteTarget],
+    hedge_after: Option<Duration>,
+    caller: &Caller<'_>,
     is_stream: bool,
-) -> Result<Response, AppError> {
+    anthropic_body: Option<&Value>,
+) -> Result<Served, AppError> {
     let mut last_error = None;
-    let execution_session_id = req
-        .extra
-        .get("execution_session_id")
-        .and_then(Value::as_str)
-        .map(str::trim)
-        .filter(|value| !value.is_empty())
-        .map(ToString::to_string);
 
-    for target in targets {
+    let targets: Vec<ModelRouteTarget> = targets
+        .iter()
+        .filter(|target| caller.allows_provider(&target.provider))
+        .cloned()
+        .collect();
+    if targets.is_empty() {
+        if let Some(key) = caller.key {
+            return Err(AppError::Forbidden(format!(
+                "API key '{}' may not use any provider serving model '{}'",
+                key.label(),
+                req.model
+            )));
+        }
+    }
+
+    let strategy = state.current_runtime_snapshot().await.balancer().strategy();
+    for target in ordered_route_targets(&targets, strategy) {
         let mut upstream_req = req.clone();
-        upstream_req.model = target.model.to_string();
-        let provider_hint = Some(target.provider.to_string());
-        let request_selected_auth_id = req
-            .extra
-            .get("selected_auth_id")
-            .and_then(Value::as_str)
-            .map(str::trim)
-            .filter(|value| !value.is_empty())
-            .map(ToString::to_string);
-
-        let effective_selected_auth_id = if let Some(selected_auth_id) = request_selected_auth_id {
-            Some(selected_auth_id)
-        } else if let Some(session_id) = execution_session_id.as_ref() {
-            state.execution_sessions.get_selected_auth(session_id).await
-        } else {
-            None
-        };
+        upstream_req.model = target.model.clone();
+        let provider_hint = Some(target.provider.clone());
+        let effective_selected_auth_id = selected_auth_id(&state, req, caller).await;
 
         let runtime_snapshot = state.current_runtime_snapshot().await;
         let candidates = resolve_candidates_for_model(
             &state,
             runtime_snapshot.clone(),
-            target.model,
+            &target.model,
             &provider_hint,
             effective_selected_auth_id.as_deref(),
+            caller,
         )
         .await;
 
@@ -647,7 +1253,9 @@ async fn try_route_with_targets(
             &upstream_req,
             candidates,
             is_stream,
-            execution_session_id.as_deref(),
+            caller.session_key(),
+            anthropic_body,
+            hedge_after,
         )
         .await
         {
@@ -668,12 +1276,38 @@ async fn try_route_with_targets(
     }))
 }
 
+/// Targets in try order, without those weighted out of rotation.
+///
+/// The `weighted` strategy draws the first target by weight and keeps the
+/// configured order for fallbacks; other strategies use the configured order.
+fn ordered_route_targets(
+    targets: &[ModelRouteTarget],
+    strategy: Strategy,
+) -> Vec<&ModelRouteTarget> {
+    let mut ordered: Vec<&ModelRouteTarget> =
+        targets.iter().filter(|target| target.weight > 0).collect();
+    if strategy == Strategy::Weighted {
+        let weights: Vec<u32> = ordered.iter().map(|target| target.weight).collect();
+        if let Some(first) = weighted_index(&weights, rand::random()) {
+            let target = ordered.remove(first);
+            ordered.insert(0, target);
+        }
+    }
+    ordered
+}
+
+#[tracing::instrument(
+    name = "resolve_candidates",
+    skip_all,
+    fields(model = model_id, provider = provider_hint.as_deref(), candidates = tracing::field::Empty)
+)]
 async fn resolve_candidates_for_model(
     state: &ProxyState,
     runtime_snapshot: Arc<crate::proxy::RuntimeSnapshot>,
     model_id: &str,
     provider_hint: &Option<String>,
     selected_auth_id: Option<&str>,
+    caller: &Caller<'_>,
 ) -> Vec<Arc<dyn Provider>> {
     let model_providers = runtime_snapshot.model_providers(model_id);
     let providers = runtime_snapshot.providers().to_vec();
@@ -696,6 +1330,10 @@ async fn resolve_candidates_for_model(
             continue;
         }
 
+        if !caller.allows_account(provider.as_ref()) {
+            continue;
+        }
+
         let client_id = provider.client_id();
         if let Some(selected) = selected_auth_id {
             if !client_id.eq_ignore_ascii_case(selected) {
@@ -703,6 +1341,13 @@ async fn resolve_candidates_for_model(
             }
         }
 
+        if !state
+            .circuit_breaker
+            .is_available(client_id, model_id, Instant::now())
+        {
+            continue;
+        }
+
         if state
             .model_registry
             .client_is_effectively_available(client_id, model_id)
@@ -712,9 +1357,15 @@ async fn resolve_candidates_for_model(
         }
     }
 
+    tracing::Span::current().record("candidates", available_candidates.len() as i64);
     available_candidates
 }
 
+/// Try `candidates` for `req`, starting from the balancer's pick.
+///
+/// With `hedge_after`, a first call that has not answered in time is raced against
+/// the next candidate.
+#[allow(clippy::too_many_arguments)]
 async fn execute_candidates(
     state: Arc<ProxyState>,
     runtime_snapshot: Arc<crate::proxy::RuntimeSnapshot>,
@@ -722,7 +1373,9 @@ async fn execute_candidates(
     candidates: Vec<Arc<dyn Provider>>,
     is_stream: bool,
     execution_session_id: Option<&str>,
-) -> Result<Response, AppError> {
+    anthropic_body: Option<&Value>,
+    hedge_after: Option<Duration>,
+) -> Result<Served, AppError> {
     if candidates.is_empty() {
         return Err(AppError::QuotaExceeded(format!(
             "All providers for model '{}' are currently unavailable (quota exceeded or suspended)",
@@ -734,20 +1387,118 @@ async fn execute_candidates(
         let config = state.config.read().await;
         config.request_retry.max(1) as usize
     };
-    let candidate_indices: Vec<usize> = (0..candidates.len()).collect();
-    let start_idx = runtime_snapshot.balancer().pick(&candidate_indices);
+    // Balancer slots are snapshot positions, so per-account stats survive across requests.
+    let candidate_indices: Vec<usize> = candidates
+        .iter()
+        .map(|provider| {
+            runtime_snapshot
+                .provider_index(provider)
+                .unwrap_or(usize::MAX)
+        })
+        .collect();
+    let balancer = runtime_snapshot.balancer();
+    // A session goes back to its last account while that account is still a candidate.
+    let sticky_idx = match execution_session_id {
+        Some(session_id) => state
+            .execution_sessions
+            .get_selected_auth(session_id)
+            .await
+            .and_then(|auth_id| {
+                candidates
+                    .iter()
+                    .position(|provider| provider.client_id().eq_ignore_ascii_case(&auth_id))
+            })
+            .map(|pos| candidate_indices[pos]),
+        None => None,
+    };
+    let start_idx = balancer.pick_preferring(&candidate_indices, sticky_idx);
     let start_pos = candidate_indices
         .iter()
         .position(|&candidate_index| candidate_index == start_idx)
         .unwrap_or(0);
-    let ordered: Vec<Arc<dyn Provider>> = (0..candidates.len())
-        .map(|offset| candidates[(start_pos + offset) % candidates.len()].clone())
+    let ordered: Vec<(usize, Arc<dyn Provider>)> = (0..candidates.len())
+        .map(|offset| {
+            let pos = (start_pos + offset) % candidates.len();
+            (candidate_indices[pos], candidates[pos].clone())
+        })
         .collect();
     let mut last_error = None;
+    let mut called_any = false;
+    let mut failed_hedge = None;
+    let reasoning = crate::providers::thinking::requested_reasoning(req);
+
+    // Only the first call of a request is hedged, against the next candidate in line.
+    let mut hedge = match (hedge_after, ordered.get(1)) {
+        (Some(delay), Some((slot, provider))) => {
+            let supported = match &reasoning {
+                Some(reasoning) => {
+                    let support = state
+                        .model_registry
+                        .thinking_support(&req.model, provider.provider_type())
+                        .await;
+                    crate::providers::thinking::validate(reasoning, support.as_ref(), &req.model)
+                        .is_ok()
+                }
+                None => true,
+            };
+            supported.then(|| (delay, *slot, provider.clone()))
+        }
+        _ => None,
+    };
+
+    for (slot, provider) in ordered {
+        // The hedge already had its call; going back to it would just repeat the failure.
+        if failed_hedge == Some(slot) {
+            continue;
+        }
+        if let Some(reasoning) = &reasoning {
+            let support = state
+                .model_registry
+                .thinking_support(&req.model, provider.provider_type())
+                .await;
+            if let Err(e) =
+                crate::providers::thinking::validate(reasoning, support.as_ref(), &req.model)
+            {
+                tracing::warn!(
+                    "provider {} rejected reasoning request: {e}",
+                    provider.name()
+                );
+                last_error = Some(e);
+                continue;
+            }
+        }
 
-    for provider in ordered {
         for attempt in 0..max_retries {
+            if !state
+                .circuit_breaker
+                .allow(provider.client_id(), &req.model, Instant::now())
+            {
+                tracing::warn!(
+                    "provider {} skipped: circuit open for {}",
+                    provider.name(),
+                    req.model
+                );
+                last_error.get_or_insert_with(|| {
+                    UpstreamError::new(
+                        UpstreamErrorKind::Server,
+                        format!(
+                            "circuit open for provider {} and model '{}'",
+                            provider.name(),
+                            req.model
+                        ),
+                    )
+                    .with_provider(provider.name())
+                    .into()
+                });
+                break;
+            }
+            if attempt == 0 && std::mem::replace(&mut called_any, true) {
+                state.metrics.record_failover(&req.model);
+            }
             if attempt > 0 {
+                state
+                    .metrics
+                    .record_retry(provider.provider_type(), &req.model);
                 let delay = std::time::Duration::from_millis(100 << (attempt - 1).min(4));
                 tracing::info!(
                     "retry {}/{} for provider {} (backoff {}ms)",
@@ -759,20 +1510,77 @@ async fn execute_candidates(
                 tokio::time::sleep(delay).await;
             }
 
-            let result = if is_stream {
-                provider
-                    .chat_completion_stream(req)
-                    .await
-                    .map(crate::proxy::stream::sse_response)
-            } else {
-                provider
-                    .chat_completion(req)
+            let call = call_candidate(
+                balancer,
+                slot,
+                provider.clone(),
+                req,
+                is_stream,
+                anthropic_body,
+            );
+            let (call, lost) = match hedge
+                .take()
+                .filter(|(_, hedge_slot, _)| *hedge_slot != slot)
+            {
+                Some((delay, hedge_slot, hedge_provider)) => {
+                    race_with_hedge(call, delay, || {
+                        if !state.circuit_breaker.allow(
+                            hedge_provider.client_id(),
+                            &req.model,
+                            Instant::now(),
+                        ) {
+                            return None;
+                        }
+                        tracing::info!(
+                            "hedging {} on provider {} after {}ms",
+                            req.model,
+                            hedge_provider.name(),
+                            delay.as_millis()
+                        );
+                        balancer.record_hedge(hedge_slot);
+                        Some(call_candidate(
+                            balancer,
+                            hedge_slot,
+                            hedge_provider,
+                            req,
+                            is_stream,
+                            anthropic_body,
+                        ))
+                    })
                     .await
-                    .map(|r| Json(r).into_response())
+                }
+                None => (call.await, None),
             };
+            if let Some(Call {
+                slot: lost_slot,
+                provider: lost_provider,
+                result: Err(e),
+                ..
+            }) = &lost
+            {
+                tracing::warn!(
+                    "provider {} failed in hedged race: {e}",
+                    lost_provider.name()
+                );
+                record_failure(&state, lost_provider.as_ref(), &req.model, e).await;
+                if *lost_slot != slot {
+                    failed_hedge = Some(*lost_slot);
+                }
+            }
+            if call.slot != slot {
+                balancer.record_hedge_win(call.slot);
+            }
 
-            match result {
+            match call.result {
                 Ok(resp) => {
+                    let provider = call.provider;
+                    state
+                        .circuit_breaker
+                        .record_success(provider.client_id(), &req.model);
+                    let resp = match call.in_flight {
+                        Some(in_flight) => resp.track(in_flight, call.started),
+                        None => resp,
+                    };
                     if let Some(session_id) = execution_session_id {
                         let selected_auth_id = provider.client_id().to_string();
                         let selected_auth_is_active = state
@@ -792,9 +1600,14 @@ async fn execute_candidates(
                             )
                             .await;
                     }
-                    return Ok(resp);
+                    return Ok(Served {
+                        outcome: resp,
+                        client_id: provider.client_id().to_string(),
+                        provider: provider.provider_type().to_string(),
+                    });
                 }
                 Err(e) => {
+                    let circuit = record_failure(&state, provider.as_ref(), &req.model, &e).await;
                     if e.is_account_error() {
                         tracing::warn!(
                             "provider {} account error (skipping): {e}",
@@ -803,7 +1616,10 @@ async fn execute_candidates(
                         last_error = Some(e);
                         break;
                     }
-                    if e.is_transient() && attempt + 1 < max_retries {
+                    if e.is_transient()
+                        && circuit != CircuitState::Open
+                        && attempt + 1 < max_retries
+                    {
                         tracing::warn!(
                             "provider {} transient error (attempt {}/{}): {e}",
                             provider.name(),
@@ -829,6 +1645,172 @@ async fn execute_candidates(
     }))
 }
 
+/// A finished call to one candidate, with what is needed to report on it.
+struct Call {
+    slot: usize,
+    provider: Arc<dyn Provider>,
+    in_flight: Option<InFlight>,
+    started: Instant,
+    result: Result<RouteOutcome, AppError>,
+}
+
+/// Call `provider` once.
+///
+/// Streams are held until their first content so an empty or broken stream still
+/// fails over like any other error.
+#[tracing::instrument(
+    name = "provider_attempt",
+    skip_all,
+    fields(
+        client_id = provider.client_id(),
+        provider = provider.provider_type(),
+        model = %req.model,
+        error = tracing::field::Empty,
+        otel.status_code = tracing::field::Empty,
+    )
+)]
+async fn call_candidate(
+    balancer: &Balancer,
+    slot: usize,
+    provider: Arc<dyn Provider>,
+    req: &ChatCompletionRequest,
+    is_stream: bool,
+    anthropic_body: Option<&Value>,
+) -> Call {
+    let in_flight = balancer.begin(slot);
+    let started = Instant::now();
+    let native_body = anthropic_body.filter(|_| provider.supports_anthropic_messages(&req.model));
+    let result = if let Some(body) = native_body {
+        let mut body = body.clone();
+        body["model"] = Value::String(req.model.clone());
+        async {
+            match provider.anthropic_messages(&body).await? {
+                AnthropicResponse::Stream(stream) => {
+                    hold_until_content(stream, ApiFamily::Anthropic)
+                        .await
+                        .map(AnthropicResponse::Stream)
+                }
+                message => Ok(message),
+            }
+        }
+        .await
+        .map(RouteOutcome::Anthropic)
+    } else if is_stream {
+        async {
+            let stream = provider.chat_completion_stream(req).await?;
+            hold_until_content(stream, ApiFamily::OpenAi).await
+        }
+        .await
+        .map(RouteOutcome::Stream)
+    } else {
+        provider
+            .chat_completion(req)
+            .await
+            .map(RouteOutcome::Completion)
+    };
+    if let Err(e) = &result {
+        tracing::Span::current()
+            .record("error", tracing::field::display(e))
+            .record("otel.status_code", "error");
+    }
+
+    Call {
+        slot,
+        provider,
+        in_flight,
+        started,
+        result,
+    }
+}
+
+/// Await `primary`, launching the call from `hedge` alongside it once `delay` passes
+/// without an answer.
+///
+/// Resolves to the first call that succeeds, or to the primary's failure when none
+/// does; the other call comes back only if it also finished with an error. A call
+/// still running when the race is decided is dropped, which cancels it.
+async fn race_with_hedge<P, H>(
+    primary: P,
+    delay: Duration,
+    hedge: impl FnOnce() -> Option<H>,
+) -> (Call, Option<Call>)
+where
+    P: Future<Output = Call>,
+    H: Future<Output = Call>,
+{
+    tokio::pin!(primary);
+    let hedge = tokio::select! {
+        call = &mut primary => return (call, None),
+        _ = tokio::time::sleep(delay) => match hedge() {
+            Some(hedge) => hedge,
+            None => return (primary.await, None),
+        },
+    };
+    tokio::pin!(hedge);
+
+    tokio::select! {
+        call = &mut primary => {
+            if call.result.is_ok() {
+                return (call, None);
+            }
+            let hedged = hedge.await;
+            if hedged.result.is_ok() {
+                (hedged, Some(call))
+            } else {
+                (call, Some(hedged))
+            }
+        }
+        hedged = &mut hedge => {
+            if hedged.result.is_ok() {
+                return (hedged, None);
+            }
+            (primary.await, Some(hedged))
+        }
+    }
+}
+
+/// Record a failed call against its account and return the account's circuit state.
+///
+/// A rate-limited account stays out until the upstream says the limit resets, or for
+/// the registry's default window; transient failures count towards its circuit.
+async fn record_failure(
+    state: &ProxyState,
+    provider: &dyn Provider,
+    model: &str,
+    error: &AppError,
+) -> CircuitState {
+    if error.upstream_kind() == Some(UpstreamErrorKind::RateLimited) {
+        match error.retry_after() {
+            Some(reset_in) => {
+                state
+                    .model_registry
+                    .set_quota_exceeded_until(
+                        provider.client_id(),
+                        model,
+                        Instant::now() + reset_in,
+                    )
+                    .await
+            }
+            None => {
+                state
+                    .model_registry
+                    .set_quota_exceeded(provider.client_id(), model)
+                    .await
+            }
+        }
+    }
+    if error.is_transient() {
+        state.circuit_breaker.record_failure(
+            provider.client_id(),
+            model,
+            &error.to_string(),
+            Instant::now(),
+        )
+    } else {
+        CircuitState::Closed
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
@@ -836,12 +1818,13 @@ mod tests {
 
     use async_trait::async_trait;
     use axum::body::to_bytes;
+    use axum::response::IntoResponse;
     use serde_json::{json, Value};
     use tokio::sync::Mutex;
 
     use super::{
         execute_candidates, resolve_candidates_for_model, responses_body_to_chat_request,
-        try_route_with_model, MessageContent,
+        try_route_with_model, Caller, MessageContent,
     };
     use crate::auth::manager::AccountManager;
     use crate::config::Config;
@@ -1010,6 +1993,7 @@ mod tests {
                         name: None,
                         tool_calls: None,
                         tool_call_id: None,
+                        reasoning_content: None,
                     }),
                     delta: None,
                     finish_reason: Some("stop".to_string()),
@@ -1076,6 +2060,7 @@ mod tests {
                 name: None,
                 tool_calls: None,
                 tool_call_id: None,
+                reasoning_content: None,
             }],
             stream: None,
             max_tokens: None,
@@ -1146,6 +2131,7 @@ mod tests {
             model_id,
             &Some("codex".to_string()),
             Some("auth-first"),
+            &Caller::default(),
         )
         .await;
         assert_eq!(candidates.len(), 1);
@@ -1169,10 +2155,12 @@ mod tests {
             candidates,
             false,
             None,
+            None,
+            None,
         )
         .await
         .expect("resolved candidate should still execute against the originally selected auth");
-        let body = to_bytes(response.into_body(), 1024 * 1024)
+        let body = to_bytes(response.into_response().into_body(), 1024 * 1024)
             .await
             .expect("response body should be readable");
         let json: serde_json::Value =
@@ -1218,10 +2206,17 @@ mod tests {
 
         let provider_hint = Some("codex".to_string());
         let request = test_request_with_selected_auth(model_id, "auth-first");
-        let response = try_route_with_model(state.clone(), &request, &provider_hint, false)
-            .await
-            .expect("routing should continue to use the published runtime snapshot");
-        let body = to_bytes(response.into_body(), 1024 * 1024)
+        let response = try_route_with_model(
+            state.clone(),
+            &request,
+            &provider_hint,
+            &Caller::default(),
+            false,
+            None,
+        )
+        .await
+        .expect("routing should continue to use the published runtime snapshot");
+        let body = to_bytes(response.into_response().into_body(), 1024 * 1024)
             .await
             .expect("response body should be readable");
         let json: serde_json::Value =
@@ -1280,6 +2275,7 @@ mod tests {
             model_id,
             &Some("codex".to_string()),
             None,
+            &Caller::default(),
         )
         .await;
         assert_eq!(candidates.len(), 2);
@@ -1318,10 +2314,12 @@ mod tests {
             candidates,
             false,
             None,
+            None,
+            None,
         )
             .await
             .expect("execution should keep using the resolved candidate order");
-        let body = to_bytes(response.into_body(), 1024 * 1024)
+        let body = to_bytes(response.into_response().into_body(), 1024 * 1024)
             .await
             .expect("response body should be readable");
         let json: serde_json::Value =
@@ -1364,6 +2362,7 @@ mod tests {
             model_id,
             &Some("codex".to_string()),
             Some("auth-first"),
+            &Caller::default(),
         )
         .await;
         assert_eq!(candidates.len(), 1);
@@ -1386,10 +2385,12 @@ mod tests {
             candidates,
             false,
             Some("session-stale"),
+            None,
+            None,
         )
         .await
         .expect("execution should still complete against resolved provider");
-        let body = to_bytes(response.into_body(), 1024 * 1024)
+        let body = to_bytes(response.into_response().into_body(), 1024 * 1024)
             .await
             .expect("response body should be readable");
         let json: serde_json::Value =
@@ -0,0 +1,156 @@
+//! Daily request and token budgets of scoped client keys.
+//!
+//! Counters are kept in memory per key and start over at UTC midnight. Only keys
+//! with a `requests-per-day` or `tokens-per-day` limit are tracked. At startup
+//! they are seeded from the usage ledger so a restart does not reset the day.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use chrono::{DateTime, Days, NaiveDate, Utc};
+
+use crate::config::ApiKeyEntry;
+use crate::error::{AppError, AppResult};
+use crate::proxy::usage_ledger::{UsageGroup, UsageLedger, UsageRange};
+
+#[derive(Debug, Clone, Copy)]
+struct DayUsage {
+    day: NaiveDate,
+    requests: u64,
+    tokens: u64,
+}
+
+#[derive(Debug, Default)]
+pub struct KeyBudgets {
+    days: Mutex<HashMap<String, DayUsage>>,
+}
+
+impl KeyBudgets {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Refuse a request from `key` once either daily budget is spent, without
+    /// counting it.
+    pub fn check(&self, key: &ApiKeyEntry, now: DateTime<Utc>) -> AppResult<()> {
+        if !is_budgeted(key) {
+            return Ok(());
+        }
+        let mut days = self.days.lock().unwrap();
+        Self::refuse_if_spent(key, Self::today(&mut days, &key.key, now), now)
+    }
+
+    /// Count one request against `key`, or refuse it once either daily budget is
+    /// spent. A token budget is checked against tokens already recorded, so the
+    /// request that crosses it still completes.
+    pub fn admit(&self, key: &ApiKeyEntry, now: DateTime<Utc>) -> AppResult<()> {
+        if !is_budgeted(key) {
+            return Ok(());
+        }
+        let mut days = self.days.lock().unwrap();
+        let usage = Self::today(&mut days, &key.key, now);
+        Self::refuse_if_spent(key, usage, now)?;
+        usage.requests += 1;
+        Ok(())
+    }
+
+    /// Start today's counts of budgeted `keys` from what `ledger` recorded for
+    /// them today. The ledger also holds refused requests, so a key can only end
+    /// up with less of its budget left than before the restart, never more.
+    pub fn seed_from_ledger(&self, keys: &[ApiKeyEntry], ledger: &UsageLedger, now: DateTime<Utc>) {
+        let today = Some(now.date_naive());
+        let rows = ledger.query(
+            &[UsageGroup::Key],
+            UsageRange {
+                from: today,
+                to: today,
+            },
+        );
+        let mut days = self.days.lock().unwrap();
+        for key in keys.iter().filter(|key| is_budgeted(key)) {
+            let id = key.id();
+            let Some(row) = rows.iter().find(|row| row.key_id.as_deref() == Some(&id)) else {
+                continue;
+            };
+            let usage = Self::today(&mut days, &key.key, now);
+            usage.requests = row.totals.requests;
+            usage.tokens = row.totals.prompt_tokens + row.totals.completion_tokens;
+        }
+    }
+
+    /// Add tokens a completed request used to `key`'s count for today. Keys
+    /// without a budget are not tracked.
+    pub fn record_tokens(&self, key: &str, tokens: u64, now: DateTime<Utc>) {
+        let mut days = self.days.lock().unwrap();
+        if days.contains_key(key) {
+            Self::today(&mut days, key, now).tokens += tokens;
+        }
+    }
+
+    /// Requests and tokens counted against `key` today.
+    pub fn used_today(&self, key: &str, now: DateTime<Utc>) -> (u64, u64) {
+        let days = self.days.lock().unwrap();
+        days.get(key)
+            .filter(|usage| usage.day == now.date_naive())
+            .map_or((0, 0), |usage| (usage.requests, usage.tokens))
+    }
+
+    fn refuse_if_spent(key: &ApiKeyEntry, usage: &DayUsage, now: DateTime<Utc>) -> AppResult<()> {
+        let scope = &key.scope;
+        let spent = if scope
+            .requests_per_day
+            .is_some_and(|limit| usage.requests >= limit)
+        {
+            Some("request")
+        } else if scope
+            .tokens_per_day
+            .is_some_and(|limit| usage.tokens >= limit)
+        {
+            Some("token")
+        } else {
+            None
+        };
+        match spent {
+            Some(budget) => Err(AppError::RateLimited {
+                message: format!(
+                    "daily {budget} budget of API key '{}' is spent",
+                    key.label()
+                ),
+                retry_after: (next_midnight(now) - now).to_std().ok(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    fn today<'a>(
+        days: &'a mut HashMap<String, DayUsage>,
+        key: &str,
+        now: DateTime<Utc>,
+    ) -> &'a mut DayUsage {
+        let today = now.date_naive();
+        let usage = days.entry(key.to_string()).or_insert(DayUsage {
+            day: today,
+            requests: 0,
+            tokens: 0,
+        });
+        if usage.day != today {
+            *usage = DayUsage {
+                day: today,
+                requests: 0,
+                tokens: 0,
+            };
+        }
+        usage
+    }
+}
+
+fn is_budgeted(key: &ApiKeyEntry) -> bool {
+    key.scope.requests_per_day.is_some() || key.scope.tokens_per_day.is_some()
+}
+
+fn next_midnight(now: DateTime<Utc>) -> DateTime<Utc> {
+    now.date_naive()
+        .checked_add_days(Days::new(1))
+        .and_then(|day| day.and_hms_opt(0, 0, 0))
+        .map_or(now, |midnight| midnight.and_utc())
+}
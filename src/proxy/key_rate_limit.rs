@@ -0,0 +1,176 @@
+//! Per-minute limits on client keys: token buckets for requests and for prompt
+//! plus completion tokens, and a cap on streams open at once.
+//!
+//! Request tokens are taken on admission. Model tokens are only known once a reply
+//! is done, so that bucket is charged afterwards and may go negative; the key is
+//! then refused until it refills.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::config::KeyRateLimit;
+use crate::error::{AppError, AppResult};
+
+/// Continuously refilling bucket; `level` is never above the capacity it was last
+/// refilled against.
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    level: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn full(capacity: f64, now: Instant) -> Self {
+        Self {
+            level: capacity,
+            updated: now,
+        }
+    }
+
+    /// Refill for the time since the last update at `capacity` per minute.
+    fn refill(&mut self, capacity: f64, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.level = (self.level + elapsed * capacity / 60.0).min(capacity);
+        self.updated = now;
+    }
+
+    /// Time until the level reaches `needed`.
+    fn wait_for(&self, needed: f64, capacity: f64) -> Duration {
+        let missing = (needed - self.level).max(0.0);
+        Duration::from_secs_f64(missing * 60.0 / capacity)
+    }
+}
+
+#[derive(Debug)]
+struct KeyState {
+    requests: Option<Bucket>,
+    tokens: Option<Bucket>,
+    open_streams: u32,
+}
+
+#[derive(Debug, Default)]
+pub struct KeyRateLimiter {
+    keys: Arc<Mutex<HashMap<String, KeyState>>>,
+}
+
+/// Holds one of a key's concurrent-stream slots until dropped.
+#[derive(Debug)]
+pub struct StreamPermit {
+    keys: Arc<Mutex<HashMap<String, KeyState>>>,
+    key: String,
+}
+
+impl Drop for StreamPermit {
+    fn drop(&mut self) {
+        if let Some(state) = self.keys.lock().unwrap().get_mut(&self.key) {
+            state.open_streams = state.open_streams.saturating_sub(1);
+        }
+    }
+}
+
+impl KeyRateLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Let one request on `key` through, or refuse it with the time until it
+    /// would be allowed. Nothing is charged when the request is refused.
+    ///
+    /// Streaming requests get a permit that keeps a stream slot taken.
+    pub fn admit(
+        &self,
+        key: &str,
+        limits: KeyRateLimit,
+        is_stream: bool,
+        now: Instant,
+    ) -> AppResult<Option<StreamPermit>> {
+        if limits.is_unlimited() {
+            return Ok(None);
+        }
+        let mut keys = self.keys.lock().unwrap();
+        let state = keys.entry(key.to_string()).or_insert(KeyState {
+            requests: None,
+            tokens: None,
+            open_streams: 0,
+        });
+
+        if let Some(capacity) = limits.concurrent_streams.filter(|_| is_stream) {
+            if state.open_streams >= capacity {
+                return Err(limited(
+                    format!("{capacity} concurrent streams"),
+                    Duration::from_secs(1),
+                ));
+            }
+        }
+        if let Some(limit) = limits.tokens_per_minute {
+            let capacity = limit as f64;
+            let bucket = state
+                .tokens
+                .get_or_insert_with(|| Bucket::full(capacity, now));
+            bucket.refill(capacity, now);
+            if bucket.level <= 0.0 {
+                return Err(limited(
+                    format!("{limit} tokens per minute"),
+                    bucket.wait_for(1.0, capacity),
+                ));
+            }
+        }
+        if let Some(limit) = limits.requests_per_minute {
+            let capacity = f64::from(limit);
+            let bucket = state
+                .requests
+                .get_or_insert_with(|| Bucket::full(capacity, now));
+            bucket.refill(capacity, now);
+            if bucket.level < 1.0 {
+                return Err(limited(
+                    format!("{limit} requests per minute"),
+                    bucket.wait_for(1.0, capacity),
+                ));
+            }
+            bucket.level -= 1.0;
+        }
+
+        if !is_stream || limits.concurrent_streams.is_none() {
+            return Ok(None);
+        }
+        state.open_streams += 1;
+        Ok(Some(StreamPermit {
+            keys: self.keys.clone(),
+            key: key.to_string(),
+        }))
+    }
+
+    /// Give back the request slot of an admitted request that was refused later on,
+    /// before it reached an upstream.
+    pub fn refund(&self, key: &str, limits: KeyRateLimit) {
+        let Some(limit) = limits.requests_per_minute else {
+            return;
+        };
+        let capacity = f64::from(limit);
+        let mut keys = self.keys.lock().unwrap();
+        if let Some(bucket) = keys.get_mut(key).and_then(|state| state.requests.as_mut()) {
+            bucket.level = (bucket.level + 1.0).min(capacity);
+        }
+    }
+
+    /// Charge tokens a finished reply used to `key`'s per-minute token bucket.
+    pub fn record_tokens(&self, key: &str, limits: KeyRateLimit, tokens: u64, now: Instant) {
+        let Some(capacity) = limits.tokens_per_minute else {
+            return;
+        };
+        let capacity = capacity as f64;
+        let mut keys = self.keys.lock().unwrap();
+        if let Some(bucket) = keys.get_mut(key).and_then(|state| state.tokens.as_mut()) {
+            bucket.refill(capacity, now);
+            bucket.level -= tokens as f64;
+        }
+    }
+}
+
+fn limited(limit: String, retry_after: Duration) -> AppError {
+    AppError::RateLimited {
+        message: format!("API key is over its limit of {limit}"),
+        retry_after: Some(retry_after),
+    }
+}
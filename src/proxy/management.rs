@@ -9,7 +9,10 @@ use crate::auth::zed::{canonical_zed_login_filename, zed_user_ids_match};
 use crate::auth::zed_callback::start_callback_server;
 use crate::auth::zed_login::{build_login_url, decrypt_credential, generate_keypair};
 use crate::auth::zed_session::{cleanup_expired_sessions, ZedLoginSession, ZedLoginSessionStatus};
+use crate::config::{validate_model_routes, ApiKeyEntry, ModelRoute};
 use crate::error::{AppError, AppResult};
+use crate::providers::http_client;
+use crate::proxy::usage_ledger::{UsageGroup, UsageRange};
 use crate::proxy::ProxyState;
 
 async fn refresh_runtime_after_auth_change(state: &Arc<ProxyState>) -> AppResult<()> {
@@ -17,6 +20,13 @@ async fn refresh_runtime_after_auth_change(state: &Arc<ProxyState>) -> AppResult
     state.refresh_provider_runtime().await?;
     Ok(())
 }
+
+/// The account's own `proxy_url`, falling back to the global `proxy-url`.
+async fn account_proxy_url(state: &ProxyState, record: &AuthRecord) -> Option<String> {
+    let config = state.config.read().await;
+    http_client::effective_proxy_url(record.proxy_url(), config.proxy_url.as_deref())
+        .map(str::to_string)
+}
 use axum::{
     extract::{ConnectInfo, Query, Request, State},
     http::{HeaderMap, HeaderValue, StatusCode},
@@ -50,6 +60,15 @@ pub fn router(state: Arc<ProxyState>) -> Router<Arc<ProxyState>> {
                 .patch(patch_api_keys)
                 .delete(delete_api_keys),
         )
+        // ── Public model routes ──────────────────────────────────────────────
+        .route("/model-routes", get(get_model_routes).put(put_model_routes))
+        // ── Circuit breaker ──────────────────────────────────────────────────
+        .route(
+            "/circuit-breakers",
+            get(get_circuit_breakers).delete(reset_circuit_breakers),
+        )
+        // ── Token usage ──────────────────────────────────────────────────────
+        .route("/usage", get(get_usage))
         // ── Auth file CRUD ───────────────────────────────────────────────────
         .route(
             "/auth-files",
@@ -156,6 +175,19 @@ async fn management_auth(
     }
 }
 
+/// Middleware for `/metrics`: open when `metrics.public` is set, otherwise gated
+/// like the management routes, since its labels name accounts.
+pub async fn metrics_auth(
+    State(state): State<Arc<ProxyState>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    if state.config.read().await.metrics.public {
+        return next.run(req).await;
+    }
+    management_auth(State(state), req, next).await
+}
+
 /// Extract management secret from request headers.
 /// Checks `Authorization: Bearer <key>` first, then `X-Management-Key`.
 fn extract_management_key(req: &Request) -> Option<String> {
@@ -278,16 +310,19 @@ async fn get_api_keys(State(state): State<Arc<ProxyState>>) -> Json<Value> {
 
 /// `PUT /v0/management/api-keys` — replace the entire API key list.
 ///
-/// Body: `["key1", "key2"]` or `{"items": ["key1", "key2"]}`
+/// Body: `["key1", "key2"]` or `{"items": ["key1", "key2"]}`. Scoped keys are given
+/// as objects: `{"key": "k", "name": "ci", "models": [...], "requests-per-day": 100}`.
 async fn put_api_keys(
     State(state): State<Arc<ProxyState>>,
     Json(body): Json<Value>,
 ) -> impl IntoResponse {
-    let keys = parse_string_list(&body);
+    let keys = parse_api_key_list(&body);
     let Some(keys) = keys else {
         return (
             StatusCode::BAD_REQUEST,
-            Json(json!({"error": "invalid body — expected [\"key\", ...] or {\"items\": [...]}"})),
+            Json(
+                json!({"error": "invalid body — expected [\"key\" or {\"key\": ...}, ...] or {\"items\": [...]}"}),
+            ),
         );
     };
 
@@ -304,6 +339,9 @@ async fn put_api_keys(
 /// - `{"value": "new-key"}` — append a key
 /// - `{"old": "x", "new": "y"}` — replace key x with y
 /// - `{"index": 0, "value": "y"}` — replace key at index
+/// - `{"entry": {"key": "x", "models": [...]}}` — add key x, or replace its name and scope
+///
+/// Replacing a key's value keeps its name and scope.
 #[derive(Deserialize)]
 struct PatchBody {
     generate: Option<bool>,
@@ -312,6 +350,7 @@ struct PatchBody {
     old: Option<String>,
     new: Option<String>,
     index: Option<usize>,
+    entry: Option<ApiKeyEntry>,
 }
 
 async fn patch_api_keys(
@@ -324,7 +363,8 @@ async fn patch_api_keys(
     if body.generate.unwrap_or(false) {
         let count = body.count.unwrap_or(1).clamp(1, 50);
         let generated: Vec<String> = (0..count).map(|_| generate_api_key()).collect();
-        cfg.api_keys.extend(generated.clone());
+        cfg.api_keys
+            .extend(generated.iter().cloned().map(ApiKeyEntry::from));
         return (
             StatusCode::OK,
             Json(json!({
@@ -334,10 +374,25 @@ async fn patch_api_keys(
         );
     }
 
+    // Add or rescope an entry
+    if let Some(entry) = body.entry {
+        if entry.key.trim().is_empty() {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": "entry key must not be empty"})),
+            );
+        }
+        match cfg.api_keys.iter_mut().find(|k| k.key == entry.key) {
+            Some(existing) => *existing = entry,
+            None => cfg.api_keys.push(entry),
+        }
+        return (StatusCode::OK, Json(json!({ "api-keys": cfg.api_keys })));
+    }
+
     // Replace by index
     if let (Some(idx), Some(val)) = (body.index, &body.value) {
         if idx < cfg.api_keys.len() {
-            cfg.api_keys[idx] = val.clone();
+            cfg.api_keys[idx].key = val.clone();
             return (StatusCode::OK, Json(json!({ "api-keys": cfg.api_keys })));
         }
         return (
@@ -348,11 +403,11 @@ async fn patch_api_keys(
 
     // Replace by old → new
     if let (Some(old), Some(new)) = (&body.old, &body.new) {
-        if let Some(pos) = cfg.api_keys.iter().position(|k| k == old) {
-            cfg.api_keys[pos] = new.clone();
+        if let Some(pos) = cfg.api_keys.iter().position(|k| k.key == *old) {
+            cfg.api_keys[pos].key = new.clone();
         } else {
             // Key not found — append as new
-            cfg.api_keys.push(new.clone());
+            cfg.api_keys.push(new.clone().into());
         }
         return (StatusCode::OK, Json(json!({ "api-keys": cfg.api_keys })));
     }
@@ -366,13 +421,15 @@ async fn patch_api_keys(
                 Json(json!({"error": "value must not be empty"})),
             );
         }
-        cfg.api_keys.push(val);
+        cfg.api_keys.push(val.into());
         return (StatusCode::OK, Json(json!({ "api-keys": cfg.api_keys })));
     }
 
     (
         StatusCode::BAD_REQUEST,
-        Json(json!({"error": "missing fields — use generate, value, old/new, or index/value"})),
+        Json(
+            json!({"error": "missing fields — use generate, value, old/new, index/value, or entry"}),
+        ),
     )
 }
 
@@ -413,7 +470,7 @@ async fn delete_api_keys(
 
     if let Some(val) = &q.value {
         let before = cfg.api_keys.len();
-        cfg.api_keys.retain(|k| k != val);
+        cfg.api_keys.retain(|k| k.key != *val);
         if cfg.api_keys.len() == before {
             return (
                 StatusCode::NOT_FOUND,
@@ -429,29 +486,155 @@ async fn delete_api_keys(
     )
 }
 
+// ── Model route management ───────────────────────────────────────────────────
+
+/// `GET /v0/management/model-routes` — the live public routing table.
+async fn get_model_routes(State(state): State<Arc<ProxyState>>) -> Json<Value> {
+    let cfg = state.config.read().await;
+    Json(json!({ "model-routes": cfg.model_routes }))
+}
+
+/// `PUT /v0/management/model-routes` — replace the routing table; takes effect on the
+/// next request.
+///
+/// Body: `[{"name": ..., "targets": [...]}]` or `{"model-routes": [...]}`. Targets no
+/// registered client serves are accepted and listed under `unknown-targets`.
+async fn put_model_routes(
+    State(state): State<Arc<ProxyState>>,
+    Json(body): Json<Value>,
+) -> impl IntoResponse {
+    let body = body.get("model-routes").cloned().unwrap_or(body);
+    let routes: Vec<ModelRoute> = match serde_json::from_value(body) {
+        Ok(routes) => routes,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": format!("invalid model routes: {e}")})),
+            );
+        }
+    };
+    if let Err(e) = validate_model_routes(&routes) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        );
+    }
+
+    let unknown = state.model_registry.unknown_route_targets(&routes).await;
+    state.config.write().await.model_routes = routes.clone();
+    (
+        StatusCode::OK,
+        Json(json!({ "model-routes": routes, "unknown-targets": unknown })),
+    )
+}
+
+// ── Circuit breaker ──────────────────────────────────────────────────────────
+
+/// `GET /v0/management/circuit-breakers` — accounts with failures on record.
+///
+/// Pairs missing from the list are closed.
+async fn get_circuit_breakers(State(state): State<Arc<ProxyState>>) -> Json<Value> {
+    let circuits = state.circuit_breaker.snapshot(Instant::now());
+    Json(json!({ "circuit-breakers": circuits }))
+}
+
+#[derive(Deserialize)]
+struct CircuitResetQuery {
+    client_id: Option<String>,
+    model: Option<String>,
+}
+
+/// `DELETE /v0/management/circuit-breakers?client_id=...&model=...` — close circuits.
+///
+/// Both filters are optional; with neither, every circuit is closed.
+async fn reset_circuit_breakers(
+    State(state): State<Arc<ProxyState>>,
+    Query(query): Query<CircuitResetQuery>,
+) -> Json<Value> {
+    let cleared = state
+        .circuit_breaker
+        .reset(query.client_id.as_deref(), query.model.as_deref());
+    Json(json!({ "status": "ok", "cleared": cleared }))
+}
+
+// ── Token usage ──────────────────────────────────────────────────────────────
+
+#[derive(Deserialize)]
+struct UsageQuery {
+    group_by: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+}
+
+/// `GET /v0/management/usage?group_by=day,key&from=2026-01-01&to=2026-01-31` —
+/// request counts, tokens and latency from the usage ledger.
+///
+/// `group_by` takes any of `day`, `key`, `account` and `model`, comma separated;
+/// without it a single total is returned. `from` and `to` are inclusive UTC days.
+async fn get_usage(
+    State(state): State<Arc<ProxyState>>,
+    Query(query): Query<UsageQuery>,
+) -> impl IntoResponse {
+    let (group_by, range) = match parse_usage_query(&query) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": e.to_string()})),
+            );
+        }
+    };
+
+    let rows = state.usage_ledger.query(&group_by, range);
+    (
+        StatusCode::OK,
+        Json(json!({ "group_by": group_by, "rows": rows })),
+    )
+}
+
+fn parse_usage_query(query: &UsageQuery) -> AppResult<(Vec<UsageGroup>, UsageRange)> {
+    let mut group_by = Vec::new();
+    for group in query.group_by.iter().flat_map(|groups| groups.split(',')) {
+        if group.trim().is_empty() {
+            continue;
+        }
+        let group = group.parse()?;
+        if !group_by.contains(&group) {
+            group_by.push(group);
+        }
+    }
+    let day = |value: &Option<String>| {
+        value
+            .as_deref()
+            .map(|day| {
+                chrono::NaiveDate::parse_from_str(day.trim(), "%Y-%m-%d").map_err(|_| {
+                    AppError::BadRequest(format!("invalid day '{day}', expected YYYY-MM-DD"))
+                })
+            })
+            .transpose()
+    };
+    let range = UsageRange {
+        from: day(&query.from)?,
+        to: day(&query.to)?,
+    };
+    Ok((group_by, range))
+}
+
 // ── Helpers ──────────────────────────────────────────────────────────────────
 
-/// Parse body as `["a","b"]` or `{"items": ["a","b"]}`.
-fn parse_string_list(body: &Value) -> Option<Vec<String>> {
-    // Direct array
-    if let Some(arr) = body.as_array() {
-        let strings: Vec<String> = arr
-            .iter()
-            .filter_map(|v| v.as_str().map(|s| s.trim().to_string()))
-            .filter(|s| !s.is_empty())
-            .collect();
-        return Some(strings);
-    }
-    // Wrapped: {"items": [...]}
-    if let Some(items) = body.get("items").and_then(|v| v.as_array()) {
-        let strings: Vec<String> = items
-            .iter()
-            .filter_map(|v| v.as_str().map(|s| s.trim().to_string()))
-            .filter(|s| !s.is_empty())
-            .collect();
-        return Some(strings);
+/// Parse body as `["a", {"key": "b", ...}]` or `{"items": [...]}`.
+fn parse_api_key_list(body: &Value) -> Option<Vec<ApiKeyEntry>> {
+    // Direct array, or wrapped: {"items": [...]}
+    let items = body.as_array().or_else(|| body.get("items")?.as_array())?;
+    let mut keys = Vec::with_capacity(items.len());
+    for item in items {
+        let mut entry: ApiKeyEntry = serde_json::from_value(item.clone()).ok()?;
+        entry.key = entry.key.trim().to_string();
+        if !entry.key.is_empty() {
+            keys.push(entry);
+        }
     }
-    None
+    Some(keys)
 }
 
 /// Validate an auth-file name against path-traversal and injection attacks.
@@ -658,7 +841,16 @@ async fn import_kiro_social_refresh_token(
         );
     }
 
-    let mut token_data = match SocialAuthClient::new()
+    let client = match crate::proxy::oauth::login_client(&state, "kiro").await {
+        Ok(client) => client,
+        Err(error) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": error.to_string()})),
+            );
+        }
+    };
+    let mut token_data = match SocialAuthClient::with_client(client)
         .refresh_social_token(&refresh_token)
         .await
     {
@@ -962,9 +1154,10 @@ async fn check_kiro_quota(
             }
             resp
         }
-        crate::auth::kiro_runtime::QuotaStatus::Exhausted { detail } => json!({
+        crate::auth::kiro_runtime::QuotaStatus::Exhausted { detail, next_reset } => json!({
             "status": "exhausted",
             "detail": detail,
+            "next_reset": next_reset,
         }),
     };
 
@@ -1019,7 +1212,7 @@ async fn check_quota_with_refresh(
 
     // If Unknown (likely 403), attempt token refresh and retry once
     if matches!(status, crate::auth::kiro_runtime::QuotaStatus::Unknown) {
-        if let Some(refreshed) = attempt_token_refresh(record).await {
+        if let Some(refreshed) = attempt_token_refresh(state, record).await {
             // Update record with refreshed token
             record.metadata.insert(
                 "access_token".to_string(),
@@ -1062,9 +1255,21 @@ async fn check_quota_with_refresh(
 }
 
 /// Attempt to refresh a Kiro token based on its auth method.
-async fn attempt_token_refresh(record: &crate::auth::store::AuthRecord) -> Option<RefreshedToken> {
+async fn attempt_token_refresh(
+    state: &ProxyState,
+    record: &crate::auth::store::AuthRecord,
+) -> Option<RefreshedToken> {
     use crate::auth::kiro_login::{SSOOIDCClient, SocialAuthClient};
 
+    let proxy_url = account_proxy_url(state, record).await;
+    let http_client = match http_client::default_client("kiro", proxy_url.as_deref()) {
+        Ok(client) => client,
+        Err(e) => {
+            tracing::warn!("Kiro token refresh skipped: {}", e);
+            return None;
+        }
+    };
+
     let auth_method = record
         .metadata
         .get("auth_method")
@@ -1093,7 +1298,7 @@ async fn attempt_token_refresh(record: &crate::auth::store::AuthRecord) -> Optio
         "builder-id" => {
             // Builder ID uses SSO OIDC with default region and Builder ID start URL
             if let (Some(cid), Some(secret)) = (client_id, client_secret) {
-                let client = SSOOIDCClient::new();
+                let client = SSOOIDCClient::with_client(http_client);
                 let start_url = "https://view.awsapps.com/start";
                 match client
                     .refresh_token_with_region(cid, secret, refresh_token, region, start_url)
@@ -1121,7 +1326,7 @@ async fn attempt_token_refresh(record: &crate::auth::store::AuthRecord) -> Optio
                     .and_then(|v| v.as_str())
                     .unwrap_or("");
 
-                let client = SSOOIDCClient::new();
+                let client = SSOOIDCClient::with_client(http_client);
                 match client
                     .refresh_token_with_region(cid, secret, refresh_token, region, start_url)
                     .await
@@ -1141,7 +1346,7 @@ async fn attempt_token_refresh(record: &crate::auth::store::AuthRecord) -> Optio
         }
         _ => {
             // Social auth (Google/GitHub) or legacy
-            let client = SocialAuthClient::new();
+            let client = SocialAuthClient::with_client(http_client);
             match client.refresh_social_token(refresh_token).await {
                 Ok(token_data) => {
                     return Some(RefreshedToken {
@@ -1276,6 +1481,7 @@ async fn check_codex_quota(
 async fn probe_codex_quota(state: &Arc<ProxyState>, mut record: AuthRecord) -> Value {
     let mut account = codex_account_id_from_record(&record);
     let base_url = codex_base_url_from_record(&record);
+    let proxy_url = account_proxy_url(state, &record).await;
     let plan_type = record
         .metadata
         .get("plan_type")
@@ -1309,7 +1515,19 @@ async fn probe_codex_quota(state: &Arc<ProxyState>, mut record: AuthRecord) -> V
             }
         };
 
-        match refresh_tokens_with_retry(&reqwest::Client::new(), refresh_token, 3).await {
+        let client = match http_client::default_client("codex", proxy_url.as_deref()) {
+            Ok(client) => client,
+            Err(error) => {
+                return json!({
+                    "account": account,
+                    "status": "error",
+                    "upstream_status": 0,
+                    "detail": format!("request setup failed: {error}"),
+                    "plan_type": plan_type,
+                });
+            }
+        };
+        match refresh_tokens_with_retry(&client, refresh_token, 3).await {
             Ok(refreshed) => {
                 if let Err(error) =
                     apply_refreshed_codex_record(state, &record.id, &refreshed).await
@@ -1377,11 +1595,13 @@ async fn probe_codex_quota(state: &Arc<ProxyState>, mut record: AuthRecord) -> V
         }
     };
 
-    let client = match reqwest::Client::builder()
-        .connect_timeout(Duration::from_secs(5))
-        .timeout(Duration::from_secs(15))
-        .build()
-    {
+    let client = match http_client::builder("codex", proxy_url.as_deref()).and_then(|builder| {
+        builder
+            .connect_timeout(Duration::from_secs(5))
+            .timeout(Duration::from_secs(15))
+            .build()
+            .map_err(|error| AppError::Config(error.to_string()))
+    }) {
         Ok(client) => client,
         Err(error) => {
             return json!({
@@ -1405,6 +1625,14 @@ async fn probe_codex_quota(state: &Arc<ProxyState>, mut record: AuthRecord) -> V
             if let Some(retry_after_secs) =
                 parse_codex_retry_after_seconds(status, &body_json, chrono::Utc::now())
             {
+                // The weekly/5h limit covers every model on the account.
+                state
+                    .model_registry
+                    .set_client_quota_exceeded_until(
+                        &record.id,
+                        Instant::now() + Duration::from_secs(retry_after_secs),
+                    )
+                    .await;
                 let mut response = json!({
                     "account": account,
                     "status": "exhausted",
@@ -2377,7 +2605,9 @@ async fn check_zed_quota(
     };
 
     // Call /client/users/me to get quota info
-    let response = fetch_zed_user_info(&name, &user_id, &credential_json).await;
+    let proxy_url = account_proxy_url(&state, &record).await;
+    let response =
+        fetch_zed_user_info(&name, &user_id, &credential_json, proxy_url.as_deref()).await;
 
     (StatusCode::OK, Json(response))
 }
@@ -2461,7 +2691,11 @@ async fn list_github_copilot_models(
         }
     };
 
-    let provider = match crate::providers::github_copilot::GithubCopilotProvider::new(record.clone()) {
+    let default_proxy_url = state.config.read().await.proxy_url.clone();
+    let provider = match crate::providers::github_copilot::GithubCopilotProvider::new_with_proxy(
+        record.clone(),
+        default_proxy_url.as_deref(),
+    ) {
         Ok(provider) => provider,
         Err(error) => {
             return (
@@ -2496,13 +2730,23 @@ async fn list_github_copilot_models(
 }
 
 /// Fetch user info from Zed /client/users/me endpoint and return flattened response.
-async fn fetch_zed_user_info(account: &str, user_id: &str, credential_json: &str) -> Value {
+async fn fetch_zed_user_info(
+    account: &str,
+    user_id: &str,
+    credential_json: &str,
+    proxy_url: Option<&str>,
+) -> Value {
     use crate::providers::zed::ZedClient;
 
     let client = ZedClient;
     let url = client.users_me_endpoint();
 
-    let http_client = reqwest::Client::new();
+    let http_client = match http_client::default_client("zed", proxy_url) {
+        Ok(http_client) => http_client,
+        Err(e) => {
+            return build_zed_quota_response_error(account, 0, format!("request setup failed: {e}"))
+        }
+    };
     let result = http_client
         .get(url)
         .header("authorization", format!("{} {}", user_id, credential_json))
@@ -1,9 +1,19 @@
 pub mod balancer;
+pub mod circuit_breaker;
+pub mod claude;
 pub mod execution_session;
+pub mod gemini;
 pub mod handlers;
+pub mod key_budget;
+pub mod key_rate_limit;
 pub mod management;
 pub mod oauth;
+pub mod responses;
+pub mod state_file;
 pub mod stream;
+mod translate;
+pub mod usage;
+pub mod usage_ledger;
 pub mod zed_import;
 
 use std::collections::{HashMap, HashSet};
@@ -14,15 +24,20 @@ use tokio::sync::{Mutex, RwLock};
 use crate::auth::kiro_runtime::{CooldownManager, KiroRateLimiter, NoOpQuotaChecker, QuotaChecker};
 use crate::auth::manager::AccountManager;
 use crate::auth::zed_session::{new_session_store, ZedLoginSessionStore};
-use crate::config::Config;
+use crate::config::{Config, RoutingConfig};
 use crate::error::{AppError, AppResult};
+use crate::metrics::Metrics;
 use crate::providers::model_info::ExtModelInfo;
 use crate::providers::model_registry::ModelRegistry;
 use crate::providers::Provider;
 
 use self::balancer::{Balancer, Strategy};
+use self::circuit_breaker::CircuitBreaker;
 use self::execution_session::ExecutionSessionStore;
+use self::key_budget::KeyBudgets;
+use self::key_rate_limit::KeyRateLimiter;
 use self::oauth::OAuthSessionStore;
+use self::usage_ledger::UsageLedger;
 
 pub(crate) struct RuntimeSnapshot {
     providers: Vec<Arc<dyn Provider>>,
@@ -35,10 +50,14 @@ pub(crate) struct RuntimeSnapshot {
 impl RuntimeSnapshot {
     fn new(
         providers: Vec<Arc<dyn Provider>>,
-        strategy: Strategy,
+        routing: &RoutingConfig,
         replacement_models: &HashMap<String, (String, Vec<ExtModelInfo>)>,
     ) -> Self {
-        let provider_count = providers.len();
+        let strategy = Strategy::parse(&routing.strategy);
+        let weights = providers
+            .iter()
+            .map(|provider| routing.weight_for(provider.client_id(), provider.provider_type()))
+            .collect();
         let mut clients_by_model: HashMap<String, Vec<String>> = HashMap::new();
         let mut providers_by_model: HashMap<String, HashSet<String>> = HashMap::new();
         let mut models_by_client: HashMap<String, HashSet<String>> = HashMap::new();
@@ -69,7 +88,7 @@ impl RuntimeSnapshot {
 
         Self {
             providers,
-            balancer: Balancer::new(strategy, provider_count),
+            balancer: Balancer::with_weights(strategy, weights),
             clients_by_model,
             providers_by_model,
             models_by_client,
@@ -88,6 +107,13 @@ impl RuntimeSnapshot {
         self.providers.len()
     }
 
+    /// Position of `provider` in this snapshot, which is its balancer slot.
+    pub(crate) fn provider_index(&self, provider: &Arc<dyn Provider>) -> Option<usize> {
+        self.providers
+            .iter()
+            .position(|candidate| Arc::ptr_eq(candidate, provider))
+    }
+
     pub(crate) fn model_providers(&self, model_id: &str) -> &[String] {
         self.providers_by_model
             .get(model_id)
@@ -146,6 +172,16 @@ pub struct ProxyState {
     pub execution_sessions: ExecutionSessionStore,
     /// Kiro-specific cooldown, rate-limit, and quota probing state
     pub kiro_runtime: KiroRuntimeState,
+    /// Provider-agnostic circuit breaker keyed by account and model
+    pub circuit_breaker: CircuitBreaker,
+    /// Daily request and token counts of client keys with budgets
+    pub key_budgets: KeyBudgets,
+    /// Per-minute request, token and stream limits of client keys
+    pub key_rate_limiter: KeyRateLimiter,
+    /// Token usage of finished requests, persisted in auth-dir when enabled
+    pub usage_ledger: UsageLedger,
+    /// Prometheus metrics served on `/metrics`
+    pub metrics: Metrics,
     /// Serializes provider runtime refreshes so runtime state is swapped atomically.
     runtime_refresh_lock: Mutex<()>,
 }
@@ -168,11 +204,8 @@ impl ProxyState {
         providers: Vec<Arc<dyn Provider>>,
     ) -> AppResult<HashMap<String, (String, Vec<ExtModelInfo>)>> {
         let replacement_models = Self::collect_runtime_models(&providers).await?;
-        let strategy = {
-            let config = self.config.read().await;
-            Strategy::parse(&config.routing.strategy)
-        };
-        let snapshot = RuntimeSnapshot::new(providers, strategy, &replacement_models);
+        let routing = self.config.read().await.routing.clone();
+        let snapshot = RuntimeSnapshot::new(providers, &routing, &replacement_models);
         self.publish_runtime_snapshot(snapshot).await;
         Ok(replacement_models)
     }
@@ -292,12 +325,12 @@ impl ProxyState {
         model_registry: Arc<ModelRegistry>,
         provider_count: usize,
     ) -> Self {
-        let strategy = Strategy::parse(&config.routing.strategy);
         let initial_snapshot = Arc::new(RuntimeSnapshot::new(
             Vec::with_capacity(provider_count),
-            strategy,
+            &config.routing,
             &HashMap::new(),
         ));
+        let circuit_breaker = CircuitBreaker::new(&config.circuit_breaker);
         Self {
             config: RwLock::new(config),
             providers: RwLock::new(Vec::with_capacity(provider_count)),
@@ -308,6 +341,11 @@ impl ProxyState {
             zed_login_sessions: new_session_store(),
             execution_sessions: ExecutionSessionStore::new(),
             kiro_runtime: KiroRuntimeState::default(),
+            circuit_breaker,
+            key_budgets: KeyBudgets::new(),
+            key_rate_limiter: KeyRateLimiter::new(),
+            usage_ledger: UsageLedger::in_memory(),
+            metrics: Metrics::new(),
             runtime_refresh_lock: Mutex::new(()),
         }
     }
@@ -25,9 +25,28 @@ use crate::auth::kiro::{KiroTokenData, KiroTokenSource, BUILDER_ID_START_URL, DE
 use crate::auth::kiro_login::SSOOIDCClient;
 use crate::auth::kiro_record::KiroRecordInput;
 use crate::auth::store::{AuthRecord, AuthStatus};
-use crate::error::{AppError, AppResult};
+use crate::error::{AppError, AppResult, UpstreamError};
+use crate::providers::http_client;
 use crate::proxy::ProxyState;
 
+/// Client builder for login traffic. A new account has no `proxy_url` of its own
+/// yet, so the global `proxy-url` applies.
+pub(crate) async fn login_client_builder(
+    state: &ProxyState,
+    provider: &str,
+) -> AppResult<reqwest::ClientBuilder> {
+    let proxy_url = state.config.read().await.proxy_url.clone();
+    http_client::builder(provider, proxy_url.as_deref())
+}
+
+/// [`login_client_builder`] with reqwest defaults.
+pub(crate) async fn login_client(state: &ProxyState, provider: &str) -> AppResult<reqwest::Client> {
+    login_client_builder(state, provider)
+        .await?
+        .build()
+        .map_err(|error| AppError::Config(format!("failed to build {provider} client: {error}")))
+}
+
 async fn refresh_runtime_after_auth_change(state: &Arc<ProxyState>) -> anyhow::Result<()> {
     state
         .accounts
@@ -401,7 +420,7 @@ async fn process_antigravity_callback(
     drop(cfg);
 
     let redirect_uri = format!("http://localhost:{port}/antigravity/callback");
-    let client = reqwest::Client::new();
+    let client = login_client(state, "antigravity").await?;
 
     // Exchange code for tokens
     let params = [
@@ -694,7 +713,8 @@ async fn start_codex_oauth(state: Arc<ProxyState>) -> Response {
 
 async fn start_github_copilot_oauth(state: Arc<ProxyState>) -> AppResult<Response> {
     let oauth_state = uuid::Uuid::new_v4().to_string();
-    let client = reqwest::Client::builder()
+    let client = login_client_builder(&state, "github-copilot")
+        .await?
         .timeout(Duration::from_secs(30))
         .build()
         .map_err(|e| AppError::Internal(anyhow::anyhow!("failed to build HTTP client: {e}")))?;
@@ -862,22 +882,32 @@ async fn request_github_copilot_device_code(client: &reqwest::Client) -> AppResu
         .form(&[("client_id", GITHUB_COPILOT_CLIENT_ID), ("scope", "read:user")])
         .send()
         .await
-        .map_err(|error| AppError::Upstream(format!("github device code request failed: {error}")))?;
+        .map_err(|error| {
+            UpstreamError::from_reqwest("github device code request failed", &error)
+        })?;
 
     let status = response.status();
     if !status.is_success() {
         let body = response.text().await.unwrap_or_default();
-        return Err(AppError::Upstream(format!(
-            "github device code request failed (status {}): {}",
-            status.as_u16(),
-            body.trim()
+        return Err(AppError::Upstream(UpstreamError::from_status(
+            status,
+            format!(
+                "github device code request failed (status {}): {}",
+                status.as_u16(),
+                body.trim()
+            ),
         )));
     }
 
     response
         .json::<DeviceCodeResponse>()
         .await
-        .map_err(|error| AppError::Upstream(format!("failed to parse device code response: {error}")))
+        .map_err(|error| {
+            AppError::Upstream(UpstreamError::from_reqwest(
+                "failed to parse device code response",
+                &error,
+            ))
+        })
 }
 
 async fn poll_github_copilot_token(
@@ -904,7 +934,9 @@ async fn poll_github_copilot_token(
             ])
             .send()
             .await
-            .map_err(|error| AppError::Upstream(format!("github device token polling failed: {error}")))?;
+            .map_err(|error| {
+                UpstreamError::from_reqwest("github device token polling failed", &error)
+            })?;
 
         let status = response.status();
 
@@ -912,11 +944,11 @@ async fn poll_github_copilot_token(
         let payload: Value = match response.json().await {
             Ok(json) => json,
             Err(e) => {
-                return Err(AppError::Upstream(format!(
+                return Err(AppError::Upstream(UpstreamError::other(format!(
                     "github device token polling failed to parse response (status {}): {}",
                     status.as_u16(),
                     e
-                )));
+                ))));
             }
         };
 
@@ -946,10 +978,14 @@ async fn poll_github_copilot_token(
 
         // If status is not success and no recognized error, fail with context
         if !status.is_success() {
-            return Err(AppError::Upstream(format!(
-                "github device token polling failed (status {}): {}",
-                status.as_u16(),
-                serde_json::to_string(&payload).unwrap_or_else(|_| "unable to serialize response".to_string())
+            return Err(AppError::Upstream(UpstreamError::from_status(
+                status,
+                format!(
+                    "github device token polling failed (status {}): {}",
+                    status.as_u16(),
+                    serde_json::to_string(&payload)
+                        .unwrap_or_else(|_| "unable to serialize response".to_string())
+                ),
             )));
         }
 
@@ -958,7 +994,7 @@ async fn poll_github_copilot_token(
             .and_then(Value::as_str)
             .map(str::trim)
             .filter(|value| !value.is_empty())
-            .ok_or_else(|| AppError::Upstream("github token response missing access_token".to_string()))?
+            .ok_or_else(|| UpstreamError::other("github token response missing access_token"))?
             .to_string();
 
         return Ok(GithubOAuthTokenData {
@@ -988,20 +1024,27 @@ async fn fetch_github_copilot_user(
         .header(reqwest::header::ACCEPT, "application/json")
         .send()
         .await
-        .map_err(|e| AppError::Upstream(format!("github user info request failed: {}", e)))?;
+        .map_err(|e| UpstreamError::from_reqwest("github user info request failed", &e))?;
 
     let status = response.status();
     if !status.is_success() {
         let body = response.text().await.unwrap_or_default();
-        return Err(AppError::Upstream(format!(
-            "github user info request failed (status {}): {}",
-            status.as_u16(),
-            body.trim()
+        return Err(AppError::Upstream(UpstreamError::from_status(
+            status,
+            format!(
+                "github user info request failed (status {}): {}",
+                status.as_u16(),
+                body.trim()
+            ),
         )));
     }
 
-    response.json::<GithubUserInfo>().await
-        .map_err(|e| AppError::Upstream(format!("failed to parse github user info: {}", e)))
+    response.json::<GithubUserInfo>().await.map_err(|e| {
+        AppError::Upstream(UpstreamError::from_reqwest(
+            "failed to parse github user info",
+            &e,
+        ))
+    })
 }
 
 async fn validate_github_copilot_entitlement(
@@ -1015,27 +1058,33 @@ async fn validate_github_copilot_entitlement(
         .header(reqwest::header::ACCEPT, "application/json")
         .send()
         .await
-        .map_err(|e| AppError::Upstream(format!("copilot entitlement request failed: {}", e)))?;
+        .map_err(|e| UpstreamError::from_reqwest("copilot entitlement request failed", &e))?;
 
     let status = response.status();
     if !status.is_success() {
         let body = response.text().await.unwrap_or_default();
-        return Err(AppError::Upstream(format!(
-            "copilot entitlement validation failed (status {}): {}",
-            status.as_u16(),
-            body.trim()
+        return Err(AppError::Upstream(UpstreamError::from_status(
+            status,
+            format!(
+                "copilot entitlement validation failed (status {}): {}",
+                status.as_u16(),
+                body.trim()
+            ),
         )));
     }
 
-    let payload: Value = response.json().await
-        .map_err(|e| AppError::Upstream(format!("failed to parse copilot entitlement response: {}", e)))?;
+    let payload: Value = response.json().await.map_err(|e| {
+        UpstreamError::from_reqwest("failed to parse copilot entitlement response", &e)
+    })?;
     let token = payload
         .get("token")
         .and_then(Value::as_str)
         .map(str::trim)
         .unwrap_or_default();
     if token.is_empty() {
-        return Err(AppError::Upstream("copilot entitlement response missing token".to_string()));
+        return Err(AppError::Upstream(UpstreamError::other(
+            "copilot entitlement response missing token",
+        )));
     }
 
     Ok(())
@@ -1046,7 +1095,8 @@ async fn process_github_copilot_device_flow(
     oauth_state: &str,
     device_code: &DeviceCodeResponse,
 ) -> AppResult<()> {
-    let client = reqwest::Client::builder()
+    let client = login_client_builder(state, "github-copilot")
+        .await?
         .timeout(Duration::from_secs(30))
         .build()
         .map_err(|e| AppError::Internal(anyhow::anyhow!("failed to build HTTP client: {e}")))?;
@@ -1272,7 +1322,7 @@ async fn process_codex_manual_callback(
         code_verifier,
     };
 
-    let client = reqwest::Client::new();
+    let client = login_client(state, "codex").await?;
     let bundle =
         exchange_code_for_tokens_with_redirect(&client, code, codex::REDIRECT_URI, &pkce_codes)
             .await
@@ -1430,7 +1480,11 @@ pub async fn build_builder_id_start_response(
     let redirect_uri = builder_id_redirect_uri(cfg.port);
     drop(cfg);
 
-    let sso_client = SSOOIDCClient::new();
+    let sso_client = SSOOIDCClient::with_client(
+        login_client(&state, "kiro")
+            .await
+            .map_err(|error| error.to_string())?,
+    );
     let code_verifier = crate::auth::kiro_login::generate_code_verifier();
     let code_challenge = crate::auth::kiro_login::generate_code_challenge(&code_verifier);
     let session_id = uuid::Uuid::new_v4().to_string();
@@ -1555,7 +1609,7 @@ async fn process_builder_id_callback(
     let client_id = required_context_string(&context, "client_id")?;
     let client_secret = required_context_string(&context, "client_secret")?;
     let redirect_uri = required_context_string(&context, "redirect_uri")?;
-    let sso_client = SSOOIDCClient::new();
+    let sso_client = SSOOIDCClient::with_client(login_client(state, "kiro").await?);
     let token_resp = sso_client
         .create_token_with_auth_code(
             &client_id,
@@ -0,0 +1,737 @@
+//! OpenAI Responses API shaping — converts routed chat completions into `response`
+//! objects and typed `response.*` stream events.
+
+use bytes::Bytes;
+use futures::StreamExt;
+use serde_json::{json, Value};
+
+use crate::models::{ChatCompletionResponse, MessageContent};
+use crate::providers::BoxStream;
+use crate::proxy::translate::{self, message_thinking};
+
+fn response_id(id: &str) -> String {
+    if id.starts_with("resp_") {
+        id.to_string()
+    } else {
+        format!("resp_{}", uuid::Uuid::new_v4().simple())
+    }
+}
+
+fn item_id(prefix: &str) -> String {
+    format!("{prefix}_{}", uuid::Uuid::new_v4().simple())
+}
+
+fn usage_value(input_tokens: u64, output_tokens: u64) -> Value {
+    json!({
+        "input_tokens": input_tokens,
+        "output_tokens": output_tokens,
+        "total_tokens": input_tokens + output_tokens,
+    })
+}
+
+/// `status` and `incomplete_details` for an OpenAI `finish_reason`.
+fn completion_status(finish_reason: Option<&str>) -> (&'static str, Value) {
+    match finish_reason {
+        Some("length") => ("incomplete", json!({"reason": "max_output_tokens"})),
+        Some("content_filter") => ("incomplete", json!({"reason": "content_filter"})),
+        _ => ("completed", Value::Null),
+    }
+}
+
+fn reasoning_item(id: String, text: &str) -> Value {
+    json!({
+        "type": "reasoning",
+        "id": id,
+        "summary": [{"type": "summary_text", "text": text}],
+    })
+}
+
+fn message_item(id: String, status: &str, text: &str) -> Value {
+    json!({
+        "type": "message",
+        "id": id,
+        "status": status,
+        "role": "assistant",
+        "content": [{"type": "output_text", "text": text, "annotations": []}],
+    })
+}
+
+fn function_call_item(
+    id: String,
+    status: &str,
+    call_id: &str,
+    name: &str,
+    arguments: &str,
+) -> Value {
+    json!({
+        "type": "function_call",
+        "id": id,
+        "status": status,
+        "call_id": call_id,
+        "name": name,
+        "arguments": arguments,
+    })
+}
+
+/// Output items for the first choice of a routed chat completion.
+fn completion_output_items(response: &ChatCompletionResponse) -> Vec<Value> {
+    let mut output = Vec::new();
+    let Some(message) = response
+        .choices
+        .first()
+        .and_then(|choice| choice.message.as_ref())
+    else {
+        return output;
+    };
+
+    if let Some(thinking) = message_thinking(message) {
+        output.push(reasoning_item(item_id("rs"), &thinking));
+    }
+
+    let text = match &message.content {
+        MessageContent::Text(text) => text.clone(),
+        MessageContent::Parts(parts) => parts
+            .iter()
+            .filter_map(|part| part.text.as_deref())
+            .collect::<Vec<_>>()
+            .join(""),
+    };
+    if !text.is_empty() {
+        output.push(message_item(item_id("msg"), "completed", &text));
+    }
+
+    for call in message.tool_calls.iter().flatten() {
+        let function = call.get("function");
+        let arguments = match function.and_then(|function| function.get("arguments")) {
+            Some(Value::String(arguments)) => arguments.clone(),
+            Some(Value::Null) | None => String::new(),
+            Some(other) => other.to_string(),
+        };
+        output.push(function_call_item(
+            item_id("fc"),
+            "completed",
+            call.get("id").and_then(Value::as_str).unwrap_or_default(),
+            function
+                .and_then(|function| function.get("name"))
+                .and_then(Value::as_str)
+                .unwrap_or_default(),
+            &arguments,
+        ));
+    }
+
+    output
+}
+
+/// Shape a routed chat completion into a Responses API `response` object.
+pub fn chat_response_to_responses(response: &ChatCompletionResponse, model: &str) -> Value {
+    let finish_reason = response
+        .choices
+        .first()
+        .and_then(|choice| choice.finish_reason.as_deref());
+    let (status, incomplete_details) = completion_status(finish_reason);
+    let usage = response.usage.clone().unwrap_or_default();
+
+    json!({
+        "id": response_id(&response.id),
+        "object": "response",
+        "created_at": response.created,
+        "status": status,
+        "incomplete_details": incomplete_details,
+        "error": null,
+        "model": model,
+        "output": completion_output_items(response),
+        "usage": usage_value(usage.prompt_tokens.into(), usage.completion_tokens.into()),
+    })
+}
+
+/// Shape a routed chat completion into a `/v1/responses/compact` reply.
+///
+/// Compaction is a one-shot request, so only the assistant `message` items are kept
+/// as the replacement history; reasoning and tool calls are dropped.
+pub fn chat_response_to_compaction(response: &ChatCompletionResponse, model: &str) -> Value {
+    let output = completion_output_items(response)
+        .into_iter()
+        .filter(|item| item["type"].as_str() == Some("message"))
+        .collect::<Vec<_>>();
+    let usage = response.usage.clone().unwrap_or_default();
+
+    json!({
+        "id": response_id(&response.id),
+        "object": "response.compaction",
+        "created_at": response.created,
+        "model": model,
+        "output": output,
+        "usage": usage_value(usage.prompt_tokens.into(), usage.completion_tokens.into()),
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OpenItem {
+    Reasoning,
+    Message,
+    FunctionCall(u64),
+}
+
+/// An output item being assembled from stream deltas.
+struct StreamItem {
+    kind: OpenItem,
+    id: String,
+    call_id: String,
+    name: String,
+    text: String,
+}
+
+impl StreamItem {
+    fn to_value(&self, status: &str) -> Value {
+        match self.kind {
+            OpenItem::Reasoning => reasoning_item(self.id.clone(), &self.text),
+            OpenItem::Message => message_item(self.id.clone(), status, &self.text),
+            OpenItem::FunctionCall(_) => function_call_item(
+                self.id.clone(),
+                status,
+                &self.call_id,
+                &self.name,
+                &self.text,
+            ),
+        }
+    }
+}
+
+/// Incremental translator from OpenAI `chat.completion.chunk` payloads to Responses API
+/// stream events.
+pub struct ResponsesStreamTranslator {
+    id: String,
+    model: String,
+    created_at: i64,
+    started: bool,
+    finished: bool,
+    sequence_number: u64,
+    items: Vec<StreamItem>,
+    open_item: Option<usize>,
+    finish_reason: Option<String>,
+    input_tokens: u64,
+    output_tokens: u64,
+}
+
+impl ResponsesStreamTranslator {
+    pub fn new(model: impl Into<String>) -> Self {
+        Self {
+            id: format!("resp_{}", uuid::Uuid::new_v4().simple()),
+            model: model.into(),
+            created_at: chrono::Utc::now().timestamp(),
+            started: false,
+            finished: false,
+            sequence_number: 0,
+            items: Vec::new(),
+            open_item: None,
+            finish_reason: None,
+            input_tokens: 0,
+            output_tokens: 0,
+        }
+    }
+
+    fn event(&mut self, name: &str, mut data: Value) -> String {
+        data["type"] = Value::String(name.to_string());
+        data["sequence_number"] = json!(self.sequence_number);
+        self.sequence_number += 1;
+        format!("event: {name}\ndata: {data}\n\n")
+    }
+
+    fn response_value(&self, status: &str) -> Value {
+        let (incomplete_details, output, usage) = if status == "in_progress" {
+            (Value::Null, Vec::new(), Value::Null)
+        } else {
+            (
+                completion_status(self.finish_reason.as_deref()).1,
+                self.items
+                    .iter()
+                    .map(|item| item.to_value("completed"))
+                    .collect(),
+                usage_value(self.input_tokens, self.output_tokens),
+            )
+        };
+        json!({
+            "id": self.id,
+            "object": "response",
+            "created_at": self.created_at,
+            "status": status,
+            "incomplete_details": incomplete_details,
+            "error": null,
+            "model": self.model,
+            "output": output,
+            "usage": usage,
+        })
+    }
+
+    fn ensure_started(&mut self, events: &mut Vec<String>) {
+        if self.started {
+            return;
+        }
+        self.started = true;
+        let response = self.response_value("in_progress");
+        events.push(self.event("response.created", json!({"response": response})));
+        events.push(self.event("response.in_progress", json!({"response": response})));
+    }
+
+    fn close_open_item(&mut self, events: &mut Vec<String>) {
+        let Some(output_index) = self.open_item.take() else {
+            return;
+        };
+        let item = &self.items[output_index];
+        let (id, text, kind) = (item.id.clone(), item.text.clone(), item.kind);
+        let done_item = item.to_value("completed");
+
+        match kind {
+            OpenItem::Reasoning => {
+                let part = json!({"type": "summary_text", "text": text});
+                let base = json!({"item_id": id, "output_index": output_index, "summary_index": 0});
+                let mut text_done = base.clone();
+                text_done["text"] = Value::String(text);
+                events.push(self.event("response.reasoning_summary_text.done", text_done));
+                let mut part_done = base;
+                part_done["part"] = part;
+                events.push(self.event("response.reasoning_summary_part.done", part_done));
+            }
+            OpenItem::Message => {
+                let part = json!({"type": "output_text", "text": text, "annotations": []});
+                let base = json!({"item_id": id, "output_index": output_index, "content_index": 0});
+                let mut text_done = base.clone();
+                text_done["text"] = Value::String(text);
+                events.push(self.event("response.output_text.done", text_done));
+                let mut part_done = base;
+                part_done["part"] = part;
+                events.push(self.event("response.content_part.done", part_done));
+            }
+            OpenItem::FunctionCall(_) => {
+                events.push(self.event(
+                    "response.function_call_arguments.done",
+                    json!({"item_id": id, "output_index": output_index, "arguments": text}),
+                ));
+            }
+        }
+
+        events.push(self.event(
+            "response.output_item.done",
+            json!({"output_index": output_index, "item": done_item}),
+        ));
+    }
+
+    /// Return the output index of the open item of `kind`, opening a new one if needed.
+    fn open_item(
+        &mut self,
+        kind: OpenItem,
+        call_id: &str,
+        name: &str,
+        events: &mut Vec<String>,
+    ) -> usize {
+        if let Some(output_index) = self.open_item {
+            if self.items[output_index].kind == kind {
+                return output_index;
+            }
+        }
+        self.close_open_item(events);
+
+        let prefix = match kind {
+            OpenItem::Reasoning => "rs",
+            OpenItem::Message => "msg",
+            OpenItem::FunctionCall(_) => "fc",
+        };
+        let item = StreamItem {
+            kind,
+            id: item_id(prefix),
+            call_id: call_id.to_string(),
+            name: name.to_string(),
+            text: String::new(),
+        };
+        let output_index = self.items.len();
+        let (id, mut added_item) = (item.id.clone(), item.to_value("in_progress"));
+        self.items.push(item);
+        self.open_item = Some(output_index);
+
+        match kind {
+            OpenItem::Reasoning => added_item["summary"] = json!([]),
+            OpenItem::Message => added_item["content"] = json!([]),
+            OpenItem::FunctionCall(_) => {}
+        }
+        events.push(self.event(
+            "response.output_item.added",
+            json!({"output_index": output_index, "item": added_item}),
+        ));
+
+        match kind {
+            OpenItem::Reasoning => events.push(self.event(
+                "response.reasoning_summary_part.added",
+                json!({
+                    "item_id": id,
+                    "output_index": output_index,
+                    "summary_index": 0,
+                    "part": {"type": "summary_text", "text": ""},
+                }),
+            )),
+            OpenItem::Message => events.push(self.event(
+                "response.content_part.added",
+                json!({
+                    "item_id": id,
+                    "output_index": output_index,
+                    "content_index": 0,
+                    "part": {"type": "output_text", "text": "", "annotations": []},
+                }),
+            )),
+            OpenItem::FunctionCall(_) => {}
+        }
+
+        output_index
+    }
+
+    fn push_delta(&mut self, output_index: usize, delta: &str, events: &mut Vec<String>) {
+        let item = &mut self.items[output_index];
+        item.text.push_str(delta);
+        let id = item.id.clone();
+        let event = match item.kind {
+            OpenItem::Reasoning => self.event(
+                "response.reasoning_summary_text.delta",
+                json!({"item_id": id, "output_index": output_index, "summary_index": 0, "delta": delta}),
+            ),
+            OpenItem::Message => self.event(
+                "response.output_text.delta",
+                json!({"item_id": id, "output_index": output_index, "content_index": 0, "delta": delta}),
+            ),
+            OpenItem::FunctionCall(_) => self.event(
+                "response.function_call_arguments.delta",
+                json!({"item_id": id, "output_index": output_index, "delta": delta}),
+            ),
+        };
+        events.push(event);
+    }
+
+    fn record_usage(&mut self, usage: &Value) {
+        translate::record_usage(usage, &mut self.input_tokens, &mut self.output_tokens);
+    }
+
+    /// Translate one upstream `data:` payload into zero or more Responses SSE events.
+    pub fn push_data(&mut self, data: &str) -> Vec<String> {
+        let mut events = Vec::new();
+        if self.finished {
+            return events;
+        }
+
+        if data == "[DONE]" {
+            return self.finish();
+        }
+
+        let Ok(chunk) = serde_json::from_str::<Value>(data) else {
+            return events;
+        };
+
+        if let Some(error) = chunk.get("error") {
+            self.ensure_started(&mut events);
+            let message = error
+                .get("message")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .unwrap_or_else(|| error.to_string());
+            let mut response = self.response_value("failed");
+            response["error"] = json!({"code": "server_error", "message": message});
+            events.push(self.event("response.failed", json!({"response": response})));
+            self.finished = true;
+            return events;
+        }
+
+        if let Some(usage) = chunk.get("usage").filter(|usage| usage.is_object()) {
+            self.record_usage(usage);
+        }
+
+        self.ensure_started(&mut events);
+
+        let Some(choice) = chunk
+            .get("choices")
+            .and_then(Value::as_array)
+            .and_then(|choices| choices.first())
+        else {
+            return events;
+        };
+        let delta = choice.get("delta").cloned().unwrap_or(Value::Null);
+
+        for key in ["reasoning_content", "thinking"] {
+            if let Some(thinking) = delta.get(key).and_then(Value::as_str) {
+                if !thinking.is_empty() {
+                    let index = self.open_item(OpenItem::Reasoning, "", "", &mut events);
+                    self.push_delta(index, thinking, &mut events);
+                }
+            }
+        }
+
+        if let Some(text) = delta.get("content").and_then(Value::as_str) {
+            if !text.is_empty() {
+                let index = self.open_item(OpenItem::Message, "", "", &mut events);
+                self.push_delta(index, text, &mut events);
+            }
+        }
+
+        for call in delta
+            .get("tool_calls")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            let call_index = call.get("index").and_then(Value::as_u64).unwrap_or(0);
+            let function = call.get("function");
+            let call_id = call
+                .get("id")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("call_{}", uuid::Uuid::new_v4().simple()));
+            let name = function
+                .and_then(|function| function.get("name"))
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            let index = self.open_item(
+                OpenItem::FunctionCall(call_index),
+                &call_id,
+                name,
+                &mut events,
+            );
+
+            let arguments = match function.and_then(|function| function.get("arguments")) {
+                Some(Value::String(arguments)) => arguments.clone(),
+                Some(Value::Null) | None => String::new(),
+                Some(other) => other.to_string(),
+            };
+            if !arguments.is_empty() {
+                self.push_delta(index, &arguments, &mut events);
+            }
+        }
+
+        if let Some(finish_reason) = choice.get("finish_reason").and_then(Value::as_str) {
+            self.finish_reason = Some(finish_reason.to_string());
+        }
+
+        events
+    }
+
+    /// Close any open item and emit the terminal `response.completed` or
+    /// `response.incomplete` event.
+    pub fn finish(&mut self) -> Vec<String> {
+        let mut events = Vec::new();
+        if self.finished {
+            return events;
+        }
+        self.ensure_started(&mut events);
+        self.close_open_item(&mut events);
+        self.finished = true;
+
+        let (status, _) = completion_status(self.finish_reason.as_deref());
+        let response = self.response_value(status);
+        let name = if status == "incomplete" {
+            "response.incomplete"
+        } else {
+            "response.completed"
+        };
+        events.push(self.event(name, json!({"response": response})));
+        events
+    }
+}
+
+/// Re-shape an OpenAI SSE stream into a Responses API SSE stream.
+pub fn responses_sse_stream(upstream: BoxStream, model: String) -> BoxStream {
+    let stream = async_stream::stream! {
+        let mut translator = ResponsesStreamTranslator::new(model);
+        let payloads = crate::proxy::stream::sse_data_payloads(upstream);
+        futures::pin_mut!(payloads);
+
+        while let Some(payload) = payloads.next().await {
+            let events = match payload {
+                Ok(data) => translator.push_data(&data),
+                Err(error) => translator.push_data(
+                    &json!({"error": {"message": error.to_string()}}).to_string(),
+                ),
+            };
+            for event in events {
+                yield Ok(Bytes::from(event));
+            }
+        }
+
+        for event in translator.finish() {
+            yield Ok(Bytes::from(event));
+        }
+    };
+
+    Box::pin(stream)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ChatMessage, Choice, Usage};
+
+    fn completion(message: ChatMessage, finish_reason: &str) -> ChatCompletionResponse {
+        ChatCompletionResponse {
+            id: "chatcmpl-1".to_string(),
+            object: "chat.completion".to_string(),
+            created: 1_700_000_000,
+            model: "gpt-5-codex".to_string(),
+            choices: vec![Choice {
+                index: 0,
+                message: Some(message),
+                delta: None,
+                finish_reason: Some(finish_reason.to_string()),
+            }],
+            usage: Some(Usage {
+                prompt_tokens: 12,
+                completion_tokens: 7,
+                total_tokens: 19,
+            }),
+        }
+    }
+
+    #[test]
+    fn chat_response_maps_message_function_call_and_usage() {
+        let response = completion(
+            ChatMessage {
+                role: "assistant".to_string(),
+                content: MessageContent::Text("let me check".to_string()),
+                name: None,
+                tool_calls: Some(vec![json!({
+                    "id": "call_1",
+                    "type": "function",
+                    "function": {"name": "lookup", "arguments": "{\"q\":\"rain\"}"}
+                })]),
+                tool_call_id: None,
+                reasoning_content: None,
+            },
+            "tool_calls",
+        );
+
+        let body = chat_response_to_responses(&response, "gpt-5.4");
+
+        assert_eq!(body["object"], "response");
+        assert_eq!(body["status"], "completed");
+        assert_eq!(body["model"], "gpt-5.4");
+        assert!(body["id"].as_str().unwrap().starts_with("resp_"));
+        assert_eq!(body["output"][0]["type"], "message");
+        assert_eq!(body["output"][0]["content"][0]["type"], "output_text");
+        assert_eq!(body["output"][0]["content"][0]["text"], "let me check");
+        assert_eq!(body["output"][1]["type"], "function_call");
+        assert_eq!(body["output"][1]["call_id"], "call_1");
+        assert_eq!(body["output"][1]["arguments"], "{\"q\":\"rain\"}");
+        assert_eq!(body["usage"]["input_tokens"], 12);
+        assert_eq!(body["usage"]["total_tokens"], 19);
+    }
+
+    #[test]
+    fn truncated_chat_response_is_incomplete() {
+        let response = completion(
+            ChatMessage {
+                role: "assistant".to_string(),
+                content: MessageContent::Text("partial".to_string()),
+                name: None,
+                tool_calls: None,
+                tool_call_id: None,
+                reasoning_content: None,
+            },
+            "length",
+        );
+
+        let body = chat_response_to_responses(&response, "gpt-5.4");
+
+        assert_eq!(body["status"], "incomplete");
+        assert_eq!(body["incomplete_details"]["reason"], "max_output_tokens");
+    }
+
+    #[test]
+    fn compaction_keeps_only_message_items() {
+        let response = completion(
+            ChatMessage {
+                role: "assistant".to_string(),
+                content: MessageContent::Text("summary of the thread".to_string()),
+                name: None,
+                tool_calls: Some(vec![json!({
+                    "id": "call_1",
+                    "function": {"name": "lookup", "arguments": "{}"}
+                })]),
+                tool_call_id: None,
+                reasoning_content: None,
+            },
+            "stop",
+        );
+
+        let body = chat_response_to_compaction(&response, "gpt-5.4");
+
+        assert_eq!(body["object"], "response.compaction");
+        assert_eq!(body["output"].as_array().unwrap().len(), 1);
+        assert_eq!(
+            body["output"][0]["content"][0]["text"],
+            "summary of the thread"
+        );
+    }
+
+    #[test]
+    fn stream_translator_emits_item_lifecycle() {
+        let mut translator = ResponsesStreamTranslator::new("gpt-5.4");
+        let mut events = Vec::new();
+        events.extend(translator.push_data(
+            &json!({"choices": [{"index": 0, "delta": {"role": "assistant", "content": "Hel"}}]})
+                .to_string(),
+        ));
+        events.extend(translator.push_data(
+            &json!({"choices": [{"index": 0, "delta": {"content": "lo"}}]}).to_string(),
+        ));
+        events.extend(translator.push_data(
+            &json!({"choices": [{"index": 0, "delta": {"tool_calls": [{
+                "index": 0, "id": "call_1", "function": {"name": "lookup", "arguments": "{\"q\":"}
+            }]}}]})
+            .to_string(),
+        ));
+        events.extend(
+            translator.push_data(
+                &json!({"choices": [{"index": 0, "delta": {"tool_calls": [{
+                "index": 0, "function": {"arguments": "\"rain\"}"}
+            }]}, "finish_reason": "tool_calls"}]})
+                .to_string(),
+            ),
+        );
+        events.extend(translator.push_data("[DONE]"));
+        events.extend(translator.finish());
+
+        let names = events
+            .iter()
+            .map(|event| event.lines().next().unwrap().trim_start_matches("event: "))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                "response.created",
+                "response.in_progress",
+                "response.output_item.added",
+                "response.content_part.added",
+                "response.output_text.delta",
+                "response.output_text.delta",
+                "response.output_text.done",
+                "response.content_part.done",
+                "response.output_item.done",
+                "response.output_item.added",
+                "response.function_call_arguments.delta",
+                "response.function_call_arguments.delta",
+                "response.function_call_arguments.done",
+                "response.output_item.done",
+                "response.completed",
+            ]
+        );
+        assert!(events[6].contains("\"text\":\"Hello\""));
+        assert!(events[12].contains("\"arguments\":\"{\\\"q\\\":\\\"rain\\\"}\""));
+
+        let completed = events.last().unwrap();
+        let data: Value = serde_json::from_str(
+            completed
+                .lines()
+                .nth(1)
+                .unwrap()
+                .trim_start_matches("data: "),
+        )
+        .unwrap();
+        assert_eq!(data["sequence_number"], 14);
+        assert_eq!(data["response"]["status"], "completed");
+        assert_eq!(data["response"]["output"][0]["content"][0]["text"], "Hello");
+        assert_eq!(data["response"]["output"][1]["call_id"], "call_1");
+    }
+}
@@ -0,0 +1,253 @@
+//! Optional on-disk snapshot of the routing state a restart would otherwise forget:
+//! sticky sessions, Kiro cooldowns and backoff, and model quota marks.
+//!
+//! Deadlines are stored as wall-clock times and turned back into `Instant`s on
+//! restore; anything that expired while the server was down is dropped.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use super::ProxyState;
+use crate::error::{AppError, AppResult};
+use crate::providers::model_registry::QuotaMarkEntry;
+
+/// File name inside auth-dir. It has no `.json` extension so the account scan
+/// does not take it for a credential file.
+pub const STATE_FILE_NAME: &str = ".rusuh-state";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PersistedState {
+    pub sessions: Vec<PersistedSession>,
+    pub cooldowns: Vec<PersistedCooldown>,
+    pub backoffs: Vec<PersistedBackoff>,
+    pub quotas: Vec<PersistedQuota>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PersistedSession {
+    pub session_id: String,
+    pub auth_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PersistedCooldown {
+    pub auth_id: String,
+    pub model_id: String,
+    pub reason: String,
+    pub until: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PersistedBackoff {
+    pub token_key: String,
+    pub fail_count: usize,
+    pub until: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PersistedQuota {
+    pub client_id: String,
+    pub model_id: String,
+    pub until: DateTime<Utc>,
+    pub reset_known: bool,
+}
+
+/// Maps monotonic deadlines to wall-clock times and back around one instant.
+#[derive(Clone, Copy)]
+struct Clock {
+    now: Instant,
+    wall: DateTime<Utc>,
+}
+
+impl Clock {
+    fn now() -> Self {
+        Self {
+            now: Instant::now(),
+            wall: Utc::now(),
+        }
+    }
+
+    fn to_wall(self, deadline: Instant) -> DateTime<Utc> {
+        let remaining = deadline.saturating_duration_since(self.now);
+        chrono::Duration::from_std(remaining)
+            .ok()
+            .and_then(|remaining| self.wall.checked_add_signed(remaining))
+            .unwrap_or(DateTime::<Utc>::MAX_UTC)
+    }
+
+    /// `None` once `at` has passed.
+    fn to_instant(self, at: DateTime<Utc>) -> Option<Instant> {
+        (at - self.wall)
+            .to_std()
+            .ok()
+            .filter(|remaining| !remaining.is_zero())
+            .and_then(|remaining| self.now.checked_add(remaining))
+    }
+}
+
+impl ProxyState {
+    /// Capture the state worth keeping across a restart.
+    pub async fn persisted_state(&self) -> PersistedState {
+        let clock = Clock::now();
+        let sessions = self
+            .execution_sessions
+            .entries()
+            .into_iter()
+            .map(|(session_id, auth_id)| PersistedSession {
+                session_id,
+                auth_id,
+            })
+            .collect();
+        let cooldowns = self
+            .kiro_runtime
+            .cooldown
+            .read()
+            .await
+            .active_entries(clock.now)
+            .map(|(auth_id, model_id, entry)| PersistedCooldown {
+                auth_id: auth_id.to_string(),
+                model_id: model_id.to_string(),
+                reason: entry.reason.clone(),
+                until: clock.to_wall(entry.expires_at),
+            })
+            .collect();
+        let backoffs = self
+            .kiro_runtime
+            .rate_limiter
+            .read()
+            .await
+            .active_backoffs(clock.now)
+            .map(|(token_key, fail_count, until)| PersistedBackoff {
+                token_key: token_key.to_string(),
+                fail_count,
+                until: clock.to_wall(until),
+            })
+            .collect();
+        let quotas = self
+            .model_registry
+            .active_quota_marks()
+            .await
+            .into_iter()
+            .map(|mark| PersistedQuota {
+                client_id: mark.client_id,
+                model_id: mark.model_id,
+                until: clock.to_wall(mark.until),
+                reset_known: mark.reset_known,
+            })
+            .collect();
+
+        PersistedState {
+            sessions,
+            cooldowns,
+            backoffs,
+            quotas,
+        }
+    }
+
+    /// Reapply a snapshot once providers are registered, skipping expired entries
+    /// and accounts that no longer exist.
+    pub async fn restore_persisted_state(&self, saved: PersistedState) {
+        let clock = Clock::now();
+
+        for session in saved.sessions {
+            self.execution_sessions
+                .set_selected_auth(session.session_id, session.auth_id, true)
+                .await;
+        }
+        let client_ids: HashSet<String> = self
+            .current_runtime_snapshot()
+            .await
+            .providers()
+            .iter()
+            .map(|provider| provider.client_id().to_string())
+            .collect();
+        self.execution_sessions
+            .invalidate_unknown_selected_auths(&client_ids)
+            .await;
+
+        {
+            let mut cooldown = self.kiro_runtime.cooldown.write().await;
+            for entry in saved.cooldowns {
+                if let Some(until) = clock.to_instant(entry.until) {
+                    cooldown.set_cooldown(
+                        &entry.auth_id,
+                        &entry.model_id,
+                        until - clock.now,
+                        &entry.reason,
+                        clock.now,
+                    );
+                }
+            }
+        }
+        {
+            let mut rate_limiter = self.kiro_runtime.rate_limiter.write().await;
+            for backoff in saved.backoffs {
+                if let Some(until) = clock.to_instant(backoff.until) {
+                    rate_limiter.restore_backoff(&backoff.token_key, backoff.fail_count, until);
+                }
+            }
+        }
+        for quota in saved.quotas {
+            if let Some(until) = clock.to_instant(quota.until) {
+                self.model_registry
+                    .restore_quota_mark(QuotaMarkEntry {
+                        client_id: quota.client_id,
+                        model_id: quota.model_id,
+                        until,
+                        reset_known: quota.reset_known,
+                    })
+                    .await;
+            }
+        }
+    }
+}
+
+/// Read a snapshot; `None` when none has been written yet.
+pub async fn load(path: &Path) -> AppResult<Option<PersistedState>> {
+    let data = match tokio::fs::read_to_string(path).await {
+        Ok(data) => data,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => {
+            return Err(AppError::Config(format!("read {}: {e}", path.display())));
+        }
+    };
+    serde_json::from_str(&data)
+        .map(Some)
+        .map_err(|e| AppError::Config(format!("parse {}: {e}", path.display())))
+}
+
+/// Write the current state, replacing the previous snapshot atomically.
+pub async fn save(state: &ProxyState, path: &Path) -> AppResult<()> {
+    let json = serde_json::to_vec_pretty(&state.persisted_state().await)
+        .map_err(|e| AppError::Config(format!("serialize state: {e}")))?;
+    let tmp = path.with_extension("tmp");
+    tokio::fs::write(&tmp, json)
+        .await
+        .map_err(|e| AppError::Config(format!("write {}: {e}", tmp.display())))?;
+    tokio::fs::rename(&tmp, path)
+        .await
+        .map_err(|e| AppError::Config(format!("replace {}: {e}", path.display())))?;
+    debug!("saved routing state to {}", path.display());
+    Ok(())
+}
+
+/// Save the state every `period` until the runtime shuts down.
+pub fn spawn_writer(state: Arc<ProxyState>, path: PathBuf, period: Duration) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(period);
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            if let Err(e) = save(&state, &path).await {
+                warn!("failed to save routing state: {e}");
+            }
+        }
+    });
+}
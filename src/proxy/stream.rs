@@ -5,7 +5,7 @@ use bytes::Bytes;
 use futures::stream::{Stream, StreamExt};
 use serde_json::{json, Value};
 
-use crate::error::AppError;
+use crate::error::{ApiFamily, AppError, AppResult, UpstreamError, UpstreamErrorKind};
 use crate::providers::BoxStream;
 
 /// Wrap a raw byte stream (possibly chunked) into a properly buffered SSE stream
@@ -29,14 +29,8 @@ where
             let chunk = match chunk_result {
                 Ok(c) => c,
                 Err(e) => {
-                    // Emit error as SSE event and stop
-                    let err_event = format!(
-                        "data: {}\n\n",
-                        serde_json::to_string(&json!({
-                            "error": { "message": format!("stream read error: {e}"), "type": "stream_error" }
-                        })).unwrap_or_default()
-                    );
-                    yield Ok(Bytes::from(err_event));
+                    // Routing decides whether this fails over or ends the client stream
+                    yield Err(UpstreamError::from_reqwest("stream read error", &e).into());
                     break;
                 }
             };
@@ -102,10 +96,167 @@ where
     Box::pin(stream)
 }
 
+/// Error reported when a stream stops without `[DONE]` or a finish reason.
+pub const STREAM_CUT_OFF: &str = "upstream stream ended before the reply was complete";
+
+/// Split an already-normalized SSE byte stream back into its `data:` payloads.
+///
+/// Used by the Claude/Gemini surfaces to re-shape routed OpenAI chunks. `[DONE]` is
+/// yielded verbatim so callers can tell a clean end from a dropped connection.
+pub fn sse_data_payloads(
+    upstream: BoxStream,
+) -> std::pin::Pin<Box<dyn Stream<Item = Result<String, AppError>> + Send>> {
+    let stream = async_stream::stream! {
+        let mut buf = String::new();
+        let mut upstream = upstream;
+
+        while let Some(chunk_result) = upstream.next().await {
+            let chunk = match chunk_result {
+                Ok(c) => c,
+                Err(e) => {
+                    yield Err(e);
+                    return;
+                }
+            };
+
+            buf.push_str(&String::from_utf8_lossy(&chunk));
+
+            while let Some(newline_pos) = buf.find('\n') {
+                let line = buf[..newline_pos].trim().to_string();
+                buf = buf[newline_pos + 1..].to_string();
+
+                if let Some(data) = line
+                    .strip_prefix("data:")
+                    .map(str::trim)
+                    .filter(|data| !data.is_empty())
+                {
+                    yield Ok(data.to_string());
+                }
+            }
+        }
+
+        if let Some(data) = buf
+            .trim()
+            .strip_prefix("data:")
+            .map(str::trim)
+            .filter(|data| !data.is_empty())
+        {
+            yield Ok(data.to_string());
+        }
+    };
+
+    Box::pin(stream)
+}
+
+/// Hold a routed stream back until its first content chunk arrives.
+///
+/// An error, or an end before any content, comes back as `Err` so routing can still
+/// move on to the next candidate. Once content is through, a failure ends the stream
+/// with a well-formed error event in `family`'s format instead of a dropped body.
+pub async fn hold_until_content(
+    mut upstream: BoxStream,
+    family: ApiFamily,
+) -> AppResult<BoxStream> {
+    let mut held = Vec::new();
+    let mut buf = String::new();
+
+    'read: loop {
+        let chunk = match upstream.next().await {
+            Some(Ok(chunk)) => chunk,
+            Some(Err(e)) => return Err(e),
+            None => return Err(empty_stream_error()),
+        };
+        buf.push_str(&String::from_utf8_lossy(&chunk));
+        held.push(chunk);
+
+        while let Some(newline_pos) = buf.find('\n') {
+            let line: String = buf.drain(..=newline_pos).collect();
+            let Some(data) = line.trim().strip_prefix("data:").map(str::trim) else {
+                continue;
+            };
+            if data == "[DONE]" {
+                return Err(empty_stream_error());
+            }
+            let Ok(payload) = serde_json::from_str::<Value>(data) else {
+                continue;
+            };
+            if let Some(error) = payload.get("error") {
+                let message = error
+                    .get("message")
+                    .and_then(Value::as_str)
+                    .map(str::to_string)
+                    .unwrap_or_else(|| error.to_string());
+                return Err(UpstreamError::new(UpstreamErrorKind::Server, message).into());
+            }
+            if is_content_payload(&payload) {
+                break 'read;
+            }
+        }
+    }
+
+    let stream = async_stream::stream! {
+        for chunk in held {
+            yield Ok(chunk);
+        }
+        while let Some(chunk_result) = upstream.next().await {
+            match chunk_result {
+                Ok(chunk) => yield Ok(chunk),
+                Err(e) => {
+                    yield Ok(stream_error_event(&e, family));
+                    break;
+                }
+            }
+        }
+    };
+
+    Ok(Box::pin(stream))
+}
+
+fn empty_stream_error() -> AppError {
+    UpstreamError::other("upstream stream ended before any content").into()
+}
+
+/// Whether an OpenAI chunk or Anthropic event carries model output.
+fn is_content_payload(payload: &Value) -> bool {
+    if payload["type"] == "content_block_delta" {
+        return true;
+    }
+    payload["choices"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .any(|choice| {
+            ["content", "reasoning_content", "tool_calls"]
+                .into_iter()
+                .any(|key| match &choice["delta"][key] {
+                    Value::String(text) => !text.is_empty(),
+                    Value::Array(items) => !items.is_empty(),
+                    _ => false,
+                })
+        })
+}
+
+/// Terminal SSE event for a stream that failed after content was sent.
+fn stream_error_event(error: &AppError, family: ApiFamily) -> Bytes {
+    let message = error.to_string();
+    match family {
+        ApiFamily::Anthropic => Bytes::from(format!(
+            "event: error\ndata: {}\n\n",
+            json!({"type": "error", "error": {"type": "api_error", "message": message}})
+        )),
+        ApiFamily::OpenAi | ApiFamily::Gemini => Bytes::from(format!(
+            "data: {}\n\ndata: [DONE]\n\n",
+            json!({"error": {"message": message, "type": "stream_error"}})
+        )),
+    }
+}
+
 /// Build an Antigravity→OpenAI SSE transform function.
 ///
 /// Each Antigravity SSE `data:` payload is a JSON object with Gemini-like structure.
-/// This converts it into an OpenAI `chat.completion.chunk` JSON string.
+/// This converts it into an OpenAI `chat.completion.chunk` JSON string. The token
+/// counts in `usageMetadata` go out as `usage` on the finishing chunk, or on a
+/// chunk without choices when they arrive on their own.
 pub fn antigravity_to_openai_transform(
     id: String,
     model: String,
@@ -115,12 +266,24 @@ pub fn antigravity_to_openai_transform(
         let data: Value = serde_json::from_str(data_str).ok()?;
 
         let response = data.get("response").unwrap_or(&data);
+        let usage = response
+            .get("usageMetadata")
+            .or_else(|| data.get("usageMetadata"))
+            .map(gemini_usage_to_openai);
         let candidate = &response["candidates"][0];
         let parts = candidate["content"]["parts"].as_array();
 
-        let content = parts
-            .and_then(|p| p.iter().find_map(|part| part["text"].as_str()))
-            .unwrap_or("");
+        // Gemini marks reasoning parts with `thought: true`; they go out as reasoning_content.
+        let text_of = |thought: bool| -> String {
+            parts
+                .into_iter()
+                .flatten()
+                .filter(|part| part["thought"].as_bool().unwrap_or(false) == thought)
+                .filter_map(|part| part["text"].as_str())
+                .collect()
+        };
+        let content = text_of(false);
+        let reasoning = text_of(true);
 
         let finish = candidate["finishReason"].as_str().and_then(|r| match r {
             "STOP" => Some("stop"),
@@ -154,19 +317,30 @@ pub fn antigravity_to_openai_transform(
         });
 
         // Skip chunks with no content and no tool calls and no finish reason
-        if content.is_empty() && tool_calls.is_none() && finish.is_none() {
-            return None;
+        if content.is_empty() && reasoning.is_empty() && tool_calls.is_none() && finish.is_none() {
+            let chunk = json!({
+                "id": id,
+                "object": "chat.completion.chunk",
+                "created": created,
+                "model": model,
+                "choices": [],
+                "usage": usage?,
+            });
+            return serde_json::to_string(&chunk).ok();
         }
 
         let mut delta = json!({});
         if !content.is_empty() {
             delta["content"] = json!(content);
         }
+        if !reasoning.is_empty() {
+            delta["reasoning_content"] = json!(reasoning);
+        }
         if let Some(tc) = tool_calls {
             delta["tool_calls"] = json!(tc);
         }
 
-        let chunk = json!({
+        let mut chunk = json!({
             "id": id,
             "object": "chat.completion.chunk",
             "created": created,
@@ -177,8 +351,59 @@ pub fn antigravity_to_openai_transform(
                 "finish_reason": finish,
             }]
         });
+        if let (Some(usage), Some(_)) = (usage, finish) {
+            chunk["usage"] = usage;
+        }
+
+        serde_json::to_string(&chunk).ok()
+    }
+}
+
+/// OpenAI `usage` object for a Gemini `usageMetadata`.
+fn gemini_usage_to_openai(metadata: &Value) -> Value {
+    let count = |key: &str| metadata[key].as_u64().unwrap_or(0);
+    let prompt = count("promptTokenCount");
+    let completion = count("candidatesTokenCount");
+    json!({
+        "prompt_tokens": prompt,
+        "completion_tokens": completion,
+        "total_tokens": prompt + completion,
+        "prompt_tokens_details": {"cached_tokens": count("cachedContentTokenCount")},
+    })
+}
+
+/// Rename the reasoning field of an OpenAI-style chunk to `reasoning_content`.
+///
+/// Upstreams disagree on the name (`reasoning`, `reasoning_text`, ...); clients only
+/// look for `reasoning_content`. Payloads that are not chunks pass through untouched.
+pub fn normalize_reasoning_chunk(data_str: &str) -> Option<String> {
+    let Ok(mut chunk) = serde_json::from_str::<Value>(data_str) else {
+        return Some(data_str.to_string());
+    };
+
+    let mut changed = false;
+    if let Some(choices) = chunk.get_mut("choices").and_then(Value::as_array_mut) {
+        for delta in choices
+            .iter_mut()
+            .filter_map(|choice| choice.get_mut("delta").and_then(Value::as_object_mut))
+        {
+            if delta.contains_key("reasoning_content") {
+                continue;
+            }
+            let alias = ["reasoning", "reasoning_text", "thinking"]
+                .into_iter()
+                .find(|key| delta.get(*key).is_some_and(Value::is_string));
+            if let Some(text) = alias.and_then(|key| delta.remove(key)) {
+                delta.insert("reasoning_content".to_string(), text);
+                changed = true;
+            }
+        }
+    }
 
+    if changed {
         serde_json::to_string(&chunk).ok()
+    } else {
+        Some(data_str.to_string())
     }
 }
 
@@ -199,7 +424,8 @@ pub fn sse_response(stream: BoxStream) -> axum::response::Response {
 pub fn passthrough_sse_stream(
     upstream: impl Stream<Item = Result<Bytes, reqwest::Error>> + Send + 'static,
 ) -> BoxStream {
-    let stream =
-        upstream.map(|chunk| chunk.map_err(|e| AppError::Upstream(format!("stream read: {e}"))));
+    let stream = upstream.map(|chunk| {
+        chunk.map_err(|e| AppError::Upstream(UpstreamError::from_reqwest("stream read", &e)))
+    });
     Box::pin(stream)
 }
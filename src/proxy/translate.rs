@@ -0,0 +1,56 @@
+//! Helpers shared by the Claude, Gemini and Responses surface translators — building
+//! OpenAI chat messages from surface content and reading upstream usage counters.
+
+use serde_json::{json, Value};
+
+use crate::models::{ChatMessage, MessageContent};
+
+/// A plain text chat message with no tool or reasoning fields.
+pub(crate) fn text_message(role: &str, text: String) -> ChatMessage {
+    ChatMessage {
+        role: role.to_string(),
+        content: MessageContent::Text(text),
+        name: None,
+        tool_calls: None,
+        tool_call_id: None,
+        reasoning_content: None,
+    }
+}
+
+/// Parse OpenAI tool call arguments (a JSON string or object) into a JSON object.
+pub(crate) fn tool_arguments_value(arguments: Option<&Value>) -> Value {
+    match arguments {
+        Some(Value::String(raw)) if !raw.trim().is_empty() => {
+            serde_json::from_str(raw).unwrap_or_else(|_| json!({}))
+        }
+        Some(value @ Value::Object(_)) => value.clone(),
+        _ => json!({}),
+    }
+}
+
+/// The non-empty reasoning text carried by an assistant message, if any.
+pub(crate) fn message_thinking(message: &ChatMessage) -> Option<String> {
+    message
+        .reasoning_content
+        .clone()
+        .filter(|text| !text.is_empty())
+}
+
+/// Copy the prompt and completion counts from an OpenAI or Anthropic style usage
+/// object, leaving either counter untouched when the field is absent.
+pub(crate) fn record_usage(usage: &Value, input_tokens: &mut u64, output_tokens: &mut u64) {
+    if let Some(prompt) = usage
+        .get("prompt_tokens")
+        .or_else(|| usage.get("input_tokens"))
+        .and_then(Value::as_u64)
+    {
+        *input_tokens = prompt;
+    }
+    if let Some(completion) = usage
+        .get("completion_tokens")
+        .or_else(|| usage.get("output_tokens"))
+        .and_then(Value::as_u64)
+    {
+        *output_tokens = completion;
+    }
+}
@@ -0,0 +1,311 @@
+//! Token counts reported by upstream replies, read from complete responses and
+//! from the `usage` objects that OpenAI chunks, Anthropic events and Responses
+//! API `response.completed` events carry, and how a streamed reply ended.
+
+use bytes::Bytes;
+use futures::StreamExt;
+use serde_json::Value;
+
+use crate::models::Usage;
+use crate::providers::BoxStream;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TokenUsage {
+    pub prompt: u64,
+    pub completion: u64,
+    /// Prompt tokens served from the upstream's prompt cache
+    pub cached: u64,
+}
+
+impl TokenUsage {
+    pub fn total(&self) -> u64 {
+        self.prompt + self.completion
+    }
+
+    /// Counts from an OpenAI or Anthropic `usage` object.
+    pub fn from_value(usage: &Value) -> Option<Self> {
+        let count = |keys: &[&str]| {
+            keys.iter()
+                .find_map(|key| usage.pointer(key).and_then(Value::as_u64))
+        };
+        let prompt = count(&["/prompt_tokens", "/input_tokens"]);
+        let completion = count(&["/completion_tokens", "/output_tokens"]);
+        if prompt.is_none() && completion.is_none() {
+            return None;
+        }
+        Some(Self {
+            prompt: prompt.unwrap_or(0),
+            completion: completion.unwrap_or(0),
+            cached: count(&[
+                "/prompt_tokens_details/cached_tokens",
+                "/input_tokens_details/cached_tokens",
+                "/cache_read_input_tokens",
+            ])
+            .unwrap_or(0),
+        })
+    }
+
+    /// Keep the larger of each count; streamed usage objects are cumulative.
+    fn merge(&mut self, other: Self) {
+        self.prompt = self.prompt.max(other.prompt);
+        self.completion = self.completion.max(other.completion);
+        self.cached = self.cached.max(other.cached);
+    }
+}
+
+impl From<&Usage> for TokenUsage {
+    fn from(usage: &Usage) -> Self {
+        Self {
+            prompt: usage.prompt_tokens.into(),
+            completion: usage.completion_tokens.into(),
+            cached: 0,
+        }
+    }
+}
+
+/// How an observed reply ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplyEnd {
+    /// A complete reply, or a stream that reached its final event
+    Complete,
+    /// The stream carried an error, or the upstream stopped before the final event
+    Failed,
+    /// The client went away before the stream ended
+    Abandoned,
+}
+
+impl ReplyEnd {
+    /// Status to record for the request; 499 is nginx's "client closed request".
+    pub fn status(self) -> u16 {
+        match self {
+            Self::Complete => 200,
+            Self::Failed => 502,
+            Self::Abandoned => 499,
+        }
+    }
+}
+
+/// Usage and outcome seen so far on a stream; reports once when dropped.
+struct UsageTap {
+    usage: TokenUsage,
+    buf: String,
+    /// An error event or chunk went by
+    failed: bool,
+    /// `[DONE]`, a `finish_reason`, `message_stop` or `response.completed` went by
+    finished: bool,
+    /// The upstream stream ran to its end
+    exhausted: bool,
+    report: Option<Box<dyn FnOnce(TokenUsage, ReplyEnd) + Send>>,
+}
+
+impl UsageTap {
+    fn feed(&mut self, chunk: &Bytes) {
+        self.buf.push_str(&String::from_utf8_lossy(chunk));
+        while let Some(newline_pos) = self.buf.find('\n') {
+            let line: String = self.buf.drain(..=newline_pos).collect();
+            let Some(data) = line.trim().strip_prefix("data:").map(str::trim) else {
+                continue;
+            };
+            if data == "[DONE]" {
+                self.finished = true;
+                continue;
+            }
+            let Ok(payload) = serde_json::from_str::<Value>(data) else {
+                continue;
+            };
+            if payload.get("error").is_some() || payload["type"] == "error" {
+                self.failed = true;
+            }
+            if is_final_payload(&payload) {
+                self.finished = true;
+            }
+            let usage = payload
+                .get("usage")
+                .or_else(|| payload.pointer("/message/usage"))
+                .or_else(|| payload.pointer("/response/usage"))
+                .and_then(TokenUsage::from_value);
+            if let Some(usage) = usage {
+                self.usage.merge(usage);
+            }
+        }
+    }
+
+    fn upstream_ended(&mut self) {
+        self.exhausted = true;
+    }
+
+    fn end(&self) -> ReplyEnd {
+        if self.failed || (self.exhausted && !self.finished) {
+            ReplyEnd::Failed
+        } else if self.finished {
+            ReplyEnd::Complete
+        } else {
+            ReplyEnd::Abandoned
+        }
+    }
+}
+
+/// Whether an OpenAI chunk or Anthropic / Responses API event closes the reply.
+fn is_final_payload(payload: &Value) -> bool {
+    if matches!(
+        payload["type"].as_str(),
+        Some("message_stop" | "response.completed")
+    ) {
+        return true;
+    }
+    payload["choices"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .any(|choice| choice["finish_reason"].is_string())
+}
+
+impl Drop for UsageTap {
+    fn drop(&mut self) {
+        if let Some(report) = self.report.take() {
+            report(self.usage, self.end());
+        }
+    }
+}
+
+/// Pass `stream` through unchanged while collecting its usage. `report` runs once
+/// with the totals and how the stream ended, when it ends or the client goes away.
+pub fn observe_stream(
+    mut stream: BoxStream,
+    report: impl FnOnce(TokenUsage, ReplyEnd) + Send + 'static,
+) -> BoxStream {
+    let mut tap = UsageTap {
+        usage: TokenUsage::default(),
+        buf: String::new(),
+        failed: false,
+        finished: false,
+        exhausted: false,
+        report: Some(Box::new(report)),
+    };
+    Box::pin(async_stream::stream! {
+        while let Some(chunk) = stream.next().await {
+            match &chunk {
+                Ok(bytes) => tap.feed(bytes),
+                Err(_) => tap.failed = true,
+            }
+            yield chunk;
+        }
+        tap.upstream_ended();
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn reads_openai_and_anthropic_usage_objects() {
+        let openai = json!({
+            "prompt_tokens": 12,
+            "completion_tokens": 5,
+            "prompt_tokens_details": {"cached_tokens": 8}
+        });
+        assert_eq!(
+            TokenUsage::from_value(&openai),
+            Some(TokenUsage {
+                prompt: 12,
+                completion: 5,
+                cached: 8
+            })
+        );
+        let anthropic = json!({"input_tokens": 3, "output_tokens": 9});
+        assert_eq!(TokenUsage::from_value(&anthropic).unwrap().total(), 12);
+        assert_eq!(TokenUsage::from_value(&json!({})), None);
+    }
+
+    #[tokio::test]
+    async fn stream_reports_cumulative_anthropic_usage_once() {
+        let events = [
+            "event: message_start\ndata: {\"type\":\"message_start\",\"message\":{\"usage\":{\"input_tokens\":20,\"output_tokens\":1}}}\n\n",
+            "event: message_delta\ndata: {\"type\":\"message_delta\",\"usage\":{\"output_tokens\":7}}\n\n",
+        ];
+        let upstream: BoxStream = Box::pin(futures::stream::iter(
+            events.map(|event| Ok(Bytes::from(event))),
+        ));
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = seen.clone();
+        let stream = observe_stream(upstream, move |usage, end| {
+            sink.lock().unwrap().push((usage, end))
+        });
+
+        let chunks: Vec<_> = stream.collect().await;
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![(
+                TokenUsage {
+                    prompt: 20,
+                    completion: 7,
+                    cached: 0
+                },
+                ReplyEnd::Failed
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn stream_reads_usage_from_responses_completed_event() {
+        let event = "event: response.completed\ndata: {\"type\":\"response.completed\",\"response\":{\"usage\":{\"input_tokens\":11,\"output_tokens\":4,\"input_tokens_details\":{\"cached_tokens\":3}}}}\n\n";
+        let upstream: BoxStream = Box::pin(futures::stream::iter([Ok(Bytes::from(event))]));
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = seen.clone();
+        let stream = observe_stream(upstream, move |usage, end| {
+            sink.lock().unwrap().push((usage, end))
+        });
+
+        let _: Vec<_> = stream.collect().await;
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![(
+                TokenUsage {
+                    prompt: 11,
+                    completion: 4,
+                    cached: 3
+                },
+                ReplyEnd::Complete
+            )]
+        );
+    }
+
+    /// How the stream of `events` ends when the client reads `read` chunks.
+    async fn stream_end(events: &[&'static str], read: usize) -> ReplyEnd {
+        let upstream: BoxStream = Box::pin(futures::stream::iter(
+            events
+                .iter()
+                .map(|event| Ok(Bytes::from(*event)))
+                .collect::<Vec<_>>(),
+        ));
+        let seen = Arc::new(Mutex::new(None));
+        let sink = seen.clone();
+        let stream = observe_stream(upstream, move |_, end| *sink.lock().unwrap() = Some(end));
+        let _: Vec<_> = stream.take(read).collect().await;
+        let end = seen.lock().unwrap().take();
+        end.expect("reported")
+    }
+
+    #[tokio::test]
+    async fn stream_end_tells_complete_failed_and_abandoned_streams_apart() {
+        let content = "data: {\"choices\":[{\"delta\":{\"content\":\"hi\"}}]}\n\n";
+        let error = "data: {\"error\":{\"message\":\"reset\"}}\n\ndata: [DONE]\n\n";
+        assert_eq!(
+            stream_end(&[content, "data: [DONE]\n\n"], usize::MAX).await,
+            ReplyEnd::Complete
+        );
+        assert_eq!(
+            stream_end(&[content, error], usize::MAX).await,
+            ReplyEnd::Failed
+        );
+        assert_eq!(stream_end(&[content], usize::MAX).await, ReplyEnd::Failed);
+        assert_eq!(
+            stream_end(&[content, "data: [DONE]\n\n"], 1).await,
+            ReplyEnd::Abandoned
+        );
+    }
+}
@@ -0,0 +1,332 @@
+//! Ledger of finished requests: the client key that asked, the account and
+//! provider that answered, the model, token counts, latency and status.
+//!
+//! Records are appended as JSON lines to a file in auth-dir and folded into
+//! per-day totals kept in memory. At startup the file is replayed to rebuild
+//! the totals, so queries never read the file.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+use crate::error::{AppError, AppResult};
+
+/// File name inside auth-dir. The `.jsonl` extension keeps it out of the account scan.
+pub const USAGE_FILE_NAME: &str = "usage-ledger.jsonl";
+
+/// One finished request.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageRecord {
+    pub at: DateTime<Utc>,
+    /// Label of the client key, for display; absent when auth is off
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    /// [`ApiKeyEntry::id`](crate::config::ApiKeyEntry::id) of the client key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_id: Option<String>,
+    /// Account that answered; absent when no account did
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    /// Public model that served the request, or the requested one if none did
+    pub model: String,
+    #[serde(default)]
+    pub prompt_tokens: u64,
+    #[serde(default)]
+    pub completion_tokens: u64,
+    #[serde(default)]
+    pub cached_tokens: u64,
+    /// Until the complete reply, or the end of the stream
+    pub latency_ms: u64,
+    pub status: u16,
+}
+
+/// Summed usage of a group of records.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct UsageTotals {
+    pub requests: u64,
+    /// Requests that ended with a non-success status
+    pub errors: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub cached_tokens: u64,
+    pub latency_ms: u64,
+}
+
+impl UsageTotals {
+    fn add(&mut self, other: &UsageTotals) {
+        self.requests += other.requests;
+        self.errors += other.errors;
+        self.prompt_tokens += other.prompt_tokens;
+        self.completion_tokens += other.completion_tokens;
+        self.cached_tokens += other.cached_tokens;
+        self.latency_ms += other.latency_ms;
+    }
+}
+
+impl From<&UsageRecord> for UsageTotals {
+    fn from(record: &UsageRecord) -> Self {
+        Self {
+            requests: 1,
+            errors: u64::from(!(200..300).contains(&record.status)),
+            prompt_tokens: record.prompt_tokens,
+            completion_tokens: record.completion_tokens,
+            cached_tokens: record.cached_tokens,
+            latency_ms: record.latency_ms,
+        }
+    }
+}
+
+/// Dimension usage can be grouped by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UsageGroup {
+    Day,
+    Key,
+    Account,
+    Model,
+}
+
+impl FromStr for UsageGroup {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "day" => Ok(Self::Day),
+            "key" => Ok(Self::Key),
+            "account" => Ok(Self::Account),
+            "model" => Ok(Self::Model),
+            other => Err(AppError::BadRequest(format!(
+                "unknown usage group '{other}' — use day, key, account or model"
+            ))),
+        }
+    }
+}
+
+/// Finest grain kept in memory; every grouping is a roll-up of these.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Bucket {
+    day: NaiveDate,
+    key_id: Option<String>,
+    /// Label of records written before keys had an id
+    legacy_key: Option<String>,
+    client_id: Option<String>,
+    provider: Option<String>,
+    model: String,
+}
+
+/// One row of a usage query: the group's values and its totals. Key and account
+/// are left out for requests that had none.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UsageRow {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub day: Option<NaiveDate>,
+    /// Latest label of the key
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account: Option<String>,
+    /// Provider type of the account, when grouped by account
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(flatten)]
+    pub totals: UsageTotals,
+}
+
+/// Days to include, both ends inclusive.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UsageRange {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+impl UsageRange {
+    fn contains(&self, day: NaiveDate) -> bool {
+        self.from.is_none_or(|from| day >= from) && self.to.is_none_or(|to| day <= to)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct UsageLedger {
+    totals: Mutex<HashMap<Bucket, UsageTotals>>,
+    /// Latest label seen for each key id
+    key_labels: Mutex<HashMap<String, String>>,
+    writer: Mutex<Option<mpsc::UnboundedSender<UsageRecord>>>,
+    writer_task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl UsageLedger {
+    /// A ledger that keeps totals for this run only.
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// Replay the ledger file at `path`, then append new records to it.
+    pub async fn open(path: &Path) -> AppResult<Self> {
+        let ledger = Self::in_memory();
+        match tokio::fs::read_to_string(path).await {
+            Ok(data) => {
+                for (line_no, line) in data.lines().enumerate() {
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    match serde_json::from_str::<UsageRecord>(line) {
+                        Ok(record) => ledger.add(&record),
+                        Err(e) => warn!("skipping {} line {}: {e}", path.display(), line_no + 1),
+                    }
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => {
+                return Err(AppError::Config(format!("read {}: {e}", path.display())));
+            }
+        }
+
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir)
+                .await
+                .map_err(|e| AppError::Config(format!("create {}: {e}", dir.display())))?;
+        }
+        let file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await
+            .map_err(|e| AppError::Config(format!("open {}: {e}", path.display())))?;
+        let (tx, rx) = mpsc::unbounded_channel();
+        let task = tokio::spawn(append_records(file, rx, path.display().to_string()));
+        Ok(Self {
+            writer: Mutex::new(Some(tx)),
+            writer_task: Mutex::new(Some(task)),
+            ..ledger
+        })
+    }
+
+    pub fn record(&self, record: UsageRecord) {
+        self.add(&record);
+        if let Some(writer) = self.writer.lock().unwrap().as_ref() {
+            // The writer only stops once the ledger is closed.
+            let _ = writer.send(record);
+        }
+    }
+
+    /// Write out the records still queued and stop appending to the file. Later
+    /// records only count towards the in-memory totals.
+    pub async fn close(&self) {
+        self.writer.lock().unwrap().take();
+        let task = self.writer_task.lock().unwrap().take();
+        if let Some(task) = task {
+            if let Err(e) = task.await {
+                warn!("usage ledger writer failed: {e}");
+            }
+        }
+    }
+
+    fn add(&self, record: &UsageRecord) {
+        if let (Some(key_id), Some(label)) = (&record.key_id, &record.key) {
+            self.key_labels
+                .lock()
+                .unwrap()
+                .insert(key_id.clone(), label.clone());
+        }
+        let bucket = Bucket {
+            day: record.at.date_naive(),
+            key_id: record.key_id.clone(),
+            legacy_key: record.key.clone().filter(|_| record.key_id.is_none()),
+            client_id: record.client_id.clone(),
+            provider: record.provider.clone(),
+            model: record.model.clone(),
+        };
+        self.totals
+            .lock()
+            .unwrap()
+            .entry(bucket)
+            .or_default()
+            .add(&record.into());
+    }
+
+    /// Totals within `range`, one row per distinct combination of `group_by`,
+    /// ordered by those values. No grouping gives a single overall row.
+    pub fn query(&self, group_by: &[UsageGroup], range: UsageRange) -> Vec<UsageRow> {
+        type RowKey = (
+            Option<NaiveDate>,
+            Option<(Option<String>, Option<String>)>,
+            Option<(Option<String>, Option<String>)>,
+            Option<String>,
+        );
+        let grouped = |group| group_by.contains(&group);
+        let mut rows: BTreeMap<RowKey, UsageTotals> = BTreeMap::new();
+        for (bucket, totals) in self.totals.lock().unwrap().iter() {
+            if !range.contains(bucket.day) {
+                continue;
+            }
+            let row_key = (
+                grouped(UsageGroup::Day).then_some(bucket.day),
+                grouped(UsageGroup::Key)
+                    .then(|| (bucket.key_id.clone(), bucket.legacy_key.clone())),
+                grouped(UsageGroup::Account)
+                    .then(|| (bucket.client_id.clone(), bucket.provider.clone())),
+                grouped(UsageGroup::Model).then(|| bucket.model.clone()),
+            );
+            rows.entry(row_key).or_default().add(totals);
+        }
+        let key_labels = self.key_labels.lock().unwrap();
+        rows.into_iter()
+            .map(|((day, key, account, model), totals)| {
+                let (key_id, legacy_key) = key.unzip();
+                let key_id = key_id.flatten();
+                let (account, provider) = account.unzip();
+                UsageRow {
+                    day,
+                    key: key_id
+                        .as_ref()
+                        .and_then(|id| key_labels.get(id).cloned())
+                        .or(legacy_key.flatten()),
+                    key_id,
+                    account: account.flatten(),
+                    provider: provider.flatten(),
+                    model,
+                    totals,
+                }
+            })
+            .collect()
+    }
+}
+
+async fn append_records(
+    mut file: tokio::fs::File,
+    mut rx: mpsc::UnboundedReceiver<UsageRecord>,
+    path: String,
+) {
+    while let Some(record) = rx.recv().await {
+        let mut line = match serde_json::to_vec(&record) {
+            Ok(line) => line,
+            Err(e) => {
+                warn!("failed to serialize usage record: {e}");
+                continue;
+            }
+        };
+        line.push(b'\n');
+        if let Err(e) = async {
+            file.write_all(&line).await?;
+            file.flush().await
+        }
+        .await
+        {
+            warn!("failed to append usage record to {path}: {e}");
+        }
+    }
+}
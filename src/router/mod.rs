@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
 use axum::{
+    middleware,
     routing::{get, post},
     Router,
 };
@@ -34,10 +35,18 @@ use crate::proxy::ProxyState;
 ///
 /// Health:
 ///   GET  /health
+///   GET  /metrics  (Prometheus; management key unless `metrics.public`)
 pub fn build_router(state: Arc<ProxyState>) -> Router {
     Router::new()
         // ── Health ────────────────────────────────────────────────────────────
         .route("/health", get(crate::proxy::handlers::health))
+        .route(
+            "/metrics",
+            get(crate::proxy::handlers::metrics).route_layer(middleware::from_fn_with_state(
+                state.clone(),
+                crate::proxy::management::metrics_auth,
+            )),
+        )
         // ── OpenAI-compatible ─────────────────────────────────────────────────
         .route("/v1/models", get(crate::proxy::handlers::list_models))
         .route(
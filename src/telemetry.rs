@@ -0,0 +1,114 @@
+//! OpenTelemetry span export and W3C trace-context propagation.
+//!
+//! The routing pipeline's `tracing` spans are bridged to OpenTelemetry and sent to
+//! an OTLP/HTTP collector. A `traceparent` sent by the caller parents the request
+//! span. Upstream calls carry the current trace on in their own headers only when
+//! `tracing.propagate-upstream` is set, since providers see those headers too.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use axum::http::{HeaderMap, HeaderName, HeaderValue, Request};
+use opentelemetry::propagation::{Extractor, Injector};
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::{SdkTracer, SdkTracerProvider};
+use opentelemetry_sdk::Resource;
+use tracing::{Span, Subscriber};
+use tracing_opentelemetry::{OpenTelemetryLayer, OpenTelemetrySpanExt};
+use tracing_subscriber::registry::LookupSpan;
+
+use crate::config::TracingConfig;
+
+static PROPAGATE_UPSTREAM: AtomicBool = AtomicBool::new(false);
+
+/// Build the OTLP exporter and the `tracing` layer that feeds it, and install the
+/// W3C trace-context propagator. Shut the returned provider down on exit so
+/// batched spans are flushed.
+pub fn otlp_layer<S>(
+    config: &TracingConfig,
+) -> anyhow::Result<(OpenTelemetryLayer<S, SdkTracer>, SdkTracerProvider)>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_http()
+        .with_endpoint(&config.otlp_endpoint)
+        .build()?;
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(
+            Resource::builder()
+                .with_service_name(config.service_name.clone())
+                .build(),
+        )
+        .build();
+    install_propagator();
+    set_propagate_upstream(config.propagate_upstream);
+    let layer = tracing_opentelemetry::layer().with_tracer(provider.tracer("rusuh"));
+    Ok((layer, provider))
+}
+
+/// Read and write `traceparent` / `tracestate` headers.
+pub fn install_propagator() {
+    opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+}
+
+/// Whether [`inject_context`] writes trace headers on upstream calls.
+pub fn set_propagate_upstream(enable: bool) {
+    PROPAGATE_UPSTREAM.store(enable, Ordering::Relaxed);
+}
+
+/// `make_span_with` for the HTTP trace layer: one `request` span per request,
+/// continuing the caller's trace when it sent a `traceparent`.
+pub fn request_span<B>(request: &Request<B>) -> Span {
+    let span = tracing::info_span!(
+        "request",
+        http.request.method = %request.method(),
+        url.path = request.uri().path(),
+        otel.kind = "server",
+    );
+    let parent = opentelemetry::global::get_text_map_propagator(|propagator| {
+        propagator.extract(&HeaderExtractor(request.headers()))
+    });
+    // Only fails when no OpenTelemetry layer is installed.
+    let _ = span.set_parent(parent);
+    span
+}
+
+/// Write `span`'s trace context into outgoing `headers`, when propagation
+/// upstream is on.
+pub fn inject_context(span: &Span, headers: &mut HeaderMap) {
+    if !PROPAGATE_UPSTREAM.load(Ordering::Relaxed) {
+        return;
+    }
+    let context = span.context();
+    opentelemetry::global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&context, &mut HeaderInjector(headers))
+    });
+}
+
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(HeaderName::as_str).collect()
+    }
+}
+
+struct HeaderInjector<'a>(&'a mut HeaderMap);
+
+impl Injector for HeaderInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(key.as_bytes()),
+            HeaderValue::from_str(&value),
+        ) {
+            self.0.insert(name, value);
+        }
+    }
+}
@@ -0,0 +1,74 @@
+//! Native HTTPS serving from the `tls` config block.
+//!
+//! The certificate and key are loaded once at startup and then polled for
+//! changes, so a renewed certificate is picked up without a restart.
+
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use anyhow::Context;
+use axum_server::tls_rustls::RustlsConfig;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use crate::config::TlsConfig;
+
+/// How often the cert/key files are checked for modification.
+pub const RELOAD_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Load the PEM certificate chain and private key referenced by `tls`.
+pub async fn load(tls: &TlsConfig) -> anyhow::Result<RustlsConfig> {
+    if tls.cert.trim().is_empty() || tls.key.trim().is_empty() {
+        anyhow::bail!("tls.enable is true but tls.cert or tls.key is empty");
+    }
+
+    RustlsConfig::from_pem_file(&tls.cert, &tls.key)
+        .await
+        .with_context(|| format!("failed to load tls cert {} / key {}", tls.cert, tls.key))
+}
+
+/// Reload `config` whenever the cert or key file changes on disk.
+///
+/// A failed reload (e.g. the key is written after the cert) keeps serving the
+/// previous certificate and is retried on the next poll.
+pub fn spawn_reload_watcher(
+    config: RustlsConfig,
+    tls: TlsConfig,
+    interval: Duration,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut last_seen = modified_times(&tls).await;
+        loop {
+            tokio::time::sleep(interval).await;
+
+            let current = modified_times(&tls).await;
+            if current == last_seen {
+                continue;
+            }
+
+            match config.reload_from_pem_file(&tls.cert, &tls.key).await {
+                Ok(()) => {
+                    info!("reloaded tls certificate from {}", tls.cert);
+                    last_seen = current;
+                }
+                Err(error) => {
+                    warn!(
+                        "failed to reload tls certificate from {}: {error}",
+                        tls.cert
+                    );
+                }
+            }
+        }
+    })
+}
+
+async fn modified_times(tls: &TlsConfig) -> (Option<SystemTime>, Option<SystemTime>) {
+    (
+        modified_time(Path::new(&tls.cert)).await,
+        modified_time(Path::new(&tls.key)).await,
+    )
+}
+
+async fn modified_time(path: &Path) -> Option<SystemTime> {
+    tokio::fs::metadata(path).await.ok()?.modified().ok()
+}
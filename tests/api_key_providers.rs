@@ -0,0 +1,468 @@
+//! Integration tests for providers built from gemini-api-key, codex-api-key,
+//! claude-api-key and openai-compatibility config entries.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use futures::StreamExt;
+use serde_json::{json, Value};
+use tempfile::TempDir;
+use tokio::sync::Mutex;
+
+use rusuh::auth::manager::AccountManager;
+use rusuh::config::{
+    Config, ModelEntry, OpenAICompatKeyEntry, OpenAICompatProvider, ProviderKeyEntry,
+};
+use rusuh::models::{ChatCompletionRequest, MessageContent};
+use rusuh::providers::model_registry::ModelRegistry;
+use rusuh::providers::Provider;
+
+#[derive(Clone, Default)]
+struct MockUpstream {
+    response: Value,
+    stream_body: String,
+    seen: Arc<Mutex<Vec<(String, HeaderMap, Value)>>>,
+}
+
+async fn record_and_respond(
+    State(state): State<MockUpstream>,
+    path: Option<Path<String>>,
+    headers: HeaderMap,
+    Json(body): Json<Value>,
+) -> (StatusCode, [(&'static str, &'static str); 1], String) {
+    let path = path.map(|Path(path)| path).unwrap_or_default();
+    let is_stream = body["stream"].as_bool() == Some(true) || path.contains("stream");
+    state.seen.lock().await.push((path, headers, body));
+    if is_stream {
+        (
+            StatusCode::OK,
+            [("content-type", "text/event-stream")],
+            state.stream_body.clone(),
+        )
+    } else {
+        (
+            StatusCode::OK,
+            [("content-type", "application/json")],
+            state.response.to_string(),
+        )
+    }
+}
+
+async fn spawn_mock(router: Router<MockUpstream>, state: MockUpstream) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind mock listener");
+    let addr = listener.local_addr().expect("read listener addr");
+    let app = router.with_state(state);
+    tokio::spawn(async move {
+        let _ = axum::serve(listener, app).await;
+    });
+    format!("http://{addr}")
+}
+
+fn key_entry(base_url: &str) -> ProviderKeyEntry {
+    ProviderKeyEntry {
+        api_key: "test-key".to_string(),
+        base_url: Some(base_url.to_string()),
+        headers: HashMap::from([("x-extra".to_string(), "1".to_string())]),
+        ..Default::default()
+    }
+}
+
+async fn build(config: &Config) -> Vec<Arc<dyn Provider>> {
+    let dir = TempDir::new().unwrap();
+    let accounts = AccountManager::with_dir(dir.path());
+    accounts.reload().await.unwrap();
+    rusuh::providers::registry::build_providers(
+        config,
+        &accounts,
+        Arc::new(ModelRegistry::new()),
+        rusuh::proxy::KiroRuntimeState::default(),
+    )
+    .await
+}
+
+fn chat_request(model: &str, stream: bool) -> ChatCompletionRequest {
+    serde_json::from_value(json!({
+        "model": model,
+        "stream": stream,
+        "messages": [
+            {"role": "system", "content": "be brief"},
+            {"role": "user", "content": "hello"}
+        ]
+    }))
+    .unwrap()
+}
+
+fn message_text(content: &MessageContent) -> &str {
+    match content {
+        MessageContent::Text(text) => text,
+        MessageContent::Parts(_) => panic!("expected text content"),
+    }
+}
+
+#[tokio::test]
+async fn build_providers_registers_api_key_entries_with_own_client_ids() {
+    let config = Config {
+        gemini_api_keys: vec![key_entry("http://127.0.0.1:9")],
+        codex_api_keys: vec![key_entry("http://127.0.0.1:9")],
+        claude_api_keys: vec![
+            key_entry("http://127.0.0.1:9"),
+            key_entry("http://127.0.0.1:9"),
+            ProviderKeyEntry::default(),
+        ],
+        ..Default::default()
+    };
+
+    let providers = build(&config).await;
+    let ids = providers
+        .iter()
+        .map(|provider| (provider.provider_type(), provider.client_id()))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        ids,
+        [
+            ("gemini-api", "gemini-api_0"),
+            ("codex-api", "codex-api_0"),
+            ("claude-api", "claude-api_0"),
+            ("claude-api", "claude-api_1"),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn api_key_provider_lists_prefixed_aliases_and_sends_upstream_name() {
+    let state = MockUpstream {
+        response: json!({
+            "id": "msg_1",
+            "content": [{"type": "text", "text": "hi there"}],
+            "stop_reason": "end_turn",
+            "usage": {"input_tokens": 3, "output_tokens": 2}
+        }),
+        ..Default::default()
+    };
+    let seen = state.seen.clone();
+    let base_url = spawn_mock(
+        Router::new().route("/v1/messages", post(record_and_respond)),
+        state,
+    )
+    .await;
+
+    let mut entry = key_entry(&base_url);
+    entry.prefix = Some("team".to_string());
+    entry.models = vec![ModelEntry {
+        name: "claude-sonnet-4-6".to_string(),
+        alias: Some("sonnet".to_string()),
+    }];
+    let config = Config {
+        claude_api_keys: vec![entry],
+        ..Default::default()
+    };
+
+    let providers = build(&config).await;
+    let provider = providers.first().expect("claude-api provider");
+
+    let models = provider.list_models().await.unwrap();
+    assert_eq!(
+        models
+            .iter()
+            .map(|model| model.id.as_str())
+            .collect::<Vec<_>>(),
+        ["team/sonnet"]
+    );
+
+    let response = provider
+        .chat_completion(&chat_request("team/sonnet", false))
+        .await
+        .unwrap();
+    let message = response.choices[0].message.as_ref().unwrap();
+    assert_eq!(message_text(&message.content), "hi there");
+    assert_eq!(response.model, "team/sonnet");
+
+    let seen = seen.lock().await;
+    let (_, headers, body) = &seen[0];
+    assert_eq!(headers["x-api-key"], "test-key");
+    assert_eq!(headers["x-extra"], "1");
+    assert_eq!(body["model"], "claude-sonnet-4-6");
+    assert_eq!(body["system"], "be brief");
+}
+
+#[tokio::test]
+async fn claude_api_provider_translates_stream_to_openai_chunks() {
+    let events = [
+        json!({"type": "message_start", "message": {"usage": {"input_tokens": 4}}}),
+        json!({"type": "content_block_start", "index": 0, "content_block": {"type": "text", "text": ""}}),
+        json!({"type": "content_block_delta", "index": 0, "delta": {"type": "text_delta", "text": "Hi"}}),
+        json!({"type": "message_delta", "delta": {"stop_reason": "end_turn"}, "usage": {"output_tokens": 1}}),
+        json!({"type": "message_stop"}),
+    ];
+    let state = MockUpstream {
+        stream_body: events
+            .iter()
+            .map(|event| {
+                format!(
+                    "event: {}\ndata: {event}\n\n",
+                    event["type"].as_str().unwrap()
+                )
+            })
+            .collect(),
+        ..Default::default()
+    };
+    let base_url = spawn_mock(
+        Router::new().route("/v1/messages", post(record_and_respond)),
+        state,
+    )
+    .await;
+
+    let config = Config {
+        claude_api_keys: vec![key_entry(&base_url)],
+        ..Default::default()
+    };
+    let providers = build(&config).await;
+
+    let mut stream = providers[0]
+        .chat_completion_stream(&chat_request("claude-sonnet-4-6", true))
+        .await
+        .unwrap();
+    let mut text = String::new();
+    while let Some(chunk) = stream.next().await {
+        text.push_str(std::str::from_utf8(&chunk.unwrap()).unwrap());
+    }
+
+    assert!(text.contains("\"content\":\"Hi\""), "got: {text}");
+    assert!(text.contains("\"finish_reason\":\"stop\""), "got: {text}");
+    assert!(text.trim_end().ends_with("data: [DONE]"), "got: {text}");
+}
+
+#[tokio::test]
+async fn codex_api_provider_forwards_openai_request_with_bearer_key() {
+    let state = MockUpstream {
+        response: json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion",
+            "created": 0,
+            "model": "gpt-5.4",
+            "choices": [{
+                "index": 0,
+                "message": {"role": "assistant", "content": "pong"},
+                "finish_reason": "stop"
+            }],
+            "usage": {"prompt_tokens": 2, "completion_tokens": 1, "total_tokens": 3}
+        }),
+        ..Default::default()
+    };
+    let seen = state.seen.clone();
+    let base_url = spawn_mock(
+        Router::new().route("/chat/completions", post(record_and_respond)),
+        state,
+    )
+    .await;
+
+    let config = Config {
+        codex_api_keys: vec![key_entry(&base_url)],
+        ..Default::default()
+    };
+    let providers = build(&config).await;
+
+    let mut request = chat_request("gpt-5.4", false);
+    request
+        .extra
+        .insert("execution_session_id".to_string(), json!("session-1"));
+    let response = providers[0].chat_completion(&request).await.unwrap();
+    assert_eq!(
+        message_text(&response.choices[0].message.as_ref().unwrap().content),
+        "pong"
+    );
+
+    let seen = seen.lock().await;
+    let (_, headers, body) = &seen[0];
+    assert_eq!(headers["authorization"], "Bearer test-key");
+    assert_eq!(body["model"], "gpt-5.4");
+    assert!(body.get("execution_session_id").is_none());
+}
+
+#[tokio::test]
+async fn gemini_api_provider_calls_generate_content_and_maps_candidates() {
+    let state = MockUpstream {
+        response: json!({
+            "candidates": [{
+                "content": {"role": "model", "parts": [{"text": "bonjour"}]},
+                "finishReason": "STOP"
+            }],
+            "usageMetadata": {"promptTokenCount": 5, "candidatesTokenCount": 1, "totalTokenCount": 6}
+        }),
+        ..Default::default()
+    };
+    let seen = state.seen.clone();
+    let base_url = spawn_mock(
+        Router::new().route("/v1beta/models/{model_action}", post(record_and_respond)),
+        state,
+    )
+    .await;
+
+    let config = Config {
+        gemini_api_keys: vec![key_entry(&base_url)],
+        ..Default::default()
+    };
+    let providers = build(&config).await;
+
+    let response = providers[0]
+        .chat_completion(&chat_request("gemini-2.5-flash", false))
+        .await
+        .unwrap();
+    assert_eq!(
+        message_text(&response.choices[0].message.as_ref().unwrap().content),
+        "bonjour"
+    );
+    assert_eq!(response.usage.unwrap().total_tokens, 6);
+
+    let seen = seen.lock().await;
+    let (path, headers, body) = &seen[0];
+    assert_eq!(path, "gemini-2.5-flash:generateContent");
+    assert_eq!(headers["x-goog-api-key"], "test-key");
+    assert_eq!(body["systemInstruction"]["parts"][0]["text"], "be brief");
+    assert_eq!(body["contents"][0]["parts"][0]["text"], "hello");
+}
+
+fn openai_compat(base_url: &str, models: Vec<ModelEntry>) -> OpenAICompatProvider {
+    OpenAICompatProvider {
+        name: "OpenRouter".to_string(),
+        prefix: Some("or".to_string()),
+        base_url: format!("{base_url}/v1/"),
+        headers: HashMap::from([("x-title".to_string(), "rusuh".to_string())]),
+        api_key_entries: vec![
+            OpenAICompatKeyEntry {
+                api_key: "or-key-1".to_string(),
+                proxy_url: None,
+            },
+            OpenAICompatKeyEntry {
+                api_key: "or-key-2".to_string(),
+                proxy_url: None,
+            },
+        ],
+        models,
+    }
+}
+
+#[tokio::test]
+async fn openai_compat_builds_provider_per_key_and_forwards_aliased_model() {
+    let state = MockUpstream {
+        response: json!({
+            "id": "gen-1",
+            "object": "chat.completion",
+            "created": 0,
+            "model": "meta-llama/llama-3.3-70b-instruct",
+            "choices": [{
+                "index": 0,
+                "message": {"role": "assistant", "content": "hola"},
+                "finish_reason": "stop"
+            }],
+            "usage": {"prompt_tokens": 4, "completion_tokens": 1, "total_tokens": 5}
+        }),
+        stream_body: concat!(
+            "data: {\"id\":\"gen-2\",\"object\":\"chat.completion.chunk\",\"created\":0,",
+            "\"model\":\"x\",\"choices\":[{\"index\":0,\"delta\":{\"content\":\"ho\"}}]}\n\n",
+            "data: [DONE]\n\n"
+        )
+        .to_string(),
+        ..Default::default()
+    };
+    let seen = state.seen.clone();
+    let base_url = spawn_mock(
+        Router::new().route("/v1/chat/completions", post(record_and_respond)),
+        state,
+    )
+    .await;
+
+    let config = Config {
+        openai_compat: vec![openai_compat(
+            &base_url,
+            vec![ModelEntry {
+                name: "meta-llama/llama-3.3-70b-instruct".to_string(),
+                alias: Some("llama".to_string()),
+            }],
+        )],
+        ..Default::default()
+    };
+    let providers = build(&config).await;
+    let ids = providers
+        .iter()
+        .map(|provider| (provider.provider_type(), provider.client_id()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        ids,
+        [
+            ("openrouter", "openrouter_0_0"),
+            ("openrouter", "openrouter_0_1")
+        ]
+    );
+
+    let models = providers[1].list_models().await.unwrap();
+    assert_eq!(models[0].id, "or/llama");
+
+    let response = providers[1]
+        .chat_completion(&chat_request("or/llama", false))
+        .await
+        .unwrap();
+    assert_eq!(
+        message_text(&response.choices[0].message.as_ref().unwrap().content),
+        "hola"
+    );
+    assert_eq!(response.model, "or/llama");
+
+    let mut stream = providers[0]
+        .chat_completion_stream(&chat_request("or/llama", true))
+        .await
+        .unwrap();
+    let mut text = String::new();
+    while let Some(chunk) = stream.next().await {
+        text.push_str(std::str::from_utf8(&chunk.unwrap()).unwrap());
+    }
+    assert!(text.contains("\"content\":\"ho\""), "got: {text}");
+
+    let seen = seen.lock().await;
+    let (_, headers, body) = &seen[0];
+    assert_eq!(headers["authorization"], "Bearer or-key-2");
+    assert_eq!(headers["x-title"], "rusuh");
+    assert_eq!(body["model"], "meta-llama/llama-3.3-70b-instruct");
+    let (_, headers, body) = &seen[1];
+    assert_eq!(headers["authorization"], "Bearer or-key-1");
+    assert_eq!(body["stream"], true);
+    assert_eq!(body["stream_options"]["include_usage"], true);
+}
+
+#[tokio::test]
+async fn openai_compat_discovers_models_from_upstream_when_none_configured() {
+    let base_url = spawn_mock(
+        Router::new().route(
+            "/v1/models",
+            get(|| async { Json(json!({"data": [{"id": "qwen2.5-7b"}, {"id": "phi-4"}]})) }),
+        ),
+        MockUpstream::default(),
+    )
+    .await;
+
+    let mut compat = openai_compat(&base_url, vec![]);
+    compat.name = String::new();
+    compat.prefix = None;
+    let config = Config {
+        openai_compat: vec![compat],
+        ..Default::default()
+    };
+    let providers = build(&config).await;
+    assert_eq!(providers[0].provider_type(), "openai-compatibility");
+
+    let ids = providers[0]
+        .list_models()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|model| model.id)
+        .collect::<Vec<_>>();
+    assert_eq!(ids, ["qwen2.5-7b", "phi-4"]);
+}
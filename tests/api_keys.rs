@@ -0,0 +1,139 @@
+//! Integration tests for scoped entries on the `api-keys` management endpoints.
+
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use http_body_util::BodyExt;
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+use rusuh::auth::manager::AccountManager;
+use rusuh::config::{Config, ManagementConfig};
+use rusuh::providers::model_registry::ModelRegistry;
+use rusuh::proxy::ProxyState;
+use rusuh::router::build_router;
+
+const SECRET: &str = "test-mgmt-secret";
+
+fn test_app(api_keys: &[&str]) -> (axum::Router, Arc<ProxyState>) {
+    let cfg = Config {
+        api_keys: api_keys.iter().map(|&key| key.into()).collect(),
+        remote_management: ManagementConfig {
+            allow_remote: true,
+            secret_key: SECRET.into(),
+        },
+        ..Default::default()
+    };
+    let accounts = Arc::new(AccountManager::with_dir("/tmp/rusuh_test_nonexistent"));
+    let registry = Arc::new(ModelRegistry::new());
+    let state = Arc::new(ProxyState::new(cfg, accounts, registry, 0));
+
+    let app = build_router(state.clone()).layer(axum::middleware::from_fn_with_state(
+        state.clone(),
+        rusuh::middleware::auth::api_key_auth,
+    ));
+    (app, state)
+}
+
+fn mgmt_request(method: &str, uri: &str, body: Option<Value>) -> Request<Body> {
+    let builder = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("authorization", format!("Bearer {SECRET}"));
+    match body {
+        Some(body) => builder
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap(),
+        None => builder.body(Body::empty()).unwrap(),
+    }
+}
+
+async fn body_json(resp: axum::response::Response) -> Value {
+    let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+    serde_json::from_slice(&bytes).unwrap_or(json!(null))
+}
+
+#[tokio::test]
+async fn put_api_keys_accepts_plain_and_scoped_entries() {
+    let (app, state) = test_app(&[]);
+
+    let resp = app
+        .clone()
+        .oneshot(mgmt_request(
+            "PUT",
+            "/v0/management/api-keys",
+            Some(json!({
+                "items": [
+                    "plain",
+                    {"key": "ci", "name": "ci", "models": ["gpt-5.4"], "requests-per-day": 100}
+                ]
+            })),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let cfg = state.config.read().await;
+    let ci = cfg.api_key("ci").expect("scoped key stored");
+    assert_eq!(ci.scope.models, vec!["gpt-5.4"]);
+    assert_eq!(ci.scope.requests_per_day, Some(100));
+    drop(cfg);
+
+    let resp = app
+        .oneshot(mgmt_request("GET", "/v0/management/api-keys", None))
+        .await
+        .unwrap();
+    let json = body_json(resp).await;
+    assert_eq!(json["api-keys"][0], "plain");
+    assert_eq!(json["api-keys"][1]["name"], "ci");
+    assert_eq!(json["api-keys"][1]["requests-per-day"], 100);
+}
+
+#[tokio::test]
+async fn patch_entry_rescopes_a_key_and_renaming_keeps_its_scope() {
+    let (app, state) = test_app(&["ci"]);
+
+    let resp = app
+        .clone()
+        .oneshot(mgmt_request(
+            "PATCH",
+            "/v0/management/api-keys",
+            Some(json!({"entry": {"key": "ci", "allowed-auth-ids": ["codex_0"]}})),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let resp = app
+        .oneshot(mgmt_request(
+            "PATCH",
+            "/v0/management/api-keys",
+            Some(json!({"old": "ci", "new": "ci-rotated"})),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let cfg = state.config.read().await;
+    assert_eq!(cfg.api_keys.len(), 1);
+    assert_eq!(cfg.api_keys[0].key, "ci-rotated");
+    assert_eq!(cfg.api_keys[0].scope.allowed_auth_ids, vec!["codex_0"]);
+}
+
+#[tokio::test]
+async fn delete_api_key_by_value_removes_scoped_entry() {
+    let (app, state) = test_app(&["keep", "drop"]);
+
+    let resp = app
+        .oneshot(mgmt_request(
+            "DELETE",
+            "/v0/management/api-keys?value=drop",
+            None,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(state.config.read().await.api_keys, vec!["keep"]);
+}
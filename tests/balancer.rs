@@ -1,4 +1,6 @@
-use rusuh::proxy::balancer::{Balancer, Strategy};
+use std::time::Duration;
+
+use rusuh::proxy::balancer::{weighted_index, Balancer, Strategy};
 
 #[test]
 fn round_robin_distributes_evenly() {
@@ -92,3 +94,122 @@ fn fill_first_counter_tracks_first_only() {
     assert_eq!(b.request_count(1), 0);
     assert_eq!(b.request_count(2), 0);
 }
+
+#[test]
+fn strategy_parses_new_strategies() {
+    assert_eq!(Strategy::parse("least-busy"), Strategy::LeastBusy);
+    assert_eq!(Strategy::parse("least_busy"), Strategy::LeastBusy);
+    assert_eq!(Strategy::parse("ewma-latency"), Strategy::EwmaLatency);
+    assert_eq!(Strategy::parse("latency"), Strategy::EwmaLatency);
+    assert_eq!(Strategy::parse("Weighted"), Strategy::Weighted);
+    assert_eq!(Strategy::parse("least-busy").as_str(), "least-busy");
+}
+
+#[test]
+fn least_busy_avoids_candidates_with_requests_in_flight() {
+    let b = Balancer::new(Strategy::LeastBusy, 3);
+    let candidates = vec![0, 1, 2];
+
+    let first = b.begin(0).unwrap();
+    let _second = b.begin(1).unwrap();
+    assert_eq!(b.pick(&candidates), 2);
+    assert_eq!(b.stats(0).unwrap().in_flight, 1);
+
+    drop(first);
+    let _third = b.begin(2).unwrap();
+    assert_eq!(b.pick(&candidates), 0);
+    assert_eq!(b.stats(0).unwrap().in_flight, 0);
+}
+
+#[test]
+fn least_busy_rotates_between_idle_candidates() {
+    let b = Balancer::new(Strategy::LeastBusy, 2);
+    let picks: Vec<usize> = (0..4).map(|_| b.pick(&[0, 1])).collect();
+
+    assert_eq!(picks, vec![0, 1, 0, 1]);
+}
+
+#[test]
+fn ewma_latency_prefers_fastest_and_tries_unmeasured_candidates() {
+    let b = Balancer::new(Strategy::EwmaLatency, 3);
+    let candidates = vec![0, 1, 2];
+
+    b.begin(0)
+        .unwrap()
+        .record_latency(Duration::from_millis(400));
+    b.begin(1)
+        .unwrap()
+        .record_latency(Duration::from_millis(100));
+    assert_eq!(b.pick(&candidates), 2);
+
+    b.begin(2)
+        .unwrap()
+        .record_latency(Duration::from_millis(250));
+    for _ in 0..5 {
+        assert_eq!(b.pick(&candidates), 1);
+    }
+
+    for _ in 0..10 {
+        b.begin(1)
+            .unwrap()
+            .record_latency(Duration::from_millis(900));
+    }
+    assert_eq!(b.pick(&candidates), 2);
+    assert_eq!(b.stats(0).unwrap().ewma_latency_ms, Some(400.0));
+}
+
+#[test]
+fn weighted_follows_weights_smoothly() {
+    let b = Balancer::with_weights(Strategy::Weighted, vec![5, 1, 1]);
+    let picks: Vec<usize> = (0..7).map(|_| b.pick(&[0, 1, 2])).collect();
+
+    assert_eq!(picks, vec![0, 0, 1, 0, 2, 0, 0]);
+    assert_eq!(b.stats(0).unwrap().weight, 5);
+}
+
+#[test]
+fn weighted_skips_zero_weight_candidates() {
+    let b = Balancer::with_weights(Strategy::Weighted, vec![0, 2, 1]);
+    let mut picks = [0u32; 3];
+    for _ in 0..30 {
+        picks[b.pick(&[0, 1, 2])] += 1;
+    }
+
+    assert_eq!(picks, [0, 20, 10]);
+}
+
+#[test]
+fn weighted_index_maps_rolls_to_weight_ranges() {
+    assert_eq!(weighted_index(&[1, 3], 0), Some(0));
+    assert_eq!(weighted_index(&[1, 3], 1), Some(1));
+    assert_eq!(weighted_index(&[1, 3], 3), Some(1));
+    assert_eq!(weighted_index(&[1, 3], 4), Some(0));
+    assert_eq!(weighted_index(&[0, 0], 7), None);
+}
+
+#[test]
+fn hedges_count_as_requests_and_track_wins() {
+    let b = Balancer::new(Strategy::FillFirst, 2);
+    assert_eq!(b.pick(&[0, 1]), 0);
+    b.record_hedge(1);
+    b.record_hedge_win(1);
+    b.record_hedge(1);
+
+    assert_eq!(b.total_requests(), 3);
+    let hedged = b.stats(1).unwrap();
+    assert_eq!(hedged.requests, 2);
+    assert_eq!(hedged.hedges, 2);
+    assert_eq!(hedged.hedge_wins, 1);
+    assert_eq!(b.stats(0).unwrap().hedges, 0);
+}
+
+#[test]
+fn preferred_candidate_wins_only_while_listed() {
+    let b = Balancer::new(Strategy::RoundRobin, 3);
+    assert_eq!(b.pick_preferring(&[0, 1, 2], Some(2)), 2);
+    assert_eq!(b.pick_preferring(&[0, 1, 2], Some(2)), 2);
+    assert_eq!(b.request_count(2), 2);
+    // An unlisted preference falls back to the strategy.
+    assert_eq!(b.pick_preferring(&[0, 1], Some(2)), 0);
+    assert_eq!(b.pick_preferring(&[0, 1], None), 1);
+}
@@ -0,0 +1,221 @@
+//! Tests for the provider-agnostic circuit breaker and its management endpoints.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use http_body_util::BodyExt;
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+use rusuh::auth::manager::AccountManager;
+use rusuh::config::{CircuitBreakerConfig, Config, ManagementConfig};
+use rusuh::providers::model_registry::ModelRegistry;
+use rusuh::proxy::circuit_breaker::{CircuitBreaker, CircuitState};
+use rusuh::proxy::ProxyState;
+use rusuh::router::build_router;
+
+const SECRET: &str = "test-mgmt-secret";
+
+fn breaker(threshold: u32, open_seconds: u64) -> CircuitBreaker {
+    CircuitBreaker::new(&CircuitBreakerConfig {
+        failure_threshold: threshold,
+        open_seconds,
+    })
+}
+
+#[test]
+fn opens_after_consecutive_failures() {
+    let cb = breaker(3, 30);
+    let now = Instant::now();
+
+    assert_eq!(
+        cb.record_failure("auth1", "gpt-5.4", "503", now),
+        CircuitState::Closed
+    );
+    assert_eq!(
+        cb.record_failure("auth1", "gpt-5.4", "503", now),
+        CircuitState::Closed
+    );
+    assert!(cb.allow("auth1", "gpt-5.4", now));
+    assert_eq!(
+        cb.record_failure("auth1", "gpt-5.4", "503", now),
+        CircuitState::Open
+    );
+
+    assert!(!cb.is_available("auth1", "gpt-5.4", now));
+    assert!(!cb.allow("auth1", "gpt-5.4", now + Duration::from_secs(29)));
+    assert!(cb.allow("auth1", "gpt-5.5", now));
+    assert!(cb.allow("auth2", "gpt-5.4", now));
+}
+
+#[test]
+fn success_resets_failure_count() {
+    let cb = breaker(2, 30);
+    let now = Instant::now();
+
+    cb.record_failure("auth1", "m", "timeout", now);
+    cb.record_success("auth1", "m");
+    assert_eq!(
+        cb.record_failure("auth1", "m", "timeout", now),
+        CircuitState::Closed
+    );
+    assert_eq!(cb.snapshot(now)[0].consecutive_failures, 1);
+}
+
+#[test]
+fn half_open_admits_one_probe_then_closes_on_success() {
+    let cb = breaker(1, 10);
+    let now = Instant::now();
+    cb.record_failure("auth1", "m", "502", now);
+
+    let later = now + Duration::from_secs(10);
+    assert!(cb.is_available("auth1", "m", later));
+    assert!(cb.allow("auth1", "m", later));
+    assert_eq!(cb.state("auth1", "m"), CircuitState::HalfOpen);
+    assert!(!cb.allow("auth1", "m", later));
+
+    cb.record_success("auth1", "m");
+    assert_eq!(cb.state("auth1", "m"), CircuitState::Closed);
+    assert!(cb.snapshot(later).is_empty());
+}
+
+#[test]
+fn failed_probe_reopens_circuit() {
+    let cb = breaker(1, 10);
+    let now = Instant::now();
+    cb.record_failure("auth1", "m", "502", now);
+
+    let later = now + Duration::from_secs(11);
+    assert!(cb.allow("auth1", "m", later));
+    assert_eq!(
+        cb.record_failure("auth1", "m", "504", later),
+        CircuitState::Open
+    );
+    assert!(!cb.allow("auth1", "m", later + Duration::from_secs(5)));
+
+    let status = &cb.snapshot(later)[0];
+    assert_eq!(status.retry_in_secs, Some(10));
+    assert_eq!(status.last_error.as_deref(), Some("504"));
+}
+
+#[test]
+fn unanswered_probe_lapses() {
+    let cb = breaker(1, 10);
+    let now = Instant::now();
+    cb.record_failure("auth1", "m", "502", now);
+
+    let probe = now + Duration::from_secs(10);
+    assert!(cb.allow("auth1", "m", probe));
+    assert!(!cb.allow("auth1", "m", probe + Duration::from_secs(9)));
+    assert!(cb.allow("auth1", "m", probe + Duration::from_secs(10)));
+}
+
+#[test]
+fn zero_threshold_disables_breaker() {
+    let cb = breaker(0, 10);
+    let now = Instant::now();
+    for _ in 0..10 {
+        assert_eq!(
+            cb.record_failure("auth1", "m", "503", now),
+            CircuitState::Closed
+        );
+    }
+    assert!(cb.allow("auth1", "m", now));
+    assert!(cb.snapshot(now).is_empty());
+}
+
+#[test]
+fn reset_filters_by_account_and_model() {
+    let cb = breaker(1, 30);
+    let now = Instant::now();
+    cb.record_failure("auth1", "a", "503", now);
+    cb.record_failure("auth1", "b", "503", now);
+    cb.record_failure("auth2", "a", "503", now);
+
+    assert_eq!(cb.reset(Some("auth1"), Some("a")), 1);
+    assert_eq!(cb.reset(Some("AUTH1"), None), 1);
+    assert_eq!(cb.snapshot(now).len(), 1);
+    assert_eq!(cb.reset(None, None), 1);
+}
+
+fn test_app() -> (axum::Router, Arc<ProxyState>) {
+    let cfg = Config {
+        remote_management: ManagementConfig {
+            allow_remote: true,
+            secret_key: SECRET.into(),
+        },
+        ..Default::default()
+    };
+    let accounts = Arc::new(AccountManager::with_dir("/tmp/rusuh_test_nonexistent"));
+    let registry = Arc::new(ModelRegistry::new());
+    let state = Arc::new(ProxyState::new(cfg, accounts, registry, 0));
+
+    let app = build_router(state.clone()).layer(axum::middleware::from_fn_with_state(
+        state.clone(),
+        rusuh::middleware::auth::api_key_auth,
+    ));
+    (app, state)
+}
+
+fn mgmt_request(method: &str, uri: &str) -> Request<Body> {
+    Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("authorization", format!("Bearer {SECRET}"))
+        .body(Body::empty())
+        .unwrap()
+}
+
+async fn body_json(resp: axum::response::Response) -> Value {
+    let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+    serde_json::from_slice(&bytes).unwrap_or(json!(null))
+}
+
+#[tokio::test]
+async fn management_lists_and_resets_circuits() {
+    let (app, state) = test_app();
+    let now = Instant::now();
+    for _ in 0..5 {
+        state
+            .circuit_breaker
+            .record_failure("zed-a.json", "claude-sonnet-4-5", "timed out", now);
+    }
+    state
+        .circuit_breaker
+        .record_failure("codex-b.json", "gpt-5.4", "502", now);
+
+    let resp = app
+        .clone()
+        .oneshot(mgmt_request("GET", "/v0/management/circuit-breakers"))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body = body_json(resp).await;
+    let circuits = body["circuit-breakers"].as_array().unwrap();
+    assert_eq!(circuits.len(), 2);
+    assert_eq!(circuits[0]["client_id"], "codex-b.json");
+    assert_eq!(circuits[0]["state"], "closed");
+    assert_eq!(circuits[1]["client_id"], "zed-a.json");
+    assert_eq!(circuits[1]["state"], "open");
+    assert_eq!(circuits[1]["last_error"], "timed out");
+
+    let resp = app
+        .clone()
+        .oneshot(mgmt_request(
+            "DELETE",
+            "/v0/management/circuit-breakers?client_id=zed-a.json",
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(body_json(resp).await["cleared"], 1);
+    assert_eq!(
+        state
+            .circuit_breaker
+            .state("zed-a.json", "claude-sonnet-4-5"),
+        CircuitState::Closed
+    );
+    assert_eq!(state.circuit_breaker.snapshot(now).len(), 1);
+}
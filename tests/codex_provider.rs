@@ -142,6 +142,7 @@ fn codex_chat_request(stream: Option<bool>) -> ChatCompletionRequest {
             name: None,
             tool_calls: None,
             tool_call_id: None,
+            reasoning_content: None,
         }],
         stream,
         max_tokens: None,
@@ -288,6 +289,7 @@ fn codex_prepare_request_removes_unsupported_fields_and_sets_instructions() {
             name: None,
             tool_calls: None,
             tool_call_id: None,
+            reasoning_content: None,
         }],
         stream: Some(false),
         max_tokens: None,
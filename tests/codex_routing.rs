@@ -87,6 +87,7 @@ impl Provider for StubProvider {
                     name: None,
                     tool_calls: None,
                     tool_call_id: None,
+                    reasoning_content: None,
                 }),
                 delta: None,
                 finish_reason: Some("stop".to_string()),
@@ -111,6 +112,7 @@ impl Provider for StubProvider {
                     role: Some("assistant".to_string()),
                     content: Some(self.response_label.to_string()),
                     tool_calls: None,
+                    reasoning_content: None,
                 },
                 finish_reason: None,
             }],
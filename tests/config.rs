@@ -49,6 +49,173 @@ routing:
     assert_eq!(cfg.routing.strategy, "fill-first");
 }
 
+#[test]
+fn yaml_parse_routing_weights() {
+    let yaml = r#"
+routing:
+  strategy: "weighted"
+  weights:
+    kiro-main.json: 4
+    codex: 2
+"#;
+    let cfg: Config = serde_yaml::from_str(yaml).unwrap();
+    assert_eq!(cfg.routing.strategy, "weighted");
+    assert_eq!(cfg.routing.weight_for("kiro-main.json", "kiro"), 4);
+    assert_eq!(cfg.routing.weight_for("codex-a.json", "codex"), 2);
+    assert_eq!(cfg.routing.weight_for("zed-a.json", "zed"), 1);
+}
+
+#[test]
+fn yaml_parse_scoped_api_keys() {
+    let yaml = r#"
+api-keys:
+  - "plain-key"
+  - key: "ci-key"
+    name: "ci"
+    models: ["gpt-5.4"]
+    providers: ["codex"]
+    allowed-auth-ids: ["codex_0"]
+    expires-at: "2030-01-01T00:00:00Z"
+    requests-per-day: 500
+    tokens-per-day: 2000000
+"#;
+    let cfg: Config = serde_yaml::from_str(yaml).unwrap();
+    assert_eq!(cfg.api_keys, vec!["plain-key", "ci-key"]);
+
+    let plain = cfg.api_key("plain-key").unwrap();
+    assert!(plain.allows_model("anything"));
+    assert!(plain.name.is_none());
+
+    let ci = cfg.api_key("ci-key").unwrap();
+    assert_eq!(ci.name.as_deref(), Some("ci"));
+    assert!(ci.allows_model("GPT-5.4"));
+    assert!(!ci.allows_model("claude-opus-4-6"));
+    assert!(ci.allows_provider("codex"));
+    assert!(!ci.allows_auth_id("codex_1"));
+    assert_eq!(ci.scope.requests_per_day, Some(500));
+    assert_eq!(ci.scope.tokens_per_day, Some(2_000_000));
+    assert!(!ci.is_expired("2029-12-31T23:59:59Z".parse().unwrap()));
+    assert!(ci.is_expired("2030-01-01T00:00:00Z".parse().unwrap()));
+
+    // Unscoped keys serialize back to plain strings.
+    let json = serde_json::to_value(&cfg.api_keys).unwrap();
+    assert_eq!(json[0], "plain-key");
+    assert_eq!(json[1]["allowed-auth-ids"][0], "codex_0");
+}
+
+#[test]
+fn yaml_parse_key_rate_limits_fall_back_to_defaults() {
+    let yaml = r#"
+key-rate-limit:
+  requests-per-minute: 60
+  concurrent-streams: 4
+api-keys:
+  - key: "ci-key"
+    rate-limit:
+      requests-per-minute: 10
+      tokens-per-minute: 50000
+"#;
+    let cfg: Config = serde_yaml::from_str(yaml).unwrap();
+    let limits = cfg.api_keys[0].scope.rate_limit.or(cfg.key_rate_limit);
+    assert_eq!(limits.requests_per_minute, Some(10));
+    assert_eq!(limits.tokens_per_minute, Some(50_000));
+    assert_eq!(limits.concurrent_streams, Some(4));
+
+    let cfg: Config = serde_yaml::from_str("{}").unwrap();
+    assert!(cfg.key_rate_limit.is_unlimited());
+}
+
+#[test]
+fn yaml_parse_usage_ledger() {
+    let cfg: Config = serde_yaml::from_str("{}").unwrap();
+    assert!(cfg.usage_ledger.enable);
+
+    let cfg: Config = serde_yaml::from_str("usage-ledger:\n  enable: false\n").unwrap();
+    assert!(!cfg.usage_ledger.enable);
+}
+
+#[test]
+fn yaml_parse_tracing() {
+    let cfg: Config = serde_yaml::from_str("{}").unwrap();
+    assert!(!cfg.tracing.enable);
+    assert_eq!(cfg.tracing.otlp_endpoint, "http://localhost:4318/v1/traces");
+    assert_eq!(cfg.tracing.service_name, "rusuh");
+    assert!(!cfg.tracing.propagate_upstream);
+
+    let yaml = r#"
+tracing:
+  enable: true
+  otlp-endpoint: "http://collector:4318/v1/traces"
+  service-name: "rusuh-edge"
+  propagate-upstream: true
+"#;
+    let cfg: Config = serde_yaml::from_str(yaml).unwrap();
+    assert!(cfg.tracing.enable);
+    assert_eq!(cfg.tracing.otlp_endpoint, "http://collector:4318/v1/traces");
+    assert_eq!(cfg.tracing.service_name, "rusuh-edge");
+    assert!(cfg.tracing.propagate_upstream);
+}
+
+#[test]
+fn yaml_parse_metrics() {
+    let cfg: Config = serde_yaml::from_str("{}").unwrap();
+    assert!(!cfg.metrics.public);
+
+    let cfg: Config = serde_yaml::from_str("metrics:\n  public: true\n").unwrap();
+    assert!(cfg.metrics.public);
+}
+
+#[test]
+fn yaml_parse_persist_state() {
+    let cfg: Config = serde_yaml::from_str("{}").unwrap();
+    assert!(!cfg.persist_state.enable);
+    assert_eq!(
+        cfg.persist_state.interval(),
+        std::time::Duration::from_secs(60)
+    );
+
+    let yaml = r#"
+persist-state:
+  enable: true
+  interval-seconds: 0
+"#;
+    let cfg: Config = serde_yaml::from_str(yaml).unwrap();
+    assert!(cfg.persist_state.enable);
+    assert_eq!(
+        cfg.persist_state.interval(),
+        std::time::Duration::from_secs(1)
+    );
+}
+
+#[test]
+fn yaml_parse_session_affinity() {
+    use rusuh::config::SessionSource;
+
+    let cfg: Config = serde_yaml::from_str("{}").unwrap();
+    let affinity = &cfg.routing.session_affinity;
+    assert_eq!(
+        affinity.sources,
+        [SessionSource::Header, SessionSource::MetadataUserId]
+    );
+    assert_eq!(affinity.header, "x-session-id");
+
+    let yaml = r#"
+routing:
+  session-affinity:
+    sources: ["message-hash"]
+    hash-messages: 3
+"#;
+    let cfg: Config = serde_yaml::from_str(yaml).unwrap();
+    let affinity = &cfg.routing.session_affinity;
+    assert_eq!(affinity.sources, [SessionSource::MessageHash]);
+    assert_eq!(affinity.hash_messages, 3);
+    assert_eq!(affinity.header, "x-session-id");
+    assert!(serde_yaml::from_str::<Config>(
+        "routing:\n  session-affinity:\n    sources: [\"cookie\"]\n"
+    )
+    .is_err());
+}
+
 #[test]
 fn yaml_parse_empty() {
     let cfg: Config = serde_yaml::from_str("{}").unwrap();
@@ -73,6 +240,79 @@ oauth-model-alias:
     assert_eq!(aliases[0].alias, "sonnet");
 }
 
+#[test]
+fn yaml_parse_model_routes() {
+    let yaml = r#"
+model-routes:
+  - name: "team-sonnet"
+    hedge-after-ms: 1500
+    fallback: ["team-sonnet-thinking"]
+    targets:
+      - provider: "kiro"
+        model: "kiro-claude-sonnet-4-5"
+      - provider: "claude-api"
+        model: "claude-sonnet-4-5-20250929"
+        weight: 0
+  - name: "team-sonnet-thinking"
+    hidden: true
+    targets:
+      - provider: "kiro"
+        model: "kiro-claude-sonnet-4-5-agentic"
+"#;
+    let cfg: Config = serde_yaml::from_str(yaml).unwrap();
+    assert_eq!(cfg.model_routes.len(), 2);
+    let route = cfg.model_route("team-sonnet").unwrap();
+    assert!(!route.hidden);
+    assert_eq!(route.targets[0].weight, 1);
+    assert_eq!(route.targets[1].weight, 0);
+    assert_eq!(
+        route.hedge_after(),
+        Some(std::time::Duration::from_millis(1500))
+    );
+    let thinking = cfg.model_route("team-sonnet-thinking").unwrap();
+    assert!(thinking.hidden);
+    assert_eq!(thinking.hedge_after(), None);
+    assert_eq!(route.fallback, ["team-sonnet-thinking"]);
+    assert!(thinking.fallback.is_empty());
+    assert!(rusuh::config::validate_model_routes(&cfg.model_routes).is_ok());
+}
+
+#[test]
+fn default_model_routes_cover_public_models() {
+    let cfg = Config::default();
+    let gpt = cfg.model_route("gpt-5.4").unwrap();
+    assert_eq!(gpt.targets[0].provider, "codex");
+    assert!(
+        cfg.model_route("claude-sonnet-4-5-thinking")
+            .unwrap()
+            .hidden
+    );
+    assert!(rusuh::config::validate_model_routes(&cfg.model_routes).is_ok());
+}
+
+#[test]
+fn validate_model_routes_rejects_duplicate_names() {
+    let mut routes = Config::default().model_routes;
+    routes.push(routes[0].clone());
+    let error = rusuh::config::validate_model_routes(&routes).unwrap_err();
+    assert!(error.to_string().contains("duplicate"));
+}
+
+#[test]
+fn validate_model_routes_rejects_unknown_or_self_fallback() {
+    let mut routes = Config::default().model_routes;
+    routes[0].fallback = vec!["no-such-model".to_string()];
+    let error = rusuh::config::validate_model_routes(&routes).unwrap_err();
+    assert!(error.to_string().contains("unknown model 'no-such-model'"));
+
+    routes[0].fallback = vec![routes[0].name.clone()];
+    let error = rusuh::config::validate_model_routes(&routes).unwrap_err();
+    assert!(error.to_string().contains("falls back to itself"));
+
+    routes[0].fallback = vec![routes[1].name.clone()];
+    assert!(rusuh::config::validate_model_routes(&routes).is_ok());
+}
+
 #[test]
 fn yaml_parse_openai_compat() {
     let yaml = r#"
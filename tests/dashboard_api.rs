@@ -57,7 +57,7 @@ async fn dashboard_accounts_and_overview_include_codex_auth_files() {
 
     let app = test_app(Config {
         auth_dir: dir.path().to_string_lossy().to_string(),
-        api_keys: vec!["rsk-test".to_string()],
+        api_keys: vec!["rsk-test".into()],
         ..Default::default()
     });
 
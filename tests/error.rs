@@ -1,22 +1,27 @@
+use std::time::Duration;
+
 use axum::http::StatusCode;
 use axum::response::IntoResponse;
-use rusuh::error::AppError;
+use rusuh::error::{ApiFamily, AppError, UpstreamError, UpstreamErrorKind};
+
+fn status_error(status: u16, message: &str) -> AppError {
+    UpstreamError::from_status(StatusCode::from_u16(status).unwrap(), message).into()
+}
 
 #[test]
 fn transient_errors_detected() {
-    assert!(AppError::Upstream("502 Bad Gateway".into()).is_transient());
-    assert!(AppError::Upstream("503 Service Unavailable".into()).is_transient());
-    assert!(AppError::Upstream("504 Gateway Timeout".into()).is_transient());
-    assert!(AppError::Upstream("timeout waiting for response".into()).is_transient());
-    assert!(AppError::Upstream("connection reset by peer".into()).is_transient());
-    assert!(AppError::Upstream("connection refused".into()).is_transient());
-    assert!(AppError::Upstream("stream read error: broken".into()).is_transient());
+    assert!(status_error(502, "bad gateway").is_transient());
+    assert!(status_error(503, "service unavailable").is_transient());
+    assert!(status_error(504, "gateway timeout").is_transient());
+    assert!(
+        AppError::Upstream(UpstreamError::transport("connection reset by peer")).is_transient()
+    );
 }
 
 #[test]
 fn non_transient_errors() {
-    assert!(!AppError::Upstream("400 Bad Request".into()).is_transient());
-    assert!(!AppError::Upstream("invalid model".into()).is_transient());
+    assert!(!status_error(400, "bad request").is_transient());
+    assert!(!AppError::Upstream(UpstreamError::other("invalid model")).is_transient());
     assert!(!AppError::Auth("bad token".into()).is_transient());
     assert!(!AppError::BadRequest("missing field".into()).is_transient());
 }
@@ -25,20 +30,56 @@ fn non_transient_errors() {
 fn account_errors_detected() {
     assert!(AppError::Auth("unauthorized".into()).is_account_error());
     assert!(AppError::QuotaExceeded("gemini".into()).is_account_error());
-    assert!(AppError::Upstream("401 Unauthorized".into()).is_account_error());
-    assert!(AppError::Upstream("429 Too Many Requests".into()).is_account_error());
-    assert!(AppError::Upstream("rate limit exceeded".into()).is_account_error());
-    assert!(AppError::Upstream("quota exceeded for user".into()).is_account_error());
-    assert!(AppError::Upstream("403 no_access_on_free_plan".into()).is_account_error());
+    assert!(status_error(401, "unauthorized").is_account_error());
+    assert!(status_error(403, "no_access_on_free_plan").is_account_error());
+    assert!(status_error(429, "too many requests").is_account_error());
+    assert!(status_error(429, "too many requests").is_quota_or_unavailable());
 }
 
 #[test]
 fn non_account_errors() {
-    assert!(!AppError::Upstream("500 Internal Server Error".into()).is_account_error());
+    assert!(!status_error(500, "internal server error").is_account_error());
     assert!(!AppError::BadRequest("bad".into()).is_account_error());
     assert!(!AppError::Config("missing".into()).is_account_error());
 }
 
+#[test]
+fn message_text_does_not_decide_classification() {
+    let err = status_error(
+        400,
+        "model replied: 429 quota exceeded, HTTP 500, timed out",
+    );
+    assert!(!err.is_transient());
+    assert!(!err.is_account_error());
+    assert!(!err.is_quota_or_unavailable());
+
+    let err = AppError::Upstream(UpstreamError::other("401 unauthorized 503"));
+    assert!(!err.is_transient());
+    assert!(!err.is_account_error());
+}
+
+#[test]
+fn upstream_error_kind_follows_status() {
+    let cases = [
+        (401, UpstreamErrorKind::Auth),
+        (403, UpstreamErrorKind::Auth),
+        (429, UpstreamErrorKind::RateLimited),
+        (408, UpstreamErrorKind::Transport),
+        (500, UpstreamErrorKind::Server),
+        (529, UpstreamErrorKind::Server),
+        (404, UpstreamErrorKind::InvalidRequest),
+        (422, UpstreamErrorKind::InvalidRequest),
+    ];
+    for (status, kind) in cases {
+        let error = UpstreamError::from_status(StatusCode::from_u16(status).unwrap(), "x");
+        assert_eq!(error.kind, kind, "status {status}");
+        assert_eq!(error.status.map(|s| s.as_u16()), Some(status));
+    }
+
+    let error = UpstreamError::from_status(StatusCode::BAD_GATEWAY, "x").with_provider("codex");
+    assert_eq!(error.provider.as_deref(), Some("codex"));
+}
+
 #[test]
 fn error_response_status_codes() {
     let resp = AppError::Auth("x".into()).into_response();
@@ -56,13 +97,83 @@ fn error_response_status_codes() {
     let resp = AppError::QuotaExceeded("x".into()).into_response();
     assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
 
-    let resp = AppError::Upstream("x".into()).into_response();
+    let resp = AppError::Upstream(UpstreamError::other("x")).into_response();
+    assert_eq!(resp.status(), StatusCode::BAD_GATEWAY);
+
+    let resp = AppError::Config("x".into()).into_response();
     assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
 }
 
+#[test]
+fn upstream_status_passes_through_for_rejected_requests() {
+    for status in [400, 404, 413, 422] {
+        assert_eq!(status_error(status, "x").status().as_u16(), status);
+    }
+    assert_eq!(status_error(401, "x").status(), StatusCode::BAD_GATEWAY);
+    assert_eq!(status_error(500, "x").status(), StatusCode::BAD_GATEWAY);
+    assert_eq!(
+        status_error(503, "x").status(),
+        StatusCode::SERVICE_UNAVAILABLE
+    );
+    assert_eq!(
+        AppError::Upstream(UpstreamError::transport("timed out")).status(),
+        StatusCode::BAD_GATEWAY
+    );
+}
+
+async fn body_json(error: AppError, family: ApiFamily) -> serde_json::Value {
+    let body = error.into_response_for(family).into_body();
+    let bytes = axum::body::to_bytes(body, usize::MAX).await.unwrap();
+    serde_json::from_slice(&bytes).unwrap()
+}
+
+#[tokio::test]
+async fn error_envelope_follows_api_family() {
+    let body = body_json(status_error(400, "context too long"), ApiFamily::OpenAi).await;
+    assert_eq!(body["error"]["type"], "invalid_request_error");
+    assert_eq!(body["error"]["code"], 400);
+    assert!(body["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("context too long"));
+
+    let body = body_json(status_error(413, "too large"), ApiFamily::Anthropic).await;
+    assert_eq!(body["type"], "error");
+    assert_eq!(body["error"]["type"], "request_too_large");
+
+    let body = body_json(status_error(429, "slow down"), ApiFamily::Anthropic).await;
+    assert_eq!(body["error"]["type"], "rate_limit_error");
+
+    let body = body_json(status_error(422, "bad schema"), ApiFamily::Gemini).await;
+    assert_eq!(body["error"]["code"], 422);
+    assert_eq!(body["error"]["status"], "INVALID_ARGUMENT");
+
+    let body = body_json(status_error(500, "boom"), ApiFamily::Gemini).await;
+    assert_eq!(body["error"]["code"], 502);
+    assert_eq!(body["error"]["status"], "INTERNAL");
+}
+
+#[test]
+fn rate_limited_sets_retry_after_header() {
+    let err = AppError::Upstream(
+        UpstreamError::from_status(StatusCode::TOO_MANY_REQUESTS, "codex usage limit reached")
+            .with_retry_after(Some(Duration::from_millis(90_500))),
+    );
+    assert!(err.is_account_error());
+    assert_eq!(err.retry_after(), Some(Duration::from_millis(90_500)));
+
+    let resp = err.into_response();
+    assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(resp.headers()["retry-after"], "90");
+
+    let resp = status_error(429, "x").into_response();
+    assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(resp.headers().get("retry-after").is_none());
+}
+
 #[test]
 fn error_display_messages() {
-    let e = AppError::Upstream("test".into());
+    let e = AppError::Upstream(UpstreamError::other("test"));
     assert_eq!(e.to_string(), "upstream error: test");
 
     let e = AppError::Auth("bad".into());
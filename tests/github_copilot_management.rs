@@ -72,11 +72,13 @@ fn test_app(state: Arc<ProxyState>) -> axum::Router {
     ))
 }
 
+type ModelRequestHeaders = Arc<Mutex<Vec<(Option<String>, Option<String>, Option<String>)>>>;
+
 #[derive(Clone)]
 struct MockGithubCopilotManagementState {
     token_responses: Arc<Mutex<Vec<Value>>>,
     token_grant_requests: Arc<Mutex<Vec<String>>>,
-    model_request_headers: Arc<Mutex<Vec<(Option<String>, Option<String>, Option<String>)>>>,
+    model_request_headers: ModelRequestHeaders,
 }
 
 async fn mock_device_code_handler() -> (StatusCode, Json<Value>) {
@@ -191,11 +193,7 @@ async fn mock_models_error_handler() -> (StatusCode, Json<Value>) {
 async fn spawn_copilot_management_server_with_models_route(
     token_responses: Vec<Value>,
     models_route: axum::routing::MethodRouter<MockGithubCopilotManagementState>,
-) -> (
-    String,
-    Arc<Mutex<Vec<String>>>,
-    Arc<Mutex<Vec<(Option<String>, Option<String>, Option<String>)>>>,
-) {
+) -> (String, Arc<Mutex<Vec<String>>>, ModelRequestHeaders) {
     let token_grant_requests = Arc::new(Mutex::new(Vec::new()));
     let model_request_headers = Arc::new(Mutex::new(Vec::new()));
     let state = MockGithubCopilotManagementState {
@@ -225,12 +223,9 @@ async fn spawn_copilot_management_server_with_models_route(
 
 async fn spawn_copilot_management_server(
     token_responses: Vec<Value>,
-) -> (
-    String,
-    Arc<Mutex<Vec<String>>>,
-    Arc<Mutex<Vec<(Option<String>, Option<String>, Option<String>)>>>,
-) {
-    spawn_copilot_management_server_with_models_route(token_responses, get(mock_models_handler)).await
+) -> (String, Arc<Mutex<Vec<String>>>, ModelRequestHeaders) {
+    spawn_copilot_management_server_with_models_route(token_responses, get(mock_models_handler))
+        .await
 }
 
 struct EnvVarGuard {
@@ -218,6 +218,7 @@ fn chat_request_with_assistant_parts_and_tools() -> ChatCompletionRequest {
                 name: None,
                 tool_calls: None,
                 tool_call_id: None,
+                reasoning_content: None,
             },
             ChatMessage {
                 role: "assistant".to_string(),
@@ -236,6 +237,7 @@ fn chat_request_with_assistant_parts_and_tools() -> ChatCompletionRequest {
                 name: None,
                 tool_calls: None,
                 tool_call_id: None,
+                reasoning_content: None,
             },
             ChatMessage {
                 role: "user".to_string(),
@@ -243,6 +245,7 @@ fn chat_request_with_assistant_parts_and_tools() -> ChatCompletionRequest {
                 name: None,
                 tool_calls: None,
                 tool_call_id: None,
+                reasoning_content: None,
             },
         ],
         stream: Some(false),
@@ -302,6 +305,7 @@ fn responses_style_request_with_image() -> ChatCompletionRequest {
             name: None,
             tool_calls: None,
             tool_call_id: None,
+            reasoning_content: None,
         }],
         stream: Some(false),
         max_tokens: None,
@@ -8,14 +8,15 @@ use tokio::sync::{Mutex, Notify};
 use tower::ServiceExt;
 
 use rusuh::auth::manager::AccountManager;
-use rusuh::config::Config;
-use rusuh::error::AppError;
+use rusuh::config::{ApiKeyEntry, ApiKeyScope, Config, KeyRateLimit, ModelRoute, ModelRouteTarget};
+use rusuh::error::{AppError, UpstreamError};
 use rusuh::models::{
     ChatCompletionRequest, ChatCompletionResponse, ChatMessage, Choice, MessageContent, ModelInfo,
 };
 use rusuh::providers::model_info::ExtModelInfo;
 use rusuh::providers::model_registry::ModelRegistry;
-use rusuh::providers::{BoxStream, Provider};
+use rusuh::providers::{AnthropicResponse, BoxStream, Provider};
+use rusuh::proxy::usage_ledger::{UsageGroup, UsageRange};
 use rusuh::proxy::ProxyState;
 use rusuh::router::build_router;
 
@@ -39,6 +40,8 @@ struct StubProvider {
     models: Vec<ModelInfo>,
     observed_models: Arc<Mutex<Vec<String>>>,
     result: StubCompletionResult,
+    /// How long each completion call takes before it answers
+    delay: std::time::Duration,
 }
 
 #[derive(Debug, Clone)]
@@ -46,6 +49,9 @@ enum StubCompletionResult {
     Success,
     SuccessWithToolCalls(Vec<serde_json::Value>),
     QuotaExceeded(String),
+    UpstreamError(UpstreamError),
+    /// Stream exactly these SSE chunks, or fail with the error in their place.
+    StreamChunks(Vec<Result<String, UpstreamError>>),
 }
 
 impl StubProvider {
@@ -80,6 +86,7 @@ impl StubProvider {
                 .collect(),
             observed_models,
             result: StubCompletionResult::Success,
+            delay: std::time::Duration::ZERO,
         }
     }
 
@@ -105,6 +112,7 @@ impl StubProvider {
                 .collect(),
             observed_models,
             result: StubCompletionResult::SuccessWithToolCalls(tool_calls),
+            delay: std::time::Duration::ZERO,
         }
     }
 
@@ -130,6 +138,38 @@ impl StubProvider {
                 .collect(),
             observed_models,
             result: StubCompletionResult::QuotaExceeded(message.to_string()),
+            delay: std::time::Duration::ZERO,
+        }
+    }
+
+    fn upstream_error(
+        name: &'static str,
+        provider_type: &'static str,
+        client_id: &str,
+        model_ids: &[&str],
+        observed_models: Arc<Mutex<Vec<String>>>,
+        error: UpstreamError,
+    ) -> Self {
+        Self {
+            result: StubCompletionResult::UpstreamError(error),
+            ..Self::success_with_type(name, provider_type, client_id, model_ids, observed_models)
+        }
+    }
+
+    fn delayed(self, delay: std::time::Duration) -> Self {
+        Self { delay, ..self }
+    }
+
+    fn streaming(
+        name: &'static str,
+        client_id: &str,
+        model_ids: &[&str],
+        observed_models: Arc<Mutex<Vec<String>>>,
+        chunks: Vec<Result<String, UpstreamError>>,
+    ) -> Self {
+        Self {
+            result: StubCompletionResult::StreamChunks(chunks),
+            ..Self::success(name, client_id, model_ids, observed_models)
         }
     }
 }
@@ -157,27 +197,31 @@ impl Provider for StubProvider {
         req: &ChatCompletionRequest,
     ) -> rusuh::error::AppResult<ChatCompletionResponse> {
         self.observed_models.lock().await.push(req.model.clone());
+        tokio::time::sleep(self.delay).await;
 
         match &self.result {
-            StubCompletionResult::Success => Ok(ChatCompletionResponse {
-                id: format!("{}-ok", self.name),
-                object: "chat.completion".to_string(),
-                created: 0,
-                model: req.model.clone(),
-                choices: vec![Choice {
-                    index: 0,
-                    message: Some(ChatMessage {
-                        role: "assistant".to_string(),
-                        content: MessageContent::Text(format!("handled by {}", self.name)),
-                        name: None,
-                        tool_calls: None,
-                        tool_call_id: None,
-                    }),
-                    delta: None,
-                    finish_reason: Some("stop".to_string()),
-                }],
-                usage: None,
-            }),
+            StubCompletionResult::Success | StubCompletionResult::StreamChunks(_) => {
+                Ok(ChatCompletionResponse {
+                    id: format!("{}-ok", self.name),
+                    object: "chat.completion".to_string(),
+                    created: 0,
+                    model: req.model.clone(),
+                    choices: vec![Choice {
+                        index: 0,
+                        message: Some(ChatMessage {
+                            role: "assistant".to_string(),
+                            content: MessageContent::Text(format!("handled by {}", self.name)),
+                            name: None,
+                            tool_calls: None,
+                            tool_call_id: None,
+                            reasoning_content: None,
+                        }),
+                        delta: None,
+                        finish_reason: Some("stop".to_string()),
+                    }],
+                    usage: None,
+                })
+            }
             StubCompletionResult::SuccessWithToolCalls(tool_calls) => Ok(ChatCompletionResponse {
                 id: format!("{}-ok", self.name),
                 object: "chat.completion".to_string(),
@@ -191,6 +235,7 @@ impl Provider for StubProvider {
                         name: None,
                         tool_calls: Some(tool_calls.clone()),
                         tool_call_id: None,
+                        reasoning_content: None,
                     }),
                     delta: None,
                     finish_reason: Some("tool_calls".to_string()),
@@ -200,6 +245,7 @@ impl Provider for StubProvider {
             StubCompletionResult::QuotaExceeded(message) => {
                 Err(AppError::QuotaExceeded(message.clone()))
             }
+            StubCompletionResult::UpstreamError(error) => Err(AppError::Upstream(error.clone())),
         }
     }
 
@@ -208,15 +254,38 @@ impl Provider for StubProvider {
         req: &ChatCompletionRequest,
     ) -> rusuh::error::AppResult<BoxStream> {
         self.observed_models.lock().await.push(req.model.clone());
+        tokio::time::sleep(self.delay).await;
         match &self.result {
             StubCompletionResult::Success | StubCompletionResult::SuccessWithToolCalls(_) => {
-                Ok(Box::pin(futures::stream::iter(vec![Ok(
-                    Bytes::from_static(b"data: [DONE]\n\n"),
-                )])))
+                let chunk = serde_json::json!({
+                    "id": format!("{}-ok", self.name),
+                    "object": "chat.completion.chunk",
+                    "created": 0,
+                    "model": req.model,
+                    "choices": [{
+                        "index": 0,
+                        "delta": {"content": format!("handled by {}", self.name)},
+                        "finish_reason": "stop",
+                    }]
+                });
+                Ok(Box::pin(futures::stream::iter(vec![
+                    Ok(Bytes::from(format!("data: {chunk}\n\n"))),
+                    Ok(Bytes::from_static(b"data: [DONE]\n\n")),
+                ])))
             }
+            StubCompletionResult::StreamChunks(chunks) => Ok(Box::pin(futures::stream::iter(
+                chunks
+                    .iter()
+                    .map(|chunk| match chunk {
+                        Ok(chunk) => Ok(Bytes::from(chunk.clone())),
+                        Err(error) => Err(AppError::Upstream(error.clone())),
+                    })
+                    .collect::<Vec<_>>(),
+            ))),
             StubCompletionResult::QuotaExceeded(message) => {
                 Err(AppError::QuotaExceeded(message.clone()))
             }
+            StubCompletionResult::UpstreamError(error) => Err(AppError::Upstream(error.clone())),
         }
     }
 }
@@ -351,6 +420,7 @@ impl Provider for BlockingProvider {
                     name: None,
                     tool_calls: None,
                     tool_call_id: None,
+                    reasoning_content: None,
                 }),
                 delta: None,
                 finish_reason: Some("stop".to_string()),
@@ -708,16 +778,148 @@ async fn responses_compact_endpoint_rejects_non_public_model_on_public_endpoint(
     assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
 }
 
-#[test]
-fn responses_compact_handler_documents_intentional_parity() {
-    let source = std::fs::read_to_string("src/proxy/handlers.rs").expect("read handlers source");
+async fn responses_test_app() -> axum::Router {
+    let providers: Vec<Arc<dyn Provider>> = vec![Arc::new(StubProvider::success_with_type(
+        "codex-display",
+        "codex",
+        "codex_0",
+        &["gpt-5.4"],
+        Arc::new(Mutex::new(Vec::new())),
+    ))];
+    let registry = Arc::new(ModelRegistry::new());
+    registry
+        .register_client(
+            "codex_0",
+            "codex",
+            vec![make_ext_model("gpt-5.4", "codex", "codex")],
+        )
+        .await;
 
-    assert!(
-        source.contains("TODO: /v1/responses/compact currently matches /v1/responses intentionally"),
-        "responses_compact should document why it currently routes identically"
+    test_app_with_state(test_state_with_providers(
+        Config::default(),
+        registry,
+        providers,
+    ))
+}
+
+async fn post_responses(
+    app: axum::Router,
+    uri: &str,
+    body: serde_json::Value,
+) -> axum::response::Response {
+    app.oneshot(
+        Request::builder()
+            .method("POST")
+            .uri(uri)
+            .header("Content-Type", "application/json")
+            .body(Body::from(serde_json::to_vec(&body).unwrap()))
+            .unwrap(),
+    )
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn responses_endpoint_returns_response_object() {
+    let resp = post_responses(
+        responses_test_app().await,
+        "/v1/responses",
+        serde_json::json!({"model": "gpt-5.4", "input": "hello"}),
+    )
+    .await;
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["object"], "response");
+    assert_eq!(json["status"], "completed");
+    assert_eq!(json["model"], "gpt-5.4");
+    assert_eq!(json["output"][0]["type"], "message");
+    assert_eq!(
+        json["output"][0]["content"][0]["text"],
+        "handled by codex-display"
+    );
+}
+
+#[tokio::test]
+async fn responses_endpoint_streams_typed_events() {
+    let resp = post_responses(
+        responses_test_app().await,
+        "/v1/responses",
+        serde_json::json!({"model": "gpt-5.4", "input": "hello", "stream": true}),
+    )
+    .await;
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
+        .await
+        .unwrap();
+    let text = String::from_utf8(body.to_vec()).unwrap();
+    let events = text
+        .lines()
+        .filter_map(|line| line.strip_prefix("event: "))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        events,
+        [
+            "response.created",
+            "response.in_progress",
+            "response.output_item.added",
+            "response.content_part.added",
+            "response.output_text.delta",
+            "response.output_text.done",
+            "response.content_part.done",
+            "response.output_item.done",
+            "response.completed"
+        ]
+    );
+    assert!(text.contains("handled by codex-display"));
+}
+
+#[tokio::test]
+async fn responses_compact_endpoint_returns_compaction_object() {
+    let resp = post_responses(
+        responses_test_app().await,
+        "/v1/responses/compact",
+        serde_json::json!({"model": "gpt-5.4", "input": "hello", "stream": true}),
+    )
+    .await;
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["object"], "response.compaction");
+    assert_eq!(json["output"][0]["role"], "assistant");
+    assert_eq!(
+        json["output"][0]["content"][0]["text"],
+        "handled by codex-display"
     );
 }
 
+#[tokio::test]
+async fn chat_rejects_unknown_reasoning_effort() {
+    let resp = post_responses(
+        responses_test_app().await,
+        "/v1/chat/completions",
+        serde_json::json!({
+            "model": "gpt-5.4",
+            "messages": [{"role": "user", "content": "hello"}],
+            "reasoning_effort": "extreme"
+        }),
+    )
+    .await;
+
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
+        .await
+        .unwrap();
+    assert!(String::from_utf8_lossy(&body).contains("unknown reasoning effort"));
+}
+
 #[tokio::test]
 async fn gemini_models_endpoint() {
     let app = test_app(Config::default());
@@ -1262,7 +1464,7 @@ async fn public_gpt_54_routes_to_codex_before_copilot() {
 }
 
 #[tokio::test]
-async fn public_gpt_53_codex_routes_to_codex_before_copilot() {
+async fn configured_model_route_serves_custom_public_name() {
     let codex_observed = Arc::new(Mutex::new(Vec::new()));
     let copilot_observed = Arc::new(Mutex::new(Vec::new()));
     let providers: Vec<Arc<dyn Provider>> = vec![
@@ -1270,14 +1472,14 @@ async fn public_gpt_53_codex_routes_to_codex_before_copilot() {
             "codex-display",
             "codex",
             "codex_0",
-            &["gpt-5.3-codex"],
+            &["gpt-5.4"],
             codex_observed.clone(),
         )),
         Arc::new(StubProvider::success_with_type(
             "github-copilot-display",
             "github-copilot",
             "github-copilot_0",
-            &["gpt-5.3-codex"],
+            &["gpt-5.4"],
             copilot_observed.clone(),
         )),
     ];
@@ -1287,655 +1489,2434 @@ async fn public_gpt_53_codex_routes_to_codex_before_copilot() {
         .register_client(
             "codex_0",
             "codex",
-            vec![make_ext_model("gpt-5.3-codex", "codex", "codex")],
+            vec![make_ext_model("gpt-5.4", "codex", "codex")],
         )
         .await;
     registry
         .register_client(
             "github-copilot_0",
             "github-copilot",
-            vec![make_ext_model("gpt-5.3-codex", "github-copilot", "github-copilot")],
+            vec![make_ext_model(
+                "gpt-5.4",
+                "github-copilot",
+                "github-copilot",
+            )],
         )
         .await;
 
-    let app = test_app_with_state(test_state_with_providers(
-        Config::default(),
-        registry,
-        providers,
-    ));
+    let mut disabled_copilot = ModelRouteTarget::new("github-copilot", "gpt-5.4");
+    disabled_copilot.weight = 0;
+    let cfg = Config {
+        model_routes: vec![ModelRoute {
+            name: "team-gpt".to_string(),
+            hidden: false,
+            targets: vec![disabled_copilot, ModelRouteTarget::new("codex", "gpt-5.4")],
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    let app = test_app_with_state(test_state_with_providers(cfg, registry, providers));
 
     let resp = app
+        .clone()
         .oneshot(
             Request::builder()
                 .method("POST")
                 .uri("/v1/chat/completions")
                 .header("content-type", "application/json")
-                .body(Body::from(basic_chat_request("gpt-5.3-codex").to_string()))
+                .body(Body::from(basic_chat_request("team-gpt").to_string()))
                 .unwrap(),
         )
         .await
         .unwrap();
 
     assert_eq!(resp.status(), StatusCode::OK);
-    assert_eq!(codex_observed.lock().await.as_slice(), ["gpt-5.3-codex"]);
+    assert_eq!(codex_observed.lock().await.as_slice(), ["gpt-5.4"]);
     assert!(copilot_observed.lock().await.is_empty());
-}
-
-#[tokio::test]
-async fn public_catalog_includes_opus_models_when_copilot_provides_them() {
-    let copilot_observed = Arc::new(Mutex::new(Vec::new()));
-    let providers: Vec<Arc<dyn Provider>> = vec![Arc::new(StubProvider::success_with_type(
-        "github-copilot-display",
-        "github-copilot",
-        "github-copilot_0",
-        &["claude-opus-4.6", "claude-opus-4.5"],
-        copilot_observed,
-    ))];
-
-    let registry = Arc::new(ModelRegistry::new());
-    registry
-        .register_client(
-            "github-copilot_0",
-            "github-copilot",
-            vec![
-                make_ext_model("claude-opus-4.6", "github-copilot", "github-copilot"),
-                make_ext_model("claude-opus-4.5", "github-copilot", "github-copilot"),
-            ],
-        )
-        .await;
-
-    let app = test_app_with_state(test_state_with_providers(
-        Config::default(),
-        registry,
-        providers,
-    ));
 
     let resp = app
-        .oneshot(Request::builder().uri("/v1/models").body(Body::empty()).unwrap())
+        .oneshot(
+            Request::builder()
+                .uri("/v1/models")
+                .body(Body::empty())
+                .unwrap(),
+        )
         .await
         .unwrap();
-
-    assert_eq!(resp.status(), StatusCode::OK);
     let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
         .await
         .unwrap();
     let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
-    let ids: Vec<String> = json["data"]
-        .as_array()
-        .unwrap()
-        .iter()
-        .map(|item| item["id"].as_str().unwrap().to_string())
-        .collect();
-
-    assert!(ids.contains(&"claude-opus-4-6".to_string()));
-    assert!(ids.contains(&"claude-opus-4-5".to_string()));
+    assert_eq!(json["data"][0]["id"], "team-gpt");
+    assert_eq!(json["data"].as_array().unwrap().len(), 1);
 }
 
 #[tokio::test]
-async fn reserved_opus_model_routes_to_copilot_opus_46() {
-    let copilot_observed = Arc::new(Mutex::new(Vec::new()));
-    let providers: Vec<Arc<dyn Provider>> = vec![Arc::new(StubProvider::success_with_type(
-        "github-copilot-display",
-        "github-copilot",
-        "github-copilot_0",
-        &["claude-opus-4.6"],
-        copilot_observed.clone(),
-    ))];
+async fn weighted_strategy_splits_accounts_and_reports_stats_on_dashboard() {
+    let heavy_observed = Arc::new(Mutex::new(Vec::new()));
+    let light_observed = Arc::new(Mutex::new(Vec::new()));
+    let providers: Vec<Arc<dyn Provider>> = vec![
+        Arc::new(StubProvider::success_with_type(
+            "codex-heavy",
+            "codex",
+            "codex_heavy",
+            &["gpt-5.4"],
+            heavy_observed.clone(),
+        )),
+        Arc::new(StubProvider::success_with_type(
+            "codex-light",
+            "codex",
+            "codex_light",
+            &["gpt-5.4"],
+            light_observed.clone(),
+        )),
+    ];
 
     let registry = Arc::new(ModelRegistry::new());
-    registry
-        .register_client(
-            "github-copilot_0",
-            "github-copilot",
-            vec![make_ext_model("claude-opus-4.6", "github-copilot", "github-copilot")],
-        )
-        .await;
+    for client_id in ["codex_heavy", "codex_light"] {
+        registry
+            .register_client(
+                client_id,
+                "codex",
+                vec![make_ext_model("gpt-5.4", "codex", "codex")],
+            )
+            .await;
+    }
 
-    let app = test_app_with_state(test_state_with_providers(
-        Config::default(),
-        registry,
-        providers,
-    ));
+    let mut cfg = Config {
+        model_routes: vec![ModelRoute {
+            name: "team-gpt".to_string(),
+            hidden: false,
+            targets: vec![ModelRouteTarget::new("codex", "gpt-5.4")],
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    cfg.routing.strategy = "weighted".to_string();
+    cfg.routing.weights.insert("codex_heavy".to_string(), 3);
+    let app = test_app_with_state(test_state_with_providers(cfg, registry, providers));
+
+    for _ in 0..8 {
+        let resp = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/v1/chat/completions")
+                    .header("content-type", "application/json")
+                    .body(Body::from(basic_chat_request("team-gpt").to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+    assert_eq!(heavy_observed.lock().await.len(), 6);
+    assert_eq!(light_observed.lock().await.len(), 2);
 
     let resp = app
         .oneshot(
             Request::builder()
-                .method("POST")
-                .uri("/v1/chat/completions")
-                .header("Content-Type", "application/json")
-                .body(Body::from(
-                    serde_json::to_vec(&basic_chat_request("claude-opus-4-6")).unwrap(),
-                ))
+                .uri("/dashboard/overview")
+                .body(Body::empty())
                 .unwrap(),
         )
         .await
         .unwrap();
-
     assert_eq!(resp.status(), StatusCode::OK);
-    assert_eq!(copilot_observed.lock().await.as_slice(), ["claude-opus-4.6"]);
+    let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["routing_strategy"], "weighted");
+    let heavy = json["balancer"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|candidate| candidate["client_id"] == "codex_heavy")
+        .expect("heavy account should be listed");
+    assert_eq!(heavy["weight"], 3);
+    assert_eq!(heavy["requests"], 6);
+    assert_eq!(heavy["in_flight"], 0);
+    assert!(heavy["ewma_latency_ms"].is_number());
 }
 
 #[tokio::test]
-async fn reserved_opus_45_model_routes_to_copilot_opus_45() {
-    let copilot_observed = Arc::new(Mutex::new(Vec::new()));
-    let providers: Vec<Arc<dyn Provider>> = vec![Arc::new(StubProvider::success_with_type(
-        "github-copilot-display",
-        "github-copilot",
-        "github-copilot_0",
-        &["claude-opus-4.5"],
-        copilot_observed.clone(),
-    ))];
-
-    let registry = Arc::new(ModelRegistry::new());
-    registry
-        .register_client(
-            "github-copilot_0",
-            "github-copilot",
-            vec![make_ext_model("claude-opus-4.5", "github-copilot", "github-copilot")],
-        )
-        .await;
-
-    let app = test_app_with_state(test_state_with_providers(
-        Config::default(),
-        registry,
-        providers,
-    ));
-
-    let resp = app
-        .oneshot(
-            Request::builder()
-                .method("POST")
-                .uri("/v1/chat/completions")
-                .header("Content-Type", "application/json")
-                .body(Body::from(
-                    serde_json::to_vec(&basic_chat_request("claude-opus-4-5")).unwrap(),
-                ))
-                .unwrap(),
-        )
-        .await
-        .unwrap();
-
-    assert_eq!(resp.status(), StatusCode::OK);
-    assert_eq!(copilot_observed.lock().await.as_slice(), ["claude-opus-4.5"]);
-}
-
-#[tokio::test]
-async fn public_route_failed_selected_auth_request_does_not_poison_execution_session() {
-    let seen = Arc::new(Mutex::new(Vec::new()));
-
-    let providers: Vec<Arc<dyn Provider>> = vec![Arc::new(StubProvider::success(
-        "zed",
-        "zed_0",
-        &["claude-sonnet-4-6"],
-        seen.clone(),
-    ))];
+async fn circuit_breaker_skips_failing_account_and_reports_it_open() {
+    let failing_observed = Arc::new(Mutex::new(Vec::new()));
+    let healthy_observed = Arc::new(Mutex::new(Vec::new()));
+    let providers: Vec<Arc<dyn Provider>> = vec![
+        Arc::new(StubProvider::upstream_error(
+            "codex-failing",
+            "codex",
+            "codex_failing",
+            &["gpt-5.4"],
+            failing_observed.clone(),
+            UpstreamError::from_status(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "codex upstream error (503 Service Unavailable)",
+            ),
+        )),
+        Arc::new(StubProvider::success_with_type(
+            "codex-healthy",
+            "codex",
+            "codex_healthy",
+            &["gpt-5.4"],
+            healthy_observed.clone(),
+        )),
+    ];
 
     let registry = Arc::new(ModelRegistry::new());
-    registry
-        .register_client(
-            "zed_0",
-            "zed",
-            vec![make_ext_model("claude-sonnet-4-6", "zed", "zed")],
-        )
-        .await;
+    for client_id in ["codex_failing", "codex_healthy"] {
+        registry
+            .register_client(
+                client_id,
+                "codex",
+                vec![make_ext_model("gpt-5.4", "codex", "codex")],
+            )
+            .await;
+    }
 
-    let app = test_app_with_state(test_state_with_providers(
-        Config::default(),
-        registry,
-        providers,
-    ));
+    let mut cfg = Config {
+        model_routes: vec![ModelRoute {
+            name: "team-gpt".to_string(),
+            hidden: false,
+            targets: vec![ModelRouteTarget::new("codex", "gpt-5.4")],
+            ..Default::default()
+        }],
+        remote_management: rusuh::config::ManagementConfig {
+            allow_remote: true,
+            secret_key: "mgmt-secret".to_string(),
+        },
+        ..Default::default()
+    };
+    cfg.routing.strategy = "fill-first".to_string();
+    cfg.circuit_breaker.failure_threshold = 2;
+    let app = test_app_with_state(test_state_with_providers(cfg, registry, providers));
 
-    let initial_body = serde_json::json!({
-        "model": "claude-sonnet-4-6",
-        "selected_auth_id": "zed_99",
-        "execution_session_id": "session-public",
-        "messages": [{"role": "user", "content": "hello"}]
-    });
+    for _ in 0..4 {
+        let resp = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/v1/chat/completions")
+                    .header("content-type", "application/json")
+                    .body(Body::from(basic_chat_request("team-gpt").to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+    assert_eq!(failing_observed.lock().await.len(), 2);
+    assert_eq!(healthy_observed.lock().await.len(), 4);
 
-    let initial_resp = app
-        .clone()
+    let resp = app
         .oneshot(
             Request::builder()
-                .method("POST")
-                .uri("/v1/chat/completions")
-                .header("Content-Type", "application/json")
-                .body(Body::from(serde_json::to_vec(&initial_body).unwrap()))
+                .uri("/v0/management/circuit-breakers")
+                .header("authorization", "Bearer mgmt-secret")
+                .body(Body::empty())
                 .unwrap(),
         )
         .await
         .unwrap();
-    assert_eq!(initial_resp.status(), StatusCode::TOO_MANY_REQUESTS);
-
-    let follow_up_body = serde_json::json!({
-        "model": "claude-sonnet-4-6",
-        "execution_session_id": "session-public",
-        "messages": [{"role": "user", "content": "continue"}]
-    });
-
-    let follow_up_resp = app
-        .oneshot(
-            Request::builder()
-                .method("POST")
-                .uri("/v1/chat/completions")
-                .header("Content-Type", "application/json")
-                .body(Body::from(serde_json::to_vec(&follow_up_body).unwrap()))
-                .unwrap(),
-        )
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
         .await
         .unwrap();
-    assert_eq!(follow_up_resp.status(), StatusCode::OK);
-    assert_eq!(seen.lock().await.as_slice(), &["claude-sonnet-4-6"]);
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let circuits = json["circuit-breakers"].as_array().unwrap();
+    assert_eq!(circuits.len(), 1);
+    assert_eq!(circuits[0]["client_id"], "codex_failing");
+    assert_eq!(circuits[0]["model"], "gpt-5.4");
+    assert_eq!(circuits[0]["state"], "open");
+    assert_eq!(circuits[0]["consecutive_failures"], 2);
 }
 
 #[tokio::test]
-async fn chat_execution_session_sticks_selected_auth_across_requests() {
-    let first_seen = Arc::new(Mutex::new(Vec::new()));
-    let second_seen = Arc::new(Mutex::new(Vec::new()));
-
+async fn rate_limited_account_stays_out_until_upstream_reset() {
+    let limited_observed = Arc::new(Mutex::new(Vec::new()));
+    let healthy_observed = Arc::new(Mutex::new(Vec::new()));
+    let three_days = std::time::Duration::from_secs(3 * 24 * 3600);
     let providers: Vec<Arc<dyn Provider>> = vec![
-        Arc::new(StubProvider::success(
+        Arc::new(StubProvider::upstream_error(
+            "codex-limited",
             "codex",
-            "codex_0",
-            &["gpt-5-codex"],
-            first_seen.clone(),
+            "codex_limited",
+            &["gpt-5.4"],
+            limited_observed.clone(),
+            UpstreamError::from_status(StatusCode::TOO_MANY_REQUESTS, "usage limit reached")
+                .with_retry_after(Some(three_days)),
         )),
-        Arc::new(StubProvider::success(
+        Arc::new(StubProvider::success_with_type(
+            "codex-healthy",
             "codex",
-            "codex_1",
-            &["gpt-5-codex"],
-            second_seen.clone(),
+            "codex_healthy",
+            &["gpt-5.4"],
+            healthy_observed.clone(),
         )),
     ];
 
     let registry = Arc::new(ModelRegistry::new());
-    registry
-        .register_client(
-            "codex_0",
-            "codex",
-            vec![make_ext_model("gpt-5-codex", "codex", "codex")],
-        )
-        .await;
-    registry
-        .register_client(
-            "codex_1",
-            "codex",
-            vec![make_ext_model("gpt-5-codex", "codex", "codex")],
-        )
-        .await;
+    for client_id in ["codex_limited", "codex_healthy"] {
+        registry
+            .register_client(
+                client_id,
+                "codex",
+                vec![make_ext_model("gpt-5.4", "codex", "codex")],
+            )
+            .await;
+    }
 
-    let app = test_app_with_state(test_state_with_providers(
-        Config::default(),
-        registry,
-        providers,
-    ));
+    let mut cfg = Config {
+        model_routes: vec![ModelRoute {
+            name: "team-gpt".to_string(),
+            hidden: false,
+            targets: vec![ModelRouteTarget::new("codex", "gpt-5.4")],
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    cfg.routing.strategy = "fill-first".to_string();
+    let app = test_app_with_state(test_state_with_providers(cfg, registry.clone(), providers));
 
-    let initial_body = serde_json::json!({
-        "model": "gpt-5-codex",
-        "selected_auth_id": "codex_1",
-        "execution_session_id": "session-http",
-        "messages": [{"role": "user", "content": "hello"}]
-    });
+    for _ in 0..3 {
+        let resp = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/v1/chat/completions")
+                    .header("content-type", "application/json")
+                    .body(Body::from(basic_chat_request("team-gpt").to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+    assert_eq!(limited_observed.lock().await.len(), 1);
+    assert_eq!(healthy_observed.lock().await.len(), 3);
 
-    let initial_resp = app
-        .clone()
-        .oneshot(
-            Request::builder()
-                .method("POST")
-                .uri("/api/provider/codex/v1/chat/completions")
-                .header("Content-Type", "application/json")
-                .body(Body::from(serde_json::to_vec(&initial_body).unwrap()))
-                .unwrap(),
-        )
+    let reset_in = registry
+        .quota_reset_in("codex_limited", "gpt-5.4")
         .await
-        .unwrap();
-    assert_eq!(initial_resp.status(), StatusCode::OK);
+        .expect("rate-limited account should be marked");
+    assert!(reset_in > three_days - std::time::Duration::from_secs(60));
+    assert!(
+        !registry
+            .client_is_effectively_available("codex_limited", "gpt-5.4")
+            .await
+    );
+}
 
-    let follow_up_body = serde_json::json!({
-        "model": "gpt-5-codex",
-        "execution_session_id": "session-http",
-        "messages": [{"role": "user", "content": "continue"}]
-    });
+#[tokio::test]
+async fn upstream_errors_are_classified_by_status_not_message_text() {
+    let observed = Arc::new(Mutex::new(Vec::new()));
+    let fallback_observed = Arc::new(Mutex::new(Vec::new()));
+    let providers: Vec<Arc<dyn Provider>> = vec![
+        Arc::new(StubProvider::upstream_error(
+            "claude-rejecting",
+            "claude",
+            "claude_rejecting",
+            &["claude-sonnet-4-5"],
+            observed.clone(),
+            UpstreamError::from_status(
+                StatusCode::BAD_REQUEST,
+                "prompt mentions 429, quota and 503 but is simply invalid",
+            ),
+        )),
+        Arc::new(StubProvider::success_with_type(
+            "claude-fallback",
+            "claude",
+            "claude_fallback",
+            &["claude-sonnet-4-5"],
+            fallback_observed.clone(),
+        )),
+    ];
 
-    let follow_up_resp = app
+    let registry = Arc::new(ModelRegistry::new());
+    for client_id in ["claude_rejecting", "claude_fallback"] {
+        registry
+            .register_client(
+                client_id,
+                "claude",
+                vec![make_ext_model("claude-sonnet-4-5", "anthropic", "claude")],
+            )
+            .await;
+    }
+
+    let mut cfg = Config {
+        request_retry: 3,
+        model_routes: vec![ModelRoute {
+            name: "team-claude".to_string(),
+            hidden: false,
+            targets: vec![ModelRouteTarget::new("claude", "claude-sonnet-4-5")],
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    cfg.routing.strategy = "fill-first".to_string();
+    let app = test_app_with_state(test_state_with_providers(cfg, registry.clone(), providers));
+
+    let resp = app
         .oneshot(
             Request::builder()
                 .method("POST")
-                .uri("/api/provider/codex/v1/chat/completions")
-                .header("Content-Type", "application/json")
-                .body(Body::from(serde_json::to_vec(&follow_up_body).unwrap()))
+                .uri("/v1/chat/completions")
+                .header("content-type", "application/json")
+                .body(Body::from(basic_chat_request("team-claude").to_string()))
                 .unwrap(),
         )
         .await
         .unwrap();
-    assert_eq!(follow_up_resp.status(), StatusCode::OK);
 
-    assert!(first_seen.lock().await.is_empty());
-    assert_eq!(
-        second_seen.lock().await.as_slice(),
-        &["gpt-5-codex", "gpt-5-codex"]
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(observed.lock().await.len(), 1, "400 must not be retried");
+    assert_eq!(fallback_observed.lock().await.len(), 1);
+    assert!(
+        registry
+            .client_is_effectively_available("claude_rejecting", "claude-sonnet-4-5")
+            .await
     );
 }
 
 #[tokio::test]
-async fn management_endpoint_skips_auth() {
+async fn rate_limit_without_reset_hint_uses_default_quota_window() {
+    let observed = Arc::new(Mutex::new(Vec::new()));
+    let providers: Vec<Arc<dyn Provider>> = vec![Arc::new(StubProvider::upstream_error(
+        "gemini-limited",
+        "gemini",
+        "gemini_limited",
+        &["gemini-2.5-pro"],
+        observed.clone(),
+        UpstreamError::from_status(StatusCode::TOO_MANY_REQUESTS, "resource exhausted"),
+    ))];
+
+    let registry = Arc::new(ModelRegistry::new());
+    registry
+        .register_client(
+            "gemini_limited",
+            "gemini",
+            vec![make_ext_model("gemini-2.5-pro", "google", "gemini")],
+        )
+        .await;
     let cfg = Config {
-        api_keys: vec!["secret".into()],
+        model_routes: vec![ModelRoute {
+            name: "team-gemini".to_string(),
+            hidden: false,
+            targets: vec![ModelRouteTarget::new("gemini", "gemini-2.5-pro")],
+            ..Default::default()
+        }],
         ..Default::default()
     };
-
-    let app = test_app(cfg);
+    let app = test_app_with_state(test_state_with_providers(cfg, registry.clone(), providers));
 
     let resp = app
         .oneshot(
             Request::builder()
-                .uri("/v0/management/health")
-                .body(Body::empty())
+                .method("POST")
+                .uri("/v1/chat/completions")
+                .header("content-type", "application/json")
+                .body(Body::from(basic_chat_request("team-gemini").to_string()))
                 .unwrap(),
         )
         .await
         .unwrap();
 
-    // Should not be 401 — management skips auth
-    assert_ne!(resp.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+    let reset_in = registry
+        .quota_reset_in("gemini_limited", "gemini-2.5-pro")
+        .await
+        .expect("rate-limited account should be marked");
+    assert!(reset_in <= std::time::Duration::from_secs(300));
+    assert!(reset_in > std::time::Duration::from_secs(290));
 }
 
 #[tokio::test]
-async fn spa_fallback_does_not_override_api_routes() {
-    let app = test_app(Config::default());
+async fn rejected_requests_keep_upstream_status_in_the_callers_error_envelope() {
+    let observed = Arc::new(Mutex::new(Vec::new()));
+    let providers: Vec<Arc<dyn Provider>> = vec![Arc::new(StubProvider::upstream_error(
+        "claude-strict",
+        "claude",
+        "claude_strict",
+        &["claude-sonnet-4-5"],
+        observed.clone(),
+        UpstreamError::from_status(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            "prompt is too long: 250000 tokens > 200000 maximum",
+        ),
+    ))];
 
-    let api_resp = app
-        .clone()
-        .oneshot(
-            Request::builder()
-                .uri("/v1/models")
-                .body(Body::empty())
-                .unwrap(),
+    let registry = Arc::new(ModelRegistry::new());
+    registry
+        .register_client(
+            "claude_strict",
+            "claude",
+            vec![make_ext_model("claude-sonnet-4-5", "anthropic", "claude")],
         )
-        .await
-        .unwrap();
+        .await;
+    let cfg = Config {
+        model_routes: vec![ModelRoute {
+            name: "team-claude".to_string(),
+            hidden: false,
+            targets: vec![ModelRouteTarget::new("claude", "claude-sonnet-4-5")],
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    let app = test_app_with_state(test_state_with_providers(cfg, registry, providers));
+
+    let requests = [
+        ("/v1/chat/completions", basic_chat_request("team-claude")),
+        (
+            "/v1/messages",
+            serde_json::json!({
+                "model": "team-claude",
+                "max_tokens": 64,
+                "messages": [{"role": "user", "content": "test"}]
+            }),
+        ),
+        (
+            "/v1beta/models/team-claude:generateContent",
+            serde_json::json!({
+                "contents": [{"role": "user", "parts": [{"text": "test"}]}]
+            }),
+        ),
+    ];
+    let mut bodies = Vec::new();
+    for (uri, body) in requests {
+        let resp = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(uri)
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::PAYLOAD_TOO_LARGE, "{uri}");
+        let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
+            .await
+            .unwrap();
+        bodies.push(serde_json::from_slice::<serde_json::Value>(&body).unwrap());
+    }
 
-    assert_eq!(api_resp.status(), StatusCode::OK);
+    assert_eq!(bodies[0]["error"]["type"], "invalid_request_error");
+    assert_eq!(bodies[0]["error"]["code"], 413);
+    assert_eq!(bodies[1]["type"], "error");
+    assert_eq!(bodies[1]["error"]["type"], "request_too_large");
+    assert!(bodies[1]["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("prompt is too long"));
+    assert_eq!(bodies[2]["error"]["code"], 413);
+    assert_eq!(bodies[2]["error"]["status"], "INVALID_ARGUMENT");
+    assert_eq!(observed.lock().await.len(), 3);
+}
 
-    let spa_resp = app
+fn content_chunk(text: &str) -> String {
+    let chunk = serde_json::json!({
+        "object": "chat.completion.chunk",
+        "choices": [{"index": 0, "delta": {"content": text}, "finish_reason": null}]
+    });
+    format!("data: {chunk}\n\n")
+}
+
+async fn streaming_test_app(providers: Vec<Arc<dyn Provider>>) -> axum::Router {
+    test_app_with_state(streaming_test_state(providers).await)
+}
+
+async fn streaming_test_state(providers: Vec<Arc<dyn Provider>>) -> Arc<ProxyState> {
+    let registry = Arc::new(ModelRegistry::new());
+    for provider in &providers {
+        registry
+            .register_client(
+                provider.client_id(),
+                provider.provider_type(),
+                vec![make_ext_model(
+                    "gpt-5.4",
+                    "openai",
+                    provider.provider_type(),
+                )],
+            )
+            .await;
+    }
+    let mut cfg = Config {
+        model_routes: vec![ModelRoute {
+            name: "team-gpt".to_string(),
+            hidden: false,
+            targets: vec![ModelRouteTarget::new("codex", "gpt-5.4")],
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    cfg.routing.strategy = "fill-first".to_string();
+    cfg.metrics.public = true;
+    test_state_with_providers(cfg, registry, providers)
+}
+
+async fn stream_chat(app: axum::Router) -> (StatusCode, String) {
+    let mut body = basic_chat_request("team-gpt");
+    body["stream"] = serde_json::json!(true);
+    let resp = app
         .oneshot(
             Request::builder()
-                .uri("/dashboard/overview")
-                .body(Body::empty())
+                .method("POST")
+                .uri("/v1/chat/completions")
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
                 .unwrap(),
         )
         .await
         .unwrap();
-
-    assert!(matches!(
-        spa_resp.status(),
-        StatusCode::OK | StatusCode::NOT_FOUND | StatusCode::INTERNAL_SERVER_ERROR
-    ));
-    assert_ne!(spa_resp.status(), StatusCode::UNAUTHORIZED);
+    let status = resp.status();
+    let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
+        .await
+        .unwrap();
+    (status, String::from_utf8(body.to_vec()).unwrap())
 }
 
-// ── Kiro auth-aware load balancing tests ─────────────────────────────────────
-
 #[tokio::test]
-async fn kiro_routing_skips_quota_exceeded_auth() {
-    use rusuh::providers::model_info::ExtModelInfo;
-    use tempfile::TempDir;
-
-    let dir = TempDir::new().unwrap();
-
-    // Create two Kiro auth files
-    let auth1 = serde_json::json!({
-        "type": "kiro",
-        "provider_key": "kiro",
-        "access_token": "token1",
-        "refresh_token": "refresh1",
-        "expires_at": "2030-01-01T00:00:00Z",
-        "auth_method": "builder-id",
-        "provider": "AWS",
-        "region": "us-east-1",
-        "client_id": "test-client-1",
-        "client_secret": "secret1"
+async fn stream_failing_before_first_content_fails_over_to_next_candidate() {
+    let broken_observed = Arc::new(Mutex::new(Vec::new()));
+    let empty_observed = Arc::new(Mutex::new(Vec::new()));
+    let healthy_observed = Arc::new(Mutex::new(Vec::new()));
+    let role_only = serde_json::json!({
+        "object": "chat.completion.chunk",
+        "choices": [{"index": 0, "delta": {"role": "assistant"}, "finish_reason": null}]
     });
+    let providers: Vec<Arc<dyn Provider>> = vec![
+        Arc::new(StubProvider::streaming(
+            "codex",
+            "codex_broken",
+            &["gpt-5.4"],
+            broken_observed.clone(),
+            vec![
+                Ok(format!("data: {role_only}\n\n")),
+                Err(UpstreamError::transport(
+                    "stream read error: connection reset",
+                )),
+            ],
+        )),
+        Arc::new(StubProvider::streaming(
+            "codex",
+            "codex_empty",
+            &["gpt-5.4"],
+            empty_observed.clone(),
+            vec![Ok("data: [DONE]\n\n".to_string())],
+        )),
+        Arc::new(StubProvider::streaming(
+            "codex",
+            "codex_healthy",
+            &["gpt-5.4"],
+            healthy_observed.clone(),
+            vec![
+                Ok(content_chunk("served by the healthy account")),
+                Ok("data: [DONE]\n\n".to_string()),
+            ],
+        )),
+    ];
 
-    let auth2 = serde_json::json!({
-        "type": "kiro",
-        "provider_key": "kiro",
-        "access_token": "token2",
-        "refresh_token": "refresh2",
-        "expires_at": "2030-01-01T00:00:00Z",
-        "auth_method": "builder-id",
-        "provider": "AWS",
-        "region": "us-east-1",
-        "client_id": "test-client-2",
-        "client_secret": "secret2"
-    });
+    let (status, body) = stream_chat(streaming_test_app(providers).await).await;
 
-    std::fs::write(
-        dir.path().join("kiro-1.json"),
-        serde_json::to_string_pretty(&auth1).unwrap(),
-    )
-    .unwrap();
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("served by the healthy account"));
+    assert!(
+        !body.contains("assistant"),
+        "held chunks of a failed stream leaked"
+    );
+    assert!(!body.contains("error"));
+    assert!(!broken_observed.lock().await.is_empty());
+    assert_eq!(empty_observed.lock().await.len(), 1);
+    assert_eq!(healthy_observed.lock().await.len(), 1);
+}
 
-    std::fs::write(
-        dir.path().join("kiro-2.json"),
-        serde_json::to_string_pretty(&auth2).unwrap(),
-    )
-    .unwrap();
+#[tokio::test]
+async fn stream_failing_after_content_ends_with_error_event_and_done() {
+    let observed = Arc::new(Mutex::new(Vec::new()));
+    let fallback_observed = Arc::new(Mutex::new(Vec::new()));
+    let providers: Vec<Arc<dyn Provider>> = vec![
+        Arc::new(StubProvider::streaming(
+            "codex",
+            "codex_flaky",
+            &["gpt-5.4"],
+            observed.clone(),
+            vec![
+                Ok(content_chunk("partial answer")),
+                Err(UpstreamError::transport(
+                    "stream read error: connection reset",
+                )),
+            ],
+        )),
+        Arc::new(StubProvider::streaming(
+            "codex",
+            "codex_fallback",
+            &["gpt-5.4"],
+            fallback_observed.clone(),
+            vec![Ok(content_chunk("unused"))],
+        )),
+    ];
 
-    // Load accounts and build providers
-    let accounts = Arc::new(AccountManager::with_dir(dir.path()));
-    accounts.reload().await.unwrap();
+    let (status, body) = stream_chat(streaming_test_app(providers).await).await;
+
+    assert_eq!(status, StatusCode::OK);
+    let events = body
+        .split("\n\n")
+        .filter(|event| !event.is_empty())
+        .collect::<Vec<_>>();
+    assert_eq!(events.len(), 3, "{body}");
+    assert!(events[0].contains("partial answer"));
+    let error: serde_json::Value =
+        serde_json::from_str(events[1].strip_prefix("data: ").unwrap()).unwrap();
+    assert_eq!(error["error"]["type"], "stream_error");
+    assert!(error["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("connection reset"));
+    assert_eq!(events[2], "data: [DONE]");
+    assert_eq!(observed.lock().await.len(), 1);
+    assert!(fallback_observed.lock().await.is_empty());
+}
 
-    let config = Config::default();
+async fn hedged_test_app(providers: Vec<Arc<dyn Provider>>, hedge_after_ms: u64) -> axum::Router {
     let registry = Arc::new(ModelRegistry::new());
-    let runtime = rusuh::proxy::KiroRuntimeState::default();
-    let providers = rusuh::providers::registry::build_providers(
-        &config,
-        &accounts,
-        registry.clone(),
-        runtime.clone(),
-    )
-    .await;
-
-    // Register both providers with the same model
-    let test_model = "claude-sonnet-4";
-
-    for (idx, provider) in providers.iter().enumerate() {
-        let client_id = format!("{}_{}", provider.name(), idx);
-        let models = vec![ExtModelInfo {
-            id: test_model.to_string(),
-            object: "model".to_string(),
-            created: 0,
-            owned_by: "kiro".to_string(),
-            provider_type: "kiro".to_string(),
-            display_name: None,
-            name: Some(test_model.to_string()),
-            version: None,
-            description: None,
-            input_token_limit: 0,
-            output_token_limit: 0,
-            supported_generation_methods: vec![],
-            context_length: 0,
-            max_completion_tokens: 0,
-            supported_parameters: vec![],
-            supported_endpoints: None,
-            thinking: None,
-            user_defined: false,
-        }];
+    for provider in &providers {
         registry
-            .register_client(&client_id, provider.name(), models)
+            .register_client(
+                provider.client_id(),
+                "codex",
+                vec![make_ext_model("gpt-5.4", "codex", "codex")],
+            )
             .await;
     }
+    let mut cfg = Config {
+        model_routes: vec![ModelRoute {
+            name: "team-gpt".to_string(),
+            hidden: false,
+            targets: vec![ModelRouteTarget::new("codex", "gpt-5.4")],
+            hedge_after_ms: Some(hedge_after_ms),
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    cfg.routing.strategy = "fill-first".to_string();
+    test_app_with_state(test_state_with_providers(cfg, registry, providers))
+}
 
-    // Mark first provider as quota-exceeded
-    registry.set_quota_exceeded("kiro_0", test_model).await;
-
-    // Verify only second provider is available
-    let available = registry.available_clients_for_model(test_model).await;
-    assert_eq!(available.len(), 1);
-    assert_eq!(available[0], "kiro_1");
-
-    // Build app state
-    let mut state = ProxyState::new(config, accounts, registry, providers.len());
-    state.kiro_runtime = runtime;
-    let state = Arc::new(state);
-    state
-        .publish_runtime_from_providers(providers)
-        .await
-        .expect("test providers should publish");
-
-    // Make a request - should use kiro_1, not kiro_0
-    let body = serde_json::json!({
-        "model": test_model,
-        "messages": [{"role": "user", "content": "test"}]
-    });
-
-    let app = build_router(state.clone());
+#[tokio::test]
+async fn hedged_request_lets_the_faster_account_win_and_reports_it() {
+    let slow_observed = Arc::new(Mutex::new(Vec::new()));
+    let fast_observed = Arc::new(Mutex::new(Vec::new()));
+    let providers: Vec<Arc<dyn Provider>> = vec![
+        Arc::new(
+            StubProvider::success_with_type(
+                "codex-slow",
+                "codex",
+                "codex_slow",
+                &["gpt-5.4"],
+                slow_observed.clone(),
+            )
+            .delayed(std::time::Duration::from_secs(30)),
+        ),
+        Arc::new(StubProvider::success_with_type(
+            "codex-fast",
+            "codex",
+            "codex_fast",
+            &["gpt-5.4"],
+            fast_observed.clone(),
+        )),
+    ];
+    let app = hedged_test_app(providers, 50).await;
 
+    let started = std::time::Instant::now();
     let resp = app
+        .clone()
         .oneshot(
             Request::builder()
                 .method("POST")
                 .uri("/v1/chat/completions")
-                .header("Content-Type", "application/json")
-                .body(Body::from(serde_json::to_vec(&body).unwrap()))
+                .header("content-type", "application/json")
+                .body(Body::from(basic_chat_request("team-gpt").to_string()))
                 .unwrap(),
         )
         .await
         .unwrap();
-
-    // Should attempt request (will fail due to fake tokens, but that's OK)
-    // The key is that it should NOT return 429 "no providers available"
-    // because kiro_1 is still available
-    assert_ne!(
-        resp.status(),
-        StatusCode::TOO_MANY_REQUESTS,
-        "should not return 429 when one provider is still available"
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert!(started.elapsed() < std::time::Duration::from_secs(10));
+    let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(
+        json["choices"][0]["message"]["content"],
+        "handled by codex-fast"
     );
+    assert_eq!(slow_observed.lock().await.len(), 1);
+    assert_eq!(fast_observed.lock().await.len(), 1);
+
+    let resp = app
+        .oneshot(
+            Request::builder()
+                .uri("/dashboard/overview")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let candidate = |client_id: &str| {
+        json["balancer"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|candidate| candidate["client_id"] == client_id)
+            .cloned()
+            .unwrap()
+    };
+    let (slow, fast) = (candidate("codex_slow"), candidate("codex_fast"));
+    assert_eq!(slow["requests"], 1);
+    assert_eq!(slow["in_flight"], 0, "losing call should be cancelled");
+    assert_eq!(slow["hedges"], 0);
+    assert_eq!(fast["requests"], 1);
+    assert_eq!(fast["hedges"], 1);
+    assert_eq!(fast["hedge_wins"], 1);
+    assert_eq!(json["hedge_win_rate"], 1.0);
 }
 
 #[tokio::test]
-async fn kiro_routing_skips_suspended_auth() {
-    use rusuh::providers::model_info::ExtModelInfo;
-    use tempfile::TempDir;
+async fn hedge_is_not_launched_when_first_account_answers_in_time() {
+    let first_observed = Arc::new(Mutex::new(Vec::new()));
+    let second_observed = Arc::new(Mutex::new(Vec::new()));
+    let providers: Vec<Arc<dyn Provider>> = vec![
+        Arc::new(StubProvider::success_with_type(
+            "codex-first",
+            "codex",
+            "codex_first",
+            &["gpt-5.4"],
+            first_observed.clone(),
+        )),
+        Arc::new(StubProvider::success_with_type(
+            "codex-second",
+            "codex",
+            "codex_second",
+            &["gpt-5.4"],
+            second_observed.clone(),
+        )),
+    ];
+    let (status, body) = stream_chat(hedged_test_app(providers, 5_000).await).await;
 
-    let dir = TempDir::new().unwrap();
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("handled by codex-first"));
+    assert_eq!(first_observed.lock().await.len(), 1);
+    assert!(second_observed.lock().await.is_empty());
+}
+
+#[tokio::test]
+async fn failed_hedge_account_is_not_called_again_after_the_primary_fails() {
+    let first_observed = Arc::new(Mutex::new(Vec::new()));
+    let hedge_observed = Arc::new(Mutex::new(Vec::new()));
+    let third_observed = Arc::new(Mutex::new(Vec::new()));
+    let providers: Vec<Arc<dyn Provider>> = vec![
+        Arc::new(
+            StubProvider::upstream_error(
+                "codex-first",
+                "codex",
+                "codex_first",
+                &["gpt-5.4"],
+                first_observed.clone(),
+                UpstreamError::other("first account failed"),
+            )
+            .delayed(std::time::Duration::from_millis(300)),
+        ),
+        Arc::new(StubProvider::upstream_error(
+            "codex-hedge",
+            "codex",
+            "codex_hedge",
+            &["gpt-5.4"],
+            hedge_observed.clone(),
+            UpstreamError::other("hedge account failed"),
+        )),
+        Arc::new(StubProvider::success_with_type(
+            "codex-third",
+            "codex",
+            "codex_third",
+            &["gpt-5.4"],
+            third_observed.clone(),
+        )),
+    ];
+
+    let resp = hedged_test_app(providers, 50)
+        .await
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/v1/chat/completions")
+                .header("content-type", "application/json")
+                .body(Body::from(basic_chat_request("team-gpt").to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(
+        json["choices"][0]["message"]["content"],
+        "handled by codex-third"
+    );
+    assert_eq!(first_observed.lock().await.len(), 1);
+    assert_eq!(hedge_observed.lock().await.len(), 1);
+    assert_eq!(third_observed.lock().await.len(), 1);
+}
+
+#[tokio::test]
+async fn public_gpt_53_codex_routes_to_codex_before_copilot() {
+    let codex_observed = Arc::new(Mutex::new(Vec::new()));
+    let copilot_observed = Arc::new(Mutex::new(Vec::new()));
+    let providers: Vec<Arc<dyn Provider>> = vec![
+        Arc::new(StubProvider::success_with_type(
+            "codex-display",
+            "codex",
+            "codex_0",
+            &["gpt-5.3-codex"],
+            codex_observed.clone(),
+        )),
+        Arc::new(StubProvider::success_with_type(
+            "github-copilot-display",
+            "github-copilot",
+            "github-copilot_0",
+            &["gpt-5.3-codex"],
+            copilot_observed.clone(),
+        )),
+    ];
+
+    let registry = Arc::new(ModelRegistry::new());
+    registry
+        .register_client(
+            "codex_0",
+            "codex",
+            vec![make_ext_model("gpt-5.3-codex", "codex", "codex")],
+        )
+        .await;
+    registry
+        .register_client(
+            "github-copilot_0",
+            "github-copilot",
+            vec![make_ext_model(
+                "gpt-5.3-codex",
+                "github-copilot",
+                "github-copilot",
+            )],
+        )
+        .await;
+
+    let app = test_app_with_state(test_state_with_providers(
+        Config::default(),
+        registry,
+        providers,
+    ));
+
+    let resp = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/v1/chat/completions")
+                .header("content-type", "application/json")
+                .body(Body::from(basic_chat_request("gpt-5.3-codex").to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(codex_observed.lock().await.as_slice(), ["gpt-5.3-codex"]);
+    assert!(copilot_observed.lock().await.is_empty());
+}
+
+#[tokio::test]
+async fn public_catalog_includes_opus_models_when_copilot_provides_them() {
+    let copilot_observed = Arc::new(Mutex::new(Vec::new()));
+    let providers: Vec<Arc<dyn Provider>> = vec![Arc::new(StubProvider::success_with_type(
+        "github-copilot-display",
+        "github-copilot",
+        "github-copilot_0",
+        &["claude-opus-4.6", "claude-opus-4.5"],
+        copilot_observed,
+    ))];
+
+    let registry = Arc::new(ModelRegistry::new());
+    registry
+        .register_client(
+            "github-copilot_0",
+            "github-copilot",
+            vec![
+                make_ext_model("claude-opus-4.6", "github-copilot", "github-copilot"),
+                make_ext_model("claude-opus-4.5", "github-copilot", "github-copilot"),
+            ],
+        )
+        .await;
+
+    let app = test_app_with_state(test_state_with_providers(
+        Config::default(),
+        registry,
+        providers,
+    ));
+
+    let resp = app
+        .oneshot(
+            Request::builder()
+                .uri("/v1/models")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let ids: Vec<String> = json["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["id"].as_str().unwrap().to_string())
+        .collect();
+
+    assert!(ids.contains(&"claude-opus-4-6".to_string()));
+    assert!(ids.contains(&"claude-opus-4-5".to_string()));
+}
+
+#[tokio::test]
+async fn reserved_opus_model_routes_to_copilot_opus_46() {
+    let copilot_observed = Arc::new(Mutex::new(Vec::new()));
+    let providers: Vec<Arc<dyn Provider>> = vec![Arc::new(StubProvider::success_with_type(
+        "github-copilot-display",
+        "github-copilot",
+        "github-copilot_0",
+        &["claude-opus-4.6"],
+        copilot_observed.clone(),
+    ))];
+
+    let registry = Arc::new(ModelRegistry::new());
+    registry
+        .register_client(
+            "github-copilot_0",
+            "github-copilot",
+            vec![make_ext_model(
+                "claude-opus-4.6",
+                "github-copilot",
+                "github-copilot",
+            )],
+        )
+        .await;
+
+    let app = test_app_with_state(test_state_with_providers(
+        Config::default(),
+        registry,
+        providers,
+    ));
+
+    let resp = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/v1/chat/completions")
+                .header("Content-Type", "application/json")
+                .body(Body::from(
+                    serde_json::to_vec(&basic_chat_request("claude-opus-4-6")).unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(
+        copilot_observed.lock().await.as_slice(),
+        ["claude-opus-4.6"]
+    );
+}
+
+#[tokio::test]
+async fn reserved_opus_45_model_routes_to_copilot_opus_45() {
+    let copilot_observed = Arc::new(Mutex::new(Vec::new()));
+    let providers: Vec<Arc<dyn Provider>> = vec![Arc::new(StubProvider::success_with_type(
+        "github-copilot-display",
+        "github-copilot",
+        "github-copilot_0",
+        &["claude-opus-4.5"],
+        copilot_observed.clone(),
+    ))];
+
+    let registry = Arc::new(ModelRegistry::new());
+    registry
+        .register_client(
+            "github-copilot_0",
+            "github-copilot",
+            vec![make_ext_model(
+                "claude-opus-4.5",
+                "github-copilot",
+                "github-copilot",
+            )],
+        )
+        .await;
+
+    let app = test_app_with_state(test_state_with_providers(
+        Config::default(),
+        registry,
+        providers,
+    ));
+
+    let resp = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/v1/chat/completions")
+                .header("Content-Type", "application/json")
+                .body(Body::from(
+                    serde_json::to_vec(&basic_chat_request("claude-opus-4-5")).unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(
+        copilot_observed.lock().await.as_slice(),
+        ["claude-opus-4.5"]
+    );
+}
+
+#[tokio::test]
+async fn public_route_failed_selected_auth_request_does_not_poison_execution_session() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+
+    let providers: Vec<Arc<dyn Provider>> = vec![Arc::new(StubProvider::success(
+        "zed",
+        "zed_0",
+        &["claude-sonnet-4-6"],
+        seen.clone(),
+    ))];
+
+    let registry = Arc::new(ModelRegistry::new());
+    registry
+        .register_client(
+            "zed_0",
+            "zed",
+            vec![make_ext_model("claude-sonnet-4-6", "zed", "zed")],
+        )
+        .await;
+
+    let app = test_app_with_state(test_state_with_providers(
+        Config::default(),
+        registry,
+        providers,
+    ));
+
+    let initial_body = serde_json::json!({
+        "model": "claude-sonnet-4-6",
+        "selected_auth_id": "zed_99",
+        "execution_session_id": "session-public",
+        "messages": [{"role": "user", "content": "hello"}]
+    });
+
+    let initial_resp = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/v1/chat/completions")
+                .header("Content-Type", "application/json")
+                .body(Body::from(serde_json::to_vec(&initial_body).unwrap()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(initial_resp.status(), StatusCode::TOO_MANY_REQUESTS);
+
+    let follow_up_body = serde_json::json!({
+        "model": "claude-sonnet-4-6",
+        "execution_session_id": "session-public",
+        "messages": [{"role": "user", "content": "continue"}]
+    });
+
+    let follow_up_resp = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/v1/chat/completions")
+                .header("Content-Type", "application/json")
+                .body(Body::from(serde_json::to_vec(&follow_up_body).unwrap()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(follow_up_resp.status(), StatusCode::OK);
+    assert_eq!(seen.lock().await.as_slice(), &["claude-sonnet-4-6"]);
+}
+
+#[tokio::test]
+async fn chat_execution_session_sticks_selected_auth_across_requests() {
+    let first_seen = Arc::new(Mutex::new(Vec::new()));
+    let second_seen = Arc::new(Mutex::new(Vec::new()));
+
+    let providers: Vec<Arc<dyn Provider>> = vec![
+        Arc::new(StubProvider::success(
+            "codex",
+            "codex_0",
+            &["gpt-5-codex"],
+            first_seen.clone(),
+        )),
+        Arc::new(StubProvider::success(
+            "codex",
+            "codex_1",
+            &["gpt-5-codex"],
+            second_seen.clone(),
+        )),
+    ];
+
+    let registry = Arc::new(ModelRegistry::new());
+    registry
+        .register_client(
+            "codex_0",
+            "codex",
+            vec![make_ext_model("gpt-5-codex", "codex", "codex")],
+        )
+        .await;
+    registry
+        .register_client(
+            "codex_1",
+            "codex",
+            vec![make_ext_model("gpt-5-codex", "codex", "codex")],
+        )
+        .await;
+
+    let app = test_app_with_state(test_state_with_providers(
+        Config::default(),
+        registry,
+        providers,
+    ));
+
+    let initial_body = serde_json::json!({
+        "model": "gpt-5-codex",
+        "selected_auth_id": "codex_1",
+        "execution_session_id": "session-http",
+        "messages": [{"role": "user", "content": "hello"}]
+    });
+
+    let initial_resp = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/provider/codex/v1/chat/completions")
+                .header("Content-Type", "application/json")
+                .body(Body::from(serde_json::to_vec(&initial_body).unwrap()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(initial_resp.status(), StatusCode::OK);
+
+    let follow_up_body = serde_json::json!({
+        "model": "gpt-5-codex",
+        "execution_session_id": "session-http",
+        "messages": [{"role": "user", "content": "continue"}]
+    });
+
+    let follow_up_resp = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/provider/codex/v1/chat/completions")
+                .header("Content-Type", "application/json")
+                .body(Body::from(serde_json::to_vec(&follow_up_body).unwrap()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(follow_up_resp.status(), StatusCode::OK);
+
+    assert!(first_seen.lock().await.is_empty());
+    assert_eq!(
+        second_seen.lock().await.as_slice(),
+        &["gpt-5-codex", "gpt-5-codex"]
+    );
+}
+
+#[tokio::test]
+async fn management_endpoint_skips_auth() {
+    let cfg = Config {
+        api_keys: vec!["secret".into()],
+        ..Default::default()
+    };
+
+    let app = test_app(cfg);
+
+    let resp = app
+        .oneshot(
+            Request::builder()
+                .uri("/v0/management/health")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    // Should not be 401 — management skips auth
+    assert_ne!(resp.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn spa_fallback_does_not_override_api_routes() {
+    let app = test_app(Config::default());
+
+    let api_resp = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/v1/models")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(api_resp.status(), StatusCode::OK);
+
+    let spa_resp = app
+        .oneshot(
+            Request::builder()
+                .uri("/dashboard/overview")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        spa_resp.status(),
+        StatusCode::OK | StatusCode::NOT_FOUND | StatusCode::INTERNAL_SERVER_ERROR
+    ));
+    assert_ne!(spa_resp.status(), StatusCode::UNAUTHORIZED);
+}
+
+// ── Kiro auth-aware load balancing tests ─────────────────────────────────────
+
+#[tokio::test]
+async fn kiro_routing_skips_quota_exceeded_auth() {
+    use rusuh::providers::model_info::ExtModelInfo;
+    use tempfile::TempDir;
+
+    let dir = TempDir::new().unwrap();
+
+    // Create two Kiro auth files
+    let auth1 = serde_json::json!({
+        "type": "kiro",
+        "provider_key": "kiro",
+        "access_token": "token1",
+        "refresh_token": "refresh1",
+        "expires_at": "2030-01-01T00:00:00Z",
+        "auth_method": "builder-id",
+        "provider": "AWS",
+        "region": "us-east-1",
+        "client_id": "test-client-1",
+        "client_secret": "secret1"
+    });
+
+    let auth2 = serde_json::json!({
+        "type": "kiro",
+        "provider_key": "kiro",
+        "access_token": "token2",
+        "refresh_token": "refresh2",
+        "expires_at": "2030-01-01T00:00:00Z",
+        "auth_method": "builder-id",
+        "provider": "AWS",
+        "region": "us-east-1",
+        "client_id": "test-client-2",
+        "client_secret": "secret2"
+    });
+
+    std::fs::write(
+        dir.path().join("kiro-1.json"),
+        serde_json::to_string_pretty(&auth1).unwrap(),
+    )
+    .unwrap();
+
+    std::fs::write(
+        dir.path().join("kiro-2.json"),
+        serde_json::to_string_pretty(&auth2).unwrap(),
+    )
+    .unwrap();
+
+    // Load accounts and build providers
+    let accounts = Arc::new(AccountManager::with_dir(dir.path()));
+    accounts.reload().await.unwrap();
+
+    let config = Config::default();
+    let registry = Arc::new(ModelRegistry::new());
+    let runtime = rusuh::proxy::KiroRuntimeState::default();
+    let providers = rusuh::providers::registry::build_providers(
+        &config,
+        &accounts,
+        registry.clone(),
+        runtime.clone(),
+    )
+    .await;
+
+    // Register both providers with the same model
+    let test_model = "claude-sonnet-4";
+
+    for (idx, provider) in providers.iter().enumerate() {
+        let client_id = format!("{}_{}", provider.name(), idx);
+        let models = vec![ExtModelInfo {
+            id: test_model.to_string(),
+            object: "model".to_string(),
+            created: 0,
+            owned_by: "kiro".to_string(),
+            provider_type: "kiro".to_string(),
+            display_name: None,
+            name: Some(test_model.to_string()),
+            version: None,
+            description: None,
+            input_token_limit: 0,
+            output_token_limit: 0,
+            supported_generation_methods: vec![],
+            context_length: 0,
+            max_completion_tokens: 0,
+            supported_parameters: vec![],
+            supported_endpoints: None,
+            thinking: None,
+            user_defined: false,
+        }];
+        registry
+            .register_client(&client_id, provider.name(), models)
+            .await;
+    }
+
+    // Mark first provider as quota-exceeded
+    registry.set_quota_exceeded("kiro_0", test_model).await;
+
+    // Verify only second provider is available
+    let available = registry.available_clients_for_model(test_model).await;
+    assert_eq!(available.len(), 1);
+    assert_eq!(available[0], "kiro_1");
+
+    // Build app state
+    let mut state = ProxyState::new(config, accounts, registry, providers.len());
+    state.kiro_runtime = runtime;
+    let state = Arc::new(state);
+    state
+        .publish_runtime_from_providers(providers)
+        .await
+        .expect("test providers should publish");
+
+    // Make a request - should use kiro_1, not kiro_0
+    let body = serde_json::json!({
+        "model": test_model,
+        "messages": [{"role": "user", "content": "test"}]
+    });
+
+    let app = build_router(state.clone());
+
+    let resp = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/v1/chat/completions")
+                .header("Content-Type", "application/json")
+                .body(Body::from(serde_json::to_vec(&body).unwrap()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    // Should attempt request (will fail due to fake tokens, but that's OK)
+    // The key is that it should NOT return 429 "no providers available"
+    // because kiro_1 is still available
+    assert_ne!(
+        resp.status(),
+        StatusCode::TOO_MANY_REQUESTS,
+        "should not return 429 when one provider is still available"
+    );
+}
+
+#[tokio::test]
+async fn kiro_routing_skips_suspended_auth() {
+    use rusuh::providers::model_info::ExtModelInfo;
+    use tempfile::TempDir;
+
+    let dir = TempDir::new().unwrap();
+
+    // Create two Kiro auth files
+    let auth1 = serde_json::json!({
+        "type": "kiro",
+        "provider_key": "kiro",
+        "access_token": "token1",
+        "refresh_token": "refresh1",
+        "expires_at": "2030-01-01T00:00:00Z",
+        "auth_method": "builder-id",
+        "provider": "AWS",
+        "region": "us-east-1",
+        "client_id": "test-client-1",
+        "client_secret": "secret1"
+    });
+
+    let auth2 = serde_json::json!({
+        "type": "kiro",
+        "provider_key": "kiro",
+        "access_token": "token2",
+        "refresh_token": "refresh2",
+        "expires_at": "2030-01-01T00:00:00Z",
+        "auth_method": "builder-id",
+        "provider": "AWS",
+        "region": "us-east-1",
+        "client_id": "test-client-2",
+        "client_secret": "secret2"
+    });
+
+    std::fs::write(
+        dir.path().join("kiro-1.json"),
+        serde_json::to_string_pretty(&auth1).unwrap(),
+    )
+    .unwrap();
+
+    std::fs::write(
+        dir.path().join("kiro-2.json"),
+        serde_json::to_string_pretty(&auth2).unwrap(),
+    )
+    .unwrap();
+
+    // Load accounts and build providers
+    let accounts = Arc::new(AccountManager::with_dir(dir.path()));
+    accounts.reload().await.unwrap();
+
+    let config = Config::default();
+    let registry = Arc::new(ModelRegistry::new());
+    let runtime = rusuh::proxy::KiroRuntimeState::default();
+    let providers = rusuh::providers::registry::build_providers(
+        &config,
+        &accounts,
+        registry.clone(),
+        runtime.clone(),
+    )
+    .await;
+
+    // Register both providers with the same model
+    let test_model = "claude-sonnet-4";
+
+    for (idx, provider) in providers.iter().enumerate() {
+        let client_id = format!("{}_{}", provider.name(), idx);
+        let models = vec![ExtModelInfo {
+            id: test_model.to_string(),
+            object: "model".to_string(),
+            created: 0,
+            owned_by: "kiro".to_string(),
+            provider_type: "kiro".to_string(),
+            display_name: None,
+            name: Some(test_model.to_string()),
+            version: None,
+            description: None,
+            input_token_limit: 0,
+            output_token_limit: 0,
+            supported_generation_methods: vec![],
+            context_length: 0,
+            max_completion_tokens: 0,
+            supported_parameters: vec![],
+            supported_endpoints: None,
+            thinking: None,
+            user_defined: false,
+        }];
+        registry
+            .register_client(&client_id, provider.name(), models)
+            .await;
+    }
+
+    // Suspend first provider
+    registry
+        .suspend_client_model("kiro_0", test_model, "test suspension")
+        .await;
+
+    // Verify only second provider is available
+    let available = registry.available_clients_for_model(test_model).await;
+    assert_eq!(available.len(), 1);
+    assert_eq!(available[0], "kiro_1");
+
+    // Build app state
+    let mut state = ProxyState::new(config, accounts, registry, providers.len());
+    state.kiro_runtime = runtime;
+    let state = Arc::new(state);
+    state
+        .publish_runtime_from_providers(providers)
+        .await
+        .expect("test providers should publish");
+
+    // Make a request - should use kiro_1, not kiro_0
+    let body = serde_json::json!({
+        "model": test_model,
+        "messages": [{"role": "user", "content": "test"}]
+    });
+
+    let app = build_router(state.clone());
+
+    let resp = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/v1/chat/completions")
+                .header("Content-Type", "application/json")
+                .body(Body::from(serde_json::to_vec(&body).unwrap()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    // Should attempt request (will fail due to fake tokens, but that's OK)
+    assert_ne!(
+        resp.status(),
+        StatusCode::TOO_MANY_REQUESTS,
+        "should not return 429 when one provider is still available"
+    );
+}
+
+#[tokio::test]
+async fn kiro_routing_returns_error_when_all_unavailable() {
+    use rusuh::providers::model_info::ExtModelInfo;
+    use tempfile::TempDir;
+
+    let dir = TempDir::new().unwrap();
+
+    // Create one Kiro auth file
+    let auth1 = serde_json::json!({
+        "type": "kiro",
+        "provider_key": "kiro",
+        "access_token": "token1",
+        "refresh_token": "refresh1",
+        "expires_at": "2030-01-01T00:00:00Z",
+        "auth_method": "builder-id",
+        "provider": "AWS",
+        "region": "us-east-1",
+        "client_id": "test-client-1",
+        "client_secret": "secret1"
+    });
+
+    std::fs::write(
+        dir.path().join("kiro-1.json"),
+        serde_json::to_string_pretty(&auth1).unwrap(),
+    )
+    .unwrap();
+
+    // Load accounts and build providers
+    let accounts = Arc::new(AccountManager::with_dir(dir.path()));
+    accounts.reload().await.unwrap();
+
+    let config = Config::default();
+    let registry = Arc::new(ModelRegistry::new());
+    let runtime = rusuh::proxy::KiroRuntimeState::default();
+    let providers = rusuh::providers::registry::build_providers(
+        &config,
+        &accounts,
+        registry.clone(),
+        runtime.clone(),
+    )
+    .await;
+
+    // Register provider with a model
+    let test_model = "claude-sonnet-4";
+
+    for (idx, provider) in providers.iter().enumerate() {
+        let client_id = format!("{}_{}", provider.name(), idx);
+        let models = vec![ExtModelInfo {
+            id: test_model.to_string(),
+            object: "model".to_string(),
+            created: 0,
+            owned_by: "kiro".to_string(),
+            provider_type: "kiro".to_string(),
+            display_name: None,
+            name: Some(test_model.to_string()),
+            version: None,
+            description: None,
+            input_token_limit: 0,
+            output_token_limit: 0,
+            supported_generation_methods: vec![],
+            context_length: 0,
+            max_completion_tokens: 0,
+            supported_parameters: vec![],
+            supported_endpoints: None,
+            thinking: None,
+            user_defined: false,
+        }];
+        registry
+            .register_client(&client_id, provider.name(), models)
+            .await;
+    }
+
+    // Mark provider as quota-exceeded
+    registry.set_quota_exceeded("kiro_0", test_model).await;
+
+    // Verify no providers are available
+    let available = registry.available_clients_for_model(test_model).await;
+    assert_eq!(available.len(), 0);
+
+    // Build app state
+    let mut state = ProxyState::new(config, accounts, registry, providers.len());
+    state.kiro_runtime = runtime;
+    let state = Arc::new(state);
+    state
+        .publish_runtime_from_providers(providers)
+        .await
+        .expect("test providers should publish");
+
+    // Make a request - should return error
+    let body = serde_json::json!({
+        "model": test_model,
+        "messages": [{"role": "user", "content": "test"}]
+    });
+
+    let app = build_router(state.clone());
+
+    let resp = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/v1/chat/completions")
+                .header("Content-Type", "application/json")
+                .body(Body::from(serde_json::to_vec(&body).unwrap()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn public_models_catalog_is_curated_to_three_router_models() {
+    let zed_observed = Arc::new(Mutex::new(Vec::new()));
+    let kiro_observed = Arc::new(Mutex::new(Vec::new()));
+    let zed_client_id = "auth-zed-record-1";
+    let kiro_client_id = "auth-kiro-record-1";
+
+    let providers: Vec<Arc<dyn Provider>> = vec![
+        Arc::new(StubProvider::success_with_type(
+            "zed-display",
+            "zed",
+            zed_client_id,
+            &["claude-sonnet-4-6", "claude-sonnet-4-5"],
+            zed_observed,
+        )),
+        Arc::new(StubProvider::success_with_type(
+            "kiro-display",
+            "kiro",
+            kiro_client_id,
+            &["kiro-claude-sonnet-4-5", "kiro-claude-sonnet-4-5-agentic"],
+            kiro_observed,
+        )),
+    ];
+
+    let registry = Arc::new(ModelRegistry::new());
+    registry
+        .register_client(
+            zed_client_id,
+            "zed",
+            vec![
+                make_ext_model("claude-sonnet-4-6", "zed", "zed"),
+                make_ext_model("claude-sonnet-4-5", "zed", "zed"),
+            ],
+        )
+        .await;
+    registry
+        .register_client(
+            kiro_client_id,
+            "kiro",
+            vec![
+                make_ext_model("kiro-claude-sonnet-4-5", "kiro", "kiro"),
+                make_ext_model("kiro-claude-sonnet-4-5-agentic", "kiro", "kiro"),
+            ],
+        )
+        .await;
+
+    let app = test_app_with_state(test_state_with_providers(
+        Config::default(),
+        registry,
+        providers,
+    ));
+
+    let resp = app
+        .oneshot(
+            Request::builder()
+                .uri("/v1/models")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let ids: Vec<String> = json["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["id"].as_str().unwrap().to_string())
+        .collect();
+
+    assert_eq!(
+        ids,
+        vec![
+            "claude-sonnet-4-6".to_string(),
+            "claude-sonnet-4-5".to_string(),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn provider_pinned_kiro_models_expose_only_raw_kiro_ids() {
+    let kiro_first_observed = Arc::new(Mutex::new(Vec::new()));
+    let kiro_second_observed = Arc::new(Mutex::new(Vec::new()));
+    let zed_observed = Arc::new(Mutex::new(Vec::new()));
+    let providers: Vec<Arc<dyn Provider>> = vec![
+        Arc::new(StubProvider::success(
+            "kiro",
+            "kiro_0",
+            &["kiro-claude-sonnet-4-5", "kiro-claude-sonnet-4-5-agentic"],
+            kiro_first_observed,
+        )),
+        Arc::new(StubProvider::success(
+            "kiro",
+            "kiro_1",
+            &["kiro-claude-sonnet-4-5", "kiro-claude-sonnet-4-5-agentic"],
+            kiro_second_observed,
+        )),
+        Arc::new(StubProvider::success(
+            "zed",
+            "zed_0",
+            &["claude-sonnet-4-6", "claude-sonnet-4-5"],
+            zed_observed,
+        )),
+    ];
+    let app = test_app_with_state(test_state_with_providers(
+        Config::default(),
+        Arc::new(ModelRegistry::new()),
+        providers,
+    ));
+
+    let resp = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/provider/kiro/v1/models")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let ids: Vec<String> = json["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["id"].as_str().unwrap().to_string())
+        .collect();
+
+    assert_eq!(
+        ids,
+        vec![
+            "kiro-claude-sonnet-4-5".to_string(),
+            "kiro-claude-sonnet-4-5-agentic".to_string(),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn provider_pinned_zed_models_expose_only_raw_zed_ids() {
+    let kiro_observed = Arc::new(Mutex::new(Vec::new()));
+    let zed_first_observed = Arc::new(Mutex::new(Vec::new()));
+    let zed_second_observed = Arc::new(Mutex::new(Vec::new()));
+    let providers: Vec<Arc<dyn Provider>> = vec![
+        Arc::new(StubProvider::success(
+            "kiro",
+            "kiro_0",
+            &["kiro-claude-sonnet-4-5", "kiro-claude-sonnet-4-5-agentic"],
+            kiro_observed,
+        )),
+        Arc::new(StubProvider::success(
+            "zed",
+            "zed_0",
+            &["claude-sonnet-4-6", "claude-sonnet-4-5"],
+            zed_first_observed,
+        )),
+        Arc::new(StubProvider::success(
+            "zed",
+            "zed_1",
+            &["claude-sonnet-4-6", "claude-sonnet-4-5"],
+            zed_second_observed,
+        )),
+    ];
+    let app = test_app_with_state(test_state_with_providers(
+        Config::default(),
+        Arc::new(ModelRegistry::new()),
+        providers,
+    ));
+
+    let resp = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/provider/zed/v1/models")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let ids: Vec<String> = json["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["id"].as_str().unwrap().to_string())
+        .collect();
+
+    assert_eq!(
+        ids,
+        vec![
+            "claude-sonnet-4-6".to_string(),
+            "claude-sonnet-4-5".to_string(),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn public_endpoint_rejects_provider_native_model_ids() {
+    let kiro_seen = Arc::new(Mutex::new(Vec::new()));
+    let zed_seen = Arc::new(Mutex::new(Vec::new()));
+    let providers: Vec<Arc<dyn Provider>> = vec![
+        Arc::new(StubProvider::success(
+            "kiro",
+            "kiro_0",
+            &["kiro-claude-sonnet-4-5"],
+            kiro_seen.clone(),
+        )),
+        Arc::new(StubProvider::success(
+            "zed",
+            "zed_1",
+            &["claude-sonnet-4-5"],
+            zed_seen.clone(),
+        )),
+    ];
+    let registry = Arc::new(ModelRegistry::new());
+    registry
+        .register_client(
+            "kiro_0",
+            "kiro",
+            vec![make_ext_model("kiro-claude-sonnet-4-5", "kiro", "kiro")],
+        )
+        .await;
+    registry
+        .register_client(
+            "zed_1",
+            "zed",
+            vec![make_ext_model("claude-sonnet-4-5", "zed", "zed")],
+        )
+        .await;
+
+    let app = test_app_with_state(test_state_with_providers(
+        Config::default(),
+        registry,
+        providers,
+    ));
+
+    for model in ["kiro-claude-sonnet-4-5", "claude-sonnet-4-5"] {
+        let resp = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/v1/chat/completions")
+                    .header("Content-Type", "application/json")
+                    .body(Body::from(
+                        serde_json::to_vec(&basic_chat_request(model)).unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    assert!(kiro_seen.lock().await.is_empty());
+    assert!(zed_seen.lock().await.is_empty());
+}
+
+#[tokio::test]
+async fn public_claude_sonnet_4_6_routes_only_to_zed_native_model() {
+    let zed_seen = Arc::new(Mutex::new(Vec::new()));
+    let kiro_seen = Arc::new(Mutex::new(Vec::new()));
+    let providers: Vec<Arc<dyn Provider>> = vec![
+        Arc::new(StubProvider::success(
+            "kiro",
+            "kiro_0",
+            &["kiro-claude-sonnet-4-6"],
+            kiro_seen.clone(),
+        )),
+        Arc::new(StubProvider::success(
+            "zed",
+            "zed_1",
+            &["claude-sonnet-4-6"],
+            zed_seen.clone(),
+        )),
+    ];
+    let registry = Arc::new(ModelRegistry::new());
+    registry
+        .register_client(
+            "kiro_0",
+            "kiro",
+            vec![make_ext_model("kiro-claude-sonnet-4-6", "kiro", "kiro")],
+        )
+        .await;
+    registry
+        .register_client(
+            "zed_1",
+            "zed",
+            vec![make_ext_model("claude-sonnet-4-6", "zed", "zed")],
+        )
+        .await;
+
+    let app = test_app_with_state(test_state_with_providers(
+        Config::default(),
+        registry,
+        providers,
+    ));
+    let body = basic_chat_request("claude-sonnet-4-6");
+
+    let resp = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/v1/chat/completions")
+                .header("Content-Type", "application/json")
+                .body(Body::from(serde_json::to_vec(&body).unwrap()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert!(kiro_seen.lock().await.is_empty());
+    assert_eq!(zed_seen.lock().await.as_slice(), &["claude-sonnet-4-6"]);
+}
+
+#[tokio::test]
+async fn public_claude_sonnet_4_5_routes_kiro_first_then_falls_back_to_zed() {
+    let kiro_seen = Arc::new(Mutex::new(Vec::new()));
+    let zed_seen = Arc::new(Mutex::new(Vec::new()));
+    let providers: Vec<Arc<dyn Provider>> = vec![
+        Arc::new(StubProvider::quota_exceeded(
+            "kiro",
+            "kiro_0",
+            &["kiro-claude-sonnet-4-5"],
+            kiro_seen.clone(),
+            "kiro unavailable",
+        )),
+        Arc::new(StubProvider::success(
+            "zed",
+            "zed_1",
+            &["claude-sonnet-4-5"],
+            zed_seen.clone(),
+        )),
+    ];
+    let registry = Arc::new(ModelRegistry::new());
+    registry
+        .register_client(
+            "kiro_0",
+            "kiro",
+            vec![make_ext_model("kiro-claude-sonnet-4-5", "kiro", "kiro")],
+        )
+        .await;
+    registry
+        .register_client(
+            "zed_1",
+            "zed",
+            vec![make_ext_model("claude-sonnet-4-5", "zed", "zed")],
+        )
+        .await;
+
+    let app = test_app_with_state(test_state_with_providers(
+        Config::default(),
+        registry,
+        providers,
+    ));
+    let body = basic_chat_request("claude-sonnet-4-5");
+
+    let resp = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/v1/chat/completions")
+                .header("Content-Type", "application/json")
+                .body(Body::from(serde_json::to_vec(&body).unwrap()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(
+        kiro_seen.lock().await.as_slice(),
+        &["kiro-claude-sonnet-4-5"]
+    );
+    assert_eq!(zed_seen.lock().await.as_slice(), &["claude-sonnet-4-5"]);
+}
+
+#[tokio::test]
+async fn public_thinking_model_stays_on_kiro_without_zed_fallback() {
+    let kiro_first_seen = Arc::new(Mutex::new(Vec::new()));
+    let kiro_second_seen = Arc::new(Mutex::new(Vec::new()));
+    let zed_seen = Arc::new(Mutex::new(Vec::new()));
+    let providers: Vec<Arc<dyn Provider>> = vec![
+        Arc::new(StubProvider::quota_exceeded(
+            "kiro",
+            "kiro_0",
+            &["kiro-claude-sonnet-4-5-agentic"],
+            kiro_first_seen.clone(),
+            "first kiro unavailable",
+        )),
+        Arc::new(StubProvider::success(
+            "kiro",
+            "kiro_1",
+            &["kiro-claude-sonnet-4-5-agentic"],
+            kiro_second_seen.clone(),
+        )),
+        Arc::new(StubProvider::success(
+            "zed",
+            "zed_0",
+            &["claude-sonnet-4-5"],
+            zed_seen.clone(),
+        )),
+    ];
+    let registry = Arc::new(ModelRegistry::new());
+    registry
+        .register_client(
+            "kiro_0",
+            "kiro",
+            vec![make_ext_model(
+                "kiro-claude-sonnet-4-5-agentic",
+                "kiro",
+                "kiro",
+            )],
+        )
+        .await;
+    registry
+        .register_client(
+            "kiro_1",
+            "kiro",
+            vec![make_ext_model(
+                "kiro-claude-sonnet-4-5-agentic",
+                "kiro",
+                "kiro",
+            )],
+        )
+        .await;
+    registry
+        .register_client(
+            "zed_2",
+            "zed",
+            vec![make_ext_model("claude-sonnet-4-5", "zed", "zed")],
+        )
+        .await;
+
+    let app = test_app_with_state(test_state_with_providers(
+        Config::default(),
+        registry,
+        providers,
+    ));
+    let body = basic_chat_request("claude-sonnet-4-5-thinking");
+
+    let resp = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/v1/chat/completions")
+                .header("Content-Type", "application/json")
+                .body(Body::from(serde_json::to_vec(&body).unwrap()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(
+        kiro_first_seen.lock().await.as_slice(),
+        &["kiro-claude-sonnet-4-5-agentic"]
+    );
+    assert_eq!(
+        kiro_second_seen.lock().await.as_slice(),
+        &["kiro-claude-sonnet-4-5-agentic"]
+    );
+    assert!(zed_seen.lock().await.is_empty());
+}
+
+#[tokio::test]
+async fn public_claude_sonnet_4_5_non_stream_returns_chat_completion_response() {
+    let kiro_seen = Arc::new(Mutex::new(Vec::new()));
+    let providers: Vec<Arc<dyn Provider>> = vec![Arc::new(StubProvider::success(
+        "kiro",
+        "kiro_0",
+        &["kiro-claude-sonnet-4-5"],
+        kiro_seen.clone(),
+    ))];
+    let registry = Arc::new(ModelRegistry::new());
+    registry
+        .register_client(
+            "kiro_0",
+            "kiro",
+            vec![make_ext_model("kiro-claude-sonnet-4-5", "kiro", "kiro")],
+        )
+        .await;
 
-    // Create two Kiro auth files
-    let auth1 = serde_json::json!({
-        "type": "kiro",
-        "provider_key": "kiro",
-        "access_token": "token1",
-        "refresh_token": "refresh1",
-        "expires_at": "2030-01-01T00:00:00Z",
-        "auth_method": "builder-id",
-        "provider": "AWS",
-        "region": "us-east-1",
-        "client_id": "test-client-1",
-        "client_secret": "secret1"
+    let app = test_app_with_state(test_state_with_providers(
+        Config::default(),
+        registry,
+        providers,
+    ));
+    let body = serde_json::json!({
+        "model": "claude-sonnet-4-5",
+        "stream": false,
+        "messages": [{"role": "user", "content": "hello"}]
     });
 
-    let auth2 = serde_json::json!({
-        "type": "kiro",
-        "provider_key": "kiro",
-        "access_token": "token2",
-        "refresh_token": "refresh2",
-        "expires_at": "2030-01-01T00:00:00Z",
-        "auth_method": "builder-id",
-        "provider": "AWS",
-        "region": "us-east-1",
-        "client_id": "test-client-2",
-        "client_secret": "secret2"
-    });
+    let resp = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/v1/chat/completions")
+                .header("Content-Type", "application/json")
+                .body(Body::from(serde_json::to_vec(&body).unwrap()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
 
-    std::fs::write(
-        dir.path().join("kiro-1.json"),
-        serde_json::to_string_pretty(&auth1).unwrap(),
-    )
-    .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(
+        kiro_seen.lock().await.as_slice(),
+        &["kiro-claude-sonnet-4-5"]
+    );
 
-    std::fs::write(
-        dir.path().join("kiro-2.json"),
-        serde_json::to_string_pretty(&auth2).unwrap(),
-    )
-    .unwrap();
+    let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
 
-    // Load accounts and build providers
-    let accounts = Arc::new(AccountManager::with_dir(dir.path()));
-    accounts.reload().await.unwrap();
+    assert_eq!(json["object"], "chat.completion");
+    assert_eq!(json["choices"].as_array().map(Vec::len), Some(1));
+    assert_eq!(json["choices"][0]["message"]["role"], "assistant");
+}
 
-    let config = Config::default();
+#[tokio::test]
+async fn public_claude_sonnet_4_5_non_stream_preserves_final_tool_calls() {
+    let kiro_seen = Arc::new(Mutex::new(Vec::new()));
+    let tool_calls = vec![serde_json::json!({
+        "id": "call_123",
+        "type": "function",
+        "function": {
+            "name": "lookup_weather",
+            "arguments": "{\"city\":\"Jakarta\"}"
+        }
+    })];
+    let providers: Vec<Arc<dyn Provider>> = vec![Arc::new(StubProvider::success_with_tool_calls(
+        "kiro",
+        "kiro_0",
+        &["kiro-claude-sonnet-4-5"],
+        kiro_seen.clone(),
+        tool_calls.clone(),
+    ))];
     let registry = Arc::new(ModelRegistry::new());
-    let runtime = rusuh::proxy::KiroRuntimeState::default();
-    let providers = rusuh::providers::registry::build_providers(
-        &config,
-        &accounts,
-        registry.clone(),
-        runtime.clone(),
-    )
-    .await;
+    registry
+        .register_client(
+            "kiro_0",
+            "kiro",
+            vec![make_ext_model("kiro-claude-sonnet-4-5", "kiro", "kiro")],
+        )
+        .await;
 
-    // Register both providers with the same model
-    let test_model = "claude-sonnet-4";
+    let app = test_app_with_state(test_state_with_providers(
+        Config::default(),
+        registry,
+        providers,
+    ));
+    let body = serde_json::json!({
+        "model": "claude-sonnet-4-5",
+        "stream": false,
+        "messages": [{"role": "user", "content": "hello"}]
+    });
 
-    for (idx, provider) in providers.iter().enumerate() {
-        let client_id = format!("{}_{}", provider.name(), idx);
-        let models = vec![ExtModelInfo {
-            id: test_model.to_string(),
-            object: "model".to_string(),
-            created: 0,
-            owned_by: "kiro".to_string(),
-            provider_type: "kiro".to_string(),
-            display_name: None,
-            name: Some(test_model.to_string()),
-            version: None,
-            description: None,
-            input_token_limit: 0,
-            output_token_limit: 0,
-            supported_generation_methods: vec![],
-            context_length: 0,
-            max_completion_tokens: 0,
-            supported_parameters: vec![],
-            supported_endpoints: None,
-            thinking: None,
-            user_defined: false,
-        }];
-        registry
-            .register_client(&client_id, provider.name(), models)
-            .await;
-    }
+    let resp = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/v1/chat/completions")
+                .header("Content-Type", "application/json")
+                .body(Body::from(serde_json::to_vec(&body).unwrap()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
 
-    // Suspend first provider
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(
+        kiro_seen.lock().await.as_slice(),
+        &["kiro-claude-sonnet-4-5"]
+    );
+
+    let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(json["object"], "chat.completion");
+    assert_eq!(
+        json["choices"][0]["message"]["tool_calls"],
+        serde_json::Value::Array(tool_calls)
+    );
+}
+
+#[tokio::test]
+async fn claude_messages_routes_public_model_and_returns_anthropic_message() {
+    let kiro_seen = Arc::new(Mutex::new(Vec::new()));
+    let tool_calls = vec![serde_json::json!({
+        "id": "call_123",
+        "type": "function",
+        "function": {
+            "name": "lookup_weather",
+            "arguments": "{\"city\":\"Jakarta\"}"
+        }
+    })];
+    let providers: Vec<Arc<dyn Provider>> = vec![Arc::new(StubProvider::success_with_tool_calls(
+        "kiro",
+        "kiro_0",
+        &["kiro-claude-sonnet-4-5"],
+        kiro_seen.clone(),
+        tool_calls,
+    ))];
+    let registry = Arc::new(ModelRegistry::new());
     registry
-        .suspend_client_model("kiro_0", test_model, "test suspension")
+        .register_client(
+            "kiro_0",
+            "kiro",
+            vec![make_ext_model("kiro-claude-sonnet-4-5", "kiro", "kiro")],
+        )
         .await;
 
-    // Verify only second provider is available
-    let available = registry.available_clients_for_model(test_model).await;
-    assert_eq!(available.len(), 1);
-    assert_eq!(available[0], "kiro_1");
+    let app = test_app_with_state(test_state_with_providers(
+        Config::default(),
+        registry,
+        providers,
+    ));
+    let body = serde_json::json!({
+        "model": "claude-sonnet-4-5",
+        "max_tokens": 256,
+        "system": "be brief",
+        "messages": [{"role": "user", "content": [{"type": "text", "text": "weather?"}]}]
+    });
 
-    // Build app state
-    let mut state = ProxyState::new(config, accounts, registry, providers.len());
-    state.kiro_runtime = runtime;
-    let state = Arc::new(state);
-    state
-        .publish_runtime_from_providers(providers)
+    let resp = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/v1/messages")
+                .header("Content-Type", "application/json")
+                .body(Body::from(serde_json::to_vec(&body).unwrap()))
+                .unwrap(),
+        )
         .await
-        .expect("test providers should publish");
+        .unwrap();
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(
+        kiro_seen.lock().await.as_slice(),
+        &["kiro-claude-sonnet-4-5"]
+    );
+
+    let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(json["type"], "message");
+    assert_eq!(json["role"], "assistant");
+    assert_eq!(json["model"], "claude-sonnet-4-5");
+    assert_eq!(json["stop_reason"], "tool_use");
+    assert_eq!(json["content"][0]["type"], "tool_use");
+    assert_eq!(json["content"][0]["name"], "lookup_weather");
+    assert_eq!(json["content"][0]["input"]["city"], "Jakarta");
+}
+
+#[tokio::test]
+async fn claude_messages_stream_emits_anthropic_event_sequence() {
+    let kiro_seen = Arc::new(Mutex::new(Vec::new()));
+    let providers: Vec<Arc<dyn Provider>> = vec![Arc::new(StubProvider::success(
+        "kiro",
+        "kiro_0",
+        &["kiro-claude-sonnet-4-5"],
+        kiro_seen.clone(),
+    ))];
+    let registry = Arc::new(ModelRegistry::new());
+    registry
+        .register_client(
+            "kiro_0",
+            "kiro",
+            vec![make_ext_model("kiro-claude-sonnet-4-5", "kiro", "kiro")],
+        )
+        .await;
 
-    // Make a request - should use kiro_1, not kiro_0
+    let app = test_app_with_state(test_state_with_providers(
+        Config::default(),
+        registry,
+        providers,
+    ));
     let body = serde_json::json!({
-        "model": test_model,
-        "messages": [{"role": "user", "content": "test"}]
+        "model": "claude-sonnet-4-5",
+        "max_tokens": 256,
+        "stream": true,
+        "messages": [{"role": "user", "content": "hello"}]
     });
 
-    let app = build_router(state.clone());
-
     let resp = app
         .oneshot(
             Request::builder()
                 .method("POST")
-                .uri("/v1/chat/completions")
+                .uri("/v1/messages")
                 .header("Content-Type", "application/json")
                 .body(Body::from(serde_json::to_vec(&body).unwrap()))
                 .unwrap(),
@@ -1943,115 +3924,193 @@ async fn kiro_routing_skips_suspended_auth() {
         .await
         .unwrap();
 
-    // Should attempt request (will fail due to fake tokens, but that's OK)
-    assert_ne!(
-        resp.status(),
-        StatusCode::TOO_MANY_REQUESTS,
-        "should not return 429 when one provider is still available"
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(
+        resp.headers().get("content-type").unwrap(),
+        "text/event-stream"
     );
+
+    let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
+        .await
+        .unwrap();
+    let text = String::from_utf8(body.to_vec()).unwrap();
+
+    let message_start = text.find("event: message_start").expect("message_start");
+    let message_delta = text.find("event: message_delta").expect("message_delta");
+    let message_stop = text.find("event: message_stop").expect("message_stop");
+    assert!(message_start < message_delta && message_delta < message_stop);
+    assert!(!text.contains("[DONE]"), "got: {text}");
 }
 
 #[tokio::test]
-async fn kiro_routing_returns_error_when_all_unavailable() {
-    use rusuh::providers::model_info::ExtModelInfo;
-    use tempfile::TempDir;
-
-    let dir = TempDir::new().unwrap();
+async fn amp_claude_messages_routes_with_provider_hint() {
+    let zed_seen = Arc::new(Mutex::new(Vec::new()));
+    let providers: Vec<Arc<dyn Provider>> = vec![Arc::new(StubProvider::success(
+        "zed",
+        "zed-user.json",
+        &["claude-sonnet-4-6"],
+        zed_seen.clone(),
+    ))];
+    let registry = Arc::new(ModelRegistry::new());
+    registry
+        .register_client(
+            "zed-user.json",
+            "zed",
+            vec![make_ext_model("claude-sonnet-4-6", "zed", "zed")],
+        )
+        .await;
 
-    // Create one Kiro auth file
-    let auth1 = serde_json::json!({
-        "type": "kiro",
-        "provider_key": "kiro",
-        "access_token": "token1",
-        "refresh_token": "refresh1",
-        "expires_at": "2030-01-01T00:00:00Z",
-        "auth_method": "builder-id",
-        "provider": "AWS",
-        "region": "us-east-1",
-        "client_id": "test-client-1",
-        "client_secret": "secret1"
+    let app = test_app_with_state(test_state_with_providers(
+        Config::default(),
+        registry,
+        providers,
+    ));
+    let body = serde_json::json!({
+        "model": "claude-sonnet-4-6",
+        "max_tokens": 256,
+        "messages": [{"role": "user", "content": "hello"}]
     });
 
-    std::fs::write(
-        dir.path().join("kiro-1.json"),
-        serde_json::to_string_pretty(&auth1).unwrap(),
-    )
-    .unwrap();
+    let resp = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/provider/zed/v1/messages")
+                .header("Content-Type", "application/json")
+                .body(Body::from(serde_json::to_vec(&body).unwrap()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
 
-    // Load accounts and build providers
-    let accounts = Arc::new(AccountManager::with_dir(dir.path()));
-    accounts.reload().await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(zed_seen.lock().await.as_slice(), &["claude-sonnet-4-6"]);
 
-    let config = Config::default();
-    let registry = Arc::new(ModelRegistry::new());
-    let runtime = rusuh::proxy::KiroRuntimeState::default();
-    let providers = rusuh::providers::registry::build_providers(
-        &config,
-        &accounts,
-        registry.clone(),
-        runtime.clone(),
-    )
-    .await;
+    let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["content"][0]["text"], "handled by zed");
+    assert_eq!(json["stop_reason"], "end_turn");
+}
 
-    // Register provider with a model
-    let test_model = "claude-sonnet-4";
+/// Provider that only speaks Anthropic natively and records the raw bodies it receives.
+struct NativeAnthropicProvider {
+    seen: Arc<Mutex<Vec<serde_json::Value>>>,
+}
 
-    for (idx, provider) in providers.iter().enumerate() {
-        let client_id = format!("{}_{}", provider.name(), idx);
-        let models = vec![ExtModelInfo {
-            id: test_model.to_string(),
+#[async_trait]
+impl Provider for NativeAnthropicProvider {
+    fn name(&self) -> &str {
+        "zed"
+    }
+
+    fn client_id(&self) -> &str {
+        "zed-native.json"
+    }
+
+    async fn list_models(&self) -> rusuh::error::AppResult<Vec<ModelInfo>> {
+        Ok(vec![ModelInfo {
+            id: "claude-sonnet-4-6".to_string(),
             object: "model".to_string(),
             created: 0,
-            owned_by: "kiro".to_string(),
-            provider_type: "kiro".to_string(),
-            display_name: None,
-            name: Some(test_model.to_string()),
-            version: None,
-            description: None,
-            input_token_limit: 0,
-            output_token_limit: 0,
-            supported_generation_methods: vec![],
-            context_length: 0,
-            max_completion_tokens: 0,
-            supported_parameters: vec![],
-            supported_endpoints: None,
-            thinking: None,
-            user_defined: false,
-        }];
-        registry
-            .register_client(&client_id, provider.name(), models)
-            .await;
+            owned_by: "zed".to_string(),
+        }])
     }
 
-    // Mark provider as quota-exceeded
-    registry.set_quota_exceeded("kiro_0", test_model).await;
+    async fn chat_completion(
+        &self,
+        _req: &ChatCompletionRequest,
+    ) -> rusuh::error::AppResult<ChatCompletionResponse> {
+        panic!("native provider should not receive translated requests")
+    }
 
-    // Verify no providers are available
-    let available = registry.available_clients_for_model(test_model).await;
-    assert_eq!(available.len(), 0);
+    async fn chat_completion_stream(
+        &self,
+        _req: &ChatCompletionRequest,
+    ) -> rusuh::error::AppResult<BoxStream> {
+        panic!("native provider should not receive translated requests")
+    }
 
-    // Build app state
-    let mut state = ProxyState::new(config, accounts, registry, providers.len());
-    state.kiro_runtime = runtime;
-    let state = Arc::new(state);
-    state
-        .publish_runtime_from_providers(providers)
-        .await
-        .expect("test providers should publish");
+    fn supports_anthropic_messages(&self, _model: &str) -> bool {
+        true
+    }
 
-    // Make a request - should return error
+    async fn anthropic_messages(
+        &self,
+        body: &serde_json::Value,
+    ) -> rusuh::error::AppResult<AnthropicResponse> {
+        self.seen.lock().await.push(body.clone());
+        if body["stream"].as_bool().unwrap_or(false) {
+            // The upstream names its own dated model id, split across reads.
+            let chunks = [
+                "event: message_start\ndata: {\"type\":\"message_start\",\"message\":{\"id\":\"msg_native\",\"model\":",
+                "\"claude-sonnet-4-6-20260101\",\"usage\":{\"input_tokens\":3,\"output_tokens\":0}}}\n\n",
+                "event: content_block_delta\ndata: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"native\"}}\n\n",
+                "event: message_stop\ndata: {\"type\":\"message_stop\"}\n\n",
+            ];
+            return Ok(AnthropicResponse::Stream(Box::pin(futures::stream::iter(
+                chunks.map(|chunk| Ok(Bytes::from(chunk))),
+            ))));
+        }
+        Ok(AnthropicResponse::Message(serde_json::json!({
+            "id": "msg_native",
+            "type": "message",
+            "role": "assistant",
+            "model": body["model"],
+            "content": [
+                {"type": "thinking", "thinking": "considering", "signature": "sig"},
+                {"type": "text", "text": "native"}
+            ],
+            "stop_reason": "end_turn",
+            "usage": {"input_tokens": 3, "output_tokens": 1, "cache_read_input_tokens": 2}
+        })))
+    }
+}
+
+async fn native_anthropic_app(seen: Arc<Mutex<Vec<serde_json::Value>>>) -> axum::Router {
+    let providers: Vec<Arc<dyn Provider>> = vec![Arc::new(NativeAnthropicProvider { seen })];
+    let registry = Arc::new(ModelRegistry::new());
+    registry
+        .register_client(
+            "zed-native.json",
+            "zed",
+            vec![make_ext_model("claude-sonnet-4-6", "zed", "zed")],
+        )
+        .await;
+
+    test_app_with_state(test_state_with_providers(
+        Config::default(),
+        registry,
+        providers,
+    ))
+}
+
+#[tokio::test]
+async fn claude_messages_pass_raw_body_to_native_anthropic_providers() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let app = native_anthropic_app(seen.clone()).await;
     let body = serde_json::json!({
-        "model": test_model,
-        "messages": [{"role": "user", "content": "test"}]
+        "model": "claude-sonnet-4.6",
+        "max_tokens": 256,
+        "thinking": {"type": "enabled", "budget_tokens": 1024},
+        "system": [{"type": "text", "text": "be brief", "cache_control": {"type": "ephemeral"}}],
+        "messages": [
+            {"role": "user", "content": "weather?"},
+            {"role": "assistant", "content": [{"type": "tool_use", "id": "toolu_1", "name": "lookup", "input": {}}]},
+            {"role": "user", "content": [{
+                "type": "tool_result",
+                "tool_use_id": "toolu_1",
+                "content": [{"type": "text", "text": "sunny"}]
+            }]}
+        ]
     });
 
-    let app = build_router(state.clone());
-
     let resp = app
         .oneshot(
             Request::builder()
                 .method("POST")
-                .uri("/v1/chat/completions")
+                .uri("/v1/messages")
                 .header("Content-Type", "application/json")
                 .body(Body::from(serde_json::to_vec(&body).unwrap()))
                 .unwrap(),
@@ -2059,250 +4118,260 @@ async fn kiro_routing_returns_error_when_all_unavailable() {
         .await
         .unwrap();
 
-    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
-}
-
-#[tokio::test]
-async fn public_models_catalog_is_curated_to_three_router_models() {
-    let zed_observed = Arc::new(Mutex::new(Vec::new()));
-    let kiro_observed = Arc::new(Mutex::new(Vec::new()));
-    let zed_client_id = "auth-zed-record-1";
-    let kiro_client_id = "auth-kiro-record-1";
+    assert_eq!(resp.status(), StatusCode::OK);
 
-    let providers: Vec<Arc<dyn Provider>> = vec![
-        Arc::new(StubProvider::success_with_type(
-            "zed-display",
-            "zed",
-            zed_client_id,
-            &["claude-sonnet-4-6", "claude-sonnet-4-5"],
-            zed_observed,
-        )),
-        Arc::new(StubProvider::success_with_type(
-            "kiro-display",
-            "kiro",
-            kiro_client_id,
-            &["kiro-claude-sonnet-4-5", "kiro-claude-sonnet-4-5-agentic"],
-            kiro_observed,
-        )),
-    ];
+    let seen = seen.lock().await;
+    assert_eq!(seen.len(), 1);
+    let mut expected = body.clone();
+    expected["model"] = serde_json::json!("claude-sonnet-4-6");
+    assert_eq!(seen[0], expected);
 
-    let registry = Arc::new(ModelRegistry::new());
-    registry
-        .register_client(
-            zed_client_id,
-            "zed",
-            vec![
-                make_ext_model("claude-sonnet-4-6", "zed", "zed"),
-                make_ext_model("claude-sonnet-4-5", "zed", "zed"),
-            ],
-        )
-        .await;
-    registry
-        .register_client(
-            kiro_client_id,
-            "kiro",
-            vec![
-                make_ext_model("kiro-claude-sonnet-4-5", "kiro", "kiro"),
-                make_ext_model("kiro-claude-sonnet-4-5-agentic", "kiro", "kiro"),
-            ],
-        )
-        .await;
+    let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["model"], "claude-sonnet-4.6");
+    assert_eq!(json["content"][0]["type"], "thinking");
+    assert_eq!(json["content"][1]["text"], "native");
+    assert_eq!(json["usage"]["cache_read_input_tokens"], 2);
+}
 
-    let app = test_app_with_state(test_state_with_providers(
-        Config::default(),
-        registry,
-        providers,
-    ));
+#[tokio::test]
+async fn native_anthropic_stream_names_the_requested_model() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let app = native_anthropic_app(seen.clone()).await;
+    let body = serde_json::json!({
+        "model": "claude-sonnet-4.6",
+        "max_tokens": 256,
+        "stream": true,
+        "messages": [{"role": "user", "content": "weather?"}]
+    });
 
     let resp = app
         .oneshot(
             Request::builder()
-                .uri("/v1/models")
-                .body(Body::empty())
+                .method("POST")
+                .uri("/v1/messages")
+                .header("Content-Type", "application/json")
+                .body(Body::from(serde_json::to_vec(&body).unwrap()))
                 .unwrap(),
         )
         .await
         .unwrap();
 
     assert_eq!(resp.status(), StatusCode::OK);
-
     let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
         .await
         .unwrap();
-    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
-    let ids: Vec<String> = json["data"]
-        .as_array()
-        .unwrap()
-        .iter()
-        .map(|item| item["id"].as_str().unwrap().to_string())
-        .collect();
-
-    assert_eq!(
-        ids,
-        vec![
-            "claude-sonnet-4-6".to_string(),
-            "claude-sonnet-4-5".to_string(),
-        ]
-    );
+    let body = String::from_utf8(body.to_vec()).unwrap();
+    let start = body
+        .lines()
+        .filter_map(|line| line.strip_prefix("data: "))
+        .map(|data| serde_json::from_str::<serde_json::Value>(data).unwrap())
+        .find(|event| event["type"] == "message_start")
+        .unwrap();
+    assert_eq!(start["message"]["model"], "claude-sonnet-4.6");
+    assert!(body.contains("\"text\":\"native\""), "{body}");
+    assert!(!body.contains("claude-sonnet-4-6-20260101"), "{body}");
 }
 
 #[tokio::test]
-async fn provider_pinned_kiro_models_expose_only_raw_kiro_ids() {
-    let kiro_first_observed = Arc::new(Mutex::new(Vec::new()));
-    let kiro_second_observed = Arc::new(Mutex::new(Vec::new()));
-    let zed_observed = Arc::new(Mutex::new(Vec::new()));
-    let providers: Vec<Arc<dyn Provider>> = vec![
-        Arc::new(StubProvider::success(
-            "kiro",
+async fn gemini_generate_content_routes_public_model_and_returns_candidates() {
+    let kiro_seen = Arc::new(Mutex::new(Vec::new()));
+    let providers: Vec<Arc<dyn Provider>> = vec![Arc::new(StubProvider::success(
+        "kiro",
+        "kiro_0",
+        &["kiro-claude-sonnet-4-5"],
+        kiro_seen.clone(),
+    ))];
+    let registry = Arc::new(ModelRegistry::new());
+    registry
+        .register_client(
             "kiro_0",
-            &["kiro-claude-sonnet-4-5", "kiro-claude-sonnet-4-5-agentic"],
-            kiro_first_observed,
-        )),
-        Arc::new(StubProvider::success(
             "kiro",
-            "kiro_1",
-            &["kiro-claude-sonnet-4-5", "kiro-claude-sonnet-4-5-agentic"],
-            kiro_second_observed,
-        )),
-        Arc::new(StubProvider::success(
-            "zed",
-            "zed_0",
-            &["claude-sonnet-4-6", "claude-sonnet-4-5"],
-            zed_observed,
-        )),
-    ];
+            vec![make_ext_model("kiro-claude-sonnet-4-5", "kiro", "kiro")],
+        )
+        .await;
+
     let app = test_app_with_state(test_state_with_providers(
         Config::default(),
-        Arc::new(ModelRegistry::new()),
+        registry,
         providers,
     ));
+    let body = serde_json::json!({
+        "systemInstruction": {"parts": [{"text": "be brief"}]},
+        "contents": [{"role": "user", "parts": [{"text": "hello"}]}],
+        "generationConfig": {"maxOutputTokens": 256}
+    });
 
     let resp = app
         .oneshot(
             Request::builder()
-                .uri("/api/provider/kiro/v1/models")
-                .body(Body::empty())
+                .method("POST")
+                .uri("/v1beta/models/claude-sonnet-4-5:generateContent")
+                .header("Content-Type", "application/json")
+                .body(Body::from(serde_json::to_vec(&body).unwrap()))
                 .unwrap(),
         )
         .await
         .unwrap();
 
     assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(
+        kiro_seen.lock().await.as_slice(),
+        &["kiro-claude-sonnet-4-5"]
+    );
 
     let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
         .await
         .unwrap();
     let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
-    let ids: Vec<String> = json["data"]
-        .as_array()
-        .unwrap()
-        .iter()
-        .map(|item| item["id"].as_str().unwrap().to_string())
-        .collect();
 
-    assert_eq!(
-        ids,
-        vec![
-            "kiro-claude-sonnet-4-5".to_string(),
-            "kiro-claude-sonnet-4-5-agentic".to_string(),
-        ]
-    );
+    let candidate = &json["candidates"][0];
+    assert_eq!(candidate["content"]["role"], "model");
+    assert_eq!(candidate["content"]["parts"][0]["text"], "handled by kiro");
+    assert_eq!(candidate["finishReason"], "STOP");
+    assert!(json["usageMetadata"].is_object());
+    assert_eq!(json["modelVersion"], "claude-sonnet-4-5");
 }
 
 #[tokio::test]
-async fn provider_pinned_zed_models_expose_only_raw_zed_ids() {
-    let kiro_observed = Arc::new(Mutex::new(Vec::new()));
-    let zed_first_observed = Arc::new(Mutex::new(Vec::new()));
-    let zed_second_observed = Arc::new(Mutex::new(Vec::new()));
-    let providers: Vec<Arc<dyn Provider>> = vec![
-        Arc::new(StubProvider::success(
+async fn gemini_stream_generate_content_supports_sse_and_json_array() {
+    for (uri, content_type) in [
+        (
+            "/v1beta/models/claude-sonnet-4-5:streamGenerateContent?alt=sse",
+            "text/event-stream",
+        ),
+        (
+            "/v1beta/models/claude-sonnet-4-5:streamGenerateContent",
+            "application/json",
+        ),
+    ] {
+        let kiro_seen = Arc::new(Mutex::new(Vec::new()));
+        let providers: Vec<Arc<dyn Provider>> = vec![Arc::new(StubProvider::success(
             "kiro",
             "kiro_0",
-            &["kiro-claude-sonnet-4-5", "kiro-claude-sonnet-4-5-agentic"],
-            kiro_observed,
-        )),
-        Arc::new(StubProvider::success(
-            "zed",
-            "zed_0",
-            &["claude-sonnet-4-6", "claude-sonnet-4-5"],
-            zed_first_observed,
-        )),
-        Arc::new(StubProvider::success(
-            "zed",
-            "zed_1",
-            &["claude-sonnet-4-6", "claude-sonnet-4-5"],
-            zed_second_observed,
-        )),
-    ];
+            &["kiro-claude-sonnet-4-5"],
+            kiro_seen.clone(),
+        ))];
+        let registry = Arc::new(ModelRegistry::new());
+        registry
+            .register_client(
+                "kiro_0",
+                "kiro",
+                vec![make_ext_model("kiro-claude-sonnet-4-5", "kiro", "kiro")],
+            )
+            .await;
+
+        let app = test_app_with_state(test_state_with_providers(
+            Config::default(),
+            registry,
+            providers,
+        ));
+        let body = serde_json::json!({
+            "contents": [{"role": "user", "parts": [{"text": "hello"}]}]
+        });
+
+        let resp = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(uri)
+                    .header("Content-Type", "application/json")
+                    .body(Body::from(serde_json::to_vec(&body).unwrap()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(resp.status(), StatusCode::OK, "uri: {uri}");
+        assert_eq!(resp.headers().get("content-type").unwrap(), content_type);
+
+        let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
+            .await
+            .unwrap();
+        let text = String::from_utf8(body.to_vec()).unwrap();
+        assert!(!text.contains("[DONE]"), "got: {text}");
+
+        let chunks: Vec<serde_json::Value> = if content_type == "text/event-stream" {
+            text.lines()
+                .filter_map(|line| line.strip_prefix("data: "))
+                .map(|data| serde_json::from_str(data).unwrap())
+                .collect()
+        } else {
+            serde_json::from_str(&text).unwrap()
+        };
+        let last = chunks.last().expect("at least one chunk");
+        assert_eq!(last["candidates"][0]["finishReason"], "STOP");
+        assert!(last["usageMetadata"].is_object());
+    }
+}
+
+#[tokio::test]
+async fn public_model_falls_back_to_claude_api_key_provider() {
+    let claude_seen = Arc::new(Mutex::new(Vec::new()));
+    let providers: Vec<Arc<dyn Provider>> = vec![Arc::new(StubProvider::success(
+        "claude-api",
+        "claude-api_0",
+        &["claude-sonnet-4-6"],
+        claude_seen.clone(),
+    ))];
+    let registry = Arc::new(ModelRegistry::new());
+    registry
+        .register_client(
+            "claude-api_0",
+            "claude-api",
+            vec![make_ext_model(
+                "claude-sonnet-4-6",
+                "anthropic",
+                "claude-api",
+            )],
+        )
+        .await;
+
     let app = test_app_with_state(test_state_with_providers(
         Config::default(),
-        Arc::new(ModelRegistry::new()),
+        registry,
         providers,
     ));
 
     let resp = app
         .oneshot(
             Request::builder()
-                .uri("/api/provider/zed/v1/models")
-                .body(Body::empty())
+                .method("POST")
+                .uri("/v1/chat/completions")
+                .header("Content-Type", "application/json")
+                .body(Body::from(
+                    serde_json::to_vec(&basic_chat_request("claude-sonnet-4-6")).unwrap(),
+                ))
                 .unwrap(),
         )
         .await
         .unwrap();
 
     assert_eq!(resp.status(), StatusCode::OK);
-
-    let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
-        .await
-        .unwrap();
-    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
-    let ids: Vec<String> = json["data"]
-        .as_array()
-        .unwrap()
-        .iter()
-        .map(|item| item["id"].as_str().unwrap().to_string())
-        .collect();
-
-    assert_eq!(
-        ids,
-        vec![
-            "claude-sonnet-4-6".to_string(),
-            "claude-sonnet-4-5".to_string(),
-        ]
-    );
+    assert_eq!(claude_seen.lock().await.as_slice(), &["claude-sonnet-4-6"]);
 }
 
 #[tokio::test]
-async fn public_endpoint_rejects_provider_native_model_ids() {
-    let kiro_seen = Arc::new(Mutex::new(Vec::new()));
-    let zed_seen = Arc::new(Mutex::new(Vec::new()));
-    let providers: Vec<Arc<dyn Provider>> = vec![
-        Arc::new(StubProvider::success(
-            "kiro",
-            "kiro_0",
-            &["kiro-claude-sonnet-4-5"],
-            kiro_seen.clone(),
-        )),
-        Arc::new(StubProvider::success(
-            "zed",
-            "zed_1",
-            &["claude-sonnet-4-5"],
-            zed_seen.clone(),
-        )),
-    ];
+async fn public_endpoint_routes_prefixed_api_key_models() {
+    let gemini_seen = Arc::new(Mutex::new(Vec::new()));
+    let providers: Vec<Arc<dyn Provider>> = vec![Arc::new(StubProvider::success(
+        "gemini-api",
+        "gemini-api_0",
+        &["team/gemini-2.5-pro"],
+        gemini_seen.clone(),
+    ))];
     let registry = Arc::new(ModelRegistry::new());
     registry
         .register_client(
-            "kiro_0",
-            "kiro",
-            vec![make_ext_model("kiro-claude-sonnet-4-5", "kiro", "kiro")],
-        )
-        .await;
-    registry
-        .register_client(
-            "zed_1",
-            "zed",
-            vec![make_ext_model("claude-sonnet-4-5", "zed", "zed")],
+            "gemini-api_0",
+            "gemini-api",
+            vec![make_ext_model(
+                "team/gemini-2.5-pro",
+                "google",
+                "gemini-api",
+            )],
         )
         .await;
 
@@ -2312,7 +4381,10 @@ async fn public_endpoint_rejects_provider_native_model_ids() {
         providers,
     ));
 
-    for model in ["kiro-claude-sonnet-4-5", "claude-sonnet-4-5"] {
+    for (model, expected) in [
+        ("team/gemini-2.5-pro", StatusCode::OK),
+        ("other/gemini-2.5-pro", StatusCode::BAD_REQUEST),
+    ] {
         let resp = app
             .clone()
             .oneshot(
@@ -2327,532 +4399,898 @@ async fn public_endpoint_rejects_provider_native_model_ids() {
             )
             .await
             .unwrap();
+        assert_eq!(resp.status(), expected, "model: {model}");
+    }
+
+    assert_eq!(
+        gemini_seen.lock().await.as_slice(),
+        &["team/gemini-2.5-pro"]
+    );
+}
+
+#[test]
+fn blocking_provider_uses_explicit_client_id() {
+    let provider = BlockingProvider::new(
+        "codex",
+        "codex_0",
+        &["gpt-5-codex"],
+        Arc::new(Notify::new()),
+        Arc::new(Notify::new()),
+        Arc::new(Notify::new()),
+        Arc::new(Notify::new()),
+    );
+
+    assert_eq!(provider.client_id(), "codex_0");
+}
+
+#[tokio::test]
+async fn provider_models_request_does_not_hold_providers_lock_while_listing_models() {
+    let list_started = Arc::new(Notify::new());
+    let list_release = Arc::new(Notify::new());
+    let chat_started = Arc::new(Notify::new());
+    let chat_release = Arc::new(Notify::new());
+
+    let providers: Vec<Arc<dyn Provider>> = vec![Arc::new(BlockingProvider::new(
+        "codex",
+        "codex_0",
+        &["gpt-5-codex"],
+        list_started.clone(),
+        list_release.clone(),
+        chat_started,
+        chat_release,
+    ))];
+    let registry = Arc::new(ModelRegistry::new());
+    let state = test_state_with_providers(Config::default(), registry, providers);
+    let app = test_app_with_state(state.clone());
+
+    let request = Request::builder()
+        .uri("/api/provider/codex/v1/models")
+        .body(Body::empty())
+        .unwrap();
+
+    let request_task = tokio::spawn(async move { app.oneshot(request).await.unwrap() });
+
+    list_started.notified().await;
+
+    let write_attempt = tokio::spawn({
+        let state = state.clone();
+        async move {
+            let _guard = state.providers.write().await;
+        }
+    });
+
+    tokio::time::timeout(std::time::Duration::from_millis(100), write_attempt)
+        .await
+        .expect("providers write lock should not be blocked by model listing")
+        .expect("write-lock task should complete successfully");
 
-        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
-    }
+    list_release.notify_waiters();
 
-    assert!(kiro_seen.lock().await.is_empty());
-    assert!(zed_seen.lock().await.is_empty());
+    let resp = request_task.await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
 }
 
 #[tokio::test]
-async fn public_claude_sonnet_4_6_routes_only_to_zed_native_model() {
-    let zed_seen = Arc::new(Mutex::new(Vec::new()));
-    let kiro_seen = Arc::new(Mutex::new(Vec::new()));
-    let providers: Vec<Arc<dyn Provider>> = vec![
-        Arc::new(StubProvider::success(
-            "kiro",
-            "kiro_0",
-            &["kiro-claude-sonnet-4-6"],
-            kiro_seen.clone(),
-        )),
-        Arc::new(StubProvider::success(
-            "zed",
-            "zed_1",
-            &["claude-sonnet-4-6"],
-            zed_seen.clone(),
-        )),
-    ];
+async fn provider_chat_request_does_not_hold_providers_lock_while_awaiting_upstream() {
+    let list_started = Arc::new(Notify::new());
+    let list_release = Arc::new(Notify::new());
+    let chat_started = Arc::new(Notify::new());
+    let chat_release = Arc::new(Notify::new());
+
+    let providers: Vec<Arc<dyn Provider>> = vec![Arc::new(BlockingProvider::new(
+        "codex",
+        "codex_0",
+        &["gpt-5-codex"],
+        list_started,
+        list_release,
+        chat_started.clone(),
+        chat_release.clone(),
+    ))];
     let registry = Arc::new(ModelRegistry::new());
     registry
         .register_client(
-            "kiro_0",
-            "kiro",
-            vec![make_ext_model("kiro-claude-sonnet-4-6", "kiro", "kiro")],
-        )
-        .await;
-    registry
-        .register_client(
-            "zed_1",
-            "zed",
-            vec![make_ext_model("claude-sonnet-4-6", "zed", "zed")],
+            "codex_0",
+            "codex",
+            vec![make_ext_model("gpt-5-codex", "codex", "codex")],
         )
         .await;
+    let state = test_state_with_providers(Config::default(), registry, providers);
+    let app = test_app_with_state(state.clone());
 
-    let app = test_app_with_state(test_state_with_providers(
-        Config::default(),
-        registry,
-        providers,
-    ));
-    let body = basic_chat_request("claude-sonnet-4-6");
+    let body = serde_json::json!({
+        "model": "gpt-5-codex",
+        "messages": [{"role": "user", "content": "hello"}]
+    });
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/provider/codex/v1/chat/completions")
+        .header("Content-Type", "application/json")
+        .body(Body::from(serde_json::to_vec(&body).unwrap()))
+        .unwrap();
 
-    let resp = app
-        .oneshot(
-            Request::builder()
-                .method("POST")
-                .uri("/v1/chat/completions")
-                .header("Content-Type", "application/json")
-                .body(Body::from(serde_json::to_vec(&body).unwrap()))
-                .unwrap(),
-        )
+    let request_task = tokio::spawn(async move { app.oneshot(request).await.unwrap() });
+
+    chat_started.notified().await;
+
+    let write_attempt = tokio::spawn({
+        let state = state.clone();
+        async move {
+            let _guard = state.providers.write().await;
+        }
+    });
+
+    tokio::time::timeout(std::time::Duration::from_millis(100), write_attempt)
         .await
-        .unwrap();
+        .expect("providers write lock should not be blocked by chat completion")
+        .expect("write-lock task should complete successfully");
+
+    chat_release.notify_waiters();
 
+    let resp = request_task.await.unwrap();
     assert_eq!(resp.status(), StatusCode::OK);
-    assert!(kiro_seen.lock().await.is_empty());
-    assert_eq!(zed_seen.lock().await.as_slice(), &["claude-sonnet-4-6"]);
 }
 
 #[tokio::test]
-async fn public_claude_sonnet_4_5_routes_kiro_first_then_falls_back_to_zed() {
-    let kiro_seen = Arc::new(Mutex::new(Vec::new()));
-    let zed_seen = Arc::new(Mutex::new(Vec::new()));
-    let providers: Vec<Arc<dyn Provider>> = vec![
-        Arc::new(StubProvider::quota_exceeded(
-            "kiro",
-            "kiro_0",
-            &["kiro-claude-sonnet-4-5"],
-            kiro_seen.clone(),
-            "kiro unavailable",
-        )),
-        Arc::new(StubProvider::success(
-            "zed",
-            "zed_1",
-            &["claude-sonnet-4-5"],
-            zed_seen.clone(),
-        )),
-    ];
-    let registry = Arc::new(ModelRegistry::new());
-    registry
-        .register_client(
-            "kiro_0",
-            "kiro",
-            vec![make_ext_model("kiro-claude-sonnet-4-5", "kiro", "kiro")],
-        )
-        .await;
-    registry
-        .register_client(
-            "zed_1",
-            "zed",
-            vec![make_ext_model("claude-sonnet-4-5", "zed", "zed")],
-        )
-        .await;
-
-    let app = test_app_with_state(test_state_with_providers(
-        Config::default(),
-        registry,
-        providers,
-    ));
-    let body = basic_chat_request("claude-sonnet-4-5");
+async fn blocking_provider_start_signals_are_not_lost_if_observed_after_spawn() {
+    let list_started = Arc::new(Notify::new());
+    let list_release = Arc::new(Notify::new());
+    let chat_started = Arc::new(Notify::new());
+    let chat_release = Arc::new(Notify::new());
+    let provider = BlockingProvider::new(
+        "codex",
+        "codex_0",
+        &["gpt-5-codex"],
+        list_started.clone(),
+        list_release.clone(),
+        chat_started.clone(),
+        chat_release.clone(),
+    );
 
-    let resp = app
-        .oneshot(
-            Request::builder()
-                .method("POST")
-                .uri("/v1/chat/completions")
-                .header("Content-Type", "application/json")
-                .body(Body::from(serde_json::to_vec(&body).unwrap()))
-                .unwrap(),
-        )
-        .await
-        .unwrap();
+    let list_task = tokio::spawn(async move { provider.list_models().await });
+    tokio::task::yield_now().await;
+    tokio::time::timeout(
+        std::time::Duration::from_millis(50),
+        list_started.notified(),
+    )
+    .await
+    .expect("list_started signal should be retained for a later waiter");
+    list_release.notify_waiters();
+    assert!(list_task.await.unwrap().is_ok());
 
-    assert_eq!(resp.status(), StatusCode::OK);
-    assert_eq!(
-        kiro_seen.lock().await.as_slice(),
-        &["kiro-claude-sonnet-4-5"]
+    let provider = BlockingProvider::new(
+        "codex",
+        "codex_0",
+        &["gpt-5-codex"],
+        Arc::new(Notify::new()),
+        Arc::new(Notify::new()),
+        chat_started.clone(),
+        chat_release.clone(),
     );
-    assert_eq!(zed_seen.lock().await.as_slice(), &["claude-sonnet-4-5"]);
+    let req = ChatCompletionRequest {
+        model: "gpt-5-codex".to_string(),
+        messages: vec![ChatMessage {
+            role: "user".to_string(),
+            content: MessageContent::Text("hello".to_string()),
+            name: None,
+            tool_calls: None,
+            tool_call_id: None,
+            reasoning_content: None,
+        }],
+        stream: None,
+        max_tokens: None,
+        temperature: None,
+        top_p: None,
+        tools: None,
+        tool_choice: None,
+        stop: None,
+        extra: std::collections::HashMap::new(),
+    };
+
+    let chat_task = tokio::spawn(async move { provider.chat_completion(&req).await });
+    tokio::task::yield_now().await;
+    tokio::time::timeout(
+        std::time::Duration::from_millis(50),
+        chat_started.notified(),
+    )
+    .await
+    .expect("chat_started signal should be retained for a later waiter");
+    chat_release.notify_waiters();
+    assert!(chat_task.await.unwrap().is_ok());
 }
 
-#[tokio::test]
-async fn public_thinking_model_stays_on_kiro_without_zed_fallback() {
-    let kiro_first_seen = Arc::new(Mutex::new(Vec::new()));
-    let kiro_second_seen = Arc::new(Mutex::new(Vec::new()));
-    let zed_seen = Arc::new(Mutex::new(Vec::new()));
+async fn fallback_test_app(
+    zed_observed: Arc<Mutex<Vec<String>>>,
+    codex_observed: Arc<Mutex<Vec<String>>>,
+) -> axum::Router {
     let providers: Vec<Arc<dyn Provider>> = vec![
         Arc::new(StubProvider::quota_exceeded(
-            "kiro",
-            "kiro_0",
-            &["kiro-claude-sonnet-4-5-agentic"],
-            kiro_first_seen.clone(),
-            "first kiro unavailable",
-        )),
-        Arc::new(StubProvider::success(
-            "kiro",
-            "kiro_1",
-            &["kiro-claude-sonnet-4-5-agentic"],
-            kiro_second_seen.clone(),
-        )),
-        Arc::new(StubProvider::success(
             "zed",
             "zed_0",
-            &["claude-sonnet-4-5"],
-            zed_seen.clone(),
+            &["claude-opus-4-6"],
+            zed_observed,
+            "zed quota exceeded",
         )),
-    ];
-    let registry = Arc::new(ModelRegistry::new());
-    registry
-        .register_client(
-            "kiro_0",
-            "kiro",
-            vec![make_ext_model(
-                "kiro-claude-sonnet-4-5-agentic",
-                "kiro",
-                "kiro",
-            )],
-        )
-        .await;
+        Arc::new(StubProvider::success(
+            "codex",
+            "codex_0",
+            &["gpt-5.4"],
+            codex_observed,
+        )),
+    ];
+    let registry = Arc::new(ModelRegistry::new());
     registry
         .register_client(
-            "kiro_1",
-            "kiro",
-            vec![make_ext_model(
-                "kiro-claude-sonnet-4-5-agentic",
-                "kiro",
-                "kiro",
-            )],
+            "zed_0",
+            "zed",
+            vec![make_ext_model("claude-opus-4-6", "zed", "zed")],
         )
         .await;
     registry
         .register_client(
-            "zed_2",
-            "zed",
-            vec![make_ext_model("claude-sonnet-4-5", "zed", "zed")],
+            "codex_0",
+            "codex",
+            vec![make_ext_model("gpt-5.4", "codex", "codex")],
         )
         .await;
+    let cfg = Config {
+        model_routes: vec![
+            ModelRoute {
+                name: "team-opus".to_string(),
+                targets: vec![ModelRouteTarget::new("zed", "claude-opus-4-6")],
+                fallback: vec!["team-gpt".to_string()],
+                ..Default::default()
+            },
+            ModelRoute {
+                name: "team-gpt".to_string(),
+                targets: vec![ModelRouteTarget::new("codex", "gpt-5.4")],
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    };
+    test_app_with_state(test_state_with_providers(cfg, registry, providers))
+}
 
-    let app = test_app_with_state(test_state_with_providers(
-        Config::default(),
-        registry,
-        providers,
-    ));
-    let body = basic_chat_request("claude-sonnet-4-5-thinking");
+#[tokio::test]
+async fn exhausted_model_falls_back_along_its_chain_and_names_the_served_model() {
+    let zed_observed = Arc::new(Mutex::new(Vec::new()));
+    let codex_observed = Arc::new(Mutex::new(Vec::new()));
+    let app = fallback_test_app(zed_observed.clone(), codex_observed.clone()).await;
 
     let resp = app
         .oneshot(
             Request::builder()
                 .method("POST")
                 .uri("/v1/chat/completions")
-                .header("Content-Type", "application/json")
-                .body(Body::from(serde_json::to_vec(&body).unwrap()))
+                .header("content-type", "application/json")
+                .body(Body::from(basic_chat_request("team-opus").to_string()))
                 .unwrap(),
         )
         .await
         .unwrap();
 
     assert_eq!(resp.status(), StatusCode::OK);
-    assert_eq!(
-        kiro_first_seen.lock().await.as_slice(),
-        &["kiro-claude-sonnet-4-5-agentic"]
-    );
-    assert_eq!(
-        kiro_second_seen.lock().await.as_slice(),
-        &["kiro-claude-sonnet-4-5-agentic"]
-    );
-    assert!(zed_seen.lock().await.is_empty());
+    assert_eq!(resp.headers()["x-rusuh-served-model"], "team-gpt");
+    let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["choices"][0]["message"]["content"], "handled by codex");
+    assert_eq!(zed_observed.lock().await.as_slice(), ["claude-opus-4-6"]);
+    assert_eq!(codex_observed.lock().await.as_slice(), ["gpt-5.4"]);
 }
 
 #[tokio::test]
-async fn public_claude_sonnet_4_5_non_stream_returns_chat_completion_response() {
-    let kiro_seen = Arc::new(Mutex::new(Vec::new()));
-    let providers: Vec<Arc<dyn Provider>> = vec![Arc::new(StubProvider::success(
-        "kiro",
-        "kiro_0",
-        &["kiro-claude-sonnet-4-5"],
-        kiro_seen.clone(),
-    ))];
-    let registry = Arc::new(ModelRegistry::new());
-    registry
-        .register_client(
-            "kiro_0",
-            "kiro",
-            vec![make_ext_model("kiro-claude-sonnet-4-5", "kiro", "kiro")],
-        )
-        .await;
+async fn client_can_opt_out_of_cross_model_fallback() {
+    let zed_observed = Arc::new(Mutex::new(Vec::new()));
+    let codex_observed = Arc::new(Mutex::new(Vec::new()));
+    let app = fallback_test_app(zed_observed.clone(), codex_observed.clone()).await;
 
-    let app = test_app_with_state(test_state_with_providers(
-        Config::default(),
-        registry,
-        providers,
-    ));
-    let body = serde_json::json!({
-        "model": "claude-sonnet-4-5",
-        "stream": false,
-        "messages": [{"role": "user", "content": "hello"}]
-    });
+    let resp = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/v1/chat/completions")
+                .header("content-type", "application/json")
+                .header("x-rusuh-fallback", "off")
+                .body(Body::from(basic_chat_request("team-opus").to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(codex_observed.lock().await.is_empty());
 
     let resp = app
         .oneshot(
             Request::builder()
                 .method("POST")
                 .uri("/v1/chat/completions")
-                .header("Content-Type", "application/json")
-                .body(Body::from(serde_json::to_vec(&body).unwrap()))
+                .header("content-type", "application/json")
+                .body(Body::from(basic_chat_request("team-gpt").to_string()))
                 .unwrap(),
         )
         .await
         .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers()["x-rusuh-served-model"], "team-gpt");
+}
+
+async fn sticky_test_app() -> axum::Router {
+    codex_pair_app(Vec::new()).await
+}
+
+/// Accounts `codex_a` and `codex_b` serving `team-gpt` and `team-gpt-alt`.
+async fn codex_pair_app(api_keys: Vec<ApiKeyEntry>) -> axum::Router {
+    test_app_with_state(codex_pair_state(api_keys).await)
+}
+
+async fn codex_pair_state(api_keys: Vec<ApiKeyEntry>) -> Arc<ProxyState> {
+    let providers: Vec<Arc<dyn Provider>> = ["codex-a", "codex-b"]
+        .into_iter()
+        .zip(["codex_a", "codex_b"])
+        .map(|(name, client_id)| {
+            Arc::new(StubProvider::success_with_type(
+                name,
+                "codex",
+                client_id,
+                &["gpt-5.4"],
+                Arc::new(Mutex::new(Vec::new())),
+            )) as Arc<dyn Provider>
+        })
+        .collect();
+    let registry = Arc::new(ModelRegistry::new());
+    for client_id in ["codex_a", "codex_b"] {
+        registry
+            .register_client(
+                client_id,
+                "codex",
+                vec![make_ext_model("gpt-5.4", "codex", "codex")],
+            )
+            .await;
+    }
+    let cfg = Config {
+        api_keys,
+        model_routes: ["team-gpt", "team-gpt-alt"]
+            .into_iter()
+            .map(|name| ModelRoute {
+                name: name.to_string(),
+                targets: vec![ModelRouteTarget::new("codex", "gpt-5.4")],
+                ..Default::default()
+            })
+            .collect(),
+        ..Default::default()
+    };
+    test_state_with_providers(cfg, registry, providers)
+}
 
+async fn served_by(app: &axum::Router, request: Request<Body>) -> String {
+    let resp = app.clone().oneshot(request).await.unwrap();
     assert_eq!(resp.status(), StatusCode::OK);
-    assert_eq!(
-        kiro_seen.lock().await.as_slice(),
-        &["kiro-claude-sonnet-4-5"]
+    let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    json["choices"][0]["message"]["content"]
+        .as_str()
+        .or_else(|| json["content"][0]["text"].as_str())
+        .unwrap()
+        .to_string()
+}
+
+#[tokio::test]
+async fn session_header_keeps_a_conversation_on_one_account() {
+    let app = sticky_test_app().await;
+    let chat = |session: Option<&str>| {
+        let mut builder = Request::builder()
+            .method("POST")
+            .uri("/v1/chat/completions")
+            .header("content-type", "application/json");
+        if let Some(session) = session {
+            builder = builder.header("x-session-id", session);
+        }
+        builder
+            .body(Body::from(basic_chat_request("team-gpt").to_string()))
+            .unwrap()
+    };
+
+    let first = served_by(&app, chat(Some("conv-1"))).await;
+    for _ in 0..3 {
+        assert_eq!(served_by(&app, chat(Some("conv-1"))).await, first);
+    }
+    // Requests without a session keep rotating between the accounts.
+    assert_ne!(
+        served_by(&app, chat(None)).await,
+        served_by(&app, chat(None)).await
     );
+}
 
+#[tokio::test]
+async fn anthropic_metadata_user_id_keys_the_session() {
+    let app = sticky_test_app().await;
+    let message = || {
+        Request::builder()
+            .method("POST")
+            .uri("/v1/messages")
+            .header("content-type", "application/json")
+            .body(Body::from(
+                serde_json::json!({
+                    "model": "team-gpt",
+                    "max_tokens": 64,
+                    "metadata": {"user_id": "user-42_session-7"},
+                    "messages": [{"role": "user", "content": "test"}]
+                })
+                .to_string(),
+            ))
+            .unwrap()
+    };
+
+    let first = served_by(&app, message()).await;
+    for _ in 0..3 {
+        assert_eq!(served_by(&app, message()).await, first);
+    }
+}
+
+fn scoped_key(key: &str, scope: ApiKeyScope) -> ApiKeyEntry {
+    ApiKeyEntry {
+        key: key.to_string(),
+        name: Some(format!("{key}-name")),
+        scope,
+    }
+}
+
+fn chat_with_key(key: &str, body: serde_json::Value) -> Request<Body> {
+    Request::builder()
+        .method("POST")
+        .uri("/v1/chat/completions")
+        .header("content-type", "application/json")
+        .header("authorization", format!("Bearer {key}"))
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn scoped_key_may_only_request_its_models() {
+    let app = codex_pair_app(vec![
+        scoped_key(
+            "ci",
+            ApiKeyScope {
+                models: vec!["team-gpt".to_string()],
+                ..Default::default()
+            },
+        ),
+        "admin".into(),
+    ])
+    .await;
+
+    served_by(&app, chat_with_key("ci", basic_chat_request("team-gpt"))).await;
+    served_by(
+        &app,
+        chat_with_key("admin", basic_chat_request("team-gpt-alt")),
+    )
+    .await;
+
+    let resp = app
+        .clone()
+        .oneshot(chat_with_key("ci", basic_chat_request("team-gpt-alt")))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
     let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
         .await
         .unwrap();
     let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["error"]["type"], "permission_error");
 
-    assert_eq!(json["object"], "chat.completion");
-    assert_eq!(json["choices"].as_array().map(Vec::len), Some(1));
-    assert_eq!(json["choices"][0]["message"]["role"], "assistant");
+    let resp = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/v1/messages")
+                .header("content-type", "application/json")
+                .header("x-api-key", "ci")
+                .body(Body::from(
+                    serde_json::json!({
+                        "model": "team-gpt-alt",
+                        "max_tokens": 64,
+                        "messages": [{"role": "user", "content": "test"}]
+                    })
+                    .to_string(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["type"], "error");
+    assert_eq!(json["error"]["type"], "permission_error");
 }
 
 #[tokio::test]
-async fn public_claude_sonnet_4_5_non_stream_preserves_final_tool_calls() {
-    let kiro_seen = Arc::new(Mutex::new(Vec::new()));
-    let tool_calls = vec![serde_json::json!({
-        "id": "call_123",
-        "type": "function",
-        "function": {
-            "name": "lookup_weather",
-            "arguments": "{\"city\":\"Jakarta\"}"
-        }
-    })];
-    let providers: Vec<Arc<dyn Provider>> = vec![Arc::new(StubProvider::success_with_tool_calls(
-        "kiro",
-        "kiro_0",
-        &["kiro-claude-sonnet-4-5"],
-        kiro_seen.clone(),
-        tool_calls.clone(),
-    ))];
-    let registry = Arc::new(ModelRegistry::new());
-    registry
-        .register_client(
-            "kiro_0",
-            "kiro",
-            vec![make_ext_model("kiro-claude-sonnet-4-5", "kiro", "kiro")],
-        )
-        .await;
+async fn scoped_key_is_served_only_by_its_accounts() {
+    let app = codex_pair_app(vec![scoped_key(
+        "ci",
+        ApiKeyScope {
+            allowed_auth_ids: vec!["codex_b".to_string()],
+            ..Default::default()
+        },
+    )])
+    .await;
 
-    let app = test_app_with_state(test_state_with_providers(
-        Config::default(),
-        registry,
-        providers,
-    ));
-    let body = serde_json::json!({
-        "model": "claude-sonnet-4-5",
-        "stream": false,
-        "messages": [{"role": "user", "content": "hello"}]
-    });
+    for _ in 0..4 {
+        assert_eq!(
+            served_by(&app, chat_with_key("ci", basic_chat_request("team-gpt"))).await,
+            "handled by codex-b"
+        );
+    }
+
+    let mut pinned = basic_chat_request("team-gpt");
+    pinned["selected_auth_id"] = "codex_a".into();
+    let resp = app.oneshot(chat_with_key("ci", pinned)).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn expired_key_is_rejected() {
+    let app = codex_pair_app(vec![scoped_key(
+        "old",
+        ApiKeyScope {
+            expires_at: Some(chrono::Utc::now() - chrono::Duration::minutes(1)),
+            ..Default::default()
+        },
+    )])
+    .await;
+
+    let resp = app
+        .oneshot(chat_with_key("old", basic_chat_request("team-gpt")))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["error"]["code"], "expired_api_key");
+}
+
+#[tokio::test]
+async fn spent_daily_request_budget_returns_429_until_midnight() {
+    let app = codex_pair_app(vec![
+        scoped_key(
+            "ci",
+            ApiKeyScope {
+                requests_per_day: Some(2),
+                ..Default::default()
+            },
+        ),
+        "admin".into(),
+    ])
+    .await;
+
+    for _ in 0..2 {
+        served_by(&app, chat_with_key("ci", basic_chat_request("team-gpt"))).await;
+    }
+    let resp = app
+        .clone()
+        .oneshot(chat_with_key("ci", basic_chat_request("team-gpt")))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+    let retry_after: u64 = resp.headers()["retry-after"]
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!((1..=86_400).contains(&retry_after));
+
+    // Other keys are unaffected.
+    served_by(&app, chat_with_key("admin", basic_chat_request("team-gpt"))).await;
+}
+
+#[tokio::test]
+async fn key_over_its_request_rate_gets_429_in_the_callers_dialect() {
+    let app = codex_pair_app(vec![scoped_key(
+        "ci",
+        ApiKeyScope {
+            rate_limit: KeyRateLimit {
+                requests_per_minute: Some(1),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    )])
+    .await;
+
+    served_by(&app, chat_with_key("ci", basic_chat_request("team-gpt"))).await;
+
+    let resp = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/v1/messages")
+                .header("content-type", "application/json")
+                .header("x-api-key", "ci")
+                .body(Body::from(
+                    serde_json::json!({
+                        "model": "team-gpt",
+                        "max_tokens": 64,
+                        "messages": [{"role": "user", "content": "test"}]
+                    })
+                    .to_string(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(resp.headers().contains_key("retry-after"));
+    let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["error"]["type"], "rate_limit_error");
 
     let resp = app
         .oneshot(
             Request::builder()
                 .method("POST")
-                .uri("/v1/chat/completions")
-                .header("Content-Type", "application/json")
-                .body(Body::from(serde_json::to_vec(&body).unwrap()))
+                .uri("/v1beta/models/team-gpt:generateContent")
+                .header("content-type", "application/json")
+                .header("x-api-key", "ci")
+                .body(Body::from(
+                    serde_json::json!({
+                        "contents": [{"role": "user", "parts": [{"text": "hello"}]}]
+                    })
+                    .to_string(),
+                ))
                 .unwrap(),
         )
         .await
         .unwrap();
-
-    assert_eq!(resp.status(), StatusCode::OK);
-    assert_eq!(
-        kiro_seen.lock().await.as_slice(),
-        &["kiro-claude-sonnet-4-5"]
-    );
-
+    assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(resp.headers().contains_key("retry-after"));
     let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
         .await
         .unwrap();
     let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
-
-    assert_eq!(json["object"], "chat.completion");
-    assert_eq!(
-        json["choices"][0]["message"]["tool_calls"],
-        serde_json::Value::Array(tool_calls)
-    );
-}
-
-#[test]
-fn blocking_provider_uses_explicit_client_id() {
-    let provider = BlockingProvider::new(
-        "codex",
-        "codex_0",
-        &["gpt-5-codex"],
-        Arc::new(Notify::new()),
-        Arc::new(Notify::new()),
-        Arc::new(Notify::new()),
-        Arc::new(Notify::new()),
-    );
-
-    assert_eq!(provider.client_id(), "codex_0");
+    assert_eq!(json["error"]["status"], "RESOURCE_EXHAUSTED");
 }
 
 #[tokio::test]
-async fn provider_models_request_does_not_hold_providers_lock_while_listing_models() {
-    let list_started = Arc::new(Notify::new());
-    let list_release = Arc::new(Notify::new());
-    let chat_started = Arc::new(Notify::new());
-    let chat_release = Arc::new(Notify::new());
-
-    let providers: Vec<Arc<dyn Provider>> = vec![Arc::new(BlockingProvider::new(
-        "codex",
-        "codex_0",
-        &["gpt-5-codex"],
-        list_started.clone(),
-        list_release.clone(),
-        chat_started,
-        chat_release,
-    ))];
-    let registry = Arc::new(ModelRegistry::new());
-    let state = test_state_with_providers(Config::default(), registry, providers);
-    let app = test_app_with_state(state.clone());
+async fn open_stream_holds_the_keys_only_stream_slot_until_read() {
+    let app = codex_pair_app(vec![scoped_key(
+        "ci",
+        ApiKeyScope {
+            rate_limit: KeyRateLimit {
+                concurrent_streams: Some(1),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    )])
+    .await;
+    let stream = || {
+        let mut body = basic_chat_request("team-gpt");
+        body["stream"] = true.into();
+        chat_with_key("ci", body)
+    };
 
-    let request = Request::builder()
-        .uri("/api/provider/codex/v1/models")
-        .body(Body::empty())
-        .unwrap();
+    let open = app.clone().oneshot(stream()).await.unwrap();
+    assert_eq!(open.status(), StatusCode::OK);
 
-    let request_task = tokio::spawn(async move { app.oneshot(request).await.unwrap() });
+    let refused = app.clone().oneshot(stream()).await.unwrap();
+    assert_eq!(refused.status(), StatusCode::TOO_MANY_REQUESTS);
+    // Complete replies need no stream slot.
+    served_by(&app, chat_with_key("ci", basic_chat_request("team-gpt"))).await;
 
-    list_started.notified().await;
+    axum::body::to_bytes(open.into_body(), 1024 * 1024)
+        .await
+        .unwrap();
+    let resp = app.oneshot(stream()).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+}
 
-    let write_attempt = tokio::spawn({
-        let state = state.clone();
-        async move {
-            let _guard = state.providers.write().await;
-        }
-    });
+#[tokio::test]
+async fn finished_requests_are_recorded_in_the_usage_ledger() {
+    let state = codex_pair_state(vec![scoped_key(
+        "ci",
+        ApiKeyScope {
+            allowed_auth_ids: vec!["codex_b".to_string()],
+            ..Default::default()
+        },
+    )])
+    .await;
+    let app = test_app_with_state(state.clone());
 
-    tokio::time::timeout(std::time::Duration::from_millis(100), write_attempt)
+    served_by(&app, chat_with_key("ci", basic_chat_request("team-gpt"))).await;
+    let resp = app
+        .oneshot(chat_with_key("ci", basic_chat_request("no-such-model")))
         .await
-        .expect("providers write lock should not be blocked by model listing")
-        .expect("write-lock task should complete successfully");
-
-    list_release.notify_waiters();
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
 
-    let resp = request_task.await.unwrap();
-    assert_eq!(resp.status(), StatusCode::OK);
+    let by_key = state
+        .usage_ledger
+        .query(&[UsageGroup::Key], UsageRange::default());
+    assert_eq!(by_key.len(), 1);
+    assert_eq!(by_key[0].key.as_deref(), Some("ci-name"));
+    assert_eq!(by_key[0].totals.requests, 2);
+    assert_eq!(by_key[0].totals.errors, 1);
+
+    let rows = state.usage_ledger.query(
+        &[UsageGroup::Account, UsageGroup::Model],
+        UsageRange::default(),
+    );
+    let served = rows
+        .iter()
+        .find(|row| row.model.as_deref() == Some("team-gpt"))
+        .expect("served request is recorded");
+    assert_eq!(served.account.as_deref(), Some("codex_b"));
+    assert_eq!(served.provider.as_deref(), Some("codex"));
+    assert_eq!(served.totals.errors, 0);
+    let failed = rows
+        .iter()
+        .find(|row| row.model.as_deref() == Some("no-such-model"))
+        .expect("failed request is recorded");
+    assert_eq!(failed.account, None);
+    assert_eq!(failed.totals.errors, 1);
 }
 
 #[tokio::test]
-async fn provider_chat_request_does_not_hold_providers_lock_while_awaiting_upstream() {
-    let list_started = Arc::new(Notify::new());
-    let list_release = Arc::new(Notify::new());
-    let chat_started = Arc::new(Notify::new());
-    let chat_release = Arc::new(Notify::new());
-
-    let providers: Vec<Arc<dyn Provider>> = vec![Arc::new(BlockingProvider::new(
+async fn streams_that_fail_after_content_are_recorded_as_errors() {
+    let providers: Vec<Arc<dyn Provider>> = vec![Arc::new(StubProvider::streaming(
         "codex",
         "codex_0",
-        &["gpt-5-codex"],
-        list_started,
-        list_release,
-        chat_started.clone(),
-        chat_release.clone(),
+        &["gpt-5.4"],
+        Arc::new(Mutex::new(Vec::new())),
+        vec![
+            Ok(content_chunk("hello")),
+            Err(UpstreamError::transport(
+                "stream read error: connection reset",
+            )),
+        ],
     ))];
-    let registry = Arc::new(ModelRegistry::new());
-    registry
-        .register_client(
-            "codex_0",
-            "codex",
-            vec![make_ext_model("gpt-5-codex", "codex", "codex")],
-        )
-        .await;
-    let state = test_state_with_providers(Config::default(), registry, providers);
-    let app = test_app_with_state(state.clone());
+    let state = streaming_test_state(providers).await;
 
-    let body = serde_json::json!({
-        "model": "gpt-5-codex",
-        "messages": [{"role": "user", "content": "hello"}]
-    });
-    let request = Request::builder()
-        .method("POST")
-        .uri("/api/provider/codex/v1/chat/completions")
-        .header("Content-Type", "application/json")
-        .body(Body::from(serde_json::to_vec(&body).unwrap()))
-        .unwrap();
-
-    let request_task = tokio::spawn(async move { app.oneshot(request).await.unwrap() });
+    let (status, body) = stream_chat(test_app_with_state(state.clone())).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("stream_error"), "{body}");
 
-    chat_started.notified().await;
+    let rows = state.usage_ledger.query(&[], UsageRange::default());
+    assert_eq!(rows[0].totals.requests, 1);
+    assert_eq!(rows[0].totals.errors, 1);
+}
 
-    let write_attempt = tokio::spawn({
-        let state = state.clone();
-        async move {
-            let _guard = state.providers.write().await;
-        }
+#[tokio::test]
+async fn metrics_report_requests_failovers_stream_timings_and_tokens() {
+    let usage = serde_json::json!({
+        "object": "chat.completion.chunk",
+        "choices": [],
+        "usage": {"prompt_tokens": 7, "completion_tokens": 3}
     });
+    let providers: Vec<Arc<dyn Provider>> = vec![
+        Arc::new(StubProvider::streaming(
+            "codex",
+            "codex_broken",
+            &["gpt-5.4"],
+            Arc::new(Mutex::new(Vec::new())),
+            vec![Err(UpstreamError::transport(
+                "stream read error: connection reset",
+            ))],
+        )),
+        Arc::new(StubProvider::streaming(
+            "codex",
+            "codex_healthy",
+            &["gpt-5.4"],
+            Arc::new(Mutex::new(Vec::new())),
+            vec![
+                Ok(content_chunk("hello")),
+                Ok(format!("data: {usage}\n\n")),
+                Ok("data: [DONE]\n\n".to_string()),
+            ],
+        )),
+    ];
+    let app = streaming_test_app(providers).await;
 
-    tokio::time::timeout(std::time::Duration::from_millis(100), write_attempt)
-        .await
-        .expect("providers write lock should not be blocked by chat completion")
-        .expect("write-lock task should complete successfully");
-
-    chat_release.notify_waiters();
+    let (status, _) = stream_chat(app.clone()).await;
+    assert_eq!(status, StatusCode::OK);
 
-    let resp = request_task.await.unwrap();
+    let resp = app
+        .oneshot(
+            Request::builder()
+                .uri("/metrics")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
     assert_eq!(resp.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
+        .await
+        .unwrap();
+    let text = String::from_utf8(body.to_vec()).unwrap();
+    for line in [
+        r#"rusuh_requests_total{model="team-gpt",provider="codex",route="/v1/chat/completions",status="200"} 1"#,
+        r#"rusuh_failovers_total{model="gpt-5.4"} 1"#,
+        r#"rusuh_time_to_first_token_seconds_count{model="team-gpt",provider="codex"} 1"#,
+        r#"rusuh_stream_duration_seconds_count{model="team-gpt",provider="codex"} 1"#,
+        r#"rusuh_tokens_total{kind="prompt",model="team-gpt",provider="codex"} 7"#,
+        r#"rusuh_tokens_total{kind="completion",model="team-gpt",provider="codex"} 3"#,
+        r#"rusuh_balancer_requests{client_id="codex_broken",provider="codex"} 1"#,
+    ] {
+        assert!(text.contains(line), "missing `{line}` in:\n{text}");
+    }
 }
 
 #[tokio::test]
-async fn blocking_provider_start_signals_are_not_lost_if_observed_after_spawn() {
-    let list_started = Arc::new(Notify::new());
-    let list_release = Arc::new(Notify::new());
-    let chat_started = Arc::new(Notify::new());
-    let chat_release = Arc::new(Notify::new());
-    let provider = BlockingProvider::new(
-        "codex",
-        "codex_0",
-        &["gpt-5-codex"],
-        list_started.clone(),
-        list_release.clone(),
-        chat_started.clone(),
-        chat_release.clone(),
-    );
+async fn failed_requests_for_unknown_models_share_one_metric_label() {
+    let state = codex_pair_state(vec!["ci".into()]).await;
+    state.config.write().await.metrics.public = true;
+    let app = test_app_with_state(state);
+    for model in ["made-up-1", "made-up-2"] {
+        let resp = app
+            .clone()
+            .oneshot(chat_with_key("ci", basic_chat_request(model)))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
 
-    let list_task = tokio::spawn(async move { provider.list_models().await });
-    tokio::task::yield_now().await;
-    tokio::time::timeout(
-        std::time::Duration::from_millis(50),
-        list_started.notified(),
-    )
-    .await
-    .expect("list_started signal should be retained for a later waiter");
-    list_release.notify_waiters();
-    assert!(list_task.await.unwrap().is_ok());
+    let resp = app
+        .oneshot(
+            Request::builder()
+                .uri("/metrics")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
+        .await
+        .unwrap();
+    let text = String::from_utf8(body.to_vec()).unwrap();
+    assert!(text.contains(
+        r#"rusuh_requests_total{model="unknown",provider="",route="/v1/chat/completions",status="400"} 2"#
+    ));
+    assert!(!text.contains("made-up"));
+}
 
-    let provider = BlockingProvider::new(
-        "codex",
-        "codex_0",
-        &["gpt-5-codex"],
-        Arc::new(Notify::new()),
-        Arc::new(Notify::new()),
-        chat_started.clone(),
-        chat_release.clone(),
-    );
-    let req = ChatCompletionRequest {
-        model: "gpt-5-codex".to_string(),
-        messages: vec![ChatMessage {
-            role: "user".to_string(),
-            content: MessageContent::Text("hello".to_string()),
-            name: None,
-            tool_calls: None,
-            tool_call_id: None,
-        }],
-        stream: None,
-        max_tokens: None,
-        temperature: None,
-        top_p: None,
-        tools: None,
-        tool_choice: None,
-        stop: None,
-        extra: std::collections::HashMap::new(),
+#[tokio::test]
+async fn metrics_need_the_management_key_unless_public() {
+    let state = codex_pair_state(vec!["ci".into()]).await;
+    let app = test_app_with_state(state.clone());
+    let scrape = |auth: Option<&str>| {
+        let mut builder = Request::builder().uri("/metrics");
+        if let Some(auth) = auth {
+            builder = builder.header("authorization", auth);
+        }
+        builder.body(Body::empty()).unwrap()
     };
 
-    let chat_task = tokio::spawn(async move { provider.chat_completion(&req).await });
-    tokio::task::yield_now().await;
-    tokio::time::timeout(
-        std::time::Duration::from_millis(50),
-        chat_started.notified(),
-    )
-    .await
-    .expect("chat_started signal should be retained for a later waiter");
-    chat_release.notify_waiters();
-    assert!(chat_task.await.unwrap().is_ok());
+    let resp = app.clone().oneshot(scrape(None)).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    state.config.write().await.remote_management.secret_key = "mgmt-secret".to_string();
+    let resp = app
+        .clone()
+        .oneshot(scrape(Some("Bearer ci")))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    let resp = app
+        .clone()
+        .oneshot(scrape(Some("Bearer mgmt-secret")))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    state.config.write().await.metrics.public = true;
+    let resp = app.oneshot(scrape(None)).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
 }
@@ -0,0 +1,128 @@
+//! Tests for daily request and token budgets of scoped client keys.
+
+use chrono::{TimeZone, Utc};
+
+use rusuh::config::{ApiKeyEntry, ApiKeyScope};
+use rusuh::error::AppError;
+use rusuh::proxy::key_budget::KeyBudgets;
+use rusuh::proxy::usage_ledger::{UsageLedger, UsageRecord};
+
+fn key(scope: ApiKeyScope) -> ApiKeyEntry {
+    ApiKeyEntry {
+        key: "ci".to_string(),
+        name: None,
+        scope,
+    }
+}
+
+#[test]
+fn request_budget_refuses_until_the_next_utc_day() {
+    let budgets = KeyBudgets::new();
+    let key = key(ApiKeyScope {
+        requests_per_day: Some(1),
+        ..Default::default()
+    });
+    let evening = Utc.with_ymd_and_hms(2026, 3, 1, 23, 0, 0).unwrap();
+
+    budgets.admit(&key, evening).unwrap();
+    match budgets.admit(&key, evening) {
+        Err(AppError::RateLimited { retry_after, .. }) => {
+            assert_eq!(retry_after, Some(std::time::Duration::from_secs(3600)));
+        }
+        other => panic!("expected rate limit, got {other:?}"),
+    }
+
+    let next_morning = Utc.with_ymd_and_hms(2026, 3, 2, 0, 0, 1).unwrap();
+    budgets.admit(&key, next_morning).unwrap();
+    assert_eq!(budgets.used_today("ci", next_morning), (1, 0));
+}
+
+#[test]
+fn token_budget_counts_recorded_usage() {
+    let budgets = KeyBudgets::new();
+    let key = key(ApiKeyScope {
+        tokens_per_day: Some(100),
+        ..Default::default()
+    });
+    let now = Utc.with_ymd_and_hms(2026, 3, 1, 12, 0, 0).unwrap();
+
+    budgets.admit(&key, now).unwrap();
+    budgets.record_tokens("ci", 60, now);
+    budgets.admit(&key, now).unwrap();
+    budgets.record_tokens("ci", 60, now);
+    assert!(matches!(
+        budgets.admit(&key, now),
+        Err(AppError::RateLimited { .. })
+    ));
+}
+
+#[test]
+fn unlimited_keys_are_not_tracked() {
+    let budgets = KeyBudgets::new();
+    let now = Utc::now();
+    budgets.admit(&key(ApiKeyScope::default()), now).unwrap();
+    assert_eq!(budgets.used_today("ci", now), (0, 0));
+}
+
+#[test]
+fn check_refuses_a_spent_budget_without_counting() {
+    let budgets = KeyBudgets::new();
+    let key = key(ApiKeyScope {
+        requests_per_day: Some(1),
+        ..Default::default()
+    });
+    let now = Utc.with_ymd_and_hms(2026, 3, 1, 12, 0, 0).unwrap();
+
+    budgets.check(&key, now).unwrap();
+    assert_eq!(budgets.used_today("ci", now), (0, 0));
+    budgets.admit(&key, now).unwrap();
+    assert!(matches!(
+        budgets.check(&key, now),
+        Err(AppError::RateLimited { .. })
+    ));
+    assert_eq!(budgets.used_today("ci", now), (1, 0));
+}
+
+#[test]
+fn seeding_from_the_ledger_keeps_todays_counts() {
+    let key = ApiKeyEntry {
+        name: Some("ci".to_string()),
+        ..key(ApiKeyScope {
+            requests_per_day: Some(2),
+            ..Default::default()
+        })
+    };
+    // Another key under the same name must not count against this one.
+    let namesake = ApiKeyEntry {
+        key: "ci-other".to_string(),
+        ..key.clone()
+    };
+    let ledger = UsageLedger::in_memory();
+    let now = Utc.with_ymd_and_hms(2026, 3, 2, 9, 0, 0).unwrap();
+    let served = |key: &ApiKeyEntry, at, tokens| UsageRecord {
+        at,
+        key: Some(key.label()),
+        key_id: Some(key.id()),
+        client_id: Some("codex_0".to_string()),
+        provider: Some("codex".to_string()),
+        model: "gpt-5.4".to_string(),
+        prompt_tokens: tokens,
+        completion_tokens: tokens,
+        cached_tokens: 0,
+        latency_ms: 10,
+        status: 200,
+    };
+    ledger.record(served(&key, now - chrono::Duration::days(1), 500));
+    ledger.record(served(&key, now, 20));
+    ledger.record(served(&key, now, 30));
+    ledger.record(served(&namesake, now, 40));
+
+    let budgets = KeyBudgets::new();
+    budgets.seed_from_ledger(std::slice::from_ref(&key), &ledger, now);
+
+    assert_eq!(budgets.used_today("ci", now), (2, 100));
+    assert!(matches!(
+        budgets.admit(&key, now),
+        Err(AppError::RateLimited { .. })
+    ));
+}
@@ -0,0 +1,100 @@
+//! Tests for per-minute request, token and stream limits of client keys.
+
+use std::time::{Duration, Instant};
+
+use rusuh::config::KeyRateLimit;
+use rusuh::error::AppError;
+use rusuh::proxy::key_rate_limit::KeyRateLimiter;
+
+fn retry_after(result: rusuh::error::AppResult<impl std::fmt::Debug>) -> Duration {
+    match result {
+        Err(error @ AppError::RateLimited { .. }) => error.retry_after().unwrap(),
+        other => panic!("expected rate limit, got {other:?}"),
+    }
+}
+
+#[test]
+fn request_bucket_refills_over_the_minute() {
+    let limiter = KeyRateLimiter::new();
+    let limits = KeyRateLimit {
+        requests_per_minute: Some(2),
+        ..Default::default()
+    };
+    let now = Instant::now();
+
+    limiter.admit("ci", limits, false, now).unwrap();
+    limiter.admit("ci", limits, false, now).unwrap();
+    assert_eq!(
+        retry_after(limiter.admit("ci", limits, false, now)),
+        Duration::from_secs(30)
+    );
+    // Another key has its own bucket.
+    limiter.admit("other", limits, false, now).unwrap();
+
+    limiter
+        .admit("ci", limits, false, now + Duration::from_secs(30))
+        .unwrap();
+}
+
+#[test]
+fn refunded_request_slot_can_be_used_again() {
+    let limiter = KeyRateLimiter::new();
+    let limits = KeyRateLimit {
+        requests_per_minute: Some(1),
+        ..Default::default()
+    };
+    let now = Instant::now();
+
+    limiter.admit("ci", limits, false, now).unwrap();
+    limiter.refund("ci", limits);
+    limiter.admit("ci", limits, false, now).unwrap();
+    assert_eq!(
+        retry_after(limiter.admit("ci", limits, false, now)),
+        Duration::from_secs(60)
+    );
+
+    // A refund never lifts the bucket above its capacity.
+    limiter.refund("ci", limits);
+    limiter.refund("ci", limits);
+    limiter.admit("ci", limits, false, now).unwrap();
+    assert!(limiter.admit("ci", limits, false, now).is_err());
+}
+
+#[test]
+fn token_bucket_is_charged_after_the_reply() {
+    let limiter = KeyRateLimiter::new();
+    let limits = KeyRateLimit {
+        tokens_per_minute: Some(600),
+        ..Default::default()
+    };
+    let now = Instant::now();
+
+    limiter.admit("ci", limits, false, now).unwrap();
+    limiter.record_tokens("ci", limits, 900, now);
+    // 300 tokens in debt at 10 per second, plus one to go positive.
+    let wait = retry_after(limiter.admit("ci", limits, false, now));
+    assert!(wait > Duration::from_secs(30) && wait < Duration::from_secs(31));
+
+    limiter
+        .admit("ci", limits, false, now + Duration::from_secs(31))
+        .unwrap();
+}
+
+#[test]
+fn stream_slots_are_released_when_permits_drop() {
+    let limiter = KeyRateLimiter::new();
+    let limits = KeyRateLimit {
+        concurrent_streams: Some(1),
+        ..Default::default()
+    };
+    let now = Instant::now();
+
+    let permit = limiter.admit("ci", limits, true, now).unwrap();
+    assert!(permit.is_some());
+    retry_after(limiter.admit("ci", limits, true, now));
+    // Non-streaming requests do not take a slot.
+    assert!(limiter.admit("ci", limits, false, now).unwrap().is_none());
+
+    drop(permit);
+    assert!(limiter.admit("ci", limits, true, now).unwrap().is_some());
+}
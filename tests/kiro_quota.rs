@@ -36,6 +36,7 @@ fn available_without_remaining() {
 fn exhausted_status() {
     let status = QuotaStatus::Exhausted {
         detail: "monthly limit reached".into(),
+        next_reset: None,
     };
     assert!(status.is_exhausted());
     assert_eq!(status.remaining(), None);
@@ -72,6 +73,7 @@ impl QuotaChecker for FakeExhaustedChecker {
     ) -> QuotaStatus {
         QuotaStatus::Exhausted {
             detail: "test exhausted".into(),
+            next_reset: None,
         }
     }
 }
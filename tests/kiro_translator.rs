@@ -18,6 +18,7 @@ fn translate_simple_request() {
                 name: None,
                 tool_calls: None,
                 tool_call_id: None,
+                reasoning_content: None,
             },
             ChatMessage {
                 role: "user".to_string(),
@@ -25,6 +26,7 @@ fn translate_simple_request() {
                 name: None,
                 tool_calls: None,
                 tool_call_id: None,
+                reasoning_content: None,
             },
         ],
         temperature: Some(0.7),
@@ -158,6 +160,31 @@ fn assistant_response_event_emits_parseable_json_data_line() {
     assert_eq!(payload["choices"][0]["delta"]["content"], "Hello");
 }
 
+#[test]
+fn reasoning_content_event_streams_reasoning_content() {
+    let sse = translate_kiro_event_to_openai_sse(
+        "reasoningContentEvent",
+        br#"{"text":"Considering options"}"#,
+        "chat_1",
+        "kiro-model",
+        123,
+    )
+    .expect("sse output");
+
+    let text = String::from_utf8(sse.to_vec()).expect("utf8 sse");
+    let json_line = text
+        .lines()
+        .find_map(|line| line.strip_prefix("data: "))
+        .expect("SSE data line");
+    let payload: serde_json::Value = serde_json::from_str(json_line).expect("valid json data line");
+
+    assert_eq!(
+        payload["choices"][0]["delta"]["reasoning_content"],
+        "Considering options"
+    );
+    assert!(payload["choices"][0]["delta"].get("content").is_none());
+}
+
 #[test]
 fn filtered_event_returns_none() {
     let result = translate_kiro_event_to_openai_sse(
@@ -181,6 +208,7 @@ fn provider_pinned_kiro_model_ids_are_mapped_to_upstream_ids() {
                 name: None,
                 tool_calls: None,
                 tool_call_id: None,
+                reasoning_content: None,
             },
             ChatMessage {
                 role: "assistant".to_string(),
@@ -188,6 +216,7 @@ fn provider_pinned_kiro_model_ids_are_mapped_to_upstream_ids() {
                 name: None,
                 tool_calls: None,
                 tool_call_id: None,
+                reasoning_content: None,
             },
             ChatMessage {
                 role: "user".to_string(),
@@ -195,6 +224,7 @@ fn provider_pinned_kiro_model_ids_are_mapped_to_upstream_ids() {
                 name: None,
                 tool_calls: None,
                 tool_call_id: None,
+                reasoning_content: None,
             },
         ],
         temperature: None,
@@ -238,6 +268,7 @@ fn agentic_kiro_model_ids_use_base_upstream_model_id() {
             name: None,
             tool_calls: None,
             tool_call_id: None,
+            reasoning_content: None,
         }],
         temperature: None,
         max_tokens: None,
@@ -288,6 +319,35 @@ fn aggregate_simple_text_only_messages() {
     assert!(result.stop_reason.is_none());
 }
 
+#[test]
+fn aggregate_reasoning_separately_from_content() {
+    let messages = vec![
+        EventStreamMessage {
+            event_type: "reasoningContentEvent".to_string(),
+            payload: Bytes::from(r#"{"text":"Let me think"}"#),
+        },
+        EventStreamMessage {
+            event_type: "contentBlockDelta".to_string(),
+            payload: Bytes::from(r#"{"delta":{"type":"thinking_delta","thinking":"..."}}"#),
+        },
+        EventStreamMessage {
+            event_type: "assistantResponseEvent".to_string(),
+            payload: Bytes::from(r#"{"content":"Answer"}"#),
+        },
+    ];
+
+    let result = aggregate_kiro_messages(&messages);
+    assert_eq!(result.content, "Answer");
+    assert_eq!(result.reasoning_content, "Let me think...");
+
+    let response = build_openai_chat_completion_response(result, "chatcmpl-1", "kiro-model", 1);
+    let message = response.choices[0].message.as_ref().unwrap();
+    assert_eq!(
+        message.reasoning_content.as_deref(),
+        Some("Let me think...")
+    );
+}
+
 #[test]
 fn aggregate_usage_data() {
     let messages = vec![
@@ -435,6 +495,7 @@ fn aggregate_flat_tool_use_event_payload() {
 fn build_text_only_response_with_stop_finish_reason() {
     let aggregate = KiroAggregatedResponse {
         content: "Hello, how can I help you?".to_string(),
+        reasoning_content: String::new(),
         tool_calls: vec![],
         usage: None,
         stop_reason: Some("end_turn".to_string()),
@@ -471,6 +532,7 @@ fn build_text_only_response_with_stop_finish_reason() {
 fn build_response_with_max_tokens_maps_to_length() {
     let aggregate = KiroAggregatedResponse {
         content: "This response was cut off due to".to_string(),
+        reasoning_content: String::new(),
         tool_calls: vec![],
         usage: None,
         stop_reason: Some("max_tokens".to_string()),
@@ -491,6 +553,7 @@ fn build_response_with_max_tokens_maps_to_length() {
 fn build_response_with_tool_use_maps_to_tool_calls_finish_reason() {
     let aggregate = KiroAggregatedResponse {
         content: "Let me check that for you.".to_string(),
+        reasoning_content: String::new(),
         tool_calls: vec![json!({
             "id": "call_abc",
             "type": "function",
@@ -532,6 +595,7 @@ fn build_response_with_tool_use_maps_to_tool_calls_finish_reason() {
 fn build_response_with_usage_populates_usage_field() {
     let aggregate = KiroAggregatedResponse {
         content: "Response with usage".to_string(),
+        reasoning_content: String::new(),
         tool_calls: vec![],
         usage: Some(json!({
             "prompt_tokens": 150,
@@ -558,6 +622,7 @@ fn build_response_with_usage_populates_usage_field() {
 fn build_response_with_only_tool_calls_uses_empty_text_content() {
     let aggregate = KiroAggregatedResponse {
         content: String::new(),
+        reasoning_content: String::new(),
         tool_calls: vec![json!({
             "id": "call_xyz",
             "type": "function",
@@ -597,6 +662,7 @@ fn build_response_with_only_tool_calls_uses_empty_text_content() {
 fn build_response_with_no_stop_reason_defaults_to_stop() {
     let aggregate = KiroAggregatedResponse {
         content: "Response without explicit stop reason".to_string(),
+        reasoning_content: String::new(),
         tool_calls: vec![],
         usage: None,
         stop_reason: None,
@@ -323,3 +323,37 @@ async fn test_trait_implementation_with_metadata() {
         _ => panic!("Expected Available status via trait, got {:?}", status),
     }
 }
+
+#[tokio::test]
+async fn test_usage_checker_sends_through_proxy() {
+    use axum::{routing::get, Router};
+
+    // A plain HTTP proxy receives the absolute-form request and serves it here.
+    let proxy = Router::new().route(
+        "/getUsageLimits",
+        get(|| async { mock_usage_response_available() }),
+    );
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let proxy_url = format!("http://{}", addr);
+
+    tokio::spawn(async move {
+        axum::serve(listener, proxy).await.unwrap();
+    });
+
+    let checker = KiroUsageChecker::new_with_proxy("http://kiro.invalid", Some(&proxy_url))
+        .expect("build kiro usage checker");
+    let req = UsageCheckRequest {
+        access_token: "fake-token".to_string(),
+        profile_arn: "arn:aws:iam::123456789012:role/test".to_string(),
+        client_id: None,
+        refresh_token: None,
+    };
+    let status = checker.check(&req).await;
+
+    match status {
+        QuotaStatus::Available { remaining, .. } => assert_eq!(remaining, Some(70)),
+        _ => panic!("Expected Available status through proxy, got {:?}", status),
+    }
+}
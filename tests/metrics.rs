@@ -0,0 +1,145 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+
+use rusuh::auth::manager::AccountManager;
+use rusuh::config::Config;
+use rusuh::error::{AppError, AppResult};
+use rusuh::models::{ChatCompletionRequest, ChatCompletionResponse, ModelInfo};
+use rusuh::providers::model_info::ExtModelInfo;
+use rusuh::providers::model_registry::ModelRegistry;
+use rusuh::providers::{BoxStream, Provider};
+use rusuh::proxy::ProxyState;
+
+#[derive(Debug)]
+struct IdleProvider;
+
+#[async_trait]
+impl Provider for IdleProvider {
+    fn name(&self) -> &str {
+        "codex"
+    }
+
+    fn client_id(&self) -> &str {
+        "codex_0"
+    }
+
+    async fn list_models(&self) -> AppResult<Vec<ModelInfo>> {
+        Ok(Vec::new())
+    }
+
+    async fn chat_completion(
+        &self,
+        _req: &ChatCompletionRequest,
+    ) -> AppResult<ChatCompletionResponse> {
+        Err(AppError::Internal(anyhow::anyhow!("idle")))
+    }
+
+    async fn chat_completion_stream(&self, _req: &ChatCompletionRequest) -> AppResult<BoxStream> {
+        Err(AppError::Internal(anyhow::anyhow!("idle")))
+    }
+}
+
+fn make_ext_model(id: &str) -> ExtModelInfo {
+    ExtModelInfo {
+        id: id.to_string(),
+        object: "model".to_string(),
+        created: 0,
+        owned_by: "codex".to_string(),
+        provider_type: "codex".to_string(),
+        display_name: None,
+        name: Some(id.to_string()),
+        version: None,
+        description: None,
+        input_token_limit: 0,
+        output_token_limit: 0,
+        supported_generation_methods: vec![],
+        context_length: 0,
+        max_completion_tokens: 0,
+        supported_parameters: vec![],
+        supported_endpoints: None,
+        thinking: None,
+        user_defined: false,
+    }
+}
+
+async fn test_state() -> Arc<ProxyState> {
+    let registry = Arc::new(ModelRegistry::new());
+    registry
+        .register_client("codex_0", "codex", vec![make_ext_model("gpt-5.4")])
+        .await;
+    let accounts = Arc::new(AccountManager::with_dir("/tmp/rusuh_test_nonexistent"));
+    let state = Arc::new(ProxyState::new(Config::default(), accounts, registry, 1));
+    state
+        .publish_runtime_from_providers(vec![Arc::new(IdleProvider)])
+        .await
+        .expect("test provider should publish");
+    state
+}
+
+/// Value of the sample whose name and labels start with `prefix`.
+fn sample(text: &str, prefix: &str) -> Option<f64> {
+    text.lines()
+        .find(|line| line.starts_with(prefix))
+        .and_then(|line| line.rsplit(' ').next())
+        .and_then(|value| value.parse().ok())
+}
+
+#[tokio::test]
+async fn scrape_reports_active_cooldowns_and_quota_marks() {
+    let state = test_state().await;
+    let now = Instant::now();
+    state.kiro_runtime.cooldown.write().await.set_cooldown(
+        "codex_0",
+        "gpt-5.4",
+        Duration::from_secs(600),
+        "rate limited",
+        now,
+    );
+    state
+        .model_registry
+        .set_quota_exceeded_until("codex_0", "gpt-5.4", now + Duration::from_secs(300))
+        .await;
+
+    let text = state.metrics.render(&state).await;
+    let cooldown = sample(
+        &text,
+        r#"rusuh_cooldown_remaining_seconds{client_id="codex_0",model="gpt-5.4"}"#,
+    )
+    .unwrap();
+    assert!(cooldown > 590.0 && cooldown <= 600.0);
+    let quota = sample(
+        &text,
+        r#"rusuh_quota_exceeded_remaining_seconds{client_id="codex_0",model="gpt-5.4"}"#,
+    )
+    .unwrap();
+    assert!(quota > 290.0 && quota <= 300.0);
+    assert_eq!(
+        sample(
+            &text,
+            r#"rusuh_balancer_requests{client_id="codex_0",provider="codex"}"#
+        ),
+        Some(0.0)
+    );
+
+    // Gauges follow the owners: a cleared mark is gone on the next scrape.
+    state
+        .model_registry
+        .clear_quota_exceeded("codex_0", "gpt-5.4")
+        .await;
+    let text = state.metrics.render(&state).await;
+    assert!(!text.contains("rusuh_quota_exceeded_remaining_seconds{"));
+}
+
+#[tokio::test]
+async fn oauth_refresh_failures_are_counted_per_provider() {
+    let state = test_state().await;
+    let prefix = r#"rusuh_oauth_refresh_failures_total{provider="antigravity"}"#;
+    let before = sample(&state.metrics.render(&state).await, prefix).unwrap_or(0.0);
+
+    rusuh::metrics::oauth_refresh_failed("antigravity");
+
+    let after = sample(&state.metrics.render(&state).await, prefix).unwrap();
+    assert_eq!(after, before + 1.0);
+}
@@ -881,3 +881,83 @@ async fn quota_expired_readmits_client() {
     let available = reg.available_clients_for_model("model-a").await;
     assert!(available.contains(&"c1".to_string()));
 }
+
+#[tokio::test]
+async fn quota_reset_from_upstream_outlasts_default_window() {
+    use std::time::{Duration, Instant};
+
+    let reg = ModelRegistry::new();
+    reg.register_client(
+        "codex-a.json",
+        "codex",
+        vec![make_model("gpt-5.4", "codex")],
+    )
+    .await;
+
+    let three_days = Duration::from_secs(3 * 24 * 3600);
+    reg.set_quota_exceeded_until("codex-a.json", "gpt-5.4", Instant::now() + three_days)
+        .await;
+    reg.cleanup_expired_quotas().await;
+
+    assert!(
+        !reg.client_is_effectively_available("codex-a.json", "gpt-5.4")
+            .await
+    );
+    let reset_in = reg.quota_reset_in("codex-a.json", "gpt-5.4").await.unwrap();
+    assert!(reset_in > Duration::from_secs(300));
+    assert!(reset_in <= three_days);
+
+    // A model refresh does not forget a reset the upstream reported.
+    reg.register_client(
+        "codex-a.json",
+        "codex",
+        vec![make_model("gpt-5.4", "codex")],
+    )
+    .await;
+    assert!(
+        !reg.client_is_effectively_available("codex-a.json", "gpt-5.4")
+            .await
+    );
+}
+
+#[tokio::test]
+async fn short_retry_after_readmits_client_before_default_window() {
+    use std::time::{Duration, Instant};
+
+    let reg = ModelRegistry::new();
+    reg.register_client("c1", "claude", vec![make_model("model-a", "claude")])
+        .await;
+
+    reg.set_quota_exceeded_until("c1", "model-a", Instant::now() + Duration::from_millis(20))
+        .await;
+    assert!(!reg.client_is_effectively_available("c1", "model-a").await);
+
+    tokio::time::sleep(Duration::from_millis(30)).await;
+    assert!(reg.client_is_effectively_available("c1", "model-a").await);
+    assert_eq!(reg.quota_reset_in("c1", "model-a").await, None);
+}
+
+#[tokio::test]
+async fn client_quota_reset_marks_every_model() {
+    use std::time::{Duration, Instant};
+
+    let reg = ModelRegistry::new();
+    reg.register_client(
+        "c1",
+        "codex",
+        vec![
+            make_model("model-a", "codex"),
+            make_model("model-b", "codex"),
+        ],
+    )
+    .await;
+    reg.register_client("c2", "codex", vec![make_model("model-a", "codex")])
+        .await;
+
+    reg.set_client_quota_exceeded_until("c1", Instant::now() + Duration::from_secs(3600))
+        .await;
+
+    assert!(!reg.client_is_effectively_available("c1", "model-a").await);
+    assert!(!reg.client_is_effectively_available("c1", "model-b").await);
+    assert!(reg.client_is_effectively_available("c2", "model-a").await);
+}
@@ -0,0 +1,125 @@
+//! Integration tests for the `model-routes` management endpoints.
+
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use http_body_util::BodyExt;
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+use rusuh::auth::manager::AccountManager;
+use rusuh::config::{Config, ManagementConfig};
+use rusuh::providers::model_registry::ModelRegistry;
+use rusuh::proxy::ProxyState;
+use rusuh::router::build_router;
+
+const SECRET: &str = "test-mgmt-secret";
+
+fn test_app() -> (axum::Router, Arc<ProxyState>) {
+    let cfg = Config {
+        remote_management: ManagementConfig {
+            allow_remote: true,
+            secret_key: SECRET.into(),
+        },
+        ..Default::default()
+    };
+    let accounts = Arc::new(AccountManager::with_dir("/tmp/rusuh_test_nonexistent"));
+    let registry = Arc::new(ModelRegistry::new());
+    let state = Arc::new(ProxyState::new(cfg, accounts, registry, 0));
+
+    let app = build_router(state.clone()).layer(axum::middleware::from_fn_with_state(
+        state.clone(),
+        rusuh::middleware::auth::api_key_auth,
+    ));
+    (app, state)
+}
+
+fn mgmt_request(method: &str, body: Option<Value>) -> Request<Body> {
+    let builder = Request::builder()
+        .method(method)
+        .uri("/v0/management/model-routes")
+        .header("authorization", format!("Bearer {SECRET}"));
+    match body {
+        Some(body) => builder
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap(),
+        None => builder.body(Body::empty()).unwrap(),
+    }
+}
+
+async fn body_json(resp: axum::response::Response) -> Value {
+    let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+    serde_json::from_slice(&bytes).unwrap_or(json!(null))
+}
+
+#[tokio::test]
+async fn get_model_routes_returns_default_table() {
+    let (app, _) = test_app();
+
+    let resp = app.oneshot(mgmt_request("GET", None)).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let json = body_json(resp).await;
+    let names: Vec<&str> = json["model-routes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|route| route["name"].as_str().unwrap())
+        .collect();
+    assert!(names.contains(&"claude-sonnet-4-5"));
+    assert!(names.contains(&"gpt-5.4"));
+}
+
+#[tokio::test]
+async fn put_model_routes_replaces_live_table_and_reports_unknown_targets() {
+    let (app, state) = test_app();
+
+    let resp = app
+        .oneshot(mgmt_request(
+            "PUT",
+            Some(json!({
+                "model-routes": [{
+                    "name": "team-default",
+                    "targets": [
+                        {"provider": "codex", "model": "gpt-5.4"},
+                        {"provider": "openrouter", "model": "or/llama", "weight": 3}
+                    ]
+                }]
+            })),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let json = body_json(resp).await;
+    assert_eq!(
+        json["unknown-targets"],
+        json!(["team-default -> openrouter/or/llama"])
+    );
+
+    let cfg = state.config.read().await;
+    assert_eq!(cfg.model_routes.len(), 1);
+    let route = cfg.model_route("team-default").expect("route stored");
+    assert_eq!(route.targets[0].weight, 1);
+    assert_eq!(route.targets[1].weight, 3);
+}
+
+#[tokio::test]
+async fn put_model_routes_rejects_route_without_targets() {
+    let (app, state) = test_app();
+
+    let resp = app
+        .oneshot(mgmt_request(
+            "PUT",
+            Some(json!([{"name": "empty", "targets": []}])),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let json = body_json(resp).await;
+    assert!(json["error"].as_str().unwrap().contains("no targets"));
+    assert!(state.config.read().await.model_route("gpt-5.4").is_some());
+}
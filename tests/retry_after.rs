@@ -0,0 +1,108 @@
+use std::time::Duration;
+
+use chrono::{TimeZone, Utc};
+use reqwest::header::{HeaderMap, HeaderValue, RETRY_AFTER};
+use serde_json::json;
+
+use rusuh::providers::retry_after;
+
+fn now() -> chrono::DateTime<Utc> {
+    Utc.with_ymd_and_hms(2026, 3, 2, 12, 0, 0).unwrap()
+}
+
+fn headers(value: &str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(RETRY_AFTER, HeaderValue::from_str(value).unwrap());
+    headers
+}
+
+#[test]
+fn retry_after_header_seconds() {
+    assert_eq!(
+        retry_after::from_headers(&headers("120"), now()),
+        Some(Duration::from_secs(120))
+    );
+}
+
+#[test]
+fn retry_after_header_http_date() {
+    assert_eq!(
+        retry_after::from_headers(&headers("Mon, 02 Mar 2026 12:05:00 GMT"), now()),
+        Some(Duration::from_secs(300))
+    );
+    assert_eq!(
+        retry_after::from_headers(&headers("Mon, 02 Mar 2026 11:00:00 GMT"), now()),
+        None
+    );
+}
+
+#[test]
+fn codex_resets_at_in_body() {
+    let body = json!({
+        "error": {
+            "type": "usage_limit_reached",
+            "resets_at": now().timestamp() + 3 * 24 * 3600,
+            "resets_in_seconds": 60
+        }
+    });
+    assert_eq!(
+        retry_after::from_body(&body, now()),
+        Some(Duration::from_secs(3 * 24 * 3600))
+    );
+}
+
+#[test]
+fn resets_in_seconds_in_body() {
+    let body = json!({"error": {"resets_in_seconds": 90}});
+    assert_eq!(
+        retry_after::from_body(&body, now()),
+        Some(Duration::from_secs(90))
+    );
+}
+
+#[test]
+fn google_retry_info_delay() {
+    let body = json!({
+        "error": {
+            "code": 429,
+            "details": [
+                {"@type": "type.googleapis.com/google.rpc.ErrorInfo"},
+                {"@type": "type.googleapis.com/google.rpc.RetryInfo", "retryDelay": "37s"}
+            ]
+        }
+    });
+    assert_eq!(
+        retry_after::from_body(&body, now()),
+        Some(Duration::from_secs(37))
+    );
+}
+
+#[test]
+fn header_wins_over_body() {
+    let body = r#"{"error": {"resets_in_seconds": 90}}"#;
+    assert_eq!(
+        retry_after::from_response(&headers("5"), body, now()),
+        Some(Duration::from_secs(5))
+    );
+    assert_eq!(
+        retry_after::from_response(&HeaderMap::new(), body, now()),
+        Some(Duration::from_secs(90))
+    );
+    assert_eq!(
+        retry_after::from_response(&HeaderMap::new(), "rate limited", now()),
+        None
+    );
+}
+
+#[test]
+fn epoch_millis_reset() {
+    let reset_ms = (now().timestamp() + 3600) * 1000;
+    assert_eq!(
+        retry_after::until_epoch_millis(reset_ms, now()),
+        Some(Duration::from_secs(3600))
+    );
+    assert_eq!(
+        retry_after::until_epoch_millis(now().timestamp_millis() - 1, now()),
+        None
+    );
+}
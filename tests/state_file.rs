@@ -0,0 +1,193 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use tempfile::TempDir;
+
+use rusuh::auth::manager::AccountManager;
+use rusuh::config::Config;
+use rusuh::error::{AppError, AppResult};
+use rusuh::models::{ChatCompletionRequest, ChatCompletionResponse, ModelInfo};
+use rusuh::providers::model_info::ExtModelInfo;
+use rusuh::providers::model_registry::ModelRegistry;
+use rusuh::providers::{BoxStream, Provider};
+use rusuh::proxy::state_file::{self, PersistedQuota, PersistedState, STATE_FILE_NAME};
+use rusuh::proxy::ProxyState;
+
+#[derive(Debug)]
+struct IdleProvider;
+
+#[async_trait]
+impl Provider for IdleProvider {
+    fn name(&self) -> &str {
+        "codex"
+    }
+
+    fn client_id(&self) -> &str {
+        "codex_0"
+    }
+
+    async fn list_models(&self) -> AppResult<Vec<ModelInfo>> {
+        Ok(Vec::new())
+    }
+
+    async fn chat_completion(
+        &self,
+        _req: &ChatCompletionRequest,
+    ) -> AppResult<ChatCompletionResponse> {
+        Err(AppError::Internal(anyhow::anyhow!("idle")))
+    }
+
+    async fn chat_completion_stream(&self, _req: &ChatCompletionRequest) -> AppResult<BoxStream> {
+        Err(AppError::Internal(anyhow::anyhow!("idle")))
+    }
+}
+
+fn make_ext_model(id: &str) -> ExtModelInfo {
+    ExtModelInfo {
+        id: id.to_string(),
+        object: "model".to_string(),
+        created: 0,
+        owned_by: "codex".to_string(),
+        provider_type: "codex".to_string(),
+        display_name: None,
+        name: Some(id.to_string()),
+        version: None,
+        description: None,
+        input_token_limit: 0,
+        output_token_limit: 0,
+        supported_generation_methods: vec![],
+        context_length: 0,
+        max_completion_tokens: 0,
+        supported_parameters: vec![],
+        supported_endpoints: None,
+        thinking: None,
+        user_defined: false,
+    }
+}
+
+async fn test_state() -> Arc<ProxyState> {
+    let registry = Arc::new(ModelRegistry::new());
+    registry
+        .register_client("codex_0", "codex", vec![make_ext_model("gpt-5.4")])
+        .await;
+    let accounts = Arc::new(AccountManager::with_dir("/tmp/rusuh_test_nonexistent"));
+    let state = Arc::new(ProxyState::new(Config::default(), accounts, registry, 1));
+    state
+        .publish_runtime_from_providers(vec![Arc::new(IdleProvider)])
+        .await
+        .expect("test provider should publish");
+    state
+}
+
+#[tokio::test]
+async fn routing_state_survives_a_restart() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join(STATE_FILE_NAME);
+    let now = Instant::now();
+
+    let before = test_state().await;
+    before
+        .execution_sessions
+        .set_selected_auth("conv-1".to_string(), "codex_0".to_string(), true)
+        .await;
+    before
+        .execution_sessions
+        .set_selected_auth("conv-2".to_string(), "removed_0".to_string(), true)
+        .await;
+    before.kiro_runtime.cooldown.write().await.set_cooldown(
+        "codex_0",
+        "gpt-5.4",
+        Duration::from_secs(600),
+        "rate limited",
+        now,
+    );
+    before
+        .kiro_runtime
+        .rate_limiter
+        .write()
+        .await
+        .mark_token_failed("codex_0", now);
+    before
+        .model_registry
+        .set_quota_exceeded_until("codex_0", "gpt-5.4", now + Duration::from_secs(600))
+        .await;
+    state_file::save(&before, &path).await.unwrap();
+
+    let after = test_state().await;
+    let saved = state_file::load(&path).await.unwrap().unwrap();
+    after.restore_persisted_state(saved).await;
+
+    assert_eq!(
+        after.execution_sessions.get_selected_auth("conv-1").await,
+        Some("codex_0".to_string())
+    );
+    assert_eq!(
+        after.execution_sessions.get_selected_auth("conv-2").await,
+        None,
+        "sessions on accounts that are gone are dropped"
+    );
+    let now = Instant::now();
+    let cooldown = after.kiro_runtime.cooldown.read().await;
+    assert_eq!(
+        cooldown.cooldown_reason("codex_0", "gpt-5.4", now),
+        Some("rate limited")
+    );
+    assert!(
+        cooldown
+            .remaining_cooldown("codex_0", "gpt-5.4", now)
+            .unwrap()
+            > Duration::from_secs(590)
+    );
+    assert!(after
+        .kiro_runtime
+        .rate_limiter
+        .read()
+        .await
+        .required_wait("codex_0", now)
+        .is_some());
+    let reset_in = after
+        .model_registry
+        .quota_reset_in("codex_0", "gpt-5.4")
+        .await
+        .unwrap();
+    assert!(reset_in > Duration::from_secs(590));
+    assert!(
+        !after
+            .model_registry
+            .client_is_effectively_available("codex_0", "gpt-5.4")
+            .await
+    );
+}
+
+#[tokio::test]
+async fn expired_entries_are_not_restored() {
+    let state = test_state().await;
+    state
+        .restore_persisted_state(PersistedState {
+            quotas: vec![PersistedQuota {
+                client_id: "codex_0".to_string(),
+                model_id: "gpt-5.4".to_string(),
+                until: chrono::Utc::now() - chrono::Duration::seconds(5),
+                reset_known: true,
+            }],
+            ..Default::default()
+        })
+        .await;
+
+    assert!(
+        state
+            .model_registry
+            .client_is_effectively_available("codex_0", "gpt-5.4")
+            .await
+    );
+}
+
+#[tokio::test]
+async fn missing_state_file_loads_as_none() {
+    let dir = TempDir::new().unwrap();
+    let loaded = state_file::load(&dir.path().join(STATE_FILE_NAME))
+        .await
+        .unwrap();
+    assert!(loaded.is_none());
+}
@@ -166,3 +166,52 @@ async fn sse_response_has_correct_headers() {
     assert_eq!(resp.headers().get("cache-control").unwrap(), "no-cache");
     assert_eq!(resp.headers().get("x-accel-buffering").unwrap(), "no");
 }
+
+#[tokio::test]
+async fn held_anthropic_stream_ends_with_error_event_after_content() {
+    use rusuh::error::{ApiFamily, UpstreamError};
+    use rusuh::proxy::stream::hold_until_content;
+
+    let upstream: rusuh::providers::BoxStream = Box::pin(stream::iter(vec![
+        Ok(Bytes::from_static(
+            b"event: message_start\ndata: {\"type\":\"message_start\"}\n\n",
+        )),
+        Ok(Bytes::from_static(
+            b"event: content_block_delta\ndata: {\"type\":\"content_block_delta\",\"delta\":{\"type\":\"text_delta\",\"text\":\"hi\"}}\n\n",
+        )),
+        Err(UpstreamError::transport("connection reset").into()),
+    ]));
+
+    let held = hold_until_content(upstream, ApiFamily::Anthropic)
+        .await
+        .expect("content arrived before the failure");
+    let result = collect_stream(held).await;
+
+    assert!(result.starts_with("event: message_start"));
+    assert!(result.contains("\"text\":\"hi\""));
+    let last_event = result.trim_end().rsplit("\n\n").next().unwrap();
+    let data = last_event
+        .strip_prefix("event: error\ndata: ")
+        .expect("stream should end with an error event");
+    let error: serde_json::Value = serde_json::from_str(data).unwrap();
+    assert_eq!(error["type"], "error");
+    assert_eq!(error["error"]["type"], "api_error");
+    assert!(!result.contains("[DONE]"));
+}
+
+#[tokio::test]
+async fn held_stream_without_content_is_an_error() {
+    use rusuh::error::ApiFamily;
+    use rusuh::proxy::stream::hold_until_content;
+
+    let upstream = buffered_sse_stream(
+        fake_upstream(vec![
+            "data: {\"choices\":[{\"delta\":{\"role\":\"assistant\"}}]}\n\n",
+        ]),
+        |data| Some(data.to_string()),
+    );
+
+    assert!(hold_until_content(upstream, ApiFamily::OpenAi)
+        .await
+        .is_err());
+}
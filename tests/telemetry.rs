@@ -0,0 +1,194 @@
+//! Spans exported for a routed request: the caller's trace is continued, each
+//! pipeline stage gets its own span, and the upstream call carries the trace on
+//! only when propagation upstream is enabled.
+
+use std::sync::{Arc, Mutex};
+
+use axum::body::Body;
+use axum::http::{HeaderMap, Request, StatusCode};
+use axum::routing::post;
+use axum::{Json, Router};
+use opentelemetry::trace::{TraceId, TracerProvider as _};
+use opentelemetry::Value as AttributeValue;
+use opentelemetry_sdk::trace::{InMemorySpanExporter, SdkTracerProvider, SpanData};
+use serde_json::{json, Value};
+use tempfile::TempDir;
+use tower::ServiceExt;
+use tower_http::trace::TraceLayer;
+use tracing_subscriber::layer::SubscriberExt;
+
+use rusuh::auth::manager::AccountManager;
+use rusuh::config::{Config, ModelRoute, ModelRouteTarget, ProviderKeyEntry};
+use rusuh::providers::model_registry::ModelRegistry;
+use rusuh::proxy::ProxyState;
+
+const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+
+async fn spawn_upstream(seen: Arc<Mutex<Vec<HeaderMap>>>) -> String {
+    let app = Router::new().route(
+        "/chat/completions",
+        post(
+            move |headers: HeaderMap, Json(_body): Json<Value>| async move {
+                seen.lock().unwrap().push(headers);
+                Json(json!({
+                    "id": "chatcmpl-1",
+                    "object": "chat.completion",
+                    "created": 0,
+                    "model": "gpt-5.3-codex",
+                    "choices": [{
+                        "index": 0,
+                        "message": {"role": "assistant", "content": "pong"},
+                        "finish_reason": "stop"
+                    }],
+                    "usage": {"prompt_tokens": 2, "completion_tokens": 1, "total_tokens": 3}
+                }))
+            },
+        ),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let _ = axum::serve(listener, app).await;
+    });
+    format!("http://{addr}")
+}
+
+fn traced_chat() -> Request<Body> {
+    Request::builder()
+        .method("POST")
+        .uri("/v1/chat/completions")
+        .header("content-type", "application/json")
+        .header("traceparent", format!("00-{TRACE_ID}-00f067aa0ba902b7-01"))
+        .body(Body::from(
+            json!({
+                "model": "team-gpt",
+                "messages": [{"role": "user", "content": "ping"}]
+            })
+            .to_string(),
+        ))
+        .unwrap()
+}
+
+async fn traced_app(base_url: &str, dir: &TempDir) -> Router {
+    let cfg = Config {
+        codex_api_keys: vec![ProviderKeyEntry {
+            api_key: "test-key".to_string(),
+            base_url: Some(base_url.to_string()),
+            ..Default::default()
+        }],
+        model_routes: vec![ModelRoute {
+            name: "team-gpt".to_string(),
+            targets: vec![ModelRouteTarget::new("codex-api", "gpt-5.3-codex")],
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    let accounts = Arc::new(AccountManager::with_dir(dir.path()));
+    let state = Arc::new(ProxyState::new(
+        cfg,
+        accounts,
+        Arc::new(ModelRegistry::new()),
+        0,
+    ));
+    let (providers, models) = state.rebuild_runtime_snapshot().await.unwrap();
+    for (client_id, (provider_name, ext_models)) in &models {
+        state
+            .model_registry
+            .register_client(client_id, provider_name, ext_models.clone())
+            .await;
+    }
+    state
+        .publish_runtime_from_providers(providers)
+        .await
+        .unwrap();
+    rusuh::router::build_router(state)
+        .layer(TraceLayer::new_for_http().make_span_with(rusuh::telemetry::request_span::<Body>))
+}
+
+fn attribute<'a>(span: &'a SpanData, key: &str) -> Option<&'a AttributeValue> {
+    span.attributes
+        .iter()
+        .find(|kv| kv.key.as_str() == key)
+        .map(|kv| &kv.value)
+}
+
+fn span<'a>(spans: &'a [SpanData], name: &str) -> &'a SpanData {
+    spans
+        .iter()
+        .find(|span| span.name == name)
+        .unwrap_or_else(|| panic!("no {name} span"))
+}
+
+#[tokio::test]
+async fn routed_request_continues_the_callers_trace_through_to_the_upstream() {
+    let exporter = InMemorySpanExporter::default();
+    let provider = SdkTracerProvider::builder()
+        .with_simple_exporter(exporter.clone())
+        .build();
+    let subscriber = tracing_subscriber::registry()
+        .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+    let _guard = tracing::subscriber::set_default(subscriber);
+    rusuh::telemetry::install_propagator();
+    rusuh::telemetry::set_propagate_upstream(true);
+
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let base_url = spawn_upstream(seen.clone()).await;
+    let dir = TempDir::new().unwrap();
+    let app = traced_app(&base_url, &dir).await;
+
+    let resp = app.clone().oneshot(traced_chat()).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    axum::body::to_bytes(resp.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    provider.force_flush().unwrap();
+
+    let spans = exporter.get_finished_spans().unwrap();
+    let trace_id = TraceId::from_hex(TRACE_ID).unwrap();
+    for name in [
+        "request",
+        "route",
+        "resolve_alias",
+        "resolve_candidates",
+        "provider_attempt",
+        "upstream_http",
+    ] {
+        assert_eq!(
+            span(&spans, name).span_context.trace_id(),
+            trace_id,
+            "{name}"
+        );
+    }
+
+    let route = span(&spans, "route");
+    assert_eq!(
+        attribute(route, "served_model"),
+        Some(&AttributeValue::from("team-gpt"))
+    );
+    let attempt = span(&spans, "provider_attempt");
+    assert_eq!(
+        attribute(attempt, "client_id"),
+        Some(&AttributeValue::from("codex-api_0"))
+    );
+    assert_eq!(
+        attribute(attempt, "model"),
+        Some(&AttributeValue::from("gpt-5.3-codex"))
+    );
+    assert_eq!(
+        attribute(span(&spans, "resolve_candidates"), "candidates"),
+        Some(&AttributeValue::I64(1))
+    );
+
+    let upstream = span(&spans, "upstream_http");
+    assert_eq!(upstream.parent_span_id, attempt.span_context.span_id());
+    assert!(attribute(upstream, "url.path").is_some());
+    assert_eq!(
+        seen.lock().unwrap()[0]["traceparent"],
+        format!("00-{TRACE_ID}-{}-01", upstream.span_context.span_id())
+    );
+
+    rusuh::telemetry::set_propagate_upstream(false);
+    let resp = app.oneshot(traced_chat()).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert!(seen.lock().unwrap()[1].get("traceparent").is_none());
+}
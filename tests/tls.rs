@@ -0,0 +1,103 @@
+use std::path::Path;
+use std::time::Duration;
+
+use axum::{routing::get, Router};
+use rusuh::config::TlsConfig;
+
+fn write_self_signed(cert_path: &Path, key_path: &Path) -> String {
+    let generated = rcgen::generate_simple_self_signed(vec!["localhost".to_string()])
+        .expect("generate self-signed cert");
+    let cert_pem = generated.cert.pem();
+    std::fs::write(cert_path, &cert_pem).expect("write cert");
+    std::fs::write(key_path, generated.signing_key.serialize_pem()).expect("write key");
+    cert_pem
+}
+
+fn client_trusting(cert_pem: &str) -> reqwest::Client {
+    let cert = reqwest::Certificate::from_pem(cert_pem.as_bytes()).expect("parse cert");
+    reqwest::Client::builder()
+        .tls_certs_only([cert])
+        .resolve("localhost", "127.0.0.1:0".parse().unwrap())
+        .build()
+        .expect("build client")
+}
+
+#[tokio::test]
+async fn tls_load_rejects_missing_cert_or_key() {
+    let error = rusuh::tls::load(&TlsConfig {
+        enable: true,
+        cert: String::new(),
+        key: "key.pem".into(),
+    })
+    .await
+    .unwrap_err();
+    assert!(error.to_string().contains("tls.cert"), "{error}");
+
+    let error = rusuh::tls::load(&TlsConfig {
+        enable: true,
+        cert: "/nonexistent/cert.pem".into(),
+        key: "/nonexistent/key.pem".into(),
+    })
+    .await
+    .unwrap_err();
+    assert!(
+        error.to_string().contains("/nonexistent/cert.pem"),
+        "{error}"
+    );
+}
+
+#[tokio::test]
+async fn tls_serves_https_and_hot_reloads_certificate() {
+    let dir = tempfile::tempdir().expect("create temp dir");
+    let cert_path = dir.path().join("cert.pem");
+    let key_path = dir.path().join("key.pem");
+    let first_pem = write_self_signed(&cert_path, &key_path);
+
+    let tls = TlsConfig {
+        enable: true,
+        cert: cert_path.to_string_lossy().to_string(),
+        key: key_path.to_string_lossy().to_string(),
+    };
+    let rustls_config = rusuh::tls::load(&tls).await.expect("load tls config");
+    let watcher = rusuh::tls::spawn_reload_watcher(
+        rustls_config.clone(),
+        tls.clone(),
+        Duration::from_millis(50),
+    );
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind");
+    listener.set_nonblocking(true).expect("set nonblocking");
+    let port = listener.local_addr().unwrap().port();
+    let app = Router::new().route("/health", get(|| async { "ok" }));
+    tokio::spawn(async move {
+        let _ = axum_server::from_tcp_rustls(listener, rustls_config)
+            .expect("tls server")
+            .serve(app.into_make_service())
+            .await;
+    });
+
+    let url = format!("https://localhost:{port}/health");
+    let body = client_trusting(&first_pem)
+        .get(&url)
+        .send()
+        .await
+        .expect("https request with first cert")
+        .text()
+        .await
+        .unwrap();
+    assert_eq!(body, "ok");
+
+    let second_pem = write_self_signed(&cert_path, &key_path);
+    let second_client = client_trusting(&second_pem);
+    let mut reloaded = false;
+    for _ in 0..40 {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        if second_client.get(&url).send().await.is_ok() {
+            reloaded = true;
+            break;
+        }
+    }
+    watcher.abort();
+
+    assert!(reloaded, "server should present the rewritten certificate");
+}
@@ -0,0 +1,360 @@
+//! Integration tests for the usage ledger and the `usage` management endpoint.
+
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::http::header::CONTENT_TYPE;
+use axum::http::{Request, StatusCode};
+use axum::routing::post;
+use axum::{Json, Router};
+use chrono::{DateTime, NaiveDate, Utc};
+use http_body_util::BodyExt;
+use serde_json::Value;
+use tempfile::TempDir;
+use tower::ServiceExt;
+
+use rusuh::auth::manager::AccountManager;
+use rusuh::config::{Config, ManagementConfig, ModelRoute, ModelRouteTarget, ProviderKeyEntry};
+use rusuh::providers::model_registry::ModelRegistry;
+use rusuh::proxy::usage_ledger::{
+    UsageGroup, UsageLedger, UsageRange, UsageRecord, UsageRow, USAGE_FILE_NAME,
+};
+use rusuh::proxy::ProxyState;
+use rusuh::router::build_router;
+
+const SECRET: &str = "test-mgmt-secret";
+
+fn at(day: &str) -> DateTime<Utc> {
+    NaiveDate::parse_from_str(day, "%Y-%m-%d")
+        .unwrap()
+        .and_hms_opt(12, 0, 0)
+        .unwrap()
+        .and_utc()
+}
+
+fn record(day: &str, key: &str, client_id: &str, model: &str, status: u16) -> UsageRecord {
+    UsageRecord {
+        at: at(day),
+        key: Some(key.to_string()),
+        key_id: Some(format!("id-{key}")),
+        client_id: Some(client_id.to_string()),
+        provider: Some("codex".to_string()),
+        model: model.to_string(),
+        prompt_tokens: 10,
+        completion_tokens: 5,
+        cached_tokens: 2,
+        latency_ms: 100,
+        status,
+    }
+}
+
+#[test]
+fn query_rolls_records_up_by_the_requested_groups() {
+    let ledger = UsageLedger::in_memory();
+    ledger.record(record("2026-03-01", "ci", "codex_a", "gpt-5.4", 200));
+    ledger.record(record("2026-03-01", "ci", "codex_b", "gpt-5.4", 200));
+    ledger.record(record("2026-03-02", "ops", "codex_a", "gpt-5.4-mini", 502));
+
+    let total = ledger.query(&[], UsageRange::default());
+    assert_eq!(total.len(), 1);
+    assert_eq!(total[0].totals.requests, 3);
+    assert_eq!(total[0].totals.errors, 1);
+    assert_eq!(total[0].totals.prompt_tokens, 30);
+    assert_eq!(total[0].totals.cached_tokens, 6);
+
+    let by_key = ledger.query(&[UsageGroup::Key], UsageRange::default());
+    let keys: Vec<_> = by_key
+        .iter()
+        .map(|row| (row.key.as_deref().unwrap(), row.totals.requests))
+        .collect();
+    assert_eq!(keys, vec![("ci", 2), ("ops", 1)]);
+
+    let by_account = ledger.query(
+        &[UsageGroup::Account],
+        UsageRange {
+            from: Some(NaiveDate::from_ymd_opt(2026, 3, 1).unwrap()),
+            to: Some(NaiveDate::from_ymd_opt(2026, 3, 1).unwrap()),
+        },
+    );
+    let accounts: Vec<_> = by_account
+        .iter()
+        .map(|row| {
+            (
+                row.account.as_deref().unwrap(),
+                row.totals.completion_tokens,
+            )
+        })
+        .collect();
+    assert_eq!(accounts, vec![("codex_a", 5), ("codex_b", 5)]);
+}
+
+#[test]
+fn keys_are_grouped_by_id_and_shown_by_label() {
+    let ledger = UsageLedger::in_memory();
+    let unnamed = |key_id: Option<&str>| UsageRecord {
+        key: Some("sk-team-…".to_string()),
+        key_id: key_id.map(str::to_string),
+        ..record("2026-03-01", "", "codex_a", "gpt-5.4", 200)
+    };
+    ledger.record(unnamed(Some("alice")));
+    ledger.record(unnamed(Some("bob")));
+    ledger.record(unnamed(Some("bob")));
+    // Written before records carried a key id.
+    ledger.record(unnamed(None));
+
+    let by_key = ledger.query(&[UsageGroup::Key], UsageRange::default());
+    let keys: Vec<_> = by_key
+        .iter()
+        .map(|row| {
+            (
+                row.key.as_deref().unwrap(),
+                row.key_id.as_deref(),
+                row.totals.requests,
+            )
+        })
+        .collect();
+    assert_eq!(
+        keys,
+        vec![
+            ("sk-team-…", None, 1),
+            ("sk-team-…", Some("alice"), 1),
+            ("sk-team-…", Some("bob"), 2),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn ledger_file_is_replayed_on_open() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join(USAGE_FILE_NAME);
+
+    let ledger = UsageLedger::open(&path).await.unwrap();
+    ledger.record(record("2026-03-01", "ci", "codex_a", "gpt-5.4", 200));
+    ledger.record(record("2026-03-02", "ci", "codex_a", "gpt-5.4", 429));
+    // Records are appended in the background; closing writes out the queue.
+    ledger.close().await;
+    let written = tokio::fs::read_to_string(&path).await.unwrap();
+    assert_eq!(written.lines().count(), 2);
+    drop(ledger);
+
+    let reopened = UsageLedger::open(&path).await.unwrap();
+    let by_day = reopened.query(&[UsageGroup::Day], UsageRange::default());
+    assert_eq!(by_day.len(), 2);
+    assert_eq!(by_day[0].day, NaiveDate::from_ymd_opt(2026, 3, 1));
+    assert_eq!(by_day[0].totals.errors, 0);
+    assert_eq!(by_day[1].totals.errors, 1);
+}
+
+fn test_app() -> (axum::Router, Arc<ProxyState>) {
+    let cfg = Config {
+        remote_management: ManagementConfig {
+            allow_remote: true,
+            secret_key: SECRET.into(),
+        },
+        ..Default::default()
+    };
+    let accounts = Arc::new(AccountManager::with_dir("/tmp/rusuh_test_nonexistent"));
+    let registry = Arc::new(ModelRegistry::new());
+    let state = Arc::new(ProxyState::new(cfg, accounts, registry, 0));
+    (build_router(state.clone()), state)
+}
+
+async fn get_usage(app: &axum::Router, query: &str) -> (StatusCode, Value) {
+    let resp = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(format!("/v0/management/usage{query}"))
+                .header("authorization", format!("Bearer {SECRET}"))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let status = resp.status();
+    let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&bytes).unwrap())
+}
+
+#[tokio::test]
+async fn usage_endpoint_groups_and_filters_rows() {
+    let (app, state) = test_app();
+    state
+        .usage_ledger
+        .record(record("2026-03-01", "ci", "codex_a", "gpt-5.4", 200));
+    state
+        .usage_ledger
+        .record(record("2026-03-02", "ci", "codex_a", "gpt-5.4", 200));
+
+    let (status, body) = get_usage(&app, "?group_by=day,model&from=2026-03-02").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["group_by"], serde_json::json!(["day", "model"]));
+    let rows = body["rows"].as_array().unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0]["day"], "2026-03-02");
+    assert_eq!(rows[0]["model"], "gpt-5.4");
+    assert_eq!(rows[0]["requests"], 1);
+    assert_eq!(rows[0]["prompt_tokens"], 10);
+    assert!(rows[0].get("key").is_none());
+
+    let (status, _) = get_usage(&app, "?group_by=provider").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = get_usage(&app, "?from=March").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+/// Gemini-style SSE upstream: two chunks whose cumulative `usageMetadata` ends at
+/// 9 prompt and 2 candidate tokens. Antigravity wraps each chunk in `response`.
+async fn spawn_gemini_stream_upstream(wrap_in_response: bool) -> String {
+    let chunks = [
+        serde_json::json!({
+            "candidates": [{"content": {"parts": [{"text": "po"}]}}],
+            "usageMetadata": {"promptTokenCount": 9, "candidatesTokenCount": 1}
+        }),
+        serde_json::json!({
+            "candidates": [{"content": {"parts": [{"text": "ng"}]}, "finishReason": "STOP"}],
+            "usageMetadata": {
+                "promptTokenCount": 9,
+                "candidatesTokenCount": 2,
+                "cachedContentTokenCount": 4
+            }
+        }),
+    ];
+    let sse: String = chunks
+        .into_iter()
+        .map(|chunk| match wrap_in_response {
+            true => serde_json::json!({ "response": chunk }),
+            false => chunk,
+        })
+        .map(|chunk| format!("data: {chunk}\n\n"))
+        .collect();
+    let app = Router::new()
+        .route(
+            "/v1internal:fetchAvailableModels",
+            post(|| async {
+                Json(serde_json::json!({"models": {"gemini-2.5-flash": {"state": "ENABLED"}}}))
+            }),
+        )
+        .fallback(post(move || {
+            let sse = sse.clone();
+            async move { ([(CONTENT_TYPE, "text/event-stream")], sse) }
+        }));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let _ = axum::serve(listener, app).await;
+    });
+    format!("http://{addr}")
+}
+
+/// Stream one chat request for `model` and return the ledger row it left.
+async fn stream_and_read_ledger(cfg: Config, dir: &TempDir, model: &str) -> UsageRow {
+    let accounts = Arc::new(AccountManager::with_dir(dir.path()));
+    accounts.reload().await.unwrap();
+    let state = Arc::new(ProxyState::new(
+        cfg,
+        accounts,
+        Arc::new(ModelRegistry::new()),
+        0,
+    ));
+    let (providers, models) = state.rebuild_runtime_snapshot().await.unwrap();
+    for (client_id, (provider_name, ext_models)) in &models {
+        state
+            .model_registry
+            .register_client(client_id, provider_name, ext_models.clone())
+            .await;
+    }
+    state
+        .publish_runtime_from_providers(providers)
+        .await
+        .unwrap();
+
+    let resp = build_router(state.clone())
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/v1/chat/completions")
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    serde_json::json!({
+                        "model": model,
+                        "stream": true,
+                        "messages": [{"role": "user", "content": "ping"}]
+                    })
+                    .to_string(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body = resp.into_body().collect().await.unwrap().to_bytes();
+    assert!(String::from_utf8_lossy(&body).contains("\"usage\""));
+
+    let rows = state
+        .usage_ledger
+        .query(&[UsageGroup::Account], UsageRange::default());
+    assert_eq!(rows.len(), 1);
+    rows[0].clone()
+}
+
+#[tokio::test]
+async fn streamed_gemini_api_reply_is_recorded_with_its_tokens() {
+    let base_url = spawn_gemini_stream_upstream(false).await;
+    let dir = TempDir::new().unwrap();
+    let cfg = Config {
+        gemini_api_keys: vec![ProviderKeyEntry {
+            api_key: "test-key".to_string(),
+            base_url: Some(base_url),
+            ..Default::default()
+        }],
+        model_routes: vec![ModelRoute {
+            name: "team-gemini".to_string(),
+            targets: vec![ModelRouteTarget::new("gemini-api", "gemini-2.5-pro")],
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+
+    let row = stream_and_read_ledger(cfg, &dir, "team-gemini").await;
+    assert_eq!(row.provider.as_deref(), Some("gemini-api"));
+    assert_eq!(row.totals.prompt_tokens, 9);
+    assert_eq!(row.totals.completion_tokens, 2);
+    assert_eq!(row.totals.cached_tokens, 4);
+}
+
+#[tokio::test]
+async fn streamed_antigravity_reply_is_recorded_with_its_tokens() {
+    let base_url = spawn_gemini_stream_upstream(true).await;
+    let dir = TempDir::new().unwrap();
+    let auth_json = serde_json::json!({
+        "type": "antigravity",
+        "provider_key": "antigravity",
+        "email": "test@example.com",
+        "access_token": "ya29.test",
+        "refresh_token": "1//test",
+        "project_id": "test-project",
+        "base_url": base_url,
+        "expired": "2030-01-01T00:00:00Z"
+    });
+    std::fs::write(
+        dir.path().join("antigravity-test.json"),
+        auth_json.to_string(),
+    )
+    .unwrap();
+
+    let cfg = Config {
+        model_routes: vec![ModelRoute {
+            name: "team-gemini".to_string(),
+            targets: vec![ModelRouteTarget::new("antigravity", "gemini-2.5-flash")],
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+
+    let row = stream_and_read_ledger(cfg, &dir, "team-gemini").await;
+    assert_eq!(row.provider.as_deref(), Some("antigravity"));
+    assert_eq!(row.totals.prompt_tokens, 9);
+    assert_eq!(row.totals.completion_tokens, 2);
+    assert_eq!(row.totals.cached_tokens, 4);
+}
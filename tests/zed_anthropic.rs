@@ -67,10 +67,10 @@ fn test_convert_anthropic_to_openai_with_thinking() {
         result["choices"][0]["message"]["content"],
         "Here's my answer."
     );
-    // Thinking block should be preserved in metadata
-    assert!(result["choices"][0]["message"]["thinking"].is_string());
+    // Thinking block should surface as reasoning_content
+    assert!(result["choices"][0]["message"]["reasoning_content"].is_string());
     assert_eq!(
-        result["choices"][0]["message"]["thinking"],
+        result["choices"][0]["message"]["reasoning_content"],
         "Let me think about this..."
     );
 }
@@ -11,7 +11,7 @@ async fn test_scan_zed_providers_empty_dir() {
     let temp_dir = TempDir::new().unwrap();
     let store = FileTokenStore::new(temp_dir.path());
 
-    let providers = scan_zed_providers(&store).await.unwrap();
+    let providers = scan_zed_providers(&store, None).await.unwrap();
     assert_eq!(providers.len(), 0);
 }
 
@@ -33,7 +33,7 @@ async fn test_scan_zed_providers_single_account() {
     )
     .unwrap();
 
-    let providers = scan_zed_providers(&store).await.unwrap();
+    let providers = scan_zed_providers(&store, None).await.unwrap();
     assert_eq!(providers.len(), 1);
 
     let (filename, provider) = &providers[0];
@@ -73,7 +73,7 @@ async fn test_scan_zed_providers_multiple_accounts() {
     )
     .unwrap();
 
-    let providers = scan_zed_providers(&store).await.unwrap();
+    let providers = scan_zed_providers(&store, None).await.unwrap();
     assert_eq!(providers.len(), 2);
 
     // Verify both providers are present
@@ -111,7 +111,7 @@ async fn test_scan_zed_providers_ignores_non_zed() {
     )
     .unwrap();
 
-    let providers = scan_zed_providers(&store).await.unwrap();
+    let providers = scan_zed_providers(&store, None).await.unwrap();
     assert_eq!(providers.len(), 1);
     assert_eq!(providers[0].1.user_id(), "user1");
 }
@@ -146,7 +146,7 @@ async fn test_scan_zed_providers_skips_invalid() {
     )
     .unwrap();
 
-    let providers = scan_zed_providers(&store).await.unwrap();
+    let providers = scan_zed_providers(&store, None).await.unwrap();
     // Should only get the valid one
     assert_eq!(providers.len(), 1);
     assert_eq!(providers[0].1.user_id(), "valid_user");